@@ -0,0 +1,246 @@
+//! Compares the segment tree variants.
+//!
+//! Run `cargo bench --bench variants` and see `target/criterion/report/index.html`.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use seg_lib::{
+    AssignSegmentTree, DynamicLazySegmentTree, DynamicSegmentTree, LazySegmentTree, SegmentTree,
+    acts::{AddQueryAddUpdate, MaxQueryAddUpdate},
+    ops::{Add, Max},
+};
+
+const SIZES: [usize; 3] = [1 << 10, 1 << 16, 1 << 20];
+/// The number of operations per iteration.
+const OPS: usize = 1 << 10;
+
+fn values(n: usize) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(n as u64);
+    Vec::from_iter((0..n).map(|_| rng.random_range(0..1 << 20)))
+}
+
+fn ranges(n: usize) -> Vec<(usize, usize)> {
+    let mut rng = StdRng::seed_from_u64(!(n as u64));
+    Vec::from_iter((0..OPS).map(|_| {
+        let l = rng.random_range(0..n);
+        (l, rng.random_range(l..=n))
+    }))
+}
+
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    for n in SIZES {
+        let values = values(n);
+        group.bench_with_input(BenchmarkId::new("SegmentTree<Add>", n), &values, |b, v| {
+            b.iter(|| SegmentTree::<Add<u64>>::from(v.clone()))
+        });
+        group.bench_with_input(BenchmarkId::new("SegmentTree<Max>", n), &values, |b, v| {
+            b.iter(|| SegmentTree::<Max<u64>>::from_iter(v.iter().copied().map(Some)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("LazySegmentTree<Add>", n),
+            &values,
+            |b, v| b.iter(|| LazySegmentTree::<AddQueryAddUpdate<u64>>::from(v.clone())),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("AssignSegmentTree<Add>", n),
+            &values,
+            |b, v| b.iter(|| AssignSegmentTree::<Add<u64>>::from(v.clone())),
+        );
+    }
+    group.finish();
+}
+
+fn point_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("point_update");
+    for n in SIZES {
+        let values = values(n);
+        let indices = Vec::from_iter(ranges(n).into_iter().map(|(l, _)| l));
+
+        let mut st = SegmentTree::<Add<u64>>::from(values.clone());
+        group.bench_function(BenchmarkId::new("SegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &i in &indices {
+                    st.point_update(i, black_box(i as u64))
+                }
+            })
+        });
+
+        let mut lst = LazySegmentTree::<AddQueryAddUpdate<u64>>::from(values.clone());
+        group.bench_function(BenchmarkId::new("LazySegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &i in &indices {
+                    lst.point_update(i, black_box(&1))
+                }
+            })
+        });
+
+        let mut ast = AssignSegmentTree::<Add<u64>>::from(values.clone());
+        group.bench_function(BenchmarkId::new("AssignSegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &i in &indices {
+                    ast.point_assign(i, black_box(i as u64))
+                }
+            })
+        });
+
+        let mut dst = DynamicSegmentTree::<Add<u64>>::new(0..n as isize).unwrap();
+        group.bench_function(BenchmarkId::new("DynamicSegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &i in &indices {
+                    dst.point_update(i as isize, black_box(i as u64))
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn range_query(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range_query");
+    for n in SIZES {
+        let values = values(n);
+        let ranges = ranges(n);
+
+        let st = SegmentTree::<Add<u64>>::from(values.clone());
+        group.bench_function(BenchmarkId::new("SegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    black_box(st.range_query(l..r));
+                }
+            })
+        });
+
+        let st = SegmentTree::<Max<u64>>::from_iter(values.iter().copied().map(Some));
+        group.bench_function(BenchmarkId::new("SegmentTree<Max>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    black_box(st.range_query(l..r));
+                }
+            })
+        });
+
+        let mut lst = LazySegmentTree::<AddQueryAddUpdate<u64>>::from(values.clone());
+        group.bench_function(BenchmarkId::new("LazySegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    black_box(lst.range_query(l..r));
+                }
+            })
+        });
+
+        let mut ast = AssignSegmentTree::<Add<u64>>::from(values.clone());
+        group.bench_function(BenchmarkId::new("AssignSegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    black_box(ast.range_query(l..r));
+                }
+            })
+        });
+
+        let mut dst = DynamicSegmentTree::<Add<u64>>::new(0..n as isize).unwrap();
+        for (i, &v) in values.iter().enumerate() {
+            dst.point_update(i as isize, v)
+        }
+        group.bench_function(BenchmarkId::new("DynamicSegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    black_box(dst.range_query(l as isize..r as isize));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn range_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range_update");
+    for n in SIZES {
+        let values = values(n);
+        let ranges = ranges(n);
+
+        let mut lst = LazySegmentTree::<AddQueryAddUpdate<u64>>::from(values.clone());
+        group.bench_function(BenchmarkId::new("LazySegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    lst.range_update(l..r, black_box(&1))
+                }
+            })
+        });
+
+        let mut lst =
+            LazySegmentTree::<MaxQueryAddUpdate<u64>>::from_iter(values.iter().copied().map(Some));
+        group.bench_function(BenchmarkId::new("LazySegmentTree<Max>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    lst.range_update(l..r, black_box(&1))
+                }
+            })
+        });
+
+        let mut ast = AssignSegmentTree::<Add<u64>>::from(values.clone());
+        group.bench_function(BenchmarkId::new("AssignSegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    ast.range_assign(l..r, black_box(l as u64))
+                }
+            })
+        });
+
+        let mut dlst =
+            DynamicLazySegmentTree::<AddQueryAddUpdate<u64>>::new(0..n as isize).unwrap();
+        group.bench_function(BenchmarkId::new("DynamicLazySegmentTree<Add>", n), |b| {
+            b.iter(|| {
+                for &(l, r) in &ranges {
+                    dlst.range_update(l as isize..r as isize, black_box(&1))
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn partition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("partition");
+    for n in SIZES {
+        let values = values(n);
+        let ranges = ranges(n);
+
+        let st = SegmentTree::<Add<u64>>::from(values.clone());
+        group.bench_function(
+            BenchmarkId::new("SegmentTree<Add>::partition_end", n),
+            |b| {
+                b.iter(|| {
+                    for &(l, r) in &ranges {
+                        let bound = (r - l) as u64 * (1 << 19);
+                        black_box(st.partition_end(l, |&sum| sum <= bound));
+                    }
+                })
+            },
+        );
+        group.bench_function(
+            BenchmarkId::new("SegmentTree<Add>::partition_start", n),
+            |b| {
+                b.iter(|| {
+                    for &(l, r) in &ranges {
+                        let bound = (r - l) as u64 * (1 << 19);
+                        black_box(st.partition_start(r, |&sum| sum <= bound));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    build,
+    point_update,
+    range_query,
+    range_update,
+    partition
+);
+criterion_main!(benches);
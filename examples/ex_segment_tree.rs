@@ -8,7 +8,7 @@ fn main() {
     let mut seg = SegmentTree::<Add<i32>>::from_iter(0..10);
     assert_eq!(seg.len(), 10);
     // Iterates all elements in O(N) time
-    assert_eq!(seg.iter().sum::<i32>(), (0 + 9) * 10 / 2);
+    assert_eq!(seg.iter().sum::<i32>(), 9 * 10 / 2);
 
     // Update a single element
     seg.point_update(0, 100);
@@ -0,0 +1,108 @@
+/*!
+Derive macros for [`seg_lib`](https://docs.rs/seg_lib).
+
+Use them through the `derive` feature of `seg_lib`.
+*/
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Index, Member, Type, parse_macro_input};
+
+/// Implements `Monoid` for a struct by combining the fields independently.
+///
+/// Every field should be annotated with `#[monoid(M)]`, where `M` is a monoid whose set is the type of the field.
+/// The set of the derived monoid is the struct itself.
+#[proc_macro_derive(Monoid, attributes(monoid))]
+pub fn derive_monoid(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`Monoid` can be derived for structs only",
+            ));
+        }
+    };
+
+    let mut members = Vec::with_capacity(fields.len());
+    let mut monoids = Vec::with_capacity(fields.len());
+    for (i, field) in fields.iter().enumerate() {
+        members.push(match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        });
+
+        let mut monoid = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("monoid"))
+        {
+            if monoid.is_some() {
+                return Err(Error::new_spanned(
+                    attr,
+                    "duplicate `#[monoid(..)]` attribute",
+                ));
+            }
+            monoid = Some(attr.parse_args::<Type>()?);
+        }
+        monoids.push(
+            monoid.ok_or_else(|| Error::new_spanned(field, "missing `#[monoid(..)]` attribute"))?,
+        );
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let predicates = where_clause.map(|where_clause| &where_clause.predicates);
+    let types = fields.iter().map(|field| &field.ty);
+
+    Ok(quote! {
+        impl #impl_generics ::seg_lib::Monoid for #name #ty_generics
+        where
+            #( #monoids: ::seg_lib::Monoid<Set = #types>, )*
+            #predicates
+        {
+            type Set = Self;
+
+            const IS_COMMUTATIVE: bool = true #( & <#monoids as ::seg_lib::Monoid>::IS_COMMUTATIVE )*;
+
+            fn identity() -> Self::Set {
+                Self { #( #members: <#monoids as ::seg_lib::Monoid>::identity(), )* }
+            }
+
+            fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+                Self {
+                    #( #members: <#monoids as ::seg_lib::Monoid>::combine(
+                        &lhs_or_prev.#members,
+                        &rhs_or_new.#members,
+                    ), )*
+                }
+            }
+
+            fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+                #( <#monoids as ::seg_lib::Monoid>::combine_assign(
+                    &mut lhs_or_prev.#members,
+                    &rhs_or_new.#members,
+                ); )*
+            }
+
+            fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+                Self {
+                    #( #members: <#monoids as ::seg_lib::Monoid>::combine_n(&element.#members, n), )*
+                }
+            }
+
+            fn is_identity(element: &Self::Set) -> bool {
+                true #( && <#monoids as ::seg_lib::Monoid>::is_identity(&element.#members) )*
+            }
+        }
+    })
+}
@@ -0,0 +1,279 @@
+//! Adapters between the algebra traits of [`ac-library-rs`](https://docs.rs/ac-library-rs) and this crate.
+//!
+//! - [`FromAcl`] and [`FromAclMap`] use [`ac_library::Monoid`] and [`ac_library::MapMonoid`]
+//!   implementations with the trees of this crate.
+//! - [`ToAcl`] and [`ToAclMap`] use [`Monoid`] and [`MonoidAction`] implementations
+//!   with [`ac_library::Segtree`] and [`ac_library::LazySegtree`].
+//!
+//! Available with the `acl` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use ac_library::{LazySegtree, MapMonoid, Max, Monoid as _};
+//! use seg_lib::{
+//!     LazySegmentTree, SegmentTree,
+//!     acl::{FromAcl, FromAclMap, ToAcl},
+//!     ops::Add,
+//! };
+//!
+//! // an ACL monoid on the trees of this crate
+//! let st = SegmentTree::<FromAcl<Max<i32>>>::from_iter([3, 1, 4, 1, 5]);
+//! assert_eq!(st.range_query(1..4), 4);
+//!
+//! // an ACL map monoid on the trees of this crate
+//! struct MaxAdd;
+//! impl MapMonoid for MaxAdd {
+//!     type M = Max<i32>;
+//!     type F = i32;
+//!
+//!     fn identity_map() -> i32 {
+//!         0
+//!     }
+//!     fn mapping(&f: &i32, &x: &i32) -> i32 {
+//!         f + x
+//!     }
+//!     fn composition(&f: &i32, &g: &i32) -> i32 {
+//!         f + g
+//!     }
+//! }
+//! let mut lst = LazySegmentTree::<FromAclMap<MaxAdd>>::from_iter([3, 1, 4, 1, 5]);
+//! lst.range_update(..2, &10);
+//! assert_eq!(lst.range_query(..), 13);
+//!
+//! // a monoid of this crate on the trees of ACL
+//! assert_eq!(<ToAcl<Add<i32>>>::binary_operation(&1, &2), 3);
+//! let mut lst = LazySegtree::<MaxAdd>::from(vec![3, 1, 4, 1, 5]);
+//! lst.apply(1, 10);
+//! assert_eq!(lst.all_prod(), 11);
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::traits::{Monoid, MonoidAction};
+
+/// Implements [`Monoid`] for an [`ac_library::Monoid`] `M`.
+///
+/// The monoid is assumed to be non-commutative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FromAcl<M>(PhantomData<M>);
+
+impl<M> Monoid for FromAcl<M>
+where
+    M: ac_library::Monoid,
+{
+    type Set = <M as ac_library::Monoid>::S;
+
+    const IS_COMMUTATIVE: bool = false;
+
+    #[inline]
+    fn identity() -> Self::Set {
+        <M as ac_library::Monoid>::identity()
+    }
+
+    #[inline]
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        <M as ac_library::Monoid>::binary_operation(lhs_or_prev, rhs_or_new)
+    }
+}
+
+/// Implements [`MonoidAction`] for an [`ac_library::MapMonoid`] `F`.
+///
+/// This is also the [`Monoid`] of the maps, whose `combine(prev, new)` is `composition(new, prev)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FromAclMap<F>(PhantomData<F>);
+
+impl<F> Monoid for FromAclMap<F>
+where
+    F: ac_library::MapMonoid,
+{
+    type Set = <F as ac_library::MapMonoid>::F;
+
+    const IS_COMMUTATIVE: bool = false;
+
+    #[inline]
+    fn identity() -> Self::Set {
+        <F as ac_library::MapMonoid>::identity_map()
+    }
+
+    #[inline]
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        <F as ac_library::MapMonoid>::composition(rhs_or_new, lhs_or_prev)
+    }
+}
+
+impl<F> MonoidAction for FromAclMap<F>
+where
+    F: ac_library::MapMonoid,
+{
+    type Map = Self;
+    type Set = FromAcl<<F as ac_library::MapMonoid>::M>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    #[inline]
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        <F as ac_library::MapMonoid>::mapping(mapping, element)
+    }
+}
+
+/// Implements [`ac_library::Monoid`] for a [`Monoid`] `M`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ToAcl<M>(PhantomData<M>);
+
+impl<M> ac_library::Monoid for ToAcl<M>
+where
+    M: Monoid<Set: Clone>,
+{
+    type S = <M as Monoid>::Set;
+
+    #[inline]
+    fn identity() -> Self::S {
+        <M as Monoid>::identity()
+    }
+
+    #[inline]
+    fn binary_operation(a: &Self::S, b: &Self::S) -> Self::S {
+        <M as Monoid>::combine(a, b)
+    }
+}
+
+/// Implements [`ac_library::MapMonoid`] for a [`MonoidAction`] `A`.
+///
+/// ACL does not provide the segment size,
+/// so actions with [`USE_SEGMENT_SIZE`](MonoidAction::USE_SEGMENT_SIZE) are rejected at compile time.
+/// Attach the size to the elements instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ToAclMap<A>(PhantomData<A>);
+
+impl<A> ac_library::MapMonoid for ToAclMap<A>
+where
+    A: MonoidAction<Map: Monoid<Set: Clone>, Set: Monoid<Set: Clone>>,
+{
+    type M = ToAcl<<A as MonoidAction>::Set>;
+    type F = <<A as MonoidAction>::Map as Monoid>::Set;
+
+    #[inline]
+    fn identity_map() -> Self::F {
+        <<A as MonoidAction>::Map as Monoid>::identity()
+    }
+
+    #[inline]
+    fn mapping(
+        f: &Self::F,
+        x: &<Self::M as ac_library::Monoid>::S,
+    ) -> <Self::M as ac_library::Monoid>::S {
+        const {
+            assert!(
+                !<A as MonoidAction>::USE_SEGMENT_SIZE,
+                "ACL does not provide the segment size"
+            )
+        };
+        <A as MonoidAction>::act(f, x, None)
+    }
+
+    #[inline]
+    fn composition(f: &Self::F, g: &Self::F) -> Self::F {
+        <<A as MonoidAction>::Map as Monoid>::combine(g, f)
+    }
+}
+
+#[cfg(test)]
+mod conversion {
+    use rand::Rng;
+
+    use super::{FromAcl, FromAclMap, ToAcl, ToAclMap};
+    use crate::{
+        LazySegmentTree, SegmentTree,
+        acts::MaxQueryAddUpdate,
+        ops::{Add, Affine},
+    };
+
+    /// The sum and the length of a segment.
+    struct SumLen;
+
+    impl ac_library::Monoid for SumLen {
+        type S = (i64, i64);
+
+        fn identity() -> Self::S {
+            (0, 0)
+        }
+
+        fn binary_operation(a: &Self::S, b: &Self::S) -> Self::S {
+            (a.0 + b.0, a.1 + b.1)
+        }
+    }
+
+    /// Affine maps `x -> ax + b`, whose composition is non-commutative.
+    struct AffineSum;
+
+    impl ac_library::MapMonoid for AffineSum {
+        type M = SumLen;
+        type F = (i64, i64);
+
+        fn identity_map() -> Self::F {
+            (1, 0)
+        }
+
+        fn mapping(&(a, b): &Self::F, &(sum, len): &(i64, i64)) -> (i64, i64) {
+            (a * sum + b * len, len)
+        }
+
+        fn composition(&(a, b): &Self::F, &(c, d): &Self::F) -> Self::F {
+            (a * c, a * d + b)
+        }
+    }
+
+    #[test]
+    fn from_acl() {
+        let mut rng = rand::rng();
+        let naive = Vec::from_iter((0..50).map(|_| rng.random_range(-100..100)));
+        let st = SegmentTree::<FromAcl<ac_library::Additive<i64>>>::from(naive.clone());
+        for l in 0..=naive.len() {
+            for r in l..=naive.len() {
+                assert_eq!(st.range_query(l..r), naive[l..r].iter().sum::<i64>())
+            }
+        }
+    }
+
+    #[test]
+    fn from_acl_map() {
+        let mut lst = LazySegmentTree::<FromAclMap<AffineSum>>::from(vec![(1, 1), (2, 1), (3, 1)]);
+        lst.range_update(.., &(1, 1));
+        // `x -> 2(x + 1)` on the last two elements
+        lst.range_update(1.., &(2, 0));
+        assert_eq!(lst.range_query(..), (16, 3));
+        assert_eq!(lst.range_query(1..), (14, 2));
+        assert_eq!(lst.to_vec(), vec![(2, 1), (6, 1), (8, 1)]);
+    }
+
+    #[test]
+    fn to_acl() {
+        let mut st = ac_library::Segtree::<ToAcl<Affine<i64>>>::from(vec![(2, 1), (3, 0)]);
+        assert_eq!(
+            st.all_prod(),
+            SegmentTree::<Affine<i64>>::from(vec![(2, 1), (3, 0)]).range_query(..)
+        );
+        st.set(0, (1, 5));
+        assert_eq!(st.get(0), (1, 5));
+
+        let mut lst = ac_library::LazySegtree::<ToAclMap<MaxQueryAddUpdate<i64>>>::from(vec![
+            Some(1),
+            None,
+            Some(3),
+        ]);
+        lst.apply(0, 10);
+        lst.apply(2, -1);
+        assert_eq!(lst.all_prod(), Some(11));
+        assert_eq!(lst.get(1), None);
+        assert_eq!(lst.get(2), Some(2));
+        assert_eq!(
+            <ToAcl<Add<i64>> as ac_library::Monoid>::binary_operation(&1, &2),
+            3
+        );
+    }
+}
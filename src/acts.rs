@@ -1,17 +1,25 @@
 /*!
 Predefined monoid actions.
 
-Use [`AssignSegmentTree`](crate::assign::AssignSegmentTree) for range assign update.
+Use [`AssignSegmentTree`](crate::assign::AssignSegmentTree) for range assign update,
+or [`AssignAction`] to combine it with other updates on [`LazySegmentTree`](crate::LazySegmentTree).
+
+Tuples of monoid actions are monoid actions as well, acting componentwise.
+For example, `(AddQueryAddUpdate<i64>, MaxQueryAddUpdate<i64>)` tracks both the sum and the maximum.
 */
 
-use std::marker::PhantomData;
+use std::{cmp::Ordering, fmt::Debug, marker::PhantomData};
 
-use num_integer::Integer;
-use num_traits::{FromPrimitive, One, Zero};
+use crate::num::Integer;
+use crate::num::{FromPrimitive, One, Zero};
 
 use crate::{
-    Monoid, MonoidAction,
-    ops::{Add, Affine, AssignOr, GCD, LCM, Max, Min, Mul},
+    Monoid, MonoidAction, QuasiMonoidAction,
+    ops::{
+        Add, Affine, Assign, AssignOr, BitAnd, BitOr, GCD, LCM, Matrix, Max, MaxCount, Min,
+        MinCount, ModAdd, ModAffine, Mul, Parity, SumSq, VectorSum, WeightedSum,
+    },
+    utility::{mod_add, mod_mul},
 };
 
 fn convert_size<T>(size: usize) -> T
@@ -75,6 +83,49 @@ where
     }
 }
 
+/// Performs **range add query range assign update**.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::AddQueryAssignUpdate};
+///
+/// let mut lst = LazySegmentTree::<AddQueryAssignUpdate<i64>>::from(vec![1, 2, 3, 4]);
+/// lst.range_update(1..3, &Some(10));
+///
+/// assert_eq!(lst.range_query(..), 1 + 10 + 10 + 4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AddQueryAssignUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for AddQueryAssignUpdate<T>
+where
+    T: Clone + Zero + FromPrimitive,
+    for<'a> &'a T: std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+{
+    type Map = Assign<T>;
+    type Set = Add<T>;
+
+    const USE_SEGMENT_SIZE: bool = true;
+
+    /// # Panic
+    ///
+    /// Panics if `T` is too small to represent the segment size.
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        match mapping {
+            Some(new_element) => {
+                let size: T = convert_size(size.unwrap());
+                new_element * &size
+            }
+            None => element.clone(),
+        }
+    }
+}
+
 /// Performs **range add query range mul update**.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct AddQueryMulUpdate<T>(PhantomData<T>);
@@ -98,6 +149,215 @@ where
     }
 }
 
+/// Performs **range add query range affine update** modulo `M`.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::ModAffineSum};
+///
+/// const MOD: u64 = 998_244_353;
+///
+/// let mut lst = LazySegmentTree::<ModAffineSum<MOD>>::from(vec![1, 2, 3]);
+/// lst.range_update(1.., &(2, MOD - 1));
+///
+/// assert_eq!(lst.range_query(..), 1 + 3 + 5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModAffineSum<const M: u64>;
+
+impl<const M: u64> MonoidAction for ModAffineSum<M> {
+    type Map = ModAffine<M>;
+    type Set = ModAdd<M>;
+
+    const USE_SEGMENT_SIZE: bool = true;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        mod_add::<M>(
+            mod_mul::<M>(mapping.0, *element),
+            mod_mul::<M>(mapping.1, size.unwrap() as u64 % M),
+        )
+    }
+}
+
+/// Performs **range sum of squares query range add update**.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::SumSqQueryAddUpdate, ops::SumSq};
+///
+/// let mut lst =
+///     LazySegmentTree::<SumSqQueryAddUpdate<i64>>::from_iter([1, 2, 3].map(SumSq::singleton));
+/// lst.range_update(1.., &10);
+///
+/// assert_eq!(lst.range_query(..), (3, 1 + 12 + 13, 1 + 144 + 169));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SumSqQueryAddUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for SumSqQueryAddUpdate<T>
+where
+    T: Clone + Zero,
+    for<'a> &'a T: std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+{
+    type Map = Add<T>;
+    type Set = SumSq<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        let (count, sum, sum_sq) = element;
+        // Σ(x + a)² = Σx² + 2aΣx + a²n
+        let cross = &(mapping + mapping) * sum;
+        let shift_sq = &(mapping * mapping) * count;
+        (
+            count.clone(),
+            sum + &(mapping * count),
+            &(sum_sq + &cross) + &shift_sq,
+        )
+    }
+}
+
+/// Performs **range weighted sum query range add update**.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::WeightedSumQueryAddUpdate, ops::WeightedSum};
+///
+/// let mut lst =
+///     LazySegmentTree::<WeightedSumQueryAddUpdate<i64>>::from_iter([0; 4].map(WeightedSum::singleton));
+/// lst.range_update(1.., &2);
+///
+/// // 0 * 2 + 1 * 2 + 2 * 2
+/// assert_eq!(lst.range_query(1..), (3, 6, 6));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WeightedSumQueryAddUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for WeightedSumQueryAddUpdate<T>
+where
+    T: Clone + Zero + One,
+    for<'a> &'a T: std::ops::Add<Output = T>
+        + std::ops::Sub<Output = T>
+        + std::ops::Mul<Output = T>
+        + std::ops::Div<Output = T>,
+{
+    type Map = Add<T>;
+    type Set = WeightedSum<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        let (count, sum, weighted_sum) = element;
+        // Σ i for i in 0..count, where the count is stored in the element
+        let offsets = if count.is_zero() {
+            T::zero()
+        } else {
+            &(count * &(count - &T::one())) / &(&T::one() + &T::one())
+        };
+        (
+            count.clone(),
+            sum + &(mapping * count),
+            weighted_sum + &(mapping * &offsets),
+        )
+    }
+}
+
+/// Performs **range count of ones query range flip update** on a 0/1 array.
+///
+/// Each element is the number of ones, and `true` flips the bits.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::SumOnesQueryFlipUpdate};
+///
+/// let mut lst = LazySegmentTree::<SumOnesQueryFlipUpdate>::from(vec![1, 0, 0, 1]);
+/// lst.range_update(1.., &true);
+///
+/// assert_eq!(lst.range_query(..), 3);
+/// assert_eq!(lst.range_query(3..), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SumOnesQueryFlipUpdate;
+
+impl MonoidAction for SumOnesQueryFlipUpdate {
+    type Map = Parity;
+    type Set = Add<usize>;
+
+    const USE_SEGMENT_SIZE: bool = true;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        if *mapping {
+            size.unwrap() - element
+        } else {
+            *element
+        }
+    }
+}
+
+/// Performs **range vector sum query range linear update** by `N × N` matrices.
+///
+/// The vectors are rows, so `v` is mapped to `v × M`,
+/// which makes [`Matrix`]'s order of multiplication the order of updates.
+/// To perform affine updates, keep a component that is `1` at every leaf, which sums up to the segment size.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::MatrixAffineOnVectorSum};
+///
+/// // (a, b, 1) for each element
+/// let mut lst = LazySegmentTree::<MatrixAffineOnVectorSum<i64, 3>>::from(vec![[1, 0, 1]; 4]);
+///
+/// // a ← a + 2 b + 3, then b ← b + a
+/// lst.range_update(.., &[[1, 0, 0], [2, 1, 0], [3, 0, 1]]);
+/// lst.range_update(1.., &[[1, 1, 0], [0, 1, 0], [0, 0, 1]]);
+///
+/// assert_eq!(lst.range_query(..), [16, 12, 4]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MatrixAffineOnVectorSum<T, const N: usize>(PhantomData<T>);
+
+impl<T, const N: usize> MonoidAction for MatrixAffineOnVectorSum<T, N>
+where
+    T: One + Zero,
+    for<'a> &'a T: std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+{
+    type Map = Matrix<T, N>;
+    type Set = VectorSum<T, N>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        std::array::from_fn(|j| {
+            (0..N).fold(T::zero(), |acc, i| &acc + &(&element[i] * &mapping[i][j]))
+        })
+    }
+}
+
 /// Performs **range gcd query range mul update**.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct GCDQueryMulUpdate<T>(PhantomData<T>);
@@ -144,6 +404,76 @@ where
     }
 }
 
+/// Performs **range bitwise or query range bitwise or update**.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::BitOrQueryOrUpdate};
+///
+/// let mut lst = LazySegmentTree::<BitOrQueryOrUpdate<u8>>::from(vec![0b001, 0b010, 0b000]);
+/// lst.range_update(1.., &0b100);
+///
+/// assert_eq!(lst.range_query(..1), 0b001);
+/// assert_eq!(lst.range_query(2..), 0b100);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitOrQueryOrUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for BitOrQueryOrUpdate<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::BitOr<Output = T>,
+{
+    type Map = BitOr<T>;
+    type Set = BitOr<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        element | mapping
+    }
+}
+
+/// Performs **range bitwise and query range bitwise and update**.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::BitAndQueryAndUpdate};
+///
+/// let mut lst = LazySegmentTree::<BitAndQueryAndUpdate<u8>>::from(vec![0b111, 0b110, 0b011]);
+/// lst.range_update(1.., &0b101);
+///
+/// assert_eq!(lst.range_query(..), 0b000);
+/// assert_eq!(lst.range_query(..2), 0b100);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitAndQueryAndUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for BitAndQueryAndUpdate<T>
+where
+    T: Zero + std::ops::Not<Output = T>,
+    for<'a> &'a T: std::ops::BitAnd<Output = T>,
+{
+    type Map = BitAnd<T>;
+    type Set = BitAnd<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        element & mapping
+    }
+}
+
 /// Performs **range max query range add update**.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MaxQueryAddUpdate<T>(PhantomData<T>);
@@ -190,6 +520,210 @@ where
     }
 }
 
+/// Performs **range min and max query range add update**.
+///
+/// Each element is `(min, max)`.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::MinMaxQueryAddUpdate};
+///
+/// let mut lst =
+///     LazySegmentTree::<MinMaxQueryAddUpdate<i64>>::from_iter([3, 1, 4, 1, 5].map(|v| (Some(v), Some(v))));
+/// lst.range_update(2.., &-3);
+///
+/// assert_eq!(lst.range_query(..), (Some(-2), Some(3)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MinMaxQueryAddUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for MinMaxQueryAddUpdate<T>
+where
+    T: Clone + Zero,
+    for<'a> &'a T: Ord + std::ops::Add<Output = T>,
+{
+    type Map = Add<T>;
+    type Set = (Min<T>, Max<T>);
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        (
+            element.0.as_ref().map(|min| mapping + min),
+            element.1.as_ref().map(|max| mapping + max),
+        )
+    }
+}
+
+/// Performs **range max count query range add update**.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaxCountQueryAddUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for MaxCountQueryAddUpdate<T>
+where
+    T: Clone + Zero,
+    for<'a> &'a T: Ord + std::ops::Add<Output = T>,
+{
+    type Map = Add<T>;
+    type Set = MaxCount<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        element
+            .as_ref()
+            .map(|(element, count)| (mapping + element, *count))
+    }
+}
+
+/// Performs **range min count query range add update**.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MinCountQueryAddUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for MinCountQueryAddUpdate<T>
+where
+    T: Clone + Zero,
+    for<'a> &'a T: Ord + std::ops::Add<Output = T>,
+{
+    type Map = Add<T>;
+    type Set = MinCount<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        element
+            .as_ref()
+            .map(|(element, count)| (mapping + element, *count))
+    }
+}
+
+/// Performs **range max query range assign update**.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaxQueryAssignUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for MaxQueryAssignUpdate<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+    type Map = Assign<T>;
+    type Set = Max<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        mapping.as_ref().or(element.as_ref()).cloned()
+    }
+}
+
+/// Performs **range min query range assign update**.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MinQueryAssignUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for MinQueryAssignUpdate<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+    type Map = Assign<T>;
+    type Set = Min<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        mapping.as_ref().or(element.as_ref()).cloned()
+    }
+}
+
+/// Performs **range max query range chmax update**.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaxQueryChmaxUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for MaxQueryChmaxUpdate<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+    type Map = Max<T>;
+    type Set = Max<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        // an empty segment stays empty
+        if element.is_none() {
+            None
+        } else {
+            <Max<T> as Monoid>::combine(mapping, element)
+        }
+    }
+}
+
+/// Performs **range min query range chmin update**.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::MinQueryChminUpdate};
+///
+/// let mut lst = LazySegmentTree::<MinQueryChminUpdate<i32>>::from(vec![Some(3), Some(1), Some(4)]);
+/// lst.range_update(1.., &Some(2));
+///
+/// assert_eq!(lst.range_query(2..), Some(2));
+/// assert_eq!(lst.range_query(..2), Some(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MinQueryChminUpdate<T>(PhantomData<T>);
+
+impl<T> MonoidAction for MinQueryChminUpdate<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+    type Map = Min<T>;
+    type Set = Min<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        // an empty segment stays empty
+        if element.is_none() {
+            None
+        } else {
+            <Min<T> as Monoid>::combine(mapping, element)
+        }
+    }
+}
+
 /// Performs **range max query range assign or add update**.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MaxQueryAddOrAssignUpdate<T>(PhantomData<T>);
@@ -247,3 +781,834 @@ where
         }
     }
 }
+
+/// Performs **range any query range assign update**.
+///
+/// The assigned aggregate of a segment is computed by [`Monoid::combine_n()`],
+/// so that `M` may be any [`Monoid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssignAction<M>(PhantomData<M>);
+
+impl<M> MonoidAction for AssignAction<M>
+where
+    M: Monoid<Set: Clone>,
+{
+    type Map = Assign<<M as Monoid>::Set>;
+    type Set = M;
+
+    const USE_SEGMENT_SIZE: bool = true;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        match mapping {
+            Some(new_element) => <M as Monoid>::combine_n(new_element, size.unwrap()),
+            None => element.clone(),
+        }
+    }
+
+    /// Leaves the element untouched unless it is assigned, avoiding a clone.
+    fn act_assign(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &mut <Self::Set as Monoid>::Set,
+        size: Option<usize>,
+    ) {
+        if let Some(new_element) = mapping {
+            *element = <M as Monoid>::combine_n(new_element, size.unwrap())
+        }
+    }
+}
+
+/// The update of [`AssignOrAction`]: either `=` or the update of `A`.
+pub enum AssignOrMap<A>
+where
+    A: MonoidAction,
+{
+    /// `=` operation, which assigns the value to every element in the range.
+    Assign(<<A as MonoidAction>::Set as Monoid>::Set),
+    /// the update of `A`.
+    Other(<<A as MonoidAction>::Map as Monoid>::Set),
+}
+
+impl<A> Monoid for AssignOrMap<A>
+where
+    A: MonoidAction,
+    <<A as MonoidAction>::Set as Monoid>::Set: Clone,
+{
+    type Set = Self;
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        Self::Other(<<A as MonoidAction>::Map as Monoid>::identity())
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        match (lhs_or_prev, rhs_or_new) {
+            (_, AssignOrMap::Assign(rhs_or_new)) => Self::Assign(rhs_or_new.clone()),
+            // every element has the same value, so that the update can be applied in advance.
+            (AssignOrMap::Assign(lhs_or_prev), AssignOrMap::Other(rhs_or_new)) => {
+                Self::Assign(<A as MonoidAction>::act(rhs_or_new, lhs_or_prev, Some(1)))
+            }
+            (AssignOrMap::Other(lhs_or_prev), AssignOrMap::Other(rhs_or_new)) => Self::Other(
+                <<A as MonoidAction>::Map as Monoid>::combine(lhs_or_prev, rhs_or_new),
+            ),
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        match element {
+            AssignOrMap::Assign(_) => false,
+            AssignOrMap::Other(element) => {
+                <<A as MonoidAction>::Map as Monoid>::is_identity(element)
+            }
+        }
+    }
+}
+
+impl<A> Debug for AssignOrMap<A>
+where
+    A: MonoidAction,
+    <<A as MonoidAction>::Set as Monoid>::Set: Debug,
+    <<A as MonoidAction>::Map as Monoid>::Set: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Assign(arg0) => f.debug_tuple("Assign").field(arg0).finish(),
+            Self::Other(arg0) => f.debug_tuple("Other").field(arg0).finish(),
+        }
+    }
+}
+
+impl<A> Clone for AssignOrMap<A>
+where
+    A: MonoidAction,
+    <<A as MonoidAction>::Set as Monoid>::Set: Clone,
+    <<A as MonoidAction>::Map as Monoid>::Set: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Assign(arg0) => Self::Assign(arg0.clone()),
+            Self::Other(arg0) => Self::Other(arg0.clone()),
+        }
+    }
+}
+
+/// Performs **range assign or `A` update** with the query of `A`.
+///
+/// For example, `AssignOrAction<AddQueryAddUpdate<i64>>` performs range sum query range assign or add update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssignOrAction<A>(PhantomData<A>);
+
+impl<A> MonoidAction for AssignOrAction<A>
+where
+    A: MonoidAction,
+    <<A as MonoidAction>::Set as Monoid>::Set: Clone,
+{
+    type Map = AssignOrMap<A>;
+    type Set = <A as MonoidAction>::Set;
+
+    const USE_SEGMENT_SIZE: bool = true;
+
+    fn act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        size: Option<usize>,
+    ) -> <Self::Set as Monoid>::Set {
+        match mapping {
+            AssignOrMap::Assign(new_element) => {
+                <<A as MonoidAction>::Set as Monoid>::combine_n(new_element, size.unwrap())
+            }
+            AssignOrMap::Other(mapping) => <A as MonoidAction>::act(mapping, element, size),
+        }
+    }
+
+    fn act_assign(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &mut <Self::Set as Monoid>::Set,
+        size: Option<usize>,
+    ) {
+        match mapping {
+            AssignOrMap::Assign(new_element) => {
+                *element =
+                    <<A as MonoidAction>::Set as Monoid>::combine_n(new_element, size.unwrap())
+            }
+            AssignOrMap::Other(mapping) => <A as MonoidAction>::act_assign(mapping, element, size),
+        }
+    }
+}
+
+/// A node of [`ChminChmaxAddSum`], which describes a non-empty segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BeatsNode<T> {
+    /// The sum of the elements.
+    pub sum: T,
+    /// The number of the elements.
+    pub len: usize,
+    /// The largest element.
+    pub max: T,
+    /// The second largest distinct element, if any.
+    pub second_max: Option<T>,
+    /// The number of the largest elements.
+    pub max_count: usize,
+    /// The smallest element.
+    pub min: T,
+    /// The second smallest distinct element, if any.
+    pub second_min: Option<T>,
+    /// The number of the smallest elements.
+    pub min_count: usize,
+}
+
+impl<T> BeatsNode<T>
+where
+    T: Copy,
+{
+    /// Returns the node which consists of a single value.
+    pub fn singleton(value: T) -> Self {
+        Self {
+            sum: value,
+            len: 1,
+            max: value,
+            second_max: None,
+            max_count: 1,
+            min: value,
+            second_min: None,
+            min_count: 1,
+        }
+    }
+}
+
+/// Merges [`BeatsNode`]s, where [`None`] represents an empty segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BeatsSet<T>(PhantomData<T>);
+
+impl<T> Monoid for BeatsSet<T>
+where
+    T: Copy + Ord + std::ops::Add<Output = T>,
+{
+    type Set = Option<BeatsNode<T>>;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        None
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        let (lhs, rhs) = match (lhs_or_prev, rhs_or_new) {
+            (None, _) => return *rhs_or_new,
+            (_, None) => return *lhs_or_prev,
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+
+        // `None` is the smallest in `Option<T>`
+        let (max, second_max, max_count) = match lhs.max.cmp(&rhs.max) {
+            Ordering::Greater => (lhs.max, lhs.second_max.max(Some(rhs.max)), lhs.max_count),
+            Ordering::Less => (rhs.max, rhs.second_max.max(Some(lhs.max)), rhs.max_count),
+            Ordering::Equal => (
+                lhs.max,
+                lhs.second_max.max(rhs.second_max),
+                lhs.max_count + rhs.max_count,
+            ),
+        };
+        let min_some = |a: Option<T>, b: Option<T>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            _ => a.or(b),
+        };
+        let (min, second_min, min_count) = match lhs.min.cmp(&rhs.min) {
+            Ordering::Less => (
+                lhs.min,
+                min_some(lhs.second_min, Some(rhs.min)),
+                lhs.min_count,
+            ),
+            Ordering::Greater => (
+                rhs.min,
+                min_some(rhs.second_min, Some(lhs.min)),
+                rhs.min_count,
+            ),
+            Ordering::Equal => (
+                lhs.min,
+                min_some(lhs.second_min, rhs.second_min),
+                lhs.min_count + rhs.min_count,
+            ),
+        };
+
+        Some(BeatsNode {
+            sum: lhs.sum + rhs.sum,
+            len: lhs.len + rhs.len,
+            max,
+            second_max,
+            max_count,
+            min,
+            second_min,
+            min_count,
+        })
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
+
+/// Composes `x ↦ min(max(x + add, lower), upper)`, where each element is `(add, lower, upper)`.
+///
+/// [`None`] bounds are unbounded. The lower bound should not exceed the upper bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChminChmaxAdd<T>(PhantomData<T>);
+
+impl<T> ChminChmaxAdd<T>
+where
+    T: Zero,
+{
+    /// Returns `x ↦ x + value`.
+    pub fn add(value: T) -> (T, Option<T>, Option<T>) {
+        (value, None, None)
+    }
+
+    /// Returns `x ↦ min(x, value)`.
+    pub fn chmin(value: T) -> (T, Option<T>, Option<T>) {
+        (T::zero(), None, Some(value))
+    }
+
+    /// Returns `x ↦ max(x, value)`.
+    pub fn chmax(value: T) -> (T, Option<T>, Option<T>) {
+        (T::zero(), Some(value), None)
+    }
+}
+
+impl<T> Monoid for ChminChmaxAdd<T>
+where
+    T: Copy + Ord + Zero,
+{
+    type Set = (T, Option<T>, Option<T>);
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        (T::zero(), None, None)
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        let &(add, lower, upper) = rhs_or_new;
+        let clamp = |x: T| {
+            let x = lower.map_or(x, |lower| x.max(lower));
+            upper.map_or(x, |upper| x.min(upper))
+        };
+        (
+            lhs_or_prev.0 + add,
+            lhs_or_prev.1.map(|x| clamp(x + add)).or(lower),
+            lhs_or_prev.2.map(|x| clamp(x + add)).or(upper),
+        )
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.0.is_zero() && element.1.is_none() && element.2.is_none()
+    }
+}
+
+/// Performs **range sum query range chmin, chmax and add update** in the manner of Segment Tree Beats.
+///
+/// [`try_act()`](QuasiMonoidAction::try_act) fails if the bounds cut deeper than the second largest or smallest element,
+/// in which case the mapping should be pushed to the children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChminChmaxAddSum<T>(PhantomData<T>);
+
+impl<T> QuasiMonoidAction for ChminChmaxAddSum<T>
+where
+    T: Copy + Ord + Zero + FromPrimitive + std::ops::Sub<Output = T> + std::ops::Mul<Output = T>,
+{
+    type Map = ChminChmaxAdd<T>;
+    type Set = BeatsSet<T>;
+
+    const USE_SEGMENT_SIZE: bool = false;
+
+    /// # Panic
+    ///
+    /// Panics if `T` is too small to represent the segment size.
+    fn try_act(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &<Self::Set as Monoid>::Set,
+        _size: Option<usize>,
+    ) -> Result<<Self::Set as Monoid>::Set, ()> {
+        let Some(mut node) = *element else {
+            return Ok(None);
+        };
+        let &(add, lower, upper) = mapping;
+
+        if !add.is_zero() {
+            node.sum = node.sum + add * convert_size(node.len);
+            node.max = node.max + add;
+            node.second_max = node.second_max.map(|x| x + add);
+            node.min = node.min + add;
+            node.second_min = node.second_min.map(|x| x + add);
+        }
+
+        if let Some(lower) = lower
+            && node.min < lower
+        {
+            if node
+                .second_min
+                .is_some_and(|second_min| second_min <= lower)
+            {
+                return Err(());
+            }
+            node.sum = node.sum + (lower - node.min) * convert_size(node.min_count);
+            if node.max == node.min {
+                node.max = lower
+            } else if node.second_max == Some(node.min) {
+                node.second_max = Some(lower)
+            }
+            node.min = lower
+        }
+
+        if let Some(upper) = upper
+            && upper < node.max
+        {
+            if node
+                .second_max
+                .is_some_and(|second_max| upper <= second_max)
+            {
+                return Err(());
+            }
+            node.sum = node.sum - (node.max - upper) * convert_size(node.max_count);
+            if node.min == node.max {
+                node.min = upper
+            } else if node.second_min == Some(node.max) {
+                node.second_min = Some(upper)
+            }
+            node.max = upper
+        }
+
+        Ok(Some(node))
+    }
+}
+
+#[cfg(test)]
+mod assign_action {
+    use rand::Rng;
+
+    use crate::{
+        LazySegmentTree,
+        acts::{AddQueryAddUpdate, AssignAction},
+        ops::{Add, Affine},
+    };
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![(1_i64, 0_i64); n];
+            let mut lst = LazySegmentTree::<AssignAction<Affine<i64>>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                lst.range_update(l..r, &Some(update));
+                naive[l..r].fill(update);
+
+                for l in 0..n {
+                    for r in l..=n {
+                        let expected = naive[l..r]
+                            .iter()
+                            .fold((1, 0), |acc, &(a, b)| (a * acc.0, a * acc.1 + b));
+                        assert_eq!(lst.range_query(l..r), expected)
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn with_other_updates() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut assigned = vec![0_i64; n];
+            let mut added = vec![0_i64; n];
+            let mut lst =
+                LazySegmentTree::<(AssignAction<Add<i64>>, AddQueryAddUpdate<i64>)>::new(n);
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let value = rng.random_range(0..11_usize) as i64 - 5;
+                lst.range_update(l..r, &(Some(value), value));
+                assigned[l..r].fill(value);
+                added[l..r].iter_mut().for_each(|e| *e += value);
+
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(
+                            lst.range_query(l..r),
+                            (
+                                assigned[l..r].iter().sum::<i64>(),
+                                added[l..r].iter().sum::<i64>()
+                            )
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod act_assign {
+    use rand::Rng;
+
+    use crate::{
+        LazySegmentTree, MonoidAction,
+        acts::{AddQueryAffineUpdate, AssignAction, AssignOrAction, AssignOrMap},
+        ops::Concat,
+    };
+
+    #[test]
+    fn agrees_with_act() {
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            let mapping = rng.random_bool(0.5).then(|| rng.random_range(0..10));
+            let mapping = mapping.map(|v| vec![v]);
+            let mut element = vec![rng.random_range(0..10)];
+            let size = Some(rng.random_range(1..4));
+            let expected = AssignAction::<Concat<i32>>::act(&mapping, &element, size);
+            AssignAction::<Concat<i32>>::act_assign(&mapping, &mut element, size);
+            assert_eq!(element, expected);
+
+            let mapping = if rng.random_bool(0.5) {
+                AssignOrMap::Assign(rng.random_range(-5..5))
+            } else {
+                AssignOrMap::Other((rng.random_range(-2..2), rng.random_range(-5..5)))
+            };
+            let mut element = rng.random_range(-5..5_i64);
+            let expected =
+                AssignOrAction::<AddQueryAffineUpdate<i64>>::act(&mapping, &element, size);
+            AssignOrAction::<AddQueryAffineUpdate<i64>>::act_assign(&mapping, &mut element, size);
+            assert_eq!(element, expected);
+        }
+    }
+
+    #[test]
+    fn heap_elements() {
+        let mut rng = rand::rng();
+        for n in 1..30 {
+            let mut naive = vec![0; n];
+            let mut lst = LazySegmentTree::<AssignAction<Concat<i32>>>::from(vec![vec![0]; n]);
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let value = rng.random_range(0..10);
+                lst.range_update(l..r, &Some(vec![value]));
+                naive[l..r].fill(value);
+
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r), naive[l..r])
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod assign_or_action {
+    use rand::Rng;
+
+    use crate::{
+        LazySegmentTree,
+        acts::{AddQueryAffineUpdate, AssignOrAction, AssignOrMap},
+    };
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i64; n];
+            let mut lst = LazySegmentTree::<AssignOrAction<AddQueryAffineUpdate<i64>>>::new(n);
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                if rng.random_bool(0.3) {
+                    let value = rng.random_range(0..11_usize) as i64 - 5;
+                    lst.range_update(l..r, &AssignOrMap::Assign(value));
+                    naive[l..r].fill(value);
+                } else {
+                    let update = (rng.random_range(-1..=1), rng.random_range(-5..=5));
+                    lst.range_update(l..r, &AssignOrMap::Other(update));
+                    for e in &mut naive[l..r] {
+                        *e = update.0 * *e + update.1
+                    }
+                }
+
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r), naive[l..r].iter().sum::<i64>())
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod min_count_query_add_update {
+    use rand::Rng;
+
+    use crate::{LazySegmentTree, acts::MinCountQueryAddUpdate};
+
+    /// Counts uncovered cells, which is the core of the area of union of rectangles.
+    #[test]
+    fn coverage() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i64; n];
+            let mut lst =
+                LazySegmentTree::<MinCountQueryAddUpdate<i64>>::from_elem(n, Some((0, 1)));
+            let mut intervals = Vec::new();
+
+            for _ in 0..40 {
+                if intervals.is_empty() || rng.random_bool(0.6) {
+                    let l = rng.random_range(0..n);
+                    let r = rng.random_range(l..=n);
+                    lst.range_update(l..r, &1);
+                    intervals.push(l..r);
+                } else {
+                    let range = intervals.swap_remove(rng.random_range(0..intervals.len()));
+                    lst.range_update(range.clone(), &-1);
+                }
+                naive.fill(0);
+                for range in &intervals {
+                    naive[range.clone()].iter_mut().for_each(|e| *e += 1)
+                }
+
+                for l in 0..n {
+                    for r in l + 1..=n {
+                        let min = *naive[l..r].iter().min().unwrap();
+                        let count = naive[l..r].iter().filter(|&&e| e == min).count();
+                        assert_eq!(lst.range_query(l..r), Some((min, count)))
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod max_query_assign_update {
+    use rand::Rng;
+
+    use crate::{LazySegmentTree, acts::MaxQueryAssignUpdate};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i64; n];
+            let mut lst = LazySegmentTree::<MaxQueryAssignUpdate<i64>>::from_elem(n, Some(0));
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let value = rng.random_range(0..21_usize) as i64 - 10;
+                lst.range_update(l..r, &Some(value));
+                naive[l..r].fill(value);
+
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r), naive[l..r].iter().max().copied())
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod sum_sq_query_add_update {
+    use rand::Rng;
+
+    use crate::{LazySegmentTree, acts::SumSqQueryAddUpdate, ops::SumSq};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = Vec::from_iter((0..n as i64).map(|v| v % 7 - 3));
+            let mut lst = LazySegmentTree::<SumSqQueryAddUpdate<i64>>::from_iter(
+                naive.iter().copied().map(SumSq::singleton),
+            );
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let add = rng.random_range(0..11_usize) as i64 - 5;
+                lst.range_update(l..r, &add);
+                for e in &mut naive[l..r] {
+                    *e += add
+                }
+
+                for l in 0..n {
+                    for r in l..=n {
+                        let naive = &naive[l..r];
+                        assert_eq!(
+                            lst.range_query(l..r),
+                            (
+                                naive.len() as i64,
+                                naive.iter().sum::<i64>(),
+                                naive.iter().map(|e| e * e).sum::<i64>()
+                            )
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod weighted_sum_query_add_update {
+    use rand::Rng;
+
+    use crate::{
+        DynamicLazySegmentTree, LazySegmentTree, acts::WeightedSumQueryAddUpdate, ops::WeightedSum,
+    };
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = Vec::from_iter((0..n as i64).map(|v| v % 7 - 3));
+            let mut lst = LazySegmentTree::<WeightedSumQueryAddUpdate<i64>>::from_iter(
+                naive.iter().copied().map(WeightedSum::singleton),
+            );
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let add = rng.random_range(0..11_usize) as i64 - 5;
+                lst.range_update(l..r, &add);
+                for e in &mut naive[l..r] {
+                    *e += add
+                }
+
+                for l in 0..n {
+                    for r in l..=n {
+                        let naive = &naive[l..r];
+                        assert_eq!(
+                            lst.range_query(l..r),
+                            (
+                                naive.len() as i64,
+                                naive.iter().sum::<i64>(),
+                                (0..).zip(naive).map(|(i, e)| i * e).sum::<i64>()
+                            )
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn identity_leaves() {
+        // the leaves of `new` hold no elements, so there is nothing to add to
+        let mut lst = LazySegmentTree::<WeightedSumQueryAddUpdate<i64>>::new(4);
+        lst.range_update(.., &2);
+        assert_eq!(lst.range_query(..), (0, 0, 0));
+
+        let mut dlst = DynamicLazySegmentTree::<WeightedSumQueryAddUpdate<i64>>::new(0..4).unwrap();
+        dlst.range_update(.., &2);
+        assert_eq!(dlst.range_query(..), (0, 0, 0));
+
+        let mut dlst = DynamicLazySegmentTree::<WeightedSumQueryAddUpdate<i64>>::with_default(
+            0..4,
+            WeightedSum::singleton(0),
+        )
+        .unwrap();
+        dlst.range_update(1.., &2);
+        assert_eq!(dlst.range_query(..), (4, 6, 2 + 4 + 6));
+    }
+
+    #[test]
+    fn weighted() {
+        // the offsets count the elements, not the weights
+        let mut lst = LazySegmentTree::<WeightedSumQueryAddUpdate<i64>>::with_weights(
+            vec![WeightedSum::singleton(0); 2],
+            vec![2, 1],
+        );
+        lst.range_update(.., &1);
+        assert_eq!(lst.range_query(..), (2, 2, 1));
+    }
+
+    #[test]
+    fn unsigned() {
+        let mut lst = LazySegmentTree::<WeightedSumQueryAddUpdate<u64>>::new(3);
+        lst.range_update(.., &1);
+        assert_eq!(lst.range_query(..), (0, 0, 0));
+    }
+}
+
+#[cfg(test)]
+mod chmin_chmax_add_sum {
+    use rand::Rng;
+
+    use crate::{
+        Monoid, QuasiMonoidAction,
+        acts::{BeatsNode, BeatsSet, ChminChmaxAdd, ChminChmaxAddSum},
+    };
+
+    fn random_mapping(rng: &mut impl Rng) -> (i64, Option<i64>, Option<i64>) {
+        let value = rng.random_range(0..21_usize) as i64 - 10;
+        match rng.random_range(0..3) {
+            0 => ChminChmaxAdd::add(value),
+            1 => ChminChmaxAdd::chmin(value),
+            _ => ChminChmaxAdd::chmax(value),
+        }
+    }
+
+    fn apply(mapping: &(i64, Option<i64>, Option<i64>), x: i64) -> i64 {
+        let x = x + mapping.0;
+        let x = mapping.1.map_or(x, |lower| x.max(lower));
+        mapping.2.map_or(x, |upper| x.min(upper))
+    }
+
+    fn node(values: &[i64]) -> Option<BeatsNode<i64>> {
+        values.iter().fold(BeatsSet::identity(), |acc, &v| {
+            BeatsSet::combine(&acc, &Some(BeatsNode::singleton(v)))
+        })
+    }
+
+    #[test]
+    fn composition() {
+        let mut rng = rand::rng();
+        for _ in 0..1_000 {
+            let f = random_mapping(&mut rng);
+            let g = random_mapping(&mut rng);
+            let fg = ChminChmaxAdd::combine(&f, &g);
+            for x in -30..=30 {
+                assert_eq!(apply(&fg, x), apply(&g, apply(&f, x)), "{f:?} {g:?}")
+            }
+        }
+    }
+
+    #[test]
+    fn try_act() {
+        let mut rng = rand::rng();
+        for _ in 0..1_000 {
+            let len = rng.random_range(1..8);
+            let values = Vec::from_iter((0..len).map(|_| rng.random_range(0..11_usize) as i64 - 5));
+            let mapping =
+                ChminChmaxAdd::combine(&random_mapping(&mut rng), &random_mapping(&mut rng));
+
+            let updated = Vec::from_iter(values.iter().map(|&x| apply(&mapping, x)));
+            match ChminChmaxAddSum::try_act(&mapping, &node(&values), None) {
+                Ok(result) => assert_eq!(result, node(&updated), "{values:?} {mapping:?}"),
+                // a leaf never fails
+                Err(()) => assert!(len > 1),
+            }
+        }
+    }
+}
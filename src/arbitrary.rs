@@ -0,0 +1,103 @@
+//! [`Arbitrary`] implementations for the trees and the [naive models](crate::naive).
+//!
+//! Each value is built from a [`Vec`] of arbitrary elements,
+//! whose length is given by the [`SizeRange`] in the parameters.
+
+use proptest::{
+    arbitrary::{Arbitrary, any_with},
+    collection::{SizeRange, VecStrategy, vec},
+    strategy::{Map, Strategy},
+};
+
+use crate::{
+    AssignSegmentTree, BufferedSegmentTree, DequeSegmentTree, DualSegmentTree, LazySegmentTree,
+    SegmentTree, VebSegmentTree,
+    naive::{NaiveDualSegmentTree, NaiveLazySegmentTree, NaiveSegmentTree},
+    traits::{Monoid, MonoidAction},
+};
+
+macro_rules! arbitrary_impl {
+    ($tree:ident<$p:ident>, $set:ty, $($bound:tt)+) => {
+        impl<$p> Arbitrary for $tree<$p>
+        where
+            $p: $($bound)+,
+        {
+            type Parameters = (SizeRange, <$set as Arbitrary>::Parameters);
+            type Strategy = Map<VecStrategy<<$set as Arbitrary>::Strategy>, fn(Vec<$set>) -> Self>;
+
+            fn arbitrary_with((size, params): Self::Parameters) -> Self::Strategy {
+                vec(any_with::<$set>(params), size).prop_map(Self::from as fn(_) -> _)
+            }
+        }
+    };
+}
+
+arbitrary_impl!(SegmentTree<Query>, <Query as Monoid>::Set, Monoid<Set: Arbitrary>);
+arbitrary_impl!(VebSegmentTree<Query>, <Query as Monoid>::Set, Monoid<Set: Arbitrary>);
+arbitrary_impl!(BufferedSegmentTree<Query>, <Query as Monoid>::Set, Monoid<Set: Arbitrary>);
+arbitrary_impl!(DequeSegmentTree<Query>, <Query as Monoid>::Set, Monoid<Set: Arbitrary>);
+arbitrary_impl!(
+    AssignSegmentTree<Query>,
+    <Query as Monoid>::Set,
+    Monoid<Set: Clone + Arbitrary>
+);
+arbitrary_impl!(DualSegmentTree<Update>, <Update as Monoid>::Set, Monoid<Set: Arbitrary>);
+arbitrary_impl!(
+    LazySegmentTree<Action>,
+    <<Action as MonoidAction>::Set as Monoid>::Set,
+    MonoidAction<Set: Monoid<Set: Arbitrary>, Map: Monoid<Set: std::fmt::Debug>>
+);
+arbitrary_impl!(NaiveSegmentTree<Query>, <Query as Monoid>::Set, Monoid<Set: Arbitrary>);
+arbitrary_impl!(NaiveDualSegmentTree<Update>, <Update as Monoid>::Set, Monoid<Set: Arbitrary>);
+arbitrary_impl!(
+    NaiveLazySegmentTree<Action>,
+    <<Action as MonoidAction>::Set as Monoid>::Set,
+    MonoidAction<Set: Monoid<Set: Arbitrary>>
+);
+
+#[cfg(test)]
+mod strategies {
+    use proptest::prelude::*;
+
+    use crate::{
+        LazySegmentTree, SegmentTree,
+        acts::AddQueryAffineUpdate,
+        naive::{NaiveLazySegmentTree, NaiveSegmentTree},
+        ops::Max,
+    };
+
+    proptest! {
+        #[test]
+        fn naive_segment_tree(model: NaiveSegmentTree<Max<i32>>) {
+            let st = SegmentTree::<Max<i32>>::from(model.as_slice().to_vec());
+            for l in 0..=model.len() {
+                prop_assert_eq!(st.range_query(l..), model.range_query(l..));
+            }
+        }
+
+        #[test]
+        fn segment_tree(st: SegmentTree<Max<i32>>, l: usize, r: usize) {
+            let (l, r) = if st.is_empty() { (0, 0) } else { (l % st.len(), r % st.len()) };
+            let (l, r) = (l.min(r), l.max(r));
+            prop_assert_eq!(st.range_query(l..r), st.as_leaf_slice()[l..r].iter().copied().max().flatten());
+        }
+
+        #[test]
+        fn lazy_segment_tree(
+            values in prop::collection::vec(-100..100_i64, 1..50),
+            updates in prop::collection::vec((any::<usize>(), any::<usize>(), -3..3_i64, -10..10_i64), 0..20),
+        ) {
+            let mut tree = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from(values.clone());
+            let mut model = NaiveLazySegmentTree::<AddQueryAffineUpdate<i64>>::from(values);
+            let n = model.len();
+            for (l, r, a, b) in updates {
+                let (l, r) = (l % n, r % n);
+                let (l, r) = (l.min(r), l.max(r) + 1);
+                tree.range_update(l..r, &(a, b));
+                model.range_update(l..r, &(a, b));
+                prop_assert_eq!(tree.range_query(..), model.range_query(..));
+            }
+            prop_assert_eq!(tree.to_vec(), model.as_slice());
+        }
+    }
+}
@@ -1,9 +1,21 @@
-use std::{fmt::Debug, ops::RangeBounds};
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+    ops::RangeBounds,
+};
 
-use crate::traits::Monoid;
+use crate::{
+    memory::{MemoryReport, vec_bytes},
+    snapshot::{self, Element},
+    traits::Monoid,
+};
 
 /// A data structure that supports **range query range assign** operations.
 ///
+/// Assigned values are cloned into the nodes.
+/// Wrap the operation with [`Shared`](crate::ops::Shared) if the elements are expensive to clone.
+///
 /// # Example
 ///
 /// ```
@@ -24,6 +36,29 @@ where
     buf_len: usize,
     /// n
     data_len: usize,
+
+    /// when to flush `lazy_map`
+    gc_policy: GcPolicy,
+}
+
+/// Determines when [`AssignSegmentTree`] flushes the table of assigned values.
+///
+/// Every [`range_assign`](AssignSegmentTree::range_assign) appends *O*(log *N*) values to the table,
+/// which is cleared by propagating all pending assignments in *O*(*N*) time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GcPolicy {
+    /// Flushes when the table holds as many values as the tree does, which amortizes the flush.
+    #[default]
+    Auto,
+    /// Flushes when the table holds at least the given number of values.
+    MaxEntries(usize),
+    /// Flushes when the table occupies at least the given number of bytes.
+    ///
+    /// Heap memory owned by the values is not taken into account.
+    MaxBytes(usize),
+    /// Never flushes automatically. Call [`gc`](AssignSegmentTree::gc) instead.
+    Manual,
 }
 
 impl<Query> AssignSegmentTree<Query>
@@ -49,6 +84,45 @@ where
         Self::from_iter(std::iter::repeat_n(<Query as Monoid>::identity(), n))
     }
 
+    #[doc = include_str!("../doc/from_elem.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::from_elem(100, 2);
+    /// assert_eq!(ast.range_query(..10), 20);
+    /// ```
+    #[inline]
+    pub fn from_elem(n: usize, element: <Query as Monoid>::Set) -> Self {
+        Self::from_iter(std::iter::repeat_n(element, n))
+    }
+
+    /// Creates a new instance initialized with clones of `values`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let values = [1, 2, 3, 4, 5];
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::from_slice(&values);
+    /// assert_eq!(ast.range_query(1..4), 9);
+    /// ```
+    #[inline]
+    pub fn from_slice(values: &[<Query as Monoid>::Set]) -> Self {
+        Self::from_iter(values.iter().cloned())
+    }
+
     /// Returns the number of elements.
     ///
     /// # Time complexity
@@ -63,12 +137,57 @@ where
     /// let ast = AssignSegmentTree::<Max<i32>>::new(100);
     /// assert_eq!(ast.len(), 100);
     /// ```
-    #[allow(clippy::len_without_is_empty)]
     #[inline]
     pub fn len(&self) -> usize {
         self.data_len
     }
 
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// Queries over an empty tree return [the identity element](crate::traits::Monoid::identity()),
+    /// and range updates do nothing.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let ast = AssignSegmentTree::<Add<i32>>::new(0);
+    /// assert!(ast.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data_len == 0
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let ast = AssignSegmentTree::<Add<u32>>::new(100);
+    /// // the table of assigned values is allocated in advance
+    /// assert!(ast.memory_usage().map >= 128 * size_of::<u32>());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            data: size_of_val(&*self.data),
+            lazy: size_of_val(&*self.lazy_ptr),
+            map: vec_bytes(&self.lazy_map),
+            ..Default::default()
+        }
+    }
+
     /// Returns an iterator over the elements
     ///
     /// # Time complexity
@@ -89,6 +208,171 @@ where
         self.data[self.buf_len..self.buf_len + self.data_len].iter()
     }
 
+    #[doc = include_str!("../doc/flush.md")]
+    ///
+    /// This is the same as [`gc`](Self::gc), which also clears the table of assigned values.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::from_iter(0..5);
+    /// ast.range_assign(1..4, 10);
+    ///
+    /// ast.flush();
+    /// assert!(ast.peek_iter().eq(&[0, 10, 10, 10, 4]));
+    /// ```
+    pub fn flush(&mut self) {
+        self.gc();
+    }
+
+    /// Returns the elements with all pending assignments resolved.
+    ///
+    /// Unlike [`iter`](Self::iter), the tree is left untouched.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::from_iter(0..5);
+    /// ast.range_assign(1..4, 10);
+    /// ast.range_assign(3.., 20);
+    ///
+    /// assert_eq!(ast.to_vec(), vec![0, 10, 10, 20, 20]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<<Query as Monoid>::Set> {
+        let half = self.data.len() >> 1;
+
+        // the newest assignment to the node, which is the one to the topmost ancestor
+        let mut map_ptr = Vec::with_capacity(half);
+        map_ptr.push(Self::NULL_MAP_PTR);
+        for i in 1..half {
+            let parent = map_ptr[i >> 1];
+            map_ptr.push(if parent == Self::NULL_MAP_PTR {
+                self.lazy_ptr[i]
+            } else {
+                parent - 1
+            });
+        }
+
+        Vec::from_iter((self.buf_len..self.buf_len + self.data_len).map(|i| {
+            let parent = map_ptr[i >> 1];
+            if parent == Self::NULL_MAP_PTR {
+                self.data[i].clone()
+            } else {
+                self.lazy_map[parent - 1].clone()
+            }
+        }))
+    }
+
+    /// Consumes the tree and returns the elements with all pending assignments resolved.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Max};
+    ///
+    /// let mut ast = AssignSegmentTree::<Max<i32>>::new(3);
+    /// ast.range_assign(1.., Some(7));
+    ///
+    /// assert_eq!(ast.into_vec(), vec![None, Some(7), Some(7)]);
+    /// ```
+    pub fn into_vec(mut self) -> Vec<<Query as Monoid>::Set> {
+        self.propagate_all();
+
+        let mut data = self.data.into_vec();
+        data.truncate(self.buf_len + self.data_len);
+        data.drain(..self.buf_len);
+        data
+    }
+
+    #[doc = include_str!("../doc/assign_from_iter.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::new(5);
+    /// ast.range_assign(.., 10);
+    ///
+    /// ast.assign_from_iter(0..5);
+    /// assert_eq!(ast.range_query(..), 10);
+    /// ```
+    pub fn assign_from_iter<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = <Query as Monoid>::Set>,
+    {
+        let mut iter = iter.into_iter();
+        for leaf in &mut self.data[self.buf_len..self.buf_len + self.data_len] {
+            *leaf = iter
+                .next()
+                .expect("`iter` should yield exactly `len()` items");
+        }
+        assert!(
+            iter.next().is_none(),
+            "`iter` should yield exactly `len()` items"
+        );
+
+        self.lazy_ptr.fill(Self::NULL_MAP_PTR);
+        self.lazy_map.clear();
+        self.recalculate_all();
+    }
+
+    /// Returns an iterator over the elements without flushing pending assignments.
+    ///
+    /// Unlike [`iter`](Self::iter), the tree is left untouched:
+    /// each element is resolved through the pending assignments of its ancestors on the fly.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*) per element
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::from_iter(0..5);
+    /// ast.range_assign(1..4, 10);
+    ///
+    /// assert!(ast.peek_iter().eq(&[0, 10, 10, 10, 4]));
+    /// ```
+    #[must_use = "iterators are lazy and do nothing unless consumed"]
+    pub fn peek_iter(&self) -> impl ExactSizeIterator<Item = &<Query as Monoid>::Set> + '_ {
+        (self.buf_len..self.buf_len + self.data_len).map(|i| self.peek_at(i))
+    }
+
+    /// Returns the `i`-th node, resolving the pending assignment of the topmost ancestor.
+    fn peek_at(&self, i: usize) -> &<Query as Monoid>::Set {
+        for d in (1..=self.buf_len.trailing_zeros()).rev() {
+            let map_ptr = self.lazy_ptr[i >> d];
+            if map_ptr != Self::NULL_MAP_PTR {
+                return &self.lazy_map[map_ptr - d as usize];
+            }
+        }
+
+        &self.data[i]
+    }
+
     #[inline]
     fn inner_index(&self, i: usize) -> usize {
         self.buf_len + i
@@ -114,6 +398,65 @@ where
         [l, r]
     }
 
+    /// Returns the current [`GcPolicy`].
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn gc_policy(&self) -> GcPolicy {
+        self.gc_policy
+    }
+
+    /// Sets the [`GcPolicy`], which takes effect from the next assignment.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, GcPolicy, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i64>>::new(100);
+    /// ast.set_gc_policy(GcPolicy::Manual);
+    ///
+    /// for i in 0..100 {
+    ///     ast.range_assign(i.., i as i64);
+    /// }
+    /// ast.gc();
+    /// assert_eq!(ast.range_query(..), 99 * 100 / 2);
+    /// ```
+    #[inline]
+    pub fn set_gc_policy(&mut self, policy: GcPolicy) {
+        self.gc_policy = policy
+    }
+
+    /// Propagates all pending assignments and clears the table of assigned values.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn gc(&mut self) {
+        if !self.lazy_map.is_empty() {
+            self.propagate_all();
+            self.lazy_map.clear();
+        }
+    }
+
+    /// Returns `true` if the table of assigned values should be flushed under the [`GcPolicy`].
+    fn should_gc(&self) -> bool {
+        match self.gc_policy {
+            GcPolicy::Auto => self.lazy_map.len() >= self.buf_len,
+            GcPolicy::MaxEntries(max_entries) => self.lazy_map.len() >= max_entries,
+            GcPolicy::MaxBytes(max_bytes) => {
+                self.lazy_map.len() * std::mem::size_of::<<Query as Monoid>::Set>() >= max_bytes
+            }
+            GcPolicy::Manual => false,
+        }
+    }
+
     fn push_map(&mut self, i: usize, map_ptr: usize) {
         if map_ptr != Self::NULL_MAP_PTR {
             self.data[i] = self.lazy_map[map_ptr].clone();
@@ -233,22 +576,90 @@ where
             }
         }
 
-        if self.lazy_map.len() < self.buf_len {
-            // recalculate data segments in bottom-to-top order
-            let diff = usize::BITS - (l ^ (r - 1)).leading_zeros();
-            for d in l.trailing_zeros() + 1..=diff {
-                self.recalculate_at(l >> d);
-            }
-            for d in r.trailing_zeros() + 1..=diff {
-                self.recalculate_at((r - 1) >> d);
+        // recalculate data segments in bottom-to-top order
+        let diff = usize::BITS - (l ^ (r - 1)).leading_zeros();
+        for d in l.trailing_zeros() + 1..=diff {
+            self.recalculate_at(l >> d);
+        }
+        for d in r.trailing_zeros() + 1..=diff {
+            self.recalculate_at((r - 1) >> d);
+        }
+        for d in diff + 1..=self.buf_len.trailing_zeros() {
+            self.recalculate_at(l >> d);
+        }
+
+        if self.should_gc() {
+            self.gc();
+        }
+    }
+
+    /// Overwrites the elements within the `range` with the items of `values` in order.
+    ///
+    /// Unlike calling [`point_assign`](Self::point_assign) for each element,
+    /// each ancestor is propagated and recalculated only once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` does not yield exactly as many items as the `range` contains.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K* + log *N*), where *K* is the length of the `range`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::new(10);
+    /// ast.range_assign(.., 1);
+    ///
+    /// ast.range_splice(3..6, [10, 20, 30]);
+    /// assert_eq!(ast.to_vec(), vec![1, 1, 1, 10, 20, 30, 1, 1, 1, 1]);
+    /// assert_eq!(ast.range_query(..), 67);
+    /// ```
+    pub fn range_splice<R, I>(&mut self, range: R, values: I)
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = <Query as Monoid>::Set>,
+    {
+        let mut values = values.into_iter();
+        let [l, r] = self.translate_range(range);
+        if l >= r {
+            assert!(
+                values.next().is_none(),
+                "`values` should yield exactly as many items as the `range` contains"
+            );
+            return;
+        }
+        assert!(r <= self.data_len, "index out of bounds");
+        let [l, r] = [self.inner_index(l), self.inner_index(r)];
+
+        let height = self.buf_len.trailing_zeros();
+
+        // lazy propagation in top-to-bottom order
+        for d in (1..=height).rev() {
+            for i in l >> d..=(r - 1) >> d {
+                self.propagate_at(i);
             }
-            for d in diff + 1..=self.buf_len.trailing_zeros() {
-                self.recalculate_at(l >> d);
+        }
+
+        // overwrite elements
+        for element in &mut self.data[l..r] {
+            *element = values
+                .next()
+                .expect("`values` should yield exactly as many items as the `range` contains");
+        }
+        assert!(
+            values.next().is_none(),
+            "`values` should yield exactly as many items as the `range` contains"
+        );
+
+        // recalculate data segments in bottom-to-top order
+        for d in 1..=height {
+            for i in l >> d..=(r - 1) >> d {
+                self.recalculate_at(i);
             }
-        } else {
-            self.propagate_all();
-            self.recalculate_all();
-            self.lazy_map.clear();
         }
     }
 
@@ -340,7 +751,7 @@ where
         let [mut acc_l, mut acc_r] = [<Query as Monoid>::identity(), <Query as Monoid>::identity()];
         while {
             if l >= r {
-                acc_l = <Query as Monoid>::combine(&acc_l, &self.data[l]);
+                <Query as Monoid>::combine_assign(&mut acc_l, &self.data[l]);
                 l += 1;
                 l >>= l.trailing_zeros();
             } else {
@@ -383,6 +794,62 @@ where
     }
 }
 
+impl<Query> AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Element>,
+{
+    #[doc = include_str!("../doc/write_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<u64>>::new(5);
+    /// ast.range_assign(1..4, 7);
+    /// let mut bytes = Vec::new();
+    /// ast.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let ast = AssignSegmentTree::<Add<u64>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(ast.to_vec(), vec![0, 7, 7, 7, 0]);
+    /// ```
+    pub fn write_snapshot<W>(&self, writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        snapshot::write(writer, self.len(), &self.to_vec())
+    }
+
+    #[doc = include_str!("../doc/read_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<u64>>::from_iter(0..5);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<u64>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(ast.range_query(..), 10);
+    /// ```
+    pub fn read_snapshot<R>(reader: R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        snapshot::read(reader).map(Self::from)
+    }
+}
+
 impl<Query> From<Vec<<Query as Monoid>::Set>> for AssignSegmentTree<Query>
 where
     Query: Monoid<Set: Clone>,
@@ -405,12 +872,22 @@ where
             lazy_map: Vec::with_capacity(buf_len + (n | 1).ilog2() as usize),
             buf_len,
             data_len: n,
+            gc_policy: GcPolicy::Auto,
         };
         ast.recalculate_all();
         ast
     }
 }
 
+impl<Query> From<&[<Query as Monoid>::Set]> for AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn from(values: &[<Query as Monoid>::Set]) -> Self {
+        Self::from_slice(values)
+    }
+}
+
 impl<Query> FromIterator<<Query as Monoid>::Set> for AssignSegmentTree<Query>
 where
     Query: Monoid<Set: Clone>,
@@ -435,6 +912,7 @@ where
                 lazy_map: Vec::with_capacity(buf_len + (min | 1).ilog2() as usize),
                 buf_len,
                 data_len: min,
+                gc_policy: GcPolicy::Auto,
             };
             ast.recalculate_all();
             ast
@@ -444,6 +922,162 @@ where
     }
 }
 
+impl<Query> From<AssignSegmentTree<Query>> for Vec<<Query as Monoid>::Set>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn from(tree: AssignSegmentTree<Query>) -> Self {
+        tree.into_vec()
+    }
+}
+
+impl<Query> IntoIterator for AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    type Item = <Query as Monoid>::Set;
+    type IntoIter = std::vec::IntoIter<<Query as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+/// Resolves the pending updates like [`iter`](AssignSegmentTree::iter).
+///
+/// There is no impl for `&AssignSegmentTree`: its elements exist only after the pending updates are resolved,
+/// so use this impl or [`to_vec`](AssignSegmentTree::to_vec) instead.
+impl<'a, Query> IntoIterator for &'a mut AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    type Item = &'a <Query as Monoid>::Set;
+    type IntoIter = std::slice::Iter<'a, <Query as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<Query> AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone + Debug>,
+{
+    #[doc = include_str!("../doc/to_dot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::new(4);
+    /// ast.range_assign(2..4, 5);
+    /// assert!(ast.to_dot().contains("{2..4 | combined: 10 | pending: Some(5)}"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = crate::render::Dot::new("AssignSegmentTree");
+        self.render(&mut dot);
+        dot.finish()
+    }
+
+    #[doc = include_str!("../doc/pretty.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::new(4);
+    /// ast.range_assign(2..4, 5);
+    /// assert_eq!(
+    ///     ast.pretty().lines().nth(1),
+    ///     Some("1: [0..2] combined: 0, pending: None  [2..4] combined: 10, pending: Some(5)")
+    /// );
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut levels = crate::render::Levels::default();
+        self.render(&mut levels);
+        levels.finish()
+    }
+
+    #[doc = include_str!("../doc/to_mermaid.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{AssignSegmentTree, ops::Add};
+    ///
+    /// let mut ast = AssignSegmentTree::<Add<i32>>::new(4);
+    /// ast.range_assign(2..4, 5);
+    /// assert!(ast.to_mermaid().contains(r#"["2..4 | combined: 10 | pending: Some(5)"]"#));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = crate::render::Mermaid::default();
+        self.render(&mut mermaid);
+        mermaid.finish()
+    }
+
+    /// Returns an adapter which [displays](std::fmt::Display) the tree like [`pretty`](Self::pretty).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and formatting takes as long as [`pretty`](Self::pretty)
+    pub fn display(&self) -> crate::Pretty<'_, Self> {
+        crate::Pretty(self)
+    }
+
+    /// Passes the nodes to `out` in level order.
+    pub(crate) fn render(&self, out: &mut impl crate::render::Render) {
+        // nodes covering only padding are omitted
+        for i in 1..self.buf_len << 1 {
+            let range = crate::render::perfect_range(i, self.buf_len, self.data_len);
+            if range.is_empty() {
+                continue;
+            }
+
+            let parent = (i > 1).then_some(i >> 1);
+            let depth = i.ilog2() as usize;
+            if i < self.buf_len {
+                let pending = (self.lazy_ptr[i] != Self::NULL_MAP_PTR)
+                    .then(|| &self.lazy_map[self.lazy_ptr[i] - 1]);
+                let fields: [(&str, &dyn Debug); 2] =
+                    [("combined", &self.data[i]), ("pending", &pending)];
+                out.node(i, parent, depth, &format!("{range:?}"), &fields);
+            } else {
+                out.node(
+                    i,
+                    parent,
+                    depth,
+                    &range.start.to_string(),
+                    &[("element", &self.data[i])],
+                );
+            }
+        }
+    }
+}
+
+/// Renders the tree like [`AssignSegmentTree::pretty`].
+impl<Query> std::fmt::Display for crate::Pretty<'_, AssignSegmentTree<Query>>
+where
+    Query: Monoid<Set: Clone + Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.pretty())
+    }
+}
+
 impl<Query> Debug for AssignSegmentTree<Query>
 where
     Query: Monoid<Set: Clone + Debug>,
@@ -455,6 +1089,7 @@ where
             .field("lazy_map", &self.lazy_map)
             .field("buf_len", &self.buf_len)
             .field("data_len", &self.data_len)
+            .field("gc_policy", &self.gc_policy)
             .finish()
     }
 }
@@ -470,6 +1105,227 @@ where
             lazy_map: self.lazy_map.clone(),
             buf_len: self.buf_len,
             data_len: self.data_len,
+            gc_policy: self.gc_policy,
+        }
+    }
+}
+
+/// Creates an empty tree.
+impl<Query> Default for AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Compares the elements with pending assignments resolved.
+impl<Query> PartialEq for AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone + PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.to_vec() == other.to_vec()
+    }
+}
+
+impl<Query> Eq for AssignSegmentTree<Query> where Query: Monoid<Set: Clone + Eq> {}
+
+/// Hashes the elements with pending assignments resolved, consistently with [`PartialEq`].
+impl<Query> Hash for AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone + Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_vec().hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Query> serde::Serialize for AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone + serde::Serialize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::AssignLeaves {
+                values: self.to_vec(),
+                gc_policy: self.gc_policy,
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Query> serde::Deserialize<'de> for AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone + serde::Deserialize<'de>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let leaves = <crate::serialize::AssignLeaves<Vec<<Query as Monoid>::Set>> as serde::Deserialize>::deserialize(
+            deserializer,
+        )?;
+        let mut tree = Self::from(leaves.values);
+        tree.set_gc_policy(leaves.gc_policy);
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod to_vec {
+    use rand::Rng;
+
+    use crate::{AssignSegmentTree, ops::Add};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i32; n];
+            let mut ast = AssignSegmentTree::<Add<i32>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let element = rng.random_range(-10..=10);
+                ast.range_assign(l..r, element);
+                naive[l..r].fill(element);
+
+                assert_eq!(ast.to_vec(), naive);
+                assert_eq!(ast.clone().into_vec(), naive);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod zero_length {
+    use crate::{AssignSegmentTree, ops::Add};
+
+    #[test]
+    fn identity() {
+        let mut tree = AssignSegmentTree::<Add<i64>>::new(0);
+        assert!(tree.is_empty());
+
+        tree.range_assign(.., 1);
+        assert_eq!(tree.range_query(..), 0);
+        assert_eq!(tree.range_query(0..0), 0);
+        assert_eq!(tree.iter().count(), 0);
+        assert!(tree.to_vec().is_empty());
+
+        tree.assign_from_iter(std::iter::empty());
+        assert!(tree.into_vec().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod gc_policy {
+    use rand::Rng;
+
+    use crate::{AssignSegmentTree, GcPolicy, ops::Add};
+
+    #[test]
+    fn random() {
+        const N: usize = 50;
+
+        let mut rng = rand::rng();
+        for policy in [
+            GcPolicy::Auto,
+            GcPolicy::MaxEntries(0),
+            GcPolicy::MaxEntries(20),
+            GcPolicy::MaxBytes(64),
+            GcPolicy::Manual,
+        ] {
+            let mut naive = vec![0_i64; N];
+            let mut ast = AssignSegmentTree::<Add<i64>>::from(naive.clone());
+            ast.set_gc_policy(policy);
+
+            for _ in 0..200 {
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                let element = rng.random_range(0..21_usize) as i64 - 10;
+                ast.range_assign(l..r, element);
+                naive[l..r].fill(element);
+
+                if rng.random_bool(0.1) {
+                    ast.gc();
+                }
+
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                assert_eq!(ast.range_query(l..r), naive[l..r].iter().sum::<i64>());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod range_splice {
+    use rand::Rng;
+
+    use crate::{AssignSegmentTree, ops::Add};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i64; n];
+            let mut ast = AssignSegmentTree::<Add<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                if rng.random_bool(0.5) {
+                    let element = rng.random_range(0..21_usize) as i64 - 10;
+                    ast.range_assign(l..r, element);
+                    naive[l..r].fill(element);
+                } else {
+                    let values =
+                        Vec::from_iter((l..r).map(|_| rng.random_range(0..21_usize) as i64 - 10));
+                    ast.range_splice(l..r, values.iter().copied());
+                    naive[l..r].copy_from_slice(&values);
+                }
+
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(ast.range_query(l..r), naive[l..r].iter().sum::<i64>())
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod peek_iter {
+    use rand::Rng;
+
+    use crate::{AssignSegmentTree, ops::Add};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i64; n];
+            let mut ast = AssignSegmentTree::<Add<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let element = rng.random_range(0..21_usize) as i64 - 10;
+                ast.range_assign(l..r, element);
+                naive[l..r].fill(element);
+
+                assert!(ast.peek_iter().eq(&naive));
+            }
         }
     }
 }
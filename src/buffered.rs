@@ -0,0 +1,484 @@
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+    ops::RangeBounds,
+};
+
+use crate::{
+    SegmentTree,
+    memory::{MemoryReport, vec_bytes},
+    snapshot::Element,
+    traits::Monoid,
+};
+
+/// A [`SegmentTree`] which buffers point updates and applies them at the next query.
+///
+/// Ancestors shared by the buffered updates are recalculated only once,
+/// which improves throughput for update-heavy and query-sparse streams.
+/// Once about *N* / log *N* updates are buffered, they are applied by rebuilding the whole tree,
+/// so the buffer never grows beyond that.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{BufferedSegmentTree, ops::Add};
+///
+/// let mut st = BufferedSegmentTree::<Add<i32>>::new(1000);
+/// for i in 0..50 {
+///     st.point_update(i, i as i32);
+/// }
+/// assert_eq!(st.pending(), 50);
+///
+/// assert_eq!(st.range_query(..10), 45);
+/// assert_eq!(st.pending(), 0);
+/// ```
+pub struct BufferedSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    tree: SegmentTree<Query>,
+    /// indices of the updated leaves whose ancestors are stale
+    pending: Vec<usize>,
+}
+
+impl<Query> BufferedSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    #[doc = include_str!("../doc/new.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn new(n: usize) -> Self {
+        Self::from(SegmentTree::new(n))
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{BufferedSegmentTree, ops::Add};
+    ///
+    /// let mut st = BufferedSegmentTree::<Add<u32>>::new(100);
+    /// st.point_update(0, 1);
+    /// let report = st.memory_usage();
+    /// assert_eq!(report.data, 200 * size_of::<u32>());
+    /// assert!(report.buffers >= size_of::<usize>());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            buffers: vec_bytes(&self.pending),
+            ..self.tree.memory_usage()
+        }
+    }
+
+    /// Returns the number of buffered point updates.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    ///
+    /// The ancestors are recalculated at the next query or [`flush`](Self::flush),
+    /// or right away once rebuilding the whole tree is cheaper than recalculating them one by one.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*) amortized
+    pub fn point_update(&mut self, i: usize, element: <Query as Monoid>::Set) {
+        *self.tree.leaf_mut(i) = element;
+        self.pending.push(i);
+        if self.pending.len() * (self.len().ilog2() as usize + 1) >= self.len() {
+            self.flush()
+        }
+    }
+
+    #[doc = include_str!("../doc/point_query.md")]
+    /// Buffered updates are already visible.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn point_query(&self, i: usize) -> &<Query as Monoid>::Set {
+        self.tree.point_query(i)
+    }
+
+    /// Applies the buffered point updates.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(min(*K* log *N* log *K*, *N*)) for *K* buffered updates
+    pub fn flush(&mut self) {
+        if !self.pending.is_empty() {
+            self.tree.recalculate_ancestors(self.pending.drain(..));
+        }
+    }
+
+    #[doc = include_str!("../doc/range_query.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*) after [`flush`](Self::flush)
+    pub fn range_query<R>(&mut self, range: R) -> <Query as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        self.flush();
+        self.tree.range_query(range)
+    }
+
+    /// Returns the underlying [`SegmentTree`] after applying the buffered updates.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(min(*K* log *N* log *K*, *N*)) for *K* buffered updates
+    pub fn into_inner(mut self) -> SegmentTree<Query> {
+        self.flush();
+        self.tree
+    }
+}
+
+impl<Query> BufferedSegmentTree<Query>
+where
+    Query: Monoid<Set: Element>,
+{
+    #[doc = include_str!("../doc/write_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{BufferedSegmentTree, SegmentTree, ops::Add};
+    ///
+    /// let mut st = BufferedSegmentTree::<Add<u32>>::new(10);
+    /// st.point_update(3, 5);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let st = SegmentTree::<Add<u32>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(st.range_query(..), 5);
+    /// ```
+    pub fn write_snapshot<W>(&self, writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.tree.write_snapshot(writer)
+    }
+
+    #[doc = include_str!("../doc/read_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{BufferedSegmentTree, SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<u32>>::from_iter(0..10);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let mut st = BufferedSegmentTree::<Add<u32>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(st.range_query(..), 45);
+    /// ```
+    pub fn read_snapshot<R>(reader: R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        SegmentTree::read_snapshot(reader).map(Self::from)
+    }
+}
+
+impl<Query> From<SegmentTree<Query>> for BufferedSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn from(tree: SegmentTree<Query>) -> Self {
+        Self {
+            tree,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<Query> From<Vec<<Query as Monoid>::Set>> for BufferedSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn from(values: Vec<<Query as Monoid>::Set>) -> Self {
+        Self::from(SegmentTree::from(values))
+    }
+}
+
+impl<Query> FromIterator<<Query as Monoid>::Set> for BufferedSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn from_iter<I: IntoIterator<Item = <Query as Monoid>::Set>>(iter: I) -> Self {
+        Self::from(SegmentTree::from_iter(iter))
+    }
+}
+
+impl<Query> BufferedSegmentTree<Query>
+where
+    Query: Monoid<Set: Debug>,
+{
+    #[doc = include_str!("../doc/to_dot.md")]
+    ///
+    /// The ancestors of the buffered updates show stale values.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{BufferedSegmentTree, ops::Add};
+    ///
+    /// let mut st = BufferedSegmentTree::<Add<i32>>::new(4);
+    /// st.point_update(1, 5);
+    /// let dot = st.to_dot();
+    /// assert!(dot.contains("{1 | element: 5}"));
+    /// // the ancestors are recalculated at the next query
+    /// assert!(dot.contains("{0..4 | combined: 0}"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        self.tree.to_dot()
+    }
+
+    #[doc = include_str!("../doc/pretty.md")]
+    ///
+    /// The ancestors of the buffered updates show stale values.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{BufferedSegmentTree, ops::Add};
+    ///
+    /// let mut st = BufferedSegmentTree::<Add<i32>>::new(4);
+    /// st.point_update(1, 5);
+    /// assert_eq!(
+    ///     st.pretty(),
+    ///     "0: [0..4] combined: 0\n\
+    ///      1: [0..2] combined: 0  [2..4] combined: 0\n\
+    ///      2: [0] element: 0  [1] element: 5  [2] element: 0  [3] element: 0\n"
+    /// );
+    /// ```
+    pub fn pretty(&self) -> String {
+        self.tree.pretty()
+    }
+
+    #[doc = include_str!("../doc/to_mermaid.md")]
+    ///
+    /// The ancestors of the buffered updates show stale values.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{BufferedSegmentTree, ops::Add};
+    ///
+    /// let mut st = BufferedSegmentTree::<Add<i32>>::new(4);
+    /// st.point_update(1, 5);
+    /// let mermaid = st.to_mermaid();
+    /// assert!(mermaid.contains(r#"["1 | element: 5"]"#));
+    /// // the ancestors are recalculated at the next query
+    /// assert!(mermaid.contains(r#"["0..4 | combined: 0"]"#));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        self.tree.to_mermaid()
+    }
+
+    /// Returns an adapter which [displays](std::fmt::Display) the tree like [`pretty`](Self::pretty).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and formatting takes as long as [`pretty`](Self::pretty)
+    pub fn display(&self) -> crate::Pretty<'_, Self> {
+        crate::Pretty(self)
+    }
+}
+
+/// Renders the tree like [`BufferedSegmentTree::pretty`].
+impl<Query> std::fmt::Display for crate::Pretty<'_, BufferedSegmentTree<Query>>
+where
+    Query: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.pretty())
+    }
+}
+
+impl<Query> Debug for BufferedSegmentTree<Query>
+where
+    Query: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferedSegmentTree")
+            .field("tree", &self.tree)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+impl<Query> Clone for BufferedSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+/// Creates an empty tree.
+impl<Query> Default for BufferedSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Compares the elements, regardless of the buffered updates.
+impl<Query> PartialEq for BufferedSegmentTree<Query>
+where
+    Query: Monoid<Set: PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.tree == other.tree
+    }
+}
+
+impl<Query> Eq for BufferedSegmentTree<Query> where Query: Monoid<Set: Eq> {}
+
+/// Hashes the elements, consistently with [`PartialEq`].
+impl<Query> Hash for BufferedSegmentTree<Query>
+where
+    Query: Monoid<Set: Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tree.hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Query> serde::Serialize for BufferedSegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Serialize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(&self.tree, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Query> serde::Deserialize<'de> for BufferedSegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Deserialize<'de>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <SegmentTree<Query> as serde::Deserialize>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod buffered_segment_tree {
+    use rand::Rng;
+
+    use crate::{BufferedSegmentTree, ops::ConcatString};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..80 {
+            let mut naive = Vec::from_iter((0..n).map(|i| i.to_string()));
+            let mut st = BufferedSegmentTree::<ConcatString>::from(naive.clone());
+
+            for _ in 0..50 {
+                for _ in 0..rng.random_range(0..2 * n) {
+                    let i = rng.random_range(0..n);
+                    let v = rng.random_range(0..100).to_string();
+                    naive[i] = v.clone();
+                    st.point_update(i, v);
+                }
+
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                assert_eq!(st.range_query(l..r), naive[l..r].concat());
+            }
+            assert_eq!(st.into_inner().to_vec(), naive)
+        }
+    }
+
+    #[test]
+    fn auto_flush() {
+        for n in 1..200 {
+            let mut st = BufferedSegmentTree::<ConcatString>::new(n);
+            let bound = n / (n.ilog2() as usize + 1);
+            for k in 0..3 * n {
+                st.point_update(k % n, k.to_string());
+                assert!(st.pending() <= bound, "n = {n}, pending = {}", st.pending());
+            }
+            assert_eq!(
+                st.range_query(..),
+                String::from_iter((2 * n..3 * n).map(|k| k.to_string()))
+            )
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use std::ops::Range;
+
+use crate::{
+    AssignSegmentTree, BufferedSegmentTree, DequeSegmentTree, DualSegmentTree,
+    DynamicLazySegmentTree, DynamicSegmentTree, GcPolicy, LazySegmentTree, SegmentTree,
+    VebSegmentTree,
+    traits::{Monoid, MonoidAction},
+};
+
+/// Configures the construction of any tree.
+///
+/// The options are shared by all the trees, and each tree uses the ones which apply to it.
+/// The tree to build, and thus the memory layout, is chosen by [`build`](Self::build).
+///
+/// | option                         | trees                                              |
+/// | ------------------------------ | -------------------------------------------------- |
+/// | [`len`](Self::len)             | all                                                |
+/// | [`elem`](Self::elem)           | all                                                |
+/// | [`values`](Self::values)       | all                                                |
+/// | [`offset`](Self::offset)       | [`DynamicSegmentTree`], [`DynamicLazySegmentTree`] |
+/// | [`capacity`](Self::capacity)   | [`DynamicSegmentTree`], [`DynamicLazySegmentTree`] |
+/// | [`gc_policy`](Self::gc_policy) | [`AssignSegmentTree`]                              |
+///
+/// The pointer width of the dynamic trees and the instrumentation are chosen at compile time
+/// by the `u32_ptr` and `instrument` features respectively.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{
+///     DynamicSegmentTree, SegmentTreeBuilder, VebSegmentTree,
+///     ops::{Add, Max},
+/// };
+///
+/// let st: VebSegmentTree<Add<i64>> = SegmentTreeBuilder::new().values(0..100).build();
+/// assert_eq!(st.range_query(..10), 45);
+///
+/// let mut dst: DynamicSegmentTree<Max<i32>> = SegmentTreeBuilder::new()
+///     .len(1 << 40)
+///     .offset(-(1 << 39))
+///     .capacity(10)
+///     .build();
+/// dst.point_update(-12345, Some(1));
+/// assert_eq!(dst.range_query(..), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SegmentTreeBuilder<T> {
+    elements: Elements<T>,
+    offset: isize,
+    capacity: usize,
+    gc_policy: GcPolicy,
+}
+
+/// The initial elements.
+#[derive(Debug, Clone)]
+enum Elements<T> {
+    /// `n` identity elements
+    Identity(usize),
+    /// `n` clones of the element
+    Elem(usize, T),
+    Values(Vec<T>),
+}
+
+impl<T> Elements<T> {
+    fn len(&self) -> usize {
+        match self {
+            Elements::Identity(n) | Elements::Elem(n, _) => *n,
+            Elements::Values(values) => values.len(),
+        }
+    }
+}
+
+impl<T> SegmentTreeBuilder<T> {
+    /// Creates a builder of an empty tree with the default options.
+    pub fn new() -> Self {
+        Self {
+            elements: Elements::Identity(0),
+            offset: 0,
+            capacity: 0,
+            gc_policy: GcPolicy::default(),
+        }
+    }
+
+    /// Starts with `n` identity elements.
+    pub fn len(mut self, n: usize) -> Self {
+        self.elements = Elements::Identity(n);
+        self
+    }
+
+    /// Starts with `n` clones of `element`.
+    pub fn elem(mut self, n: usize, element: T) -> Self {
+        self.elements = Elements::Elem(n, element);
+        self
+    }
+
+    /// Starts with the given elements.
+    pub fn values<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.elements = Elements::Values(Vec::from_iter(values));
+        self
+    }
+
+    /// Sets the index of the first element of the dynamic trees. The default is `0`.
+    pub fn offset(mut self, offset: isize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Reserves the nodes of the dynamic trees for `q` point updates.
+    pub fn capacity(mut self, q: usize) -> Self {
+        self.capacity = q;
+        self
+    }
+
+    /// Sets the [`GcPolicy`] of [`AssignSegmentTree`].
+    pub fn gc_policy(mut self, policy: GcPolicy) -> Self {
+        self.gc_policy = policy;
+        self
+    }
+
+    /// Builds the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range of a dynamic tree overflows [`isize`].
+    pub fn build<Tree>(self) -> Tree
+    where
+        Tree: FromBuilder<T>,
+    {
+        Tree::from_builder(self)
+    }
+
+    /// Returns the range of the dynamic trees.
+    fn range(&self) -> Range<isize> {
+        let end = isize::try_from(self.elements.len())
+            .ok()
+            .and_then(|n| self.offset.checked_add(n))
+            .expect("the range should not overflow `isize`");
+        self.offset..end
+    }
+}
+
+impl<T> Default for SegmentTreeBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Trees which can be built by [`SegmentTreeBuilder`] over the elements of type `T`.
+pub trait FromBuilder<T>: Sized {
+    /// Builds the tree with the options of `builder`.
+    fn from_builder(builder: SegmentTreeBuilder<T>) -> Self;
+}
+
+macro_rules! from_builder_impl {
+    ($tree:ident<$p:ident>, $set:ty, $($bound:tt)+) => {
+        impl<$p> FromBuilder<$set> for $tree<$p>
+        where
+            $p: $($bound)+,
+        {
+            fn from_builder(builder: SegmentTreeBuilder<$set>) -> Self {
+                match builder.elements {
+                    Elements::Identity(n) => Self::new(n),
+                    Elements::Elem(n, element) => Self::from(vec![element; n]),
+                    Elements::Values(values) => Self::from(values),
+                }
+            }
+        }
+    };
+}
+
+from_builder_impl!(SegmentTree<Query>, <Query as Monoid>::Set, Monoid<Set: Clone>);
+from_builder_impl!(VebSegmentTree<Query>, <Query as Monoid>::Set, Monoid<Set: Clone>);
+from_builder_impl!(BufferedSegmentTree<Query>, <Query as Monoid>::Set, Monoid<Set: Clone>);
+from_builder_impl!(DualSegmentTree<Update>, <Update as Monoid>::Set, Monoid<Set: Clone>);
+from_builder_impl!(
+    LazySegmentTree<Action>,
+    <<Action as MonoidAction>::Set as Monoid>::Set,
+    MonoidAction<Set: Monoid<Set: Clone>>
+);
+
+impl<Query> FromBuilder<<Query as Monoid>::Set> for DequeSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn from_builder(builder: SegmentTreeBuilder<<Query as Monoid>::Set>) -> Self {
+        match builder.elements {
+            Elements::Identity(n) => {
+                Self::from_iter(std::iter::repeat_with(<Query as Monoid>::identity).take(n))
+            }
+            Elements::Elem(n, element) => Self::from(vec![element; n]),
+            Elements::Values(values) => Self::from(values),
+        }
+    }
+}
+
+impl<Query> FromBuilder<<Query as Monoid>::Set> for AssignSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn from_builder(builder: SegmentTreeBuilder<<Query as Monoid>::Set>) -> Self {
+        let mut tree = match builder.elements {
+            Elements::Identity(n) => Self::new(n),
+            Elements::Elem(n, element) => Self::from_elem(n, element),
+            Elements::Values(values) => Self::from(values),
+        };
+        tree.set_gc_policy(builder.gc_policy);
+        tree
+    }
+}
+
+/// Every element is allocated only if the tree starts with [`values`](SegmentTreeBuilder::values).
+/// Clones of one element are represented by [the default element](DynamicSegmentTree::with_default).
+///
+/// Unlike [`DynamicSegmentTree::try_new`], an empty range is not an error:
+/// the builder gives [the empty tree](DynamicSegmentTree::default) over `0..0` regardless of the offset,
+/// because [`FromBuilder`] cannot fail.
+impl<Query> FromBuilder<<Query as Monoid>::Set> for DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn from_builder(builder: SegmentTreeBuilder<<Query as Monoid>::Set>) -> Self {
+        let range = builder.range();
+        let Some(mut tree) = Self::with_capacity(range.clone(), builder.capacity) else {
+            return Self::default();
+        };
+        match builder.elements {
+            Elements::Identity(_) => {}
+            Elements::Elem(_, element) => tree.set_default(element),
+            Elements::Values(values) => {
+                for (i, element) in range.zip(values) {
+                    tree.point_update(i, element)
+                }
+            }
+        }
+        tree
+    }
+}
+
+/// Every element is allocated only if the tree starts with [`values`](SegmentTreeBuilder::values).
+/// Clones of one element are represented by [the default element](DynamicLazySegmentTree::fill).
+///
+/// Unlike [`DynamicLazySegmentTree::try_new`], an empty range is not an error:
+/// the builder gives [the empty tree](DynamicLazySegmentTree::default) over `0..0` regardless of the offset,
+/// because [`FromBuilder`] cannot fail.
+impl<Action> FromBuilder<<<Action as MonoidAction>::Set as Monoid>::Set>
+    for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Clone>>,
+{
+    fn from_builder(
+        builder: SegmentTreeBuilder<<<Action as MonoidAction>::Set as Monoid>::Set>,
+    ) -> Self {
+        let range = builder.range();
+        let Some(mut tree) = Self::with_capacity(range.clone(), builder.capacity) else {
+            return Self::default();
+        };
+        match builder.elements {
+            Elements::Identity(_) => {}
+            Elements::Elem(_, element) => tree.fill(element),
+            Elements::Values(values) => tree.assign_dense(range.start, &values),
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod from_builder {
+    use super::SegmentTreeBuilder;
+    use crate::{
+        AssignSegmentTree, BufferedSegmentTree, DualSegmentTree, DynamicLazySegmentTree,
+        DynamicSegmentTree, GcPolicy, LazySegmentTree, SegmentTree, VebSegmentTree,
+        acts::{AddQueryAddUpdate, MaxQueryAddUpdate},
+        ops::Add,
+    };
+
+    #[test]
+    fn all_trees() {
+        let builder = SegmentTreeBuilder::new().values(1..=10).offset(-5);
+        let expected = Vec::from_iter(1..=10);
+
+        assert_eq!(
+            builder.clone().build::<SegmentTree<Add<i64>>>().to_vec(),
+            expected
+        );
+        let veb: VebSegmentTree<Add<i64>> = builder.clone().build();
+        assert_eq!(veb.range_query(..), 55);
+        let mut bst: BufferedSegmentTree<Add<i64>> = builder.clone().build();
+        assert_eq!(bst.range_query(..), 55);
+        assert_eq!(
+            builder
+                .clone()
+                .build::<DualSegmentTree<Add<i64>>>()
+                .to_vec(),
+            expected
+        );
+        let lst: LazySegmentTree<AddQueryAddUpdate<i64>> = builder.clone().build();
+        assert_eq!(lst.to_vec(), expected);
+
+        let ast: AssignSegmentTree<Add<i64>> = builder.clone().gc_policy(GcPolicy::Manual).build();
+        assert_eq!(ast.gc_policy(), GcPolicy::Manual);
+        assert_eq!(ast.to_vec(), expected);
+
+        let mut dst: DynamicSegmentTree<Add<i64>> = builder.clone().build();
+        assert_eq!(dst.len(), 10);
+        assert_eq!(dst.range_query(..0), 15);
+        assert_eq!(dst.point_query(4), 10);
+        let dlst: DynamicLazySegmentTree<AddQueryAddUpdate<i64>> = builder.build();
+        assert_eq!(dlst.range_query(-5..0), 15);
+        assert_eq!(dlst.to_vec(), expected);
+    }
+
+    #[test]
+    fn elements() {
+        let st: SegmentTree<Add<i32>> = SegmentTreeBuilder::new().len(5).build();
+        assert_eq!(st.to_vec(), vec![0; 5]);
+        let st: SegmentTree<Add<i32>> = SegmentTreeBuilder::new().elem(5, 2).build();
+        assert_eq!(st.to_vec(), vec![2; 5]);
+
+        let mut dst: DynamicSegmentTree<Add<i32>> = SegmentTreeBuilder::new().elem(5, 2).build();
+        assert_eq!(dst.range_query(..), 10);
+        let dlst: DynamicLazySegmentTree<MaxQueryAddUpdate<i32>> = SegmentTreeBuilder::new()
+            .len(usize::MAX >> 1)
+            .offset(isize::MIN)
+            .build();
+        assert_eq!(dlst.range_query(..), None);
+
+        // empty trees
+        assert!(
+            SegmentTreeBuilder::new()
+                .build::<LazySegmentTree<AddQueryAddUpdate<i32>>>()
+                .is_empty()
+        );
+        assert_eq!(
+            SegmentTreeBuilder::new()
+                .build::<DynamicSegmentTree<Add<i32>>>()
+                .len(),
+            0
+        );
+        assert_eq!(
+            SegmentTreeBuilder::new()
+                .offset(-5)
+                .build::<DynamicLazySegmentTree<AddQueryAddUpdate<i32>>>(),
+            DynamicLazySegmentTree::default()
+        );
+    }
+}
@@ -0,0 +1,510 @@
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::RangeBounds,
+};
+
+use crate::{memory::MemoryReport, traits::Monoid, utility::convert_range};
+
+/// A data structure that supports **range query point update** operations
+/// and pushes and pops at both ends.
+///
+/// The elements are kept in the middle of the leaves, leaving free leaves on both sides.
+/// When either side runs out, the tree is rebuilt with free leaves for at least `N / 2` pushes on each side,
+/// so that pushes take *O*(log *N*) amortized time.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{DequeSegmentTree, ops::Concat};
+///
+/// let mut st = DequeSegmentTree::<Concat<char>>::new();
+/// st.push_back(vec!['b']);
+/// st.push_back(vec!['c']);
+/// st.push_front(vec!['a']);
+/// assert_eq!(st.range_query(..), vec!['a', 'b', 'c']);
+///
+/// assert_eq!(st.pop_front(), Some(vec!['a']));
+/// st.push_back(vec!['d']);
+/// assert_eq!(st.range_query(1..), vec!['c', 'd']);
+/// ```
+pub struct DequeSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    /// - data\[1..cap\]    : nodes to store the combined value of the children.
+    /// - data\[cap..2cap\] : leaves, where the elements are in `cap + head..cap + head + len`.
+    data: Box<[<Query as Monoid>::Set]>,
+    head: usize,
+    len: usize,
+}
+
+impl<Query> DequeSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    /// Creates an empty tree.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn new() -> Self {
+        Self {
+            data: Box::new([]),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Builds the nodes over `len` elements, leaving room for at least `len / 2 + 1` pushes on each side.
+    ///
+    /// Returns the nodes and the position of the first element.
+    ///
+    /// # Time complexity
+    ///
+    /// *Θ*(*N*)
+    fn build<I>(values: I, len: usize) -> (Box<[<Query as Monoid>::Set]>, usize)
+    where
+        I: Iterator<Item = <Query as Monoid>::Set>,
+    {
+        let cap = (len + 1).next_power_of_two() << 1;
+        let head = (cap - len) >> 1;
+
+        let mut data = Vec::with_capacity(cap << 1);
+        data.resize_with(cap + head, <Query as Monoid>::identity);
+        data.extend(values.take(len));
+        data.resize_with(cap << 1, <Query as Monoid>::identity);
+        for i in (1..cap).rev() {
+            data[i] = <Query as Monoid>::combine(&data[i << 1], &data[(i << 1) + 1])
+        }
+
+        (data.into_boxed_slice(), head)
+    }
+
+    /// Moves the elements to the middle of a new buffer.
+    fn grow(&mut self) {
+        let start = (self.data.len() >> 1) + self.head;
+        let values = std::mem::take(&mut self.data)
+            .into_vec()
+            .into_iter()
+            .skip(start);
+        (self.data, self.head) = Self::build(values, self.len);
+    }
+
+    /// Returns the index of the `i`-th leaf in `data`.
+    #[inline]
+    fn inner_index(&self, i: usize) -> usize {
+        (self.data.len() >> 1) + self.head + i
+    }
+
+    /// Recalculates the ancestors of the node `i` in bottom-to-top order.
+    fn recalculate_ancestors(&mut self, mut i: usize) {
+        while i > 1 {
+            i >>= 1;
+            self.data[i] = <Query as Monoid>::combine(&self.data[i << 1], &self.data[(i << 1) + 1])
+        }
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            data: size_of_val(&*self.data),
+            ..Default::default()
+        }
+    }
+
+    /// Appends `element` to the back.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*) amortized
+    pub fn push_back(&mut self, element: <Query as Monoid>::Set) {
+        if self.head + self.len == self.data.len() >> 1 {
+            self.grow()
+        }
+
+        let i = self.inner_index(self.len);
+        self.data[i] = element;
+        self.len += 1;
+        self.recalculate_ancestors(i);
+    }
+
+    /// Prepends `element` to the front.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*) amortized
+    pub fn push_front(&mut self, element: <Query as Monoid>::Set) {
+        if self.head == 0 {
+            self.grow()
+        }
+
+        self.head -= 1;
+        self.len += 1;
+        let i = self.inner_index(0);
+        self.data[i] = element;
+        self.recalculate_ancestors(i);
+    }
+
+    /// Removes the last element and returns it, or [`None`] if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DequeSegmentTree, ops::Add};
+    ///
+    /// let mut st = DequeSegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+    /// assert_eq!(st.pop_back(), Some(3));
+    /// assert_eq!(st.range_query(..), 3);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<<Query as Monoid>::Set> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.len -= 1;
+        let i = self.inner_index(self.len);
+        let element = std::mem::replace(&mut self.data[i], <Query as Monoid>::identity());
+        self.recalculate_ancestors(i);
+        Some(element)
+    }
+
+    /// Removes the first element and returns it, or [`None`] if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DequeSegmentTree, ops::Add};
+    ///
+    /// let mut st = DequeSegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+    /// assert_eq!(st.pop_front(), Some(1));
+    /// assert_eq!(st.range_query(..), 5);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<<Query as Monoid>::Set> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let i = self.inner_index(0);
+        let element = std::mem::replace(&mut self.data[i], <Query as Monoid>::identity());
+        self.head += 1;
+        self.len -= 1;
+        self.recalculate_ancestors(i);
+        Some(element)
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    pub fn point_update(&mut self, i: usize, element: <Query as Monoid>::Set) {
+        assert!(
+            i < self.len,
+            "the index should be less than {}, but is {i}",
+            self.len
+        );
+
+        let i = self.inner_index(i);
+        self.data[i] = element;
+        self.recalculate_ancestors(i);
+    }
+
+    #[doc = include_str!("../doc/point_query.md")]
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn point_query(&self, i: usize) -> &<Query as Monoid>::Set {
+        &self.as_slice()[i]
+    }
+
+    #[doc = include_str!("../doc/range_query.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DequeSegmentTree, ops::Max};
+    ///
+    /// let mut st = DequeSegmentTree::<Max<i32>>::new();
+    /// for i in 0..10 {
+    ///     st.push_front(Some(i));
+    /// }
+    /// assert_eq!(st.range_query(..), Some(9));
+    /// assert_eq!(st.range_query(5..), Some(4));
+    /// ```
+    pub fn range_query<R>(&self, range: R) -> <Query as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len);
+        let [mut l, mut r] = [range.start, range.end].map(|i| self.inner_index(i));
+
+        let (mut acc_l, mut acc_r) = (<Query as Monoid>::identity(), <Query as Monoid>::identity());
+        while l < r {
+            if l & 1 == 1 {
+                <Query as Monoid>::combine_assign(&mut acc_l, &self.data[l]);
+                l += 1
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                acc_r = <Query as Monoid>::combine(&self.data[r], &acc_r)
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+
+        <Query as Monoid>::combine(&acc_l, &acc_r)
+    }
+
+    /// Returns the elements as a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn as_slice(&self) -> &[<Query as Monoid>::Set] {
+        let start = self.inner_index(0);
+        &self.data[start..start + self.len]
+    }
+
+    /// Consumes the tree and returns the elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn into_vec(self) -> Vec<<Query as Monoid>::Set> {
+        let start = self.inner_index(0);
+        Vec::from_iter(self.data.into_vec().into_iter().skip(start).take(self.len))
+    }
+}
+
+impl<Query> DequeSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    /// Returns the elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn to_vec(&self) -> Vec<<Query as Monoid>::Set> {
+        self.as_slice().to_vec()
+    }
+}
+
+impl<Query> From<Vec<<Query as Monoid>::Set>> for DequeSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn from(values: Vec<<Query as Monoid>::Set>) -> Self {
+        let len = values.len();
+        let (data, head) = Self::build(values.into_iter(), len);
+        Self { data, head, len }
+    }
+}
+
+impl<Query> FromIterator<<Query as Monoid>::Set> for DequeSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn from_iter<I: IntoIterator<Item = <Query as Monoid>::Set>>(iter: I) -> Self {
+        Self::from(Vec::from_iter(iter))
+    }
+}
+
+impl<Query> From<DequeSegmentTree<Query>> for Vec<<Query as Monoid>::Set>
+where
+    Query: Monoid,
+{
+    fn from(tree: DequeSegmentTree<Query>) -> Self {
+        tree.into_vec()
+    }
+}
+
+impl<Query> Debug for DequeSegmentTree<Query>
+where
+    Query: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DequeSegmentTree")
+            .field("data", &self.data)
+            .field("head", &self.head)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<Query> Clone for DequeSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            head: self.head,
+            len: self.len,
+        }
+    }
+}
+
+/// Creates an empty tree.
+impl<Query> Default for DequeSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compares the elements.
+impl<Query> PartialEq for DequeSegmentTree<Query>
+where
+    Query: Monoid<Set: PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<Query> Eq for DequeSegmentTree<Query> where Query: Monoid<Set: Eq> {}
+
+/// Hashes the elements, consistently with [`PartialEq`].
+impl<Query> Hash for DequeSegmentTree<Query>
+where
+    Query: Monoid<Set: Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Query> serde::Serialize for DequeSegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Serialize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::Leaves {
+                values: self.as_slice(),
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Query> serde::Deserialize<'de> for DequeSegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Deserialize<'de>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <crate::serialize::Leaves<Vec<<Query as Monoid>::Set>> as serde::Deserialize>::deserialize(
+            deserializer,
+        )
+        .map(|leaves| Self::from(leaves.values))
+    }
+}
+
+#[cfg(test)]
+mod random {
+    use std::collections::VecDeque;
+
+    use rand::Rng;
+
+    use crate::{DequeSegmentTree, ops::Concat};
+
+    #[test]
+    fn push_and_pop() {
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut naive = VecDeque::new();
+            let mut st = DequeSegmentTree::<Concat<u32>>::new();
+
+            for _ in 0..300 {
+                match rng.random_range(0..5) {
+                    0 => {
+                        let element = rng.random_range(0..100);
+                        st.push_back(vec![element]);
+                        naive.push_back(vec![element])
+                    }
+                    1 => {
+                        let element = rng.random_range(0..100);
+                        st.push_front(vec![element]);
+                        naive.push_front(vec![element])
+                    }
+                    2 => assert_eq!(st.pop_back(), naive.pop_back()),
+                    3 => assert_eq!(st.pop_front(), naive.pop_front()),
+                    _ if !naive.is_empty() => {
+                        let i = rng.random_range(0..naive.len());
+                        let element = rng.random_range(0..100);
+                        st.point_update(i, vec![element]);
+                        naive[i] = vec![element]
+                    }
+                    _ => {}
+                }
+
+                assert_eq!(st.len(), naive.len());
+                let l = rng.random_range(0..=naive.len());
+                let r = rng.random_range(l..=naive.len());
+                assert_eq!(
+                    st.range_query(l..r),
+                    naive.range(l..r).flatten().copied().collect::<Vec<_>>()
+                );
+                assert_eq!(st.as_slice(), naive.make_contiguous());
+            }
+        }
+    }
+
+    #[test]
+    fn queue() {
+        // the buffer does not grow while the length is bounded
+        let mut st = DequeSegmentTree::<Concat<u32>>::from_iter((0..10).map(|i| vec![i]));
+        for i in 10..10_000 {
+            st.push_back(vec![i]);
+            st.pop_front();
+        }
+        assert_eq!(st.range_query(..), Vec::from_iter(9990..10_000));
+        assert!(st.memory_usage().data <= 64 * size_of::<Vec<u32>>());
+    }
+}
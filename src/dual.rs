@@ -1,6 +1,16 @@
-use std::{fmt::Debug, ops::RangeBounds};
-
-use crate::traits::Monoid;
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+    ops::RangeBounds,
+};
+
+use crate::{
+    memory::MemoryReport,
+    snapshot::{self, Element},
+    traits::Monoid,
+    utility::{convert_range, split_cyclic},
+};
 
 /// A data structure that supports **point query range update** operations.
 ///
@@ -55,12 +65,53 @@ where
     /// let dst = DualSegmentTree::<Affine<i32>>::new(100);
     /// assert_eq!(dst.len(), 100);
     /// ```
-    #[allow(clippy::len_without_is_empty)]
     #[inline]
     pub fn len(&self) -> usize {
         self.data.len() >> 1
     }
 
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// Range updates over an empty tree do nothing.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let dst = DualSegmentTree::<Add<i32>>::new(0);
+    /// assert!(dst.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let dst = DualSegmentTree::<Add<u32>>::new(100);
+    /// assert_eq!(dst.memory_usage().data, 200 * size_of::<u32>());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            data: size_of_val(&*self.data),
+            ..Default::default()
+        }
+    }
+
     /// Returns an iterator over the elements
     ///
     /// # Time complexity
@@ -80,6 +131,126 @@ where
         self.data[self.data.len() >> 1..].iter()
     }
 
+    #[doc = include_str!("../doc/flush.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(5);
+    /// dst.range_update(1..4, &1);
+    ///
+    /// dst.flush();
+    /// assert_eq!(dst.point_query(2), 1);
+    /// ```
+    pub fn flush(&mut self) {
+        self.propagate_all();
+    }
+
+    /// Returns the elements with all pending updates resolved.
+    ///
+    /// Unlike [`iter`](Self::iter), the tree is left untouched.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(5);
+    /// dst.range_update(1..4, &1);
+    /// dst.range_update(2.., &10);
+    ///
+    /// assert_eq!(dst.to_vec(), vec![0, 1, 11, 11, 10]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<<Update as Monoid>::Set> {
+        let n = self.data.len() >> 1;
+
+        // composed updates of the ancestors, including the node itself
+        let mut composed = Vec::with_capacity(n);
+        composed.push(<Update as Monoid>::identity());
+        for i in 1..n {
+            let update = <Update as Monoid>::combine(&self.data[i], &composed[i >> 1]);
+            composed.push(update);
+        }
+
+        Vec::from_iter(
+            (n..n << 1).map(|i| <Update as Monoid>::combine(&self.data[i], &composed[i >> 1])),
+        )
+    }
+
+    /// Consumes the tree and returns the elements with all pending updates resolved.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Mul};
+    ///
+    /// let mut dst = DualSegmentTree::<Mul<i32>>::new(5);
+    /// dst.range_update(..3, &2);
+    /// dst.range_update(1..4, &3);
+    ///
+    /// assert_eq!(dst.into_vec(), vec![2, 6, 6, 3, 1]);
+    /// ```
+    pub fn into_vec(mut self) -> Vec<<Update as Monoid>::Set> {
+        self.propagate_all();
+
+        let n = self.data.len() >> 1;
+        let mut data = self.data.into_vec();
+        data.drain(..n);
+        data
+    }
+
+    #[doc = include_str!("../doc/assign_from_iter.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(5);
+    /// dst.range_update(.., &10);
+    ///
+    /// dst.assign_from_iter(0..5);
+    /// assert_eq!(dst.to_vec(), vec![0, 1, 2, 3, 4]);
+    /// ```
+    pub fn assign_from_iter<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = <Update as Monoid>::Set>,
+    {
+        let n = self.data.len() >> 1;
+        let (lazy, leaves) = self.data.split_at_mut(n);
+
+        let mut iter = iter.into_iter();
+        for leaf in leaves {
+            *leaf = iter
+                .next()
+                .expect("`iter` should yield exactly `len()` items");
+        }
+        assert!(
+            iter.next().is_none(),
+            "`iter` should yield exactly `len()` items"
+        );
+
+        lazy.fill_with(<Update as Monoid>::identity);
+    }
+
     #[inline]
     fn inner_index(&self, i: usize) -> usize {
         self.data.len() / 2 + i
@@ -116,9 +287,13 @@ where
             "{i}-th node should have two children",
         );
 
+        if <Update as Monoid>::is_identity(&self.data[i]) {
+            return;
+        }
+
         let update = std::mem::replace(&mut self.data[i], <Update as Monoid>::identity());
-        self.data[i << 1] = <Update as Monoid>::combine(&self.data[i << 1], &update);
-        self.data[(i << 1) | 1] = <Update as Monoid>::combine(&self.data[(i << 1) | 1], &update);
+        <Update as Monoid>::combine_assign(&mut self.data[i << 1], &update);
+        <Update as Monoid>::combine_assign(&mut self.data[(i << 1) | 1], &update);
 
         // let children = &mut self.data[i << 1..(i << 1) + 2];
         // children[0] = <Update as Monoid>::combine(&lazy, &children[0]);
@@ -179,12 +354,12 @@ where
         let [mut l, mut r] = [l, r];
         while {
             if l >= r {
-                self.data[l] = <Update as Monoid>::combine(&self.data[l], update);
+                <Update as Monoid>::combine_assign(&mut self.data[l], update);
                 l += 1;
                 l >>= l.trailing_zeros();
             } else {
                 r -= 1;
-                self.data[r] = <Update as Monoid>::combine(&self.data[r], update);
+                <Update as Monoid>::combine_assign(&mut self.data[r], update);
                 r >>= r.trailing_zeros()
             }
 
@@ -192,6 +367,31 @@ where
         } {}
     }
 
+    #[doc = include_str!("../doc/range_update_cyclic.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(5);
+    /// dst.range_update_cyclic(3..1, &1);
+    /// dst.range_update_cyclic(1..3, &10);
+    /// assert_eq!(dst.to_vec(), vec![1, 10, 10, 1, 1]);
+    /// ```
+    pub fn range_update_cyclic<R>(&mut self, range: R, update: &<Update as Monoid>::Set)
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let n = self.len();
+        for range in split_cyclic(convert_range(range, 0..n), n) {
+            self.range_update(range, update)
+        }
+    }
+
     #[doc = include_str!("../doc/point_update.md")]
     /// # Time complexity
     ///
@@ -222,7 +422,7 @@ where
             }
         }
 
-        self.data[i] = <Update as Monoid>::combine(&self.data[i], update);
+        <Update as Monoid>::combine_assign(&mut self.data[i], update);
     }
 
     #[doc = include_str!("../doc/point_query.md")]
@@ -243,7 +443,7 @@ where
         let mut res = <Update as Monoid>::identity();
         // combine in chronological order
         while i > 0 {
-            res = <Update as Monoid>::combine(&res, &self.data[i]);
+            <Update as Monoid>::combine_assign(&mut res, &self.data[i]);
             i >>= 1;
         }
 
@@ -279,6 +479,139 @@ where
     }
 }
 
+impl<Update> DualSegmentTree<Update>
+where
+    Update: Monoid<Set: Clone>,
+{
+    #[doc = include_str!("../doc/from_elem.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Mul};
+    ///
+    /// let mut dst = DualSegmentTree::<Mul<i32>>::from_elem(100, 2);
+    /// dst.range_update(10..20, &3);
+    ///
+    /// assert_eq!(dst.point_query(0), 2);
+    /// assert_eq!(dst.point_query(10), 6);
+    /// ```
+    #[inline]
+    pub fn from_elem(n: usize, element: <Update as Monoid>::Set) -> Self {
+        Self::from_iter(std::iter::repeat_n(element, n))
+    }
+
+    /// Creates a new instance initialized with clones of `values`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let values = [1, 2, 3, 4, 5];
+    /// let mut dst = DualSegmentTree::<Add<i32>>::from_slice(&values);
+    /// dst.range_update(1..4, &10);
+    /// assert_eq!(dst.to_vec(), vec![1, 12, 13, 14, 5]);
+    /// ```
+    #[inline]
+    pub fn from_slice(values: &[<Update as Monoid>::Set]) -> Self {
+        Self::from_iter(values.iter().cloned())
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<Update> DualSegmentTree<Update>
+where
+    Update: Monoid<Set: Send + Sync>,
+{
+    /// Answers independent point queries in parallel.
+    ///
+    /// The `k`-th result is equal to `self.point_query(indices[k])`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q* log *N*) work
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(5);
+    /// dst.range_update(1..4, &10);
+    /// assert_eq!(dst.par_point_query(&[0, 1, 3, 4]), vec![0, 10, 10, 0]);
+    /// ```
+    pub fn par_point_query(&self, indices: &[usize]) -> Vec<<Update as Monoid>::Set> {
+        use rayon::prelude::*;
+
+        indices.par_iter().map(|&i| self.point_query(i)).collect()
+    }
+}
+
+impl<Update> DualSegmentTree<Update>
+where
+    Update: Monoid<Set: Element>,
+{
+    #[doc = include_str!("../doc/write_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(5);
+    /// dst.range_update(1..4, &1);
+    /// let mut bytes = Vec::new();
+    /// dst.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let dst = DualSegmentTree::<Add<i32>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(dst.to_vec(), vec![0, 1, 1, 1, 0]);
+    /// ```
+    pub fn write_snapshot<W>(&self, writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        snapshot::write(writer, self.len(), &self.to_vec())
+    }
+
+    #[doc = include_str!("../doc/read_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_iter(0..5);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let dst = DualSegmentTree::<Add<i32>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(dst.to_vec(), vec![0, 1, 2, 3, 4]);
+    /// ```
+    pub fn read_snapshot<R>(reader: R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        snapshot::read(reader).map(Self::from)
+    }
+}
+
 impl<Update> From<Vec<<Update as Monoid>::Set>> for DualSegmentTree<Update>
 where
     Update: Monoid,
@@ -295,6 +628,15 @@ where
     }
 }
 
+impl<Update> From<&[<Update as Monoid>::Set]> for DualSegmentTree<Update>
+where
+    Update: Monoid<Set: Clone>,
+{
+    fn from(values: &[<Update as Monoid>::Set]) -> Self {
+        Self::from_slice(values)
+    }
+}
+
 impl<Update> FromIterator<<Update as Monoid>::Set> for DualSegmentTree<Update>
 where
     Update: Monoid,
@@ -317,6 +659,154 @@ where
     }
 }
 
+impl<Update> From<DualSegmentTree<Update>> for Vec<<Update as Monoid>::Set>
+where
+    Update: Monoid,
+{
+    fn from(tree: DualSegmentTree<Update>) -> Self {
+        tree.into_vec()
+    }
+}
+
+impl<Update> IntoIterator for DualSegmentTree<Update>
+where
+    Update: Monoid,
+{
+    type Item = <Update as Monoid>::Set;
+    type IntoIter = std::vec::IntoIter<<Update as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+/// Resolves the pending updates like [`iter`](DualSegmentTree::iter).
+///
+/// There is no impl for `&DualSegmentTree`: its elements exist only after the pending updates are resolved,
+/// so use this impl or [`to_vec`](DualSegmentTree::to_vec) instead.
+impl<'a, Update> IntoIterator for &'a mut DualSegmentTree<Update>
+where
+    Update: Monoid,
+{
+    type Item = &'a <Update as Monoid>::Set;
+    type IntoIter = std::slice::Iter<'a, <Update as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<Update> DualSegmentTree<Update>
+where
+    Update: Monoid<Set: Debug>,
+{
+    #[doc = include_str!("../doc/to_dot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(4);
+    /// dst.range_update(2..4, &5);
+    /// assert!(dst.to_dot().contains("{2..4 | pending: 5}"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = crate::render::Dot::new("DualSegmentTree");
+        self.render(&mut dot);
+        dot.finish()
+    }
+
+    #[doc = include_str!("../doc/pretty.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(4);
+    /// dst.range_update(2..4, &5);
+    /// assert_eq!(
+    ///     dst.pretty().lines().nth(1),
+    ///     Some("1: [0..2] pending: 0  [2..4] pending: 5")
+    /// );
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut levels = crate::render::Levels::default();
+        self.render(&mut levels);
+        levels.finish()
+    }
+
+    #[doc = include_str!("../doc/to_mermaid.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DualSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DualSegmentTree::<Add<i32>>::new(4);
+    /// dst.range_update(2..4, &5);
+    /// assert!(dst.to_mermaid().contains(r#"["2..4 | pending: 5"]"#));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = crate::render::Mermaid::default();
+        self.render(&mut mermaid);
+        mermaid.finish()
+    }
+
+    /// Returns an adapter which [displays](std::fmt::Display) the tree like [`pretty`](Self::pretty).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and formatting takes as long as [`pretty`](Self::pretty)
+    pub fn display(&self) -> crate::Pretty<'_, Self> {
+        crate::Pretty(self)
+    }
+
+    /// Passes the nodes to `out` in level order.
+    pub(crate) fn render(&self, out: &mut impl crate::render::Render) {
+        let n = self.len();
+        for i in 1..n << 1 {
+            let parent = (i > 1).then_some(i >> 1);
+            let depth = i.ilog2() as usize;
+            if i < n {
+                let range = crate::render::bottom_up_range(i, n);
+                out.node(i, parent, depth, &range, &[("pending", &self.data[i])]);
+            } else {
+                out.node(
+                    i,
+                    parent,
+                    depth,
+                    &(i - n).to_string(),
+                    &[("element", &self.data[i])],
+                );
+            }
+        }
+    }
+}
+
+/// Renders the tree like [`DualSegmentTree::pretty`].
+impl<Update> std::fmt::Display for crate::Pretty<'_, DualSegmentTree<Update>>
+where
+    Update: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.pretty())
+    }
+}
+
 impl<Update> Debug for DualSegmentTree<Update>
 where
     Update: Monoid<Set: Debug>,
@@ -339,6 +829,72 @@ where
     }
 }
 
+/// Creates an empty tree.
+impl<Update> Default for DualSegmentTree<Update>
+where
+    Update: Monoid,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Compares the elements with pending updates resolved.
+impl<Update> PartialEq for DualSegmentTree<Update>
+where
+    Update: Monoid<Set: PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.to_vec() == other.to_vec()
+    }
+}
+
+impl<Update> Eq for DualSegmentTree<Update> where Update: Monoid<Set: Eq> {}
+
+/// Hashes the elements with pending updates resolved, consistently with [`PartialEq`].
+impl<Update> Hash for DualSegmentTree<Update>
+where
+    Update: Monoid<Set: Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_vec().hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Update> serde::Serialize for DualSegmentTree<Update>
+where
+    Update: Monoid<Set: serde::Serialize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::Leaves {
+                values: self.to_vec(),
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Update> serde::Deserialize<'de> for DualSegmentTree<Update>
+where
+    Update: Monoid<Set: serde::Deserialize<'de>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <crate::serialize::Leaves<Vec<<Update as Monoid>::Set>> as serde::Deserialize>::deserialize(
+            deserializer,
+        )
+        .map(|leaves| Self::from(leaves.values))
+    }
+}
+
 #[cfg(test)]
 mod range_update {
     use rand::Rng;
@@ -377,3 +933,29 @@ mod range_update {
         }
     }
 }
+
+#[cfg(test)]
+mod to_vec {
+    use rand::Rng;
+
+    use crate::{dual::DualSegmentTree, ops::Affine};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut dual = DualSegmentTree::<Affine<i64>>::new(n);
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                dual.range_update(l..r, &update);
+
+                let expected = Vec::from_iter((0..n).map(|i| dual.point_query(i)));
+                assert_eq!(dual.to_vec(), expected);
+                assert_eq!(dual.clone().into_vec(), expected);
+            }
+        }
+    }
+}
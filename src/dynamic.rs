@@ -1,10 +1,16 @@
 use std::{
     fmt::Debug,
-    num::NonZeroUsize,
+    hash::{Hash, Hasher},
     ops::{Range, RangeBounds},
 };
 
-use crate::traits::Monoid;
+use crate::{
+    Error,
+    memory::{MemoryReport, vec_bytes},
+    stats::Counters,
+    traits::Monoid,
+    utility::{NodePtr, from_node_ptr, to_node_ptr},
+};
 
 /// A data structure that supports **range query point update** operations on large array.
 ///
@@ -18,11 +24,17 @@ pub struct DynamicSegmentTree<Query>
 where
     Query: Monoid,
 {
-    arena: Vec<Node<<Query as Monoid>::Set>>,
+    arena: Arena<<Query as Monoid>::Set>,
     range: Range<isize>,
+    /// the element at the indexes which have never been updated, or the identity if `None`
+    default: Option<<Query as Monoid>::Set>,
 
     // save allocation cost
     reusable_stack: Vec<usize>,
+    /// removed nodes to be reused
+    free_list: Vec<usize>,
+
+    counters: Counters,
 }
 // ANCHOR_END: definition
 
@@ -44,13 +56,38 @@ where
     /// ```
     #[inline]
     pub fn new(range: Range<isize>) -> Option<Self> {
+        Self::try_new(range).ok()
+    }
+
+    /// Same as [`new`](Self::new), but returns [`Error::EmptyRange`] if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, Error, ops::BitOr};
+    ///
+    /// assert!(DynamicSegmentTree::<BitOr<u32>>::try_new(-100..100).is_ok());
+    /// assert_eq!(
+    ///     DynamicSegmentTree::<BitOr<u32>>::try_new(100..-100).unwrap_err(),
+    ///     Error::EmptyRange
+    /// );
+    /// ```
+    #[inline]
+    pub fn try_new(range: Range<isize>) -> Result<Self, Error> {
         if range.is_empty() {
-            None
+            Err(Error::EmptyRange)
         } else {
-            Some(Self {
-                arena: Vec::new(),
+            Ok(Self {
+                arena: Arena::new(),
                 range,
+                default: None,
                 reusable_stack: Vec::new(),
+                free_list: Vec::new(),
+                counters: Counters::default(),
             })
         }
     }
@@ -78,14 +115,135 @@ where
             // never panic: `range.len()` is always larger than 0
             let height = range.len().ilog2() as usize + 1;
             Some(Self {
-                arena: Vec::with_capacity(q * height),
+                arena: Arena::with_capacity(q * height),
                 reusable_stack: Vec::with_capacity(height * 4),
+                free_list: Vec::new(),
                 range,
+                default: None,
+                counters: Counters::default(),
             })
         }
     }
     // ANCHOR_END: with_capacity
 
+    /// Same as [`with_capacity`](Self::with_capacity), but returns an error instead of [`None`] or panicking.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::EmptyRange`] if the range is empty.
+    /// - [`Error::CapacityOverflow`] if the capacity overflows or cannot be allocated.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, Error, ops::Add};
+    ///
+    /// assert!(DynamicSegmentTree::<Add<i32>>::try_with_capacity(-100..100, 10_000).is_ok());
+    /// assert_eq!(
+    ///     DynamicSegmentTree::<Add<i32>>::try_with_capacity(-100..100, usize::MAX).unwrap_err(),
+    ///     Error::CapacityOverflow
+    /// );
+    /// ```
+    pub fn try_with_capacity(range: Range<isize>, q: usize) -> Result<Self, Error> {
+        if range.is_empty() {
+            return Err(Error::EmptyRange);
+        }
+
+        let height = range.len().ilog2() as usize + 1;
+        let capacity = q.checked_mul(height).ok_or(Error::CapacityOverflow)?;
+        let mut reusable_stack = Vec::new();
+        reusable_stack
+            .try_reserve_exact(height * 4)
+            .map_err(|_| Error::CapacityOverflow)?;
+        Ok(Self {
+            arena: Arena::try_with_capacity(capacity)?,
+            reusable_stack,
+            free_list: Vec::new(),
+            range,
+            default: None,
+            counters: Counters::default(),
+        })
+    }
+
+    /// Same as [`new`](Self::new), but the elements which have never been updated are `element`
+    /// instead of [the identity element](crate::traits::Monoid::identity()).
+    ///
+    /// A run of `k` such elements is aggregated by [`combine_n`](crate::traits::Monoid::combine_n),
+    /// so the monoid should override it with a closed form if the default implementation is too slow.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, ops::GCD};
+    ///
+    /// // an array filled with 12
+    /// let mut dst = DynamicSegmentTree::<GCD<i64>>::with_default(0..1 << 40, 12).unwrap();
+    /// assert_eq!(dst.range_query(..), 12);
+    ///
+    /// dst.point_update(1 << 30, 18);
+    /// assert_eq!(dst.point_query(1 << 30), 18);
+    /// assert_eq!(dst.range_query(1 << 30..=1 << 30), 18);
+    /// assert_eq!(dst.range_query(..), 6);
+    /// ```
+    pub fn with_default(range: Range<isize>, element: <Query as Monoid>::Set) -> Option<Self> {
+        let mut tree = Self::new(range)?;
+        tree.set_default(element);
+        Some(tree)
+    }
+
+    /// Replaces the element at the indexes which have never been updated with `element`.
+    ///
+    /// The updated elements are kept as they are.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K*) where *K* is the number of nodes
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i64>>::new(0..100).unwrap();
+    /// dst.point_update(10, 5);
+    /// assert_eq!(dst.range_query(..), 5);
+    ///
+    /// dst.set_default(1);
+    /// assert_eq!(dst.range_query(..), 5 + 99);
+    /// assert_eq!(dst.point_query(20), 1);
+    /// ```
+    pub fn set_default(&mut self, element: <Query as Monoid>::Set) {
+        self.default = Some(element);
+        self.arena.track_bounds();
+
+        // recalculate every node in bottom-to-top order
+        let mut stack = Vec::from_iter((!self.arena.is_empty()).then_some(0));
+        while let Some(ptr) = stack.pop() {
+            self.reusable_stack.push(ptr);
+            stack.extend(self.arena.get_left_ptr(ptr));
+            stack.extend(self.arena.get_right_ptr(ptr));
+        }
+        self.recalculate_stack();
+    }
+
+    /// Returns the element at the indexes which have never been updated,
+    /// or [`None`] if it is [the identity element](crate::traits::Monoid::identity()).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn default_element(&self) -> Option<&<Query as Monoid>::Set> {
+        self.default.as_ref()
+    }
+
     /// Returns the number of elements.
     ///
     /// # Time complexity
@@ -111,6 +269,55 @@ where
         self.range.len()
     }
 
+    #[doc = include_str!("../doc/stats.md")]
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(-100..100).unwrap();
+    /// for i in 0..10 {
+    ///     dst.point_update(i, 1);
+    /// }
+    /// assert_eq!(dst.stats().allocations, 10);
+    /// ```
+    #[cfg(feature = "instrument")]
+    pub fn stats(&self) -> crate::Stats {
+        self.counters.stats()
+    }
+
+    /// Resets the counts returned by [`stats()`](Self::stats) to zero.
+    #[cfg(feature = "instrument")]
+    pub fn reset_stats(&mut self) {
+        self.counters.reset()
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(-100..100).unwrap();
+    /// assert_eq!(dst.memory_usage().arena, 0);
+    ///
+    /// dst.point_update(0, 1);
+    /// assert!(dst.memory_usage().arena > 0);
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            arena: self.arena.memory_usage(),
+            buffers: vec_bytes(&self.reusable_stack) + vec_bytes(&self.free_list),
+            ..Default::default()
+        }
+    }
+
     #[doc = include_str!("../doc/point_update.md")]
     /// # Time complexity
     ///
@@ -131,7 +338,8 @@ where
         assert!(self.range.contains(&i),);
 
         if self.arena.is_empty() {
-            self.arena.push(Node::new(i, element));
+            self.arena.push(i, element);
+            self.counters.allocate();
             return;
         }
 
@@ -142,29 +350,27 @@ where
             // for recalculating combined values
             self.reusable_stack.push(p_ptr);
 
-            if self.arena[p_ptr].index == i {
-                self.arena[p_ptr].element = element;
+            if self.arena.index[p_ptr] == i {
+                self.arena.element[p_ptr] = element;
                 break;
             }
 
             macro_rules! descend_or_grow {
                 ( $index_constraint:expr, $get_child_ptr:ident, $update_range_bounds:expr, $set_child:ident ) => {
                     if !($index_constraint) {
-                        std::mem::swap(&mut i, &mut self.arena[p_ptr].index);
-                        std::mem::swap(&mut element, &mut self.arena[p_ptr].element);
+                        std::mem::swap(&mut i, &mut self.arena.index[p_ptr]);
+                        std::mem::swap(&mut element, &mut self.arena.element[p_ptr]);
                     }
 
-                    if let Some(c_ptr) = self.arena[p_ptr].$get_child_ptr() {
+                    if let Some(c_ptr) = self.arena.$get_child_ptr(p_ptr) {
                         // descend
                         p_ptr = c_ptr;
                         $update_range_bounds;
                         continue;
                     } else {
                         // or grow
-                        let n = self.arena.len();
-                        self.arena[p_ptr].$set_child(n);
-
-                        self.arena.push(Node::new(i, element));
+                        let c_ptr = self.push_node(i, element);
+                        self.arena.$set_child(p_ptr, c_ptr);
                         break;
                     }
                 };
@@ -173,14 +379,14 @@ where
             let mid = start.midpoint(end);
             if i < mid {
                 descend_or_grow!(
-                    i < self.arena[p_ptr].index, // i_l < i_p
+                    i < self.arena.index[p_ptr], // i_l < i_p
                     get_left_ptr,
                     end = mid, // [start, end) -> [start, mid)
                     set_left_ptr
                 );
             } else {
                 descend_or_grow!(
-                    i > self.arena[p_ptr].index, // i_r > i_p
+                    i > self.arena.index[p_ptr], // i_r > i_p
                     get_right_ptr,
                     start = mid, // [start, end) -> [mid, end)
                     set_right_ptr
@@ -188,19 +394,355 @@ where
             }
         }
 
-        // recalculate `combined` value in bottom-to-top order
+        self.recalculate_stack();
+    }
+
+    /// Same as [`point_update`](Self::point_update), but returns [`Error::OutOfBounds`]
+    /// instead of panicking if `i` is out of the range.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, Error, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(-100..100).unwrap();
+    /// assert_eq!(dst.try_point_update(-100, 9), Ok(()));
+    /// assert_eq!(
+    ///     dst.try_point_update(100, 9),
+    ///     Err(Error::OutOfBounds { index: 100, range: -100..100 })
+    /// );
+    /// ```
+    pub fn try_point_update(
+        &mut self,
+        i: isize,
+        element: <Query as Monoid>::Set,
+    ) -> Result<(), Error> {
+        if !self.range.contains(&i) {
+            return Err(Error::OutOfBounds {
+                index: i,
+                range: self.range.clone(),
+            });
+        }
+        self.point_update(i, element);
+        Ok(())
+    }
+
+    /// Resets the `i`-th element to [the identity element](crate::traits::Monoid::identity()),
+    /// or to [the default element](Self::with_default) if any, and returns the previous one.
+    ///
+    /// The node which held the element is recycled for later updates.
+    /// Returns [`None`] if the `i`-th element has never been updated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(-100..100).unwrap();
+    /// dst.point_update(-50, 9);
+    /// dst.point_update(50, 3);
+    ///
+    /// assert_eq!(dst.point_remove(-50), Some(9));
+    /// assert_eq!(dst.point_remove(-50), None);
+    /// assert_eq!(dst.range_query(..), 3);
+    /// ```
+    pub fn point_remove(&mut self, i: isize) -> Option<<Query as Monoid>::Set> {
+        assert!(self.range.contains(&i));
+
+        if self.arena.is_empty() {
+            return None;
+        }
+
+        // find the node holding `i`
+        let mut p_ptr = 0;
+        let Range { mut start, mut end } = self.range;
+        while self.arena.index[p_ptr] != i {
+            self.reusable_stack.push(p_ptr);
+
+            let mid = start.midpoint(end);
+            let c_ptr = if i < mid {
+                end = mid;
+                self.arena.get_left_ptr(p_ptr)
+            } else {
+                start = mid;
+                self.arena.get_right_ptr(p_ptr)
+            };
+
+            if let Some(c_ptr) = c_ptr {
+                p_ptr = c_ptr
+            } else {
+                self.reusable_stack.clear();
+                return None;
+            }
+        }
+
+        let p_ptr = self.sink_hole(p_ptr);
+
+        // detach the leaf holding the removed element
+        self.reusable_stack.pop();
+        let removed = std::mem::replace(
+            &mut self.arena.element[p_ptr],
+            <Query as Monoid>::identity(),
+        );
+        if let Some(&parent) = self.reusable_stack.last() {
+            let is_left = self.arena.get_left_ptr(parent) == Some(p_ptr);
+            self.arena.children[parent][if is_left { 0 } else { 1 }] = None;
+            self.free_list.push(p_ptr);
+        } else {
+            self.arena.clear();
+            self.free_list.clear();
+        }
+
+        self.recalculate_stack();
+
+        Some(removed)
+    }
+
+    /// Moves the element at `ptr` down to a node without children
+    /// by filling the hole with the largest index on the left or the smallest index on the right,
+    /// and returns the pointer to that node.
+    ///
+    /// The nodes on the way, including the returned one, are pushed to `reusable_stack`.
+    fn sink_hole(&mut self, mut p_ptr: usize) -> usize {
+        loop {
+            self.reusable_stack.push(p_ptr);
+
+            let c_ptr = if let Some(mut c_ptr) = self.arena.get_left_ptr(p_ptr) {
+                while let Some(r_ptr) = self.arena.get_right_ptr(c_ptr) {
+                    self.reusable_stack.push(c_ptr);
+                    c_ptr = r_ptr
+                }
+                c_ptr
+            } else if let Some(mut c_ptr) = self.arena.get_right_ptr(p_ptr) {
+                while let Some(l_ptr) = self.arena.get_left_ptr(c_ptr) {
+                    self.reusable_stack.push(c_ptr);
+                    c_ptr = l_ptr
+                }
+                c_ptr
+            } else {
+                return p_ptr;
+            };
+
+            self.arena.swap(p_ptr, c_ptr);
+            p_ptr = c_ptr
+        }
+    }
+
+    /// Retains only the elements for which `f(index, element)` returns `true`,
+    /// visiting every updated element once.
+    ///
+    /// The removed elements are reset to [the identity element](crate::traits::Monoid::identity()),
+    /// or to [the default element](Self::with_default) if any,
+    /// and the nodes which held them are recycled for later updates.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q* + *R* log *N*), where *Q* is the number of the updated elements
+    /// and *R* is the number of the removed ones
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(-100..100).unwrap();
+    /// for i in -5..5 {
+    ///     dst.point_update(i * 10, i as i32);
+    /// }
+    ///
+    /// dst.retain(|index, &element| index >= 0 || element % 2 == 0);
+    /// assert_eq!(dst.to_vec(), vec![(-40, -4), (-20, -2), (0, 0), (10, 1), (20, 2), (30, 3), (40, 4)]);
+    /// assert_eq!(dst.range_query(..), 4);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(isize, &<Query as Monoid>::Set) -> bool,
+    {
+        if !self.arena.is_empty() && !self.retain_at(0, &mut f) {
+            self.arena.clear();
+            self.free_list.clear();
+        }
+    }
+
+    /// Removes the elements failing `f` from the subtree rooted at `ptr` in post-order,
+    /// and returns `false` if the subtree becomes empty.
+    fn retain_at<F>(&mut self, ptr: usize, f: &mut F) -> bool
+    where
+        F: FnMut(isize, &<Query as Monoid>::Set) -> bool,
+    {
+        let children = [self.arena.get_left_ptr(ptr), self.arena.get_right_ptr(ptr)];
+        for (k, c_ptr) in children.into_iter().enumerate() {
+            if let Some(c_ptr) = c_ptr
+                && !self.retain_at(c_ptr, f)
+            {
+                // prune the emptied subtree
+                self.arena.children[ptr][k] = None;
+                self.free_list.push(c_ptr);
+            }
+        }
+
+        if f(self.arena.index[ptr], self.arena.get_element(ptr)) {
+            self.reusable_stack.push(ptr);
+        } else {
+            // the descendants are already retained, so they fill the hole
+            let leaf = self.sink_hole(ptr);
+            self.reusable_stack.pop();
+            self.arena.element[leaf] = <Query as Monoid>::identity();
+            let Some(&parent) = self.reusable_stack.last() else {
+                return false;
+            };
+            let is_left = self.arena.get_left_ptr(parent) == Some(leaf);
+            self.arena.children[parent][if is_left { 0 } else { 1 }] = None;
+            self.free_list.push(leaf);
+        }
+        self.recalculate_stack();
+
+        true
+    }
+
+    /// Consumes the tree and returns the pairs of index and element
+    /// for the elements which have been updated, sorted by index.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q*), where *Q* is the number of the updated elements
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(-100..100).unwrap();
+    /// dst.point_update(50, 3);
+    /// dst.point_update(-50, 9);
+    ///
+    /// assert_eq!(dst.into_vec(), vec![(-50, 9), (50, 3)]);
+    /// ```
+    pub fn into_vec(self) -> Vec<(isize, <Query as Monoid>::Set)> {
+        let ptrs = self.in_order_ptrs();
+
+        let mut element = Vec::from_iter(self.arena.element.into_iter().map(Some));
+        Vec::from_iter(
+            ptrs.into_iter()
+                .map(|ptr| (self.arena.index[ptr], element[ptr].take().unwrap())),
+        )
+    }
+
+    /// Returns an iterator over the pairs of index and element
+    /// for the elements which have been updated, sorted by index.
+    ///
+    /// The nodes are walked in order on the fly, so nothing but a stack as deep as the tree is allocated.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q*) to consume, where *Q* is the number of the updated elements
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(-100..100).unwrap();
+    /// dst.point_update(50, 3);
+    /// dst.point_update(-50, 9);
+    ///
+    /// assert!(dst.iter().eq([(-50, &9), (50, &3)]));
+    /// ```
+    pub fn iter(&self) -> DynamicIter<'_, Query> {
+        DynamicIter {
+            tree: self,
+            stack: Vec::new(),
+            ptr: (!self.arena.is_empty()).then_some(0),
+        }
+    }
+
+    /// Returns the pointers to the nodes in ascending order of their indexes.
+    fn in_order_ptrs(&self) -> Vec<usize> {
+        let mut ptrs = Vec::with_capacity(self.arena.len() - self.free_list.len());
+        let mut stack = Vec::new();
+        let mut ptr = (!self.arena.is_empty()).then_some(0);
+        while ptr.is_some() || !stack.is_empty() {
+            while let Some(p_ptr) = ptr {
+                stack.push(p_ptr);
+                ptr = self.arena.get_left_ptr(p_ptr);
+            }
+
+            let p_ptr = stack.pop().unwrap();
+            ptrs.push(p_ptr);
+            ptr = self.arena.get_right_ptr(p_ptr);
+        }
+
+        ptrs
+    }
+
+    /// Allocates a new node, reusing removed one if exists.
+    fn push_node(&mut self, index: isize, element: <Query as Monoid>::Set) -> usize {
+        self.counters.allocate();
+        if let Some(ptr) = self.free_list.pop() {
+            self.arena.replace(ptr, index, element);
+            ptr
+        } else {
+            self.arena.push(index, element)
+        }
+    }
+
+    /// Recalculates `combined` values of the nodes on `reusable_stack` in bottom-to-top order.
+    fn recalculate_stack(&mut self) {
+        self.counters.depth(self.reusable_stack.len());
         while let Some(ptr) = self.reusable_stack.pop() {
+            let index = self.arena.index[ptr];
             let mut combined = <Query as Monoid>::identity();
+            let mut bounds = [index; 2];
 
-            if let Some(l_ptr) = self.arena[ptr].get_left_ptr() {
-                combined = <Query as Monoid>::combine(&combined, self.arena[l_ptr].get_combined())
+            if let Some(l_ptr) = self.arena.get_left_ptr(ptr) {
+                <Query as Monoid>::combine_assign(&mut combined, self.arena.get_combined(l_ptr));
+                self.counters.combine(1);
+                if let Some([lo, hi]) = self.arena.get_bounds(l_ptr) {
+                    bounds[0] = lo;
+                    self.combine_default(&mut combined, hi + 1, index);
+                }
             }
-            combined = <Query as Monoid>::combine(&combined, self.arena[ptr].get_element());
-            if let Some(r_ptr) = self.arena[ptr].get_right_ptr() {
-                combined = <Query as Monoid>::combine(&combined, self.arena[r_ptr].get_combined())
+            <Query as Monoid>::combine_assign(&mut combined, self.arena.get_element(ptr));
+            self.counters.combine(1);
+            if let Some(r_ptr) = self.arena.get_right_ptr(ptr) {
+                if let Some([lo, hi]) = self.arena.get_bounds(r_ptr) {
+                    self.combine_default(&mut combined, index + 1, lo);
+                    bounds[1] = hi;
+                }
+                <Query as Monoid>::combine_assign(&mut combined, self.arena.get_combined(r_ptr));
+                self.counters.combine(1);
             }
 
-            self.arena[ptr].set_combined(combined);
+            self.arena.set_combined(ptr, combined);
+            self.arena.set_bounds(ptr, bounds);
+        }
+    }
+
+    /// Combines the default elements at `start..end` into `acc`.
+    ///
+    /// Does nothing if the tree has no default element or the range is empty.
+    #[inline]
+    fn combine_default(&self, acc: &mut <Query as Monoid>::Set, start: isize, end: isize) {
+        if let Some(default) = self.default.as_ref()
+            && start < end
+        {
+            <Query as Monoid>::combine_assign(
+                acc,
+                &<Query as Monoid>::combine_n(default, start.abs_diff(end)),
+            );
+            self.counters.combine(1);
         }
     }
 
@@ -240,10 +782,26 @@ where
             std::ops::Bound::Unbounded => end,
         };
 
+        #[cfg(feature = "tracing")]
+        let traced = self
+            .counters
+            .enter(crate::stats::trace_span!("range_query", l..r));
+        let res = self.range_query_inner(l, r);
+        #[cfg(feature = "tracing")]
+        self.counters.exit(traced);
+        res
+    }
+
+    fn range_query_inner(&mut self, l: isize, r: isize) -> <Query as Monoid>::Set {
+        if self.default.is_some() {
+            return self.range_query_with_default(l, r);
+        }
         if l >= r || self.arena.is_empty() {
             return <Query as Monoid>::identity();
         }
 
+        let Range { start, end } = self.range;
+
         // Step 1: descend until the given `range` is within only one child.
         let mut p_ptr = 0;
         let [mut start, mut end] = [start, end];
@@ -281,21 +839,24 @@ where
 
         // (a) l <= i < mid
         let mut res = <Query as Monoid>::identity();
-        if let Some(mut p_ptr) = self.arena[p_ptr].get_left_ptr() {
+        if let Some(mut p_ptr) = self.arena.get_left_ptr(p_ptr) {
             let [mut start, mut end] = [start, mid];
             while let Some(node) = self.arena.get(p_ptr) {
                 if l <= start && end <= r {
                     res = <Query as Monoid>::combine(node.get_combined(), &res);
+                    self.counters.combine(1);
                     break;
                 }
 
                 let mid = start.midpoint(end);
                 if l < mid {
                     if let Some(r_ptr) = node.get_right_ptr() {
-                        res = <Query as Monoid>::combine(self.arena[r_ptr].get_combined(), &res)
+                        res = <Query as Monoid>::combine(self.arena.get_combined(r_ptr), &res);
+                        self.counters.combine(1);
                     }
                     if (l..r).contains(&node.index) {
-                        res = <Query as Monoid>::combine(node.get_element(), &res)
+                        res = <Query as Monoid>::combine(node.get_element(), &res);
+                        self.counters.combine(1);
                     }
                     if let Some(l_ptr) = node.get_left_ptr() {
                         p_ptr = l_ptr;
@@ -318,26 +879,30 @@ where
         }
 
         // (b) self
-        if (l..r).contains(&self.arena[p_ptr].index) {
-            res = <Query as Monoid>::combine(&res, self.arena[p_ptr].get_element());
+        if (l..r).contains(&self.arena.index[p_ptr]) {
+            <Query as Monoid>::combine_assign(&mut res, self.arena.get_element(p_ptr));
+            self.counters.combine(1);
         }
 
         // (c) mid <= i < r
-        if let Some(mut p_ptr) = self.arena[p_ptr].get_right_ptr() {
+        if let Some(mut p_ptr) = self.arena.get_right_ptr(p_ptr) {
             let [mut start, mut end] = [mid, end];
             while let Some(node) = self.arena.get(p_ptr) {
                 if l <= start && end <= r {
-                    res = <Query as Monoid>::combine(&res, node.get_combined());
+                    <Query as Monoid>::combine_assign(&mut res, node.get_combined());
+                    self.counters.combine(1);
                     break;
                 }
 
                 let mid = start.midpoint(end);
                 if r > mid {
                     if let Some(l_ptr) = node.get_left_ptr() {
-                        res = <Query as Monoid>::combine(&res, self.arena[l_ptr].get_combined());
+                        <Query as Monoid>::combine_assign(&mut res, self.arena.get_combined(l_ptr));
+                        self.counters.combine(1);
                     }
                     if (l..r).contains(&node.index) {
-                        res = <Query as Monoid>::combine(&res, node.get_element())
+                        <Query as Monoid>::combine_assign(&mut res, node.get_element());
+                        self.counters.combine(1);
                     }
                     if let Some(r_ptr) = node.get_right_ptr() {
                         p_ptr = r_ptr;
@@ -361,29 +926,87 @@ where
 
         // Step 3
         // ANCHOR: reusable_stack
+        self.counters.depth(self.reusable_stack.len());
         while let Some(ptr) = self.reusable_stack.pop() {
             const MSB: usize = 1_usize.rotate_right(1);
+            self.counters.combine(1);
             res = if ptr & MSB == 0 {
-                <Query as Monoid>::combine(self.arena[ptr].get_element(), &res)
+                <Query as Monoid>::combine(self.arena.get_element(ptr), &res)
             } else {
-                <Query as Monoid>::combine(&res, self.arena[!ptr].get_element())
+                <Query as Monoid>::combine(&res, self.arena.get_element(!ptr))
             }
         }
         // ANCHOR_END: reusable_stack
 
         res
     }
-}
 
-impl<Query> DynamicSegmentTree<Query>
-where
-    Query: Monoid<Set: Clone>,
-{
-    /// Answers query for i-th element.
-    ///
-    /// # Time complexity
-    ///
-    /// *O*(log *N*)
+    /// Answers a query over `l..r`, filling the gaps between the updated elements with the default element.
+    fn range_query_with_default(&self, l: isize, r: isize) -> <Query as Monoid>::Set {
+        let [l, r] = [l.max(self.range.start), r.min(self.range.end)];
+        let mut res = <Query as Monoid>::identity();
+        if l >= r {
+            return res;
+        }
+
+        // the first index which is not yet combined
+        let mut cursor = l;
+        if !self.arena.is_empty() {
+            self.fold_with_default(0, l..r, &mut res, &mut cursor);
+        }
+        self.combine_default(&mut res, cursor, r);
+        res
+    }
+
+    /// Combines the elements of the subtree in `range` up to the last updated one into `acc` in order.
+    ///
+    /// At most two subtrees at each depth overlap with `range` partially,
+    /// so this visits *O*(log *N*) nodes.
+    fn fold_with_default(
+        &self,
+        ptr: usize,
+        range: Range<isize>,
+        acc: &mut <Query as Monoid>::Set,
+        cursor: &mut isize,
+    ) {
+        // the bounds are tracked since the tree has a default element
+        let [lo, hi] = self.arena.get_bounds(ptr).unwrap();
+        if hi < range.start || range.end <= lo {
+            return;
+        }
+        if range.start <= lo && hi < range.end {
+            self.combine_default(acc, *cursor, lo);
+            <Query as Monoid>::combine_assign(acc, self.arena.get_combined(ptr));
+            self.counters.combine(1);
+            *cursor = hi + 1;
+            return;
+        }
+
+        if let Some(l_ptr) = self.arena.get_left_ptr(ptr) {
+            self.fold_with_default(l_ptr, range.clone(), acc, cursor);
+        }
+        let index = self.arena.index[ptr];
+        if range.contains(&index) {
+            self.combine_default(acc, *cursor, index);
+            <Query as Monoid>::combine_assign(acc, self.arena.get_element(ptr));
+            self.counters.combine(1);
+            *cursor = index + 1;
+        }
+        if let Some(r_ptr) = self.arena.get_right_ptr(ptr) {
+            self.fold_with_default(r_ptr, range, acc, cursor);
+        }
+    }
+}
+
+impl<Query> DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    /// Answers query for i-th element.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
     ///
     /// # Example
     ///
@@ -427,7 +1050,252 @@ where
             }
         }
 
-        <Query as Monoid>::identity()
+        self.default
+            .clone()
+            .unwrap_or_else(<Query as Monoid>::identity)
+    }
+
+    /// Returns the pairs of index and element
+    /// for the elements which have been updated, sorted by index.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q*), where *Q* is the number of the updated elements
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use seg_lib::{DynamicSegmentTree, ops::Max};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Max<i32>>::new(-100..100).unwrap();
+    /// dst.point_update(50, Some(3));
+    /// dst.point_update(-50, Some(9));
+    /// dst.point_update(0, None);
+    ///
+    /// assert_eq!(dst.to_vec(), vec![(-50, Some(9)), (0, None), (50, Some(3))]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<(isize, <Query as Monoid>::Set)> {
+        Vec::from_iter(self.iter().map(|(i, element)| (i, element.clone())))
+    }
+}
+
+impl<Query> From<DynamicSegmentTree<Query>> for Vec<(isize, <Query as Monoid>::Set)>
+where
+    Query: Monoid,
+{
+    fn from(tree: DynamicSegmentTree<Query>) -> Self {
+        tree.into_vec()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<Query> DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone + Send + Sync>,
+{
+    /// Answers independent point queries in parallel.
+    ///
+    /// The `k`-th result is equal to `self.point_query(indices[k])`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q* log *N*) work
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(-100..100).unwrap();
+    /// dst.point_update(-50, 9);
+    /// assert_eq!(dst.par_point_query(&[-50, 0]), vec![9, 0]);
+    /// ```
+    pub fn par_point_query(&self, indices: &[isize]) -> Vec<<Query as Monoid>::Set> {
+        use rayon::prelude::*;
+
+        indices.par_iter().map(|&i| self.point_query(i)).collect()
+    }
+}
+
+impl<Query> IntoIterator for DynamicSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    type Item = (isize, <Query as Monoid>::Set);
+    type IntoIter = std::vec::IntoIter<(isize, <Query as Monoid>::Set)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, Query> IntoIterator for &'a DynamicSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    type Item = (isize, &'a <Query as Monoid>::Set);
+    type IntoIter = DynamicIter<'a, Query>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the updated elements of a [`DynamicSegmentTree`], sorted by index.
+///
+/// Created by [`DynamicSegmentTree::iter`].
+pub struct DynamicIter<'a, Query>
+where
+    Query: Monoid,
+{
+    tree: &'a DynamicSegmentTree<Query>,
+    /// The nodes whose left subtrees are being visited.
+    stack: Vec<usize>,
+    /// The root of the subtree to be visited next.
+    ptr: Option<usize>,
+}
+
+impl<'a, Query> Iterator for DynamicIter<'a, Query>
+where
+    Query: Monoid,
+{
+    type Item = (isize, &'a <Query as Monoid>::Set);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(ptr) = self.ptr {
+            self.stack.push(ptr);
+            self.ptr = self.tree.arena.get_left_ptr(ptr);
+        }
+
+        let ptr = self.stack.pop()?;
+        self.ptr = self.tree.arena.get_right_ptr(ptr);
+        Some((self.tree.arena.index[ptr], &self.tree.arena.element[ptr]))
+    }
+}
+
+impl<Query> DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: Debug>,
+{
+    #[doc = include_str!("../doc/to_dot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K*) where *K* is the number of nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(0..8).unwrap();
+    /// dst.point_update(4, 1);
+    /// dst.point_update(6, 2);
+    /// let dot = dst.to_dot();
+    /// assert!(dot.contains("{0..8 | index: 4 | element: 1 | combined: 3}"));
+    /// assert!(dot.contains("{4..8 | index: 6 | element: 2 | combined: 2}"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = crate::render::Dot::new("DynamicSegmentTree");
+        self.render(&mut dot);
+        dot.finish()
+    }
+
+    #[doc = include_str!("../doc/pretty.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K*) where *K* is the number of nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(0..8).unwrap();
+    /// dst.point_update(4, 1);
+    /// dst.point_update(6, 2);
+    /// assert_eq!(
+    ///     dst.pretty(),
+    ///     "0: [0..8] index: 4, element: 1, combined: 3\n\
+    ///      1: [4..8] index: 6, element: 2, combined: 2\n"
+    /// );
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut levels = crate::render::Levels::default();
+        self.render(&mut levels);
+        levels.finish()
+    }
+
+    #[doc = include_str!("../doc/to_mermaid.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K*) where *K* is the number of nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicSegmentTree, ops::Add};
+    ///
+    /// let mut dst = DynamicSegmentTree::<Add<i32>>::new(0..8).unwrap();
+    /// dst.point_update(4, 1);
+    /// dst.point_update(6, 2);
+    /// let mermaid = dst.to_mermaid();
+    /// assert!(mermaid.contains(r#"["0..8 | index: 4 | element: 1 | combined: 3"]"#));
+    /// assert!(mermaid.contains(r#"["4..8 | index: 6 | element: 2 | combined: 2"]"#));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = crate::render::Mermaid::default();
+        self.render(&mut mermaid);
+        mermaid.finish()
+    }
+
+    /// Returns an adapter which [displays](std::fmt::Display) the tree like [`pretty`](Self::pretty).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and formatting takes as long as [`pretty`](Self::pretty)
+    pub fn display(&self) -> crate::Pretty<'_, Self> {
+        crate::Pretty(self)
+    }
+
+    /// Passes the nodes to `out` in preorder, visiting the left child first.
+    pub(crate) fn render(&self, out: &mut impl crate::render::Render) {
+        let mut stack =
+            Vec::from_iter((!self.arena.is_empty()).then(|| (0, None, 0, self.range.clone())));
+        while let Some((ptr, parent, depth, range)) = stack.pop() {
+            let label = format!("{range:?}");
+            out.node(
+                ptr,
+                parent,
+                depth,
+                &label,
+                &[
+                    ("index", &self.arena.index[ptr]),
+                    ("element", self.arena.get_element(ptr)),
+                    ("combined", self.arena.get_combined(ptr)),
+                ],
+            );
+
+            let mid = range.start.midpoint(range.end);
+            if let Some(r_ptr) = self.arena.get_right_ptr(ptr) {
+                stack.push((r_ptr, Some(ptr), depth + 1, mid..range.end));
+            }
+            if let Some(l_ptr) = self.arena.get_left_ptr(ptr) {
+                stack.push((l_ptr, Some(ptr), depth + 1, range.start..mid));
+            }
+        }
+    }
+}
+
+/// Renders the tree like [`DynamicSegmentTree::pretty`].
+impl<Query> std::fmt::Display for crate::Pretty<'_, DynamicSegmentTree<Query>>
+where
+    Query: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.pretty())
     }
 }
 
@@ -439,7 +1307,9 @@ where
         f.debug_struct("DynamicSegmentTree")
             .field("data", &self.arena)
             .field("range", &self.range)
+            .field("default", &self.default)
             .field("reusable_stack", &self.reusable_stack)
+            .field("free_list", &self.free_list)
             .finish()
     }
 }
@@ -452,74 +1322,675 @@ where
         Self {
             arena: self.arena.clone(),
             range: self.range.clone(),
+            default: self.default.clone(),
             reusable_stack: self.reusable_stack.clone(),
+            free_list: self.free_list.clone(),
+            counters: self.counters.clone(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Creates a tree over the empty range `0..0`.
+///
+/// Queries return the identity, and any point update panics.
+/// Unlike [`new`](DynamicSegmentTree::new), this never fails.
+impl<Query> Default for DynamicSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn default() -> Self {
+        Self {
+            arena: Arena::new(),
+            range: 0..0,
+            default: None,
+            reusable_stack: Vec::new(),
+            free_list: Vec::new(),
+            counters: Counters::default(),
+        }
+    }
+}
+
+/// Compares the elements, the ranges and the default elements.
+///
+/// Elements which are never updated are equal to the default element, or the identity if not set,
+/// so trees that allocate different nodes can be equal.
+impl<Query> PartialEq for DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let identity = <Query as Monoid>::identity();
+        let lhs_default = self.default.as_ref().unwrap_or(&identity);
+        let rhs_default = other.default.as_ref().unwrap_or(&identity);
+        self.range == other.range
+            && lhs_default == rhs_default
+            && self
+                .non_default_leaves(lhs_default)
+                .eq(other.non_default_leaves(rhs_default))
+    }
+}
+
+impl<Query> Eq for DynamicSegmentTree<Query> where Query: Monoid<Set: Eq> {}
+
+/// Hashes the range, the default element and the other elements, consistently with [`PartialEq`].
+impl<Query> Hash for DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: PartialEq + Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let identity = <Query as Monoid>::identity();
+        let default = self.default.as_ref().unwrap_or(&identity);
+        self.range.hash(state);
+        default.hash(state);
+        for leaf in self.non_default_leaves(default) {
+            leaf.hash(state)
+        }
+    }
+}
+
+impl<Query> DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: PartialEq>,
+{
+    /// Returns the indices and the elements of the leaves in order, skipping the `default` element.
+    fn non_default_leaves<'a>(
+        &'a self,
+        default: &'a <Query as Monoid>::Set,
+    ) -> impl Iterator<Item = (isize, &'a <Query as Monoid>::Set)> + 'a {
+        self.in_order_ptrs()
+            .into_iter()
+            .map(|ptr| (self.arena.index[ptr], &self.arena.element[ptr]))
+            .filter(move |(_, element)| *element != default)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Query> serde::Serialize for DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone + serde::Serialize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::RangedLeaves {
+                range: self.range.clone(),
+                values: self.to_vec(),
+                default: self.default.clone(),
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Query> serde::Deserialize<'de> for DynamicSegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Deserialize<'de>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let leaves = <crate::serialize::RangedLeaves<
+            Vec<(isize, <Query as Monoid>::Set)>,
+            <Query as Monoid>::Set,
+        > as serde::Deserialize>::deserialize(deserializer)?;
+        let mut tree = Self::try_new(leaves.range).map_err(D::Error::custom)?;
+        if let Some(default) = leaves.default {
+            tree.set_default(default)
+        }
+        for (i, element) in leaves.values {
+            tree.try_point_update(i, element)
+                .map_err(D::Error::custom)?
+        }
+        Ok(tree)
+    }
+}
+
 // ANCHOR: node
-struct Node<T> {
-    index: isize,
-    element: T,
+/// Nodes in structure-of-arrays layout.
+///
+/// Traversals mostly touch indexes, pointers and combined values,
+/// so they are stored apart from elements.
+#[derive(Debug, Clone)]
+struct Arena<T> {
+    index: Vec<isize>,
+    element: Vec<T>,
     /// may be `None` if `combined == element`, avoiding `clone()`
-    combined: Option<T>,
+    combined: Vec<Option<T>>,
 
-    left_ptr: Option<NonZeroUsize>,
-    right_ptr: Option<NonZeroUsize>,
+    /// `[left, right]`
+    children: Vec<[Option<NodePtr>; 2]>,
+    /// `[min, max]` of the indexes in the subtree,
+    /// tracked only if the tree has a default element to fill the gaps between them
+    bounds: Option<Vec<[isize; 2]>>,
 }
 // ANCHOR_END: node
 
-impl<T> Node<T> {
+impl<T> Arena<T> {
+    #[inline]
+    fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
     #[inline]
-    fn new(index: isize, element: T) -> Self {
+    fn with_capacity(capacity: usize) -> Self {
         Self {
-            index,
-            element,
-            combined: None,
-            left_ptr: None,
-            right_ptr: None,
+            index: Vec::with_capacity(capacity),
+            element: Vec::with_capacity(capacity),
+            combined: Vec::with_capacity(capacity),
+            children: Vec::with_capacity(capacity),
+            bounds: None,
         }
     }
 
+    fn try_with_capacity(capacity: usize) -> Result<Self, Error> {
+        let mut arena = Self::new();
+        arena
+            .index
+            .try_reserve_exact(capacity)
+            .and_then(|_| arena.element.try_reserve_exact(capacity))
+            .and_then(|_| arena.combined.try_reserve_exact(capacity))
+            .and_then(|_| arena.children.try_reserve_exact(capacity))
+            .map_err(|_| Error::CapacityOverflow)?;
+        Ok(arena)
+    }
+
     #[inline]
-    fn get_left_ptr(&self) -> Option<usize> {
-        self.left_ptr.map(|i| i.get())
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns the bytes allocated by the arena.
+    fn memory_usage(&self) -> usize {
+        vec_bytes(&self.index)
+            + vec_bytes(&self.element)
+            + vec_bytes(&self.combined)
+            + vec_bytes(&self.children)
+            + self.bounds.as_ref().map_or(0, vec_bytes)
     }
 
     #[inline]
-    fn get_right_ptr(&self) -> Option<usize> {
-        self.right_ptr.map(|i| i.get())
+    fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.index.clear();
+        self.element.clear();
+        self.combined.clear();
+        self.children.clear();
+        if let Some(bounds) = self.bounds.as_mut() {
+            bounds.clear()
+        }
     }
 
-    /// Invalid `ptr` will be ignored.
+    /// Appends a new node without children and returns the pointer to it.
     #[inline]
-    fn set_left_ptr(&mut self, ptr: usize) {
-        self.left_ptr = NonZeroUsize::new(ptr)
+    fn push(&mut self, index: isize, element: T) -> usize {
+        self.index.push(index);
+        self.element.push(element);
+        self.combined.push(None);
+        self.children.push([None; 2]);
+        if let Some(bounds) = self.bounds.as_mut() {
+            bounds.push([index; 2])
+        }
+        self.index.len() - 1
     }
 
-    /// Invalid `ptr` will be ignored.
+    /// Overwrites the node `ptr` points to with a new node without children.
     #[inline]
-    fn set_right_ptr(&mut self, ptr: usize) {
-        self.right_ptr = NonZeroUsize::new(ptr)
+    fn replace(&mut self, ptr: usize, index: isize, element: T) {
+        self.index[ptr] = index;
+        self.element[ptr] = element;
+        self.combined[ptr] = None;
+        self.children[ptr] = [None; 2];
+        self.set_bounds(ptr, [index; 2]);
     }
 
+    /// Swaps the indexes and elements of the two nodes.
     #[inline]
-    fn get_element(&self) -> &T {
-        &self.element
+    fn swap(&mut self, ptr1: usize, ptr2: usize) {
+        self.index.swap(ptr1, ptr2);
+        self.element.swap(ptr1, ptr2);
     }
 
     #[inline]
-    fn get_combined(&self) -> &T {
-        if let Some(combined) = self.combined.as_ref() {
+    fn get(&self, ptr: usize) -> Option<NodeRef<'_, T>> {
+        (ptr < self.len()).then(|| NodeRef {
+            arena: self,
+            ptr,
+            index: self.index[ptr],
+        })
+    }
+
+    #[inline]
+    fn get_left_ptr(&self, ptr: usize) -> Option<usize> {
+        self.children[ptr][0].map(from_node_ptr)
+    }
+
+    #[inline]
+    fn get_right_ptr(&self, ptr: usize) -> Option<usize> {
+        self.children[ptr][1].map(from_node_ptr)
+    }
+
+    /// Invalid `child` will be ignored.
+    #[inline]
+    fn set_left_ptr(&mut self, ptr: usize, child: usize) {
+        self.children[ptr][0] = to_node_ptr(child)
+    }
+
+    /// Invalid `child` will be ignored.
+    #[inline]
+    fn set_right_ptr(&mut self, ptr: usize, child: usize) {
+        self.children[ptr][1] = to_node_ptr(child)
+    }
+
+    /// Starts tracking the bounds of the subtrees if not yet.
+    ///
+    /// Each node is bounded by its own index, so the caller should recalculate the bounds in bottom-to-top order.
+    fn track_bounds(&mut self) {
+        if self.bounds.is_none() {
+            self.bounds = Some(Vec::from_iter(self.index.iter().map(|&index| [index; 2])))
+        }
+    }
+
+    /// Returns `[min, max]` of the indexes in the subtree, or [`None`] if the bounds are not tracked.
+    #[inline]
+    fn get_bounds(&self, ptr: usize) -> Option<[isize; 2]> {
+        self.bounds.as_ref().map(|bounds| bounds[ptr])
+    }
+
+    /// Does nothing if the bounds are not tracked.
+    #[inline]
+    fn set_bounds(&mut self, ptr: usize, bounds: [isize; 2]) {
+        if let Some(tracked) = self.bounds.as_mut() {
+            tracked[ptr] = bounds
+        }
+    }
+
+    #[inline]
+    fn get_element(&self, ptr: usize) -> &T {
+        &self.element[ptr]
+    }
+
+    #[inline]
+    fn get_combined(&self, ptr: usize) -> &T {
+        if let Some(combined) = self.combined[ptr].as_ref() {
             combined
         } else {
-            &self.element
+            &self.element[ptr]
         }
     }
 
     #[inline]
-    fn set_combined(&mut self, combined: T) {
-        self.combined = Some(combined);
+    fn set_combined(&mut self, ptr: usize, combined: T) {
+        self.combined[ptr] = Some(combined);
+    }
+}
+
+/// Read-only view of a node on [`Arena`].
+struct NodeRef<'a, T> {
+    arena: &'a Arena<T>,
+    ptr: usize,
+    index: isize,
+}
+
+impl<T> NodeRef<'_, T> {
+    #[inline]
+    fn get_left_ptr(&self) -> Option<usize> {
+        self.arena.get_left_ptr(self.ptr)
+    }
+
+    #[inline]
+    fn get_right_ptr(&self) -> Option<usize> {
+        self.arena.get_right_ptr(self.ptr)
+    }
+
+    #[inline]
+    fn get_element(&self) -> &T {
+        self.arena.get_element(self.ptr)
+    }
+
+    #[inline]
+    fn get_combined(&self) -> &T {
+        self.arena.get_combined(self.ptr)
+    }
+}
+
+#[cfg(test)]
+mod point_remove {
+    use rand::Rng;
+
+    use crate::{
+        DynamicSegmentTree,
+        ops::{Add, Concat},
+    };
+
+    #[test]
+    fn random() {
+        const N: usize = 40;
+        const OFFSET: isize = -20;
+
+        let mut rng = rand::rng();
+        for _ in 0..50 {
+            let mut naive = vec![None; N];
+            let mut dst = DynamicSegmentTree::<Add<i32>>::new(OFFSET..OFFSET + N as isize).unwrap();
+
+            for _ in 0..200 {
+                let i = rng.random_range(0..N);
+                if rng.random_bool(0.5) {
+                    let element = rng.random_range(-10..=10);
+                    dst.point_update(OFFSET + i as isize, element);
+                    naive[i] = Some(element);
+                } else {
+                    assert_eq!(dst.point_remove(OFFSET + i as isize), naive[i].take());
+                }
+
+                // removed nodes are reused
+                assert!(dst.arena.len() <= N);
+                assert_eq!(
+                    dst.arena.len() - dst.free_list.len(),
+                    naive.iter().flatten().count()
+                );
+                for l in 0..N {
+                    for r in l..=N {
+                        assert_eq!(
+                            dst.range_query(OFFSET + l as isize..OFFSET + r as isize),
+                            naive[l..r].iter().flatten().sum::<i32>(),
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn non_commutative() {
+        const N: usize = 40;
+        const OFFSET: isize = -20;
+
+        let mut rng = rand::rng();
+        for _ in 0..50 {
+            let mut naive = vec![None; N];
+            let mut dst =
+                DynamicSegmentTree::<Concat<i32>>::new(OFFSET..OFFSET + N as isize).unwrap();
+
+            for _ in 0..200 {
+                let i = rng.random_range(0..N);
+                if rng.random_bool(0.5) {
+                    let element = vec![rng.random_range(-10..=10), i as i32];
+                    dst.point_update(OFFSET + i as isize, element.clone());
+                    naive[i] = Some(element);
+                } else {
+                    assert_eq!(dst.point_remove(OFFSET + i as isize), naive[i].take());
+                }
+
+                for l in 0..N {
+                    for r in l..=N {
+                        assert_eq!(
+                            dst.range_query(OFFSET + l as isize..OFFSET + r as isize),
+                            naive[l..r]
+                                .iter()
+                                .flatten()
+                                .flatten()
+                                .copied()
+                                .collect::<Vec<_>>(),
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod retain {
+    use rand::Rng;
+
+    use crate::{DynamicSegmentTree, ops::Concat};
+
+    #[test]
+    fn random() {
+        const N: usize = 40;
+        const OFFSET: isize = -20;
+
+        let mut rng = rand::rng();
+        for _ in 0..50 {
+            let default = rng.random_bool(0.5).then_some(vec![-1]);
+            let mut naive = vec![None; N];
+            let mut dst =
+                DynamicSegmentTree::<Concat<i32>>::new(OFFSET..OFFSET + N as isize).unwrap();
+            if let Some(default) = default.clone() {
+                dst.set_default(default)
+            }
+
+            for _ in 0..10 {
+                for _ in 0..rng.random_range(0..20) {
+                    let i = rng.random_range(0..N);
+                    let element = rng.random_range(0..10);
+                    dst.point_update(OFFSET + i as isize, vec![element]);
+                    naive[i] = Some(vec![element]);
+                }
+
+                let (modulus, residue) = (rng.random_range(1..4_i64), rng.random_range(0..4_i64));
+                let keep = |index: isize, element: &Vec<i32>| {
+                    (index as i64 + element[0] as i64).rem_euclid(modulus) != residue
+                };
+                let mut visited = Vec::new();
+                dst.retain(|index, element| {
+                    visited.push(index);
+                    keep(index, element)
+                });
+                let mut expected_visited = Vec::new();
+                for (i, e) in naive.iter_mut().enumerate() {
+                    let index = OFFSET + i as isize;
+                    if let Some(element) = e {
+                        expected_visited.push(index);
+                        if !keep(index, element) {
+                            *e = None
+                        }
+                    }
+                }
+                visited.sort_unstable();
+                assert_eq!(visited, expected_visited);
+
+                assert_eq!(
+                    dst.arena.len() - dst.free_list.len(),
+                    naive.iter().flatten().count()
+                );
+                let filled = Vec::from_iter(naive.iter().map(|e| {
+                    e.clone()
+                        .unwrap_or_else(|| default.clone().unwrap_or_default())
+                }));
+                for l in 0..N {
+                    for r in l..=N {
+                        assert_eq!(
+                            dst.range_query(OFFSET + l as isize..OFFSET + r as isize),
+                            filled[l..r].concat(),
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod iter {
+    use rand::Rng;
+
+    use crate::{DynamicSegmentTree, ops::Add};
+
+    #[test]
+    fn random() {
+        const N: usize = 40;
+        const OFFSET: isize = -20;
+
+        let mut rng = rand::rng();
+        for _ in 0..50 {
+            let mut naive = vec![None; N];
+            let mut dst = DynamicSegmentTree::<Add<i32>>::new(OFFSET..OFFSET + N as isize).unwrap();
+
+            for _ in 0..30 {
+                let i = rng.random_range(0..N);
+                if rng.random_bool(0.3) {
+                    dst.point_remove(OFFSET + i as isize);
+                    naive[i] = None;
+                } else {
+                    let element = rng.random_range(-10..10);
+                    dst.point_update(OFFSET + i as isize, element);
+                    naive[i] = Some(element);
+                }
+
+                let expected = Vec::from_iter(
+                    (naive.iter().enumerate())
+                        .filter_map(|(i, e)| e.as_ref().map(|e| (OFFSET + i as isize, e))),
+                );
+                assert_eq!(Vec::from_iter(dst.iter()), expected);
+                assert_eq!(Vec::from_iter(&dst), expected);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod eq {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use crate::{DynamicSegmentTree, ops::Add};
+
+    #[test]
+    fn identity_leaves() {
+        let mut lhs = DynamicSegmentTree::<Add<i32>>::new(-10..10).unwrap();
+        let mut rhs = DynamicSegmentTree::<Add<i32>>::new(-10..10).unwrap();
+        lhs.point_update(-5, 3);
+        rhs.point_update(-5, 3);
+        assert_eq!(lhs, rhs);
+
+        // leaves holding the identity are equal to missing leaves
+        rhs.point_update(7, 0);
+        assert_eq!(lhs, rhs);
+
+        rhs.point_update(7, 1);
+        assert_ne!(lhs, rhs);
+        assert_ne!(lhs, DynamicSegmentTree::new(-10..11).unwrap());
+    }
+
+    #[test]
+    fn hash() {
+        let hash = |tree: &DynamicSegmentTree<Add<i32>>| {
+            let mut hasher = DefaultHasher::new();
+            tree.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let mut lhs = DynamicSegmentTree::<Add<i32>>::new(0..100).unwrap();
+        let mut rhs = DynamicSegmentTree::<Add<i32>>::new(0..100).unwrap();
+        lhs.point_update(50, 1);
+        rhs.point_update(10, 0);
+        rhs.point_update(50, 1);
+        rhs.point_update(90, 0);
+        assert_eq!(hash(&lhs), hash(&rhs));
+    }
+}
+
+#[cfg(test)]
+mod default {
+    use crate::{DynamicSegmentTree, ops::Add};
+
+    #[test]
+    fn empty_range() {
+        let mut dst = DynamicSegmentTree::<Add<i32>>::default();
+        assert_eq!(dst.len(), 0);
+        assert_eq!(dst.range_query(..), 0);
+        assert!(dst.to_vec().is_empty());
+        assert_eq!(dst, DynamicSegmentTree::default());
+    }
+
+    #[test]
+    #[should_panic]
+    fn point_update() {
+        DynamicSegmentTree::<Add<i32>>::default().point_update(0, 1)
+    }
+}
+
+#[cfg(test)]
+mod default_element {
+    use rand::Rng;
+
+    use crate::{DynamicSegmentTree, ops::Concat};
+
+    #[test]
+    fn random() {
+        const N: usize = 30;
+        const OFFSET: isize = -10;
+
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut naive = vec![vec![-1]; N];
+            let mut dst = DynamicSegmentTree::<Concat<i32>>::with_default(
+                OFFSET..OFFSET + N as isize,
+                vec![-1],
+            )
+            .unwrap();
+
+            for _ in 0..100 {
+                let i = rng.random_range(0..N);
+                if rng.random_bool(0.7) {
+                    let element = rng.random_range(0..10);
+                    dst.point_update(OFFSET + i as isize, vec![element]);
+                    naive[i] = vec![element];
+                } else {
+                    dst.point_remove(OFFSET + i as isize);
+                    naive[i] = vec![-1];
+                }
+
+                for l in 0..N {
+                    for r in l..=N {
+                        assert_eq!(
+                            dst.range_query(OFFSET + l as isize..OFFSET + r as isize),
+                            naive[l..r].concat(),
+                        )
+                    }
+                    assert_eq!(dst.point_query(OFFSET + l as isize), naive[l]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_default() {
+        let mut dst = DynamicSegmentTree::<Concat<i32>>::new(0..6).unwrap();
+        dst.point_update(1, vec![1]);
+        dst.point_update(4, vec![4]);
+        assert_eq!(dst.range_query(..), vec![1, 4]);
+
+        dst.set_default(vec![0]);
+        assert_eq!(dst.range_query(..), vec![0, 1, 0, 0, 4, 0]);
+        assert_eq!(dst.range_query(2..5), vec![0, 0, 4]);
+        assert_ne!(dst, DynamicSegmentTree::new(0..6).unwrap());
+    }
+
+    #[test]
+    fn bounds() {
+        // the bounds cost nothing without a default element
+        let mut dst = DynamicSegmentTree::<Concat<i32>>::new(0..100).unwrap();
+        for i in (0..100).step_by(7) {
+            dst.point_update(i, vec![i as i32]);
+        }
+        assert!(dst.arena.bounds.is_none());
+        let arena = dst.memory_usage().arena;
+
+        // the bounds of the existing nodes are tracked from now on
+        dst.set_default(vec![-1]);
+        assert!(dst.memory_usage().arena > arena);
+        assert_eq!(dst.range_query(13..16), vec![-1, 14, -1]);
+        dst.point_update(15, vec![15]);
+        assert_eq!(dst.range_query(13..17), vec![-1, 14, 15, -1]);
     }
 }
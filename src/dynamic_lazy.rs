@@ -1,10 +1,16 @@
 use std::{
     fmt::Debug,
-    num::NonZeroUsize,
+    hash::{Hash, Hasher},
     ops::{Range, RangeBounds},
 };
 
-use crate::traits::{Monoid, MonoidAction};
+use crate::{
+    Error,
+    memory::{MemoryReport, vec_bytes},
+    stats::Counters,
+    traits::{Monoid, MonoidAction},
+    utility::{NodePtr, from_node_ptr, to_node_ptr},
+};
 
 /// A data structure that supports **range query range update** operations on large array.
 ///
@@ -17,11 +23,17 @@ pub struct DynamicLazySegmentTree<Action>
 where
     Action: MonoidAction,
 {
-    arena: Vec<Node<<Action as MonoidAction>::Set, <Action as MonoidAction>::Map>>,
+    arena: Arena<<Action as MonoidAction>::Set, <Action as MonoidAction>::Map>,
     range: Range<isize>,
+    /// the element at the indexes which have never been updated, or the identity if `None`
+    default: Option<<<Action as MonoidAction>::Set as Monoid>::Set>,
 
     // save allocation cost
     reusable_buf: Vec<(usize, Range<isize>)>,
+    /// removed nodes to be reused
+    free_list: Vec<usize>,
+
+    counters: Counters,
 }
 
 impl<Action> DynamicLazySegmentTree<Action>
@@ -34,13 +46,41 @@ where
     /// *O*(1)
     #[inline]
     pub fn new(range: Range<isize>) -> Option<Self> {
+        Self::try_new(range).ok()
+    }
+
+    /// Same as [`new`](Self::new), but returns [`Error::EmptyRange`] if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, Error, acts::AddQueryAddUpdate};
+    ///
+    /// assert!(DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::try_new(-100..100).is_ok());
+    /// assert_eq!(
+    ///     DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::try_new(0..0).unwrap_err(),
+    ///     Error::EmptyRange
+    /// );
+    /// ```
+    pub fn try_new(range: Range<isize>) -> Result<Self, Error> {
         if range.is_empty() {
-            None
+            Err(Error::EmptyRange)
         } else {
-            Some(Self {
-                arena: vec![Node::new()],
+            Ok(Self {
+                arena: {
+                    let mut arena = Arena::new();
+                    arena.push();
+                    arena
+                },
                 reusable_buf: Vec::with_capacity((range.len().ilog2() as usize + 1) << 2),
+                free_list: Vec::new(),
                 range,
+                default: None,
+                counters: Counters::default(),
             })
         }
     }
@@ -58,16 +98,136 @@ where
             let height = range.len().ilog2() as usize + 1;
             Some(Self {
                 arena: {
-                    let mut arena = Vec::with_capacity(q * height);
-                    arena.push(Node::new());
+                    let mut arena = Arena::with_capacity(q * height);
+                    arena.push();
                     arena
                 },
                 range,
+                default: None,
                 reusable_buf: Vec::with_capacity(height * 4),
+                free_list: Vec::new(),
+                counters: Counters::default(),
             })
         }
     }
 
+    /// Same as [`with_capacity`](Self::with_capacity), but returns an error instead of [`None`] or panicking.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::EmptyRange`] if the range is empty.
+    /// - [`Error::CapacityOverflow`] if the capacity overflows or cannot be allocated.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn try_with_capacity(range: Range<isize>, q: usize) -> Result<Self, Error> {
+        if range.is_empty() {
+            return Err(Error::EmptyRange);
+        }
+
+        let height = range.len().ilog2() as usize + 1;
+        let capacity = q.checked_mul(height).ok_or(Error::CapacityOverflow)?;
+        let mut reusable_buf = Vec::new();
+        reusable_buf
+            .try_reserve_exact(height * 4)
+            .map_err(|_| Error::CapacityOverflow)?;
+        Ok(Self {
+            arena: {
+                let mut arena = Arena::try_with_capacity(capacity)?;
+                arena.push();
+                arena
+            },
+            range,
+            default: None,
+            reusable_buf,
+            free_list: Vec::new(),
+            counters: Counters::default(),
+        })
+    }
+
+    /// Same as [`new`](Self::new), but every element is `element`
+    /// instead of [the identity element](crate::traits::Monoid::identity()).
+    ///
+    /// No node is allocated: a segment of `k` elements which have never been updated is aggregated
+    /// by [`combine_n`](crate::traits::Monoid::combine_n),
+    /// so the monoid should override it with a closed form if the default implementation is too slow.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), plus one call to `combine_n`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// // an array filled with 1
+    /// let mut dlst =
+    ///     DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::with_default(0..1 << 40, 1).unwrap();
+    /// assert_eq!(dlst.range_query(..), 1 << 40);
+    ///
+    /// dlst.range_update(..10, &2);
+    /// assert_eq!(dlst.range_query(5..15), 5 * 3 + 5);
+    /// ```
+    pub fn with_default(
+        range: Range<isize>,
+        element: <<Action as MonoidAction>::Set as Monoid>::Set,
+    ) -> Option<Self> {
+        let mut tree = Self::new(range)?;
+        tree.fill(element);
+        Some(tree)
+    }
+
+    /// Resets all elements to `element`, which becomes the default element.
+    ///
+    /// Later [`clear`](Self::clear) and [`range_reset`](Self::range_reset) reset the elements to it.
+    /// The allocated capacity is retained.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*M*), where *M* is the number of allocated nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::MinQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<MinQueryAddUpdate<i64>>::new(0..100).unwrap();
+    /// dlst.range_update(..50, &1);
+    ///
+    /// dlst.fill(Some(5));
+    /// assert_eq!(dlst.range_query(..), Some(5));
+    ///
+    /// dlst.range_update(50.., &-1);
+    /// dlst.range_reset(..75);
+    /// assert_eq!(dlst.range_query(..), Some(4));
+    /// assert_eq!(dlst.range_query(..75), Some(5));
+    /// ```
+    pub fn fill(&mut self, element: <<Action as MonoidAction>::Set as Monoid>::Set) {
+        self.default = Some(element);
+        self.clear();
+    }
+
+    /// Returns the element at the indexes which have never been updated,
+    /// or [`None`] if it is [the identity element](crate::traits::Monoid::identity()).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn default_element(&self) -> Option<&<<Action as MonoidAction>::Set as Monoid>::Set> {
+        self.default.as_ref()
+    }
+
+    /// Returns the combined value of `len` elements which have never been updated.
+    fn default_n(&self, len: usize) -> <<Action as MonoidAction>::Set as Monoid>::Set {
+        if let Some(default) = self.default.as_ref() {
+            <<Action as MonoidAction>::Set as Monoid>::combine_n(default, len)
+        } else {
+            <<Action as MonoidAction>::Set as Monoid>::identity()
+        }
+    }
+
     /// Returns the number of elements.
     ///
     /// # Time complexity
@@ -79,6 +239,195 @@ where
         self.range.len()
     }
 
+    #[doc = include_str!("../doc/stats.md")]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(0..8).unwrap();
+    /// dlst.range_update(..4, &1);
+    /// assert_eq!(dlst.stats().allocations, 4);
+    /// ```
+    #[cfg(feature = "instrument")]
+    pub fn stats(&self) -> crate::Stats {
+        self.counters.stats()
+    }
+
+    /// Resets the counts returned by [`stats()`](Self::stats) to zero.
+    #[cfg(feature = "instrument")]
+    pub fn reset_stats(&mut self) {
+        self.counters.reset()
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-100..100).unwrap();
+    /// let before = dlst.memory_usage().arena;
+    ///
+    /// dlst.range_update(-50..50, &1);
+    /// assert!(dlst.memory_usage().arena > before);
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            arena: self.arena.memory_usage(),
+            buffers: vec_bytes(&self.reusable_buf) + vec_bytes(&self.free_list),
+            ..Default::default()
+        }
+    }
+
+    /// Resets all elements to [identity elements](crate::traits::Monoid::identity()),
+    /// or to [the default element](Self::fill) if any.
+    ///
+    /// The allocated capacity is retained, so the tree can be reused without reallocation.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*M*), where *M* is the number of allocated nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-100..100).unwrap();
+    /// dlst.range_update(-50..50, &1);
+    /// assert_eq!(dlst.range_query(..), 100);
+    ///
+    /// dlst.clear();
+    /// assert_eq!(dlst.range_query(..), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.arena.clear();
+        self.arena.push();
+        self.arena.element[0] = self.default_n(self.range.len());
+        self.reusable_buf.clear();
+        self.free_list.clear();
+    }
+
+    /// Rewrites the arena in depth-first order, dropping removed nodes.
+    ///
+    /// This restores memory locality after heavy churn.
+    /// Pointers are rewritten, so the contents are kept intact.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*M*), where *M* is the number of allocated nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-100..100).unwrap();
+    /// dlst.range_update(-50..50, &1);
+    /// dlst.range_reset(..0);
+    ///
+    /// dlst.compact();
+    /// assert_eq!(dlst.range_query(..), 50);
+    /// ```
+    pub fn compact(&mut self) {
+        let live = self.arena.len() - self.free_list.len();
+        let old = std::mem::replace(&mut self.arena, Arena::with_capacity(live));
+        self.free_list.clear();
+        let Arena {
+            element,
+            update,
+            children,
+        } = old;
+        let mut element = Vec::from_iter(element.into_iter().map(Some));
+        let mut update = Vec::from_iter(update.into_iter().map(Some));
+
+        // (old pointer, new pointer of the parent, whether it is the left child)
+        let mut stack = vec![(0, 0, false)];
+        while let Some((ptr, p_ptr, is_left)) = stack.pop() {
+            let new_ptr = self.arena.len();
+            if new_ptr != 0 {
+                if is_left {
+                    self.arena.set_left_ptr(p_ptr, new_ptr)
+                } else {
+                    self.arena.set_right_ptr(p_ptr, new_ptr)
+                }
+            }
+
+            // visit the left child first
+            let [l_ptr, r_ptr] = children[ptr];
+            if let Some(r_ptr) = r_ptr {
+                stack.push((from_node_ptr(r_ptr), new_ptr, false));
+            }
+            if let Some(l_ptr) = l_ptr {
+                stack.push((from_node_ptr(l_ptr), new_ptr, true));
+            }
+
+            // never panic: each node is reachable from the root only once
+            self.arena.element.push(element[ptr].take().unwrap());
+            self.arena.update.push(update[ptr].take().unwrap());
+            self.arena.children.push([None; 2]);
+        }
+    }
+
+    /// Calls [`compact`](Self::compact) if more than half of the arena is occupied by removed nodes.
+    ///
+    /// Returns `true` if the arena is compacted.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*M*) if compacted, otherwise *O*(1)
+    pub fn maybe_compact(&mut self) -> bool {
+        let is_sparse = self.free_list.len() > self.arena.len() >> 1;
+        if is_sparse {
+            self.compact();
+        }
+        is_sparse
+    }
+
+    #[doc = include_str!("../doc/flush.md")]
+    ///
+    /// No node is allocated.
+    /// The nodes without children keep their pending updates for the descendants which are not allocated yet.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*M*), where *M* is the number of allocated nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-100..100).unwrap();
+    /// dlst.range_update(-50..50, &1);
+    ///
+    /// dlst.flush();
+    /// assert_eq!(dlst.range_query(-10..10), 20);
+    /// ```
+    pub fn flush(&mut self) {
+        // top-to-bottom order
+        self.reusable_buf.push((0, self.range.clone()));
+        while let Some((ptr, range)) = self.reusable_buf.pop() {
+            if let Some(l_ptr) = self.arena.get_left_ptr(ptr)
+                && let Some(r_ptr) = self.arena.get_right_ptr(ptr)
+            {
+                // never allocates since both children exist
+                self.propagate_at(ptr, range.clone());
+
+                let mid = range.start.midpoint(range.end);
+                self.reusable_buf.push((l_ptr, range.start..mid));
+                self.reusable_buf.push((r_ptr, mid..range.end));
+            }
+        }
+    }
+
     /// Returns [L, r)
     #[inline]
     fn translate_range<R>(&self, range: R) -> [isize; 2]
@@ -106,10 +455,16 @@ where
         update: &<<Action as MonoidAction>::Map as Monoid>::Set,
     ) {
         assert!(!range.is_empty(), "invalid node");
-        let node = &mut self.arena[ptr];
-
-        node.element = <Action as MonoidAction>::act(update, &node.element, Some(range.len()));
-        node.update = <<Action as MonoidAction>::Map as Monoid>::combine(&node.update, update)
+        <Action as MonoidAction>::act_assign(
+            update,
+            &mut self.arena.element[ptr],
+            Some(range.len()),
+        );
+        <<Action as MonoidAction>::Map as Monoid>::combine_assign(
+            &mut self.arena.update[ptr],
+            update,
+        );
+        self.counters.combine(2)
     }
 
     fn propagate_at(&mut self, ptr: usize, range: Range<isize>) {
@@ -118,35 +473,72 @@ where
             "no child error: the node `ptr` points to should have two children"
         );
 
+        // children are not allocated only to receive nothing
+        if <<Action as MonoidAction>::Map as Monoid>::is_identity(&self.arena.update[ptr]) {
+            return;
+        }
+
+        let Range { start, end } = range;
+        let mid = start.midpoint(end);
+
+        let l_ptr = self.get_or_push_left(ptr, start..mid);
+        let r_ptr = self.get_or_push_right(ptr, mid..end);
+
         let update = std::mem::replace(
-            &mut self.arena[ptr].update,
+            &mut self.arena.update[ptr],
             <<Action as MonoidAction>::Map as Monoid>::identity(),
         );
+        self.counters.propagate();
 
-        let Range { start, end } = range;
-        let mid = start.midpoint(end);
+        self.push_map(l_ptr, start..mid, &update);
+        self.push_map(r_ptr, mid..end, &update);
+    }
 
-        {
-            let l_ptr = if let Some(l_ptr) = self.arena[ptr].get_left_ptr() {
-                l_ptr
-            } else {
-                let l_ptr = self.arena.len();
-                self.arena.push(Node::new());
-                self.arena[ptr].set_left_ptr(l_ptr);
-                l_ptr
-            };
-            self.push_map(l_ptr, start..mid, &update);
+    /// Returns the pointer to the left child over `range`, allocating a default node if it does not exist.
+    fn get_or_push_left(&mut self, ptr: usize, range: Range<isize>) -> usize {
+        if let Some(l_ptr) = self.arena.get_left_ptr(ptr) {
+            l_ptr
+        } else {
+            let l_ptr = self.push_node(range);
+            self.arena.set_left_ptr(ptr, l_ptr);
+            l_ptr
+        }
+    }
+
+    /// Returns the pointer to the right child over `range`, allocating a default node if it does not exist.
+    fn get_or_push_right(&mut self, ptr: usize, range: Range<isize>) -> usize {
+        if let Some(r_ptr) = self.arena.get_right_ptr(ptr) {
+            r_ptr
+        } else {
+            let r_ptr = self.push_node(range);
+            self.arena.set_right_ptr(ptr, r_ptr);
+            r_ptr
+        }
+    }
+
+    /// Allocates a node over `range` holding the default elements, reusing removed one if exists.
+    fn push_node(&mut self, range: Range<isize>) -> usize {
+        self.counters.allocate();
+        let ptr = if let Some(ptr) = self.free_list.pop() {
+            self.arena.reset(ptr);
+            ptr
+        } else {
+            self.arena.push()
+        };
+        if self.default.is_some() {
+            self.arena.element[ptr] = self.default_n(range.len());
         }
+        ptr
+    }
+
+    /// Removes all descendants of the node `ptr` points to, recycling them.
+    fn remove_children(&mut self, ptr: usize) {
+        for c_ptr in std::mem::take(&mut self.arena.children[ptr])
+            .into_iter()
+            .flatten()
         {
-            let r_ptr = if let Some(r_ptr) = self.arena[ptr].get_right_ptr() {
-                r_ptr
-            } else {
-                let r_ptr = self.arena.len();
-                self.arena.push(Node::new());
-                self.arena[ptr].set_right_ptr(r_ptr);
-                r_ptr
-            };
-            self.push_map(r_ptr, mid..end, &update);
+            self.remove_children(from_node_ptr(c_ptr));
+            self.free_list.push(from_node_ptr(c_ptr));
         }
     }
 
@@ -162,6 +554,21 @@ where
         R: RangeBounds<isize>,
     {
         let [l, r] = self.translate_range(range);
+        #[cfg(feature = "tracing")]
+        let traced = self
+            .counters
+            .enter(crate::stats::trace_span!("range_update", l..r));
+        self.range_update_inner(l, r, update);
+        #[cfg(feature = "tracing")]
+        self.counters.exit(traced);
+    }
+
+    fn range_update_inner(
+        &mut self,
+        l: isize,
+        r: isize,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) {
         if l >= r {
             return;
         }
@@ -181,14 +588,15 @@ where
                 // lazy propagation in top-to-bottom order
                 self.propagate_at(ptr, range);
 
+                // both children are needed to recalculate the node
                 let mid = start.midpoint(end);
+                let l_ptr = self.get_or_push_left(ptr, start..mid);
+                let r_ptr = self.get_or_push_right(ptr, mid..end);
                 if l < mid {
-                    self.reusable_buf
-                        .push((self.arena[ptr].get_left_ptr().unwrap(), start..mid));
+                    self.reusable_buf.push((l_ptr, start..mid));
                 }
                 if r > mid {
-                    self.reusable_buf
-                        .push((self.arena[ptr].get_right_ptr().unwrap(), mid..end));
+                    self.reusable_buf.push((r_ptr, mid..end));
                 }
             }
 
@@ -196,184 +604,1082 @@ where
         }
 
         // recalculate in bottom-to-top order
+        self.counters.depth(self.reusable_buf.len());
         while let Some((ptr, _)) = self.reusable_buf.pop() {
             assert!(
-                self.arena[ptr].get_left_ptr().is_some()
-                    == self.arena[ptr].get_right_ptr().is_some()
+                self.arena.get_left_ptr(ptr).is_some() == self.arena.get_right_ptr(ptr).is_some()
             );
-            if let Some(l_ptr) = self.arena[ptr].get_left_ptr()
-                && let Some(r_ptr) = self.arena[ptr].get_right_ptr()
+            if let Some(l_ptr) = self.arena.get_left_ptr(ptr)
+                && let Some(r_ptr) = self.arena.get_right_ptr(ptr)
             {
-                self.arena[ptr].element = <<Action as MonoidAction>::Set as Monoid>::combine(
-                    &self.arena[l_ptr].element,
-                    &self.arena[r_ptr].element,
-                )
+                self.arena.element[ptr] = <<Action as MonoidAction>::Set as Monoid>::combine(
+                    &self.arena.element[l_ptr],
+                    &self.arena.element[r_ptr],
+                );
+                self.counters.combine(1)
             }
         }
     }
 
-    #[doc = include_str!("../doc/range_query.md")]
+    /// Resets all elements in the given `range` to [identity elements](crate::traits::Monoid::identity()),
+    /// or to [the default element](Self::fill) if any.
+    ///
+    /// Nodes which are no longer needed are recycled for later updates.
+    ///
+    /// Does nothing if the `range` is empty.
+    ///
     /// # Time complexity
     ///
-    /// *O*(log *N*)
-    pub fn range_query<R>(&mut self, range: R) -> <<Action as MonoidAction>::Set as Monoid>::Set
+    /// *O*(log *N*) amortized
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-100..100).unwrap();
+    /// dlst.range_update(.., &1);
+    /// assert_eq!(dlst.range_query(..), 200);
+    ///
+    /// dlst.range_reset(..0);
+    /// assert_eq!(dlst.range_query(..), 100);
+    /// ```
+    pub fn range_reset<R>(&mut self, range: R)
     where
         R: RangeBounds<isize>,
     {
         let [l, r] = self.translate_range(range);
         if l >= r {
-            return <<Action as MonoidAction>::Set as Monoid>::identity();
+            return;
         }
 
-        let self_mid = self.range.start.midpoint(self.range.end);
-        let mut res = <<Action as MonoidAction>::Set as Monoid>::identity();
-
-        self.reusable_buf.push((0, self.range.clone()));
-        let mut i = 0;
-        while let Some((ptr, range)) = self.reusable_buf.get(i).cloned() {
-            const MSB: usize = 1_usize.rotate_right(1);
-            let Range { start, end } = range;
-
-            if l <= start && end <= r {
-                // calculate answer
-                if ptr & MSB == 0 {
-                    res = <<Action as MonoidAction>::Set as Monoid>::combine(
-                        &self.arena[ptr].element,
-                        &res,
-                    )
-                } else {
-                    res = <<Action as MonoidAction>::Set as Monoid>::combine(
-                        &res,
-                        &self.arena[!ptr].element,
-                    )
-                }
-            } else {
-                // lazy propagation in top-to-bottom order
-                let ptr = if ptr & MSB == 0 { ptr } else { !ptr };
-                self.propagate_at(ptr, range);
+        self.range_reset_at(0, self.range.clone(), &(l..r));
+    }
 
-                let mid = start.midpoint(end);
-                let is_left_size = mid < self_mid;
-                let mut pushed = 0;
-                if l < mid {
-                    let l_ptr = self.arena[ptr].get_left_ptr().unwrap();
-                    self.reusable_buf
-                        .push((if is_left_size { l_ptr } else { !l_ptr }, start..mid));
-                    pushed += 1;
-                }
-                if r > mid {
-                    let r_ptr = self.arena[ptr].get_right_ptr().unwrap();
-                    self.reusable_buf
-                        .push((if is_left_size { r_ptr } else { !r_ptr }, mid..end));
-                    pushed += 1
-                }
-                if pushed == 2 && is_left_size {
-                    let n = self.reusable_buf.len();
-                    self.reusable_buf.swap(n - 1, n - 2);
-                }
+    fn range_reset_at(&mut self, ptr: usize, range: Range<isize>, reset: &Range<isize>) {
+        let Range { start, end } = range;
+        if reset.start <= start && end <= reset.end {
+            self.remove_children(ptr);
+            self.arena.reset(ptr);
+            if self.default.is_some() {
+                self.arena.element[ptr] = self.default_n(range.len());
             }
+            return;
+        }
+        if self.arena.children[ptr] == [None; 2]
+            && <<Action as MonoidAction>::Map as Monoid>::is_identity(&self.arena.update[ptr])
+        {
+            // the elements are the default ones already
+            return;
+        }
 
-            i += 1
+        self.propagate_at(ptr, range);
+
+        let mid = start.midpoint(end);
+        let l_ptr = self.get_or_push_left(ptr, start..mid);
+        let r_ptr = self.get_or_push_right(ptr, mid..end);
+        if reset.start < mid {
+            self.range_reset_at(l_ptr, start..mid, reset);
         }
-        self.reusable_buf.clear();
+        if reset.end > mid {
+            self.range_reset_at(r_ptr, mid..end, reset);
+        }
+        self.arena.element[ptr] = <<Action as MonoidAction>::Set as Monoid>::combine(
+            &self.arena.element[l_ptr],
+            &self.arena.element[r_ptr],
+        );
+        self.counters.combine(1)
+    }
 
+    #[doc = include_str!("../doc/range_query.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// No node is allocated: pending updates are composed on the fly instead of being propagated,
+    /// and the runs are yielded lazily.
+    pub fn range_query<R>(&self, range: R) -> <<Action as MonoidAction>::Set as Monoid>::Set
+    where
+        R: RangeBounds<isize>,
+    {
+        let [l, r] = self.translate_range(range);
+        #[cfg(feature = "tracing")]
+        let traced = self
+            .counters
+            .enter(crate::stats::trace_span!("range_query", l..r));
+        let res = if l < r {
+            self.query_at(
+                Some(0),
+                self.range.clone(),
+                &(l..r),
+                &<<Action as MonoidAction>::Map as Monoid>::identity(),
+            )
+        } else {
+            <<Action as MonoidAction>::Set as Monoid>::identity()
+        };
+        #[cfg(feature = "tracing")]
+        self.counters.exit(traced);
         res
     }
-}
 
-impl<Action> Debug for DynamicLazySegmentTree<Action>
-where
-    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("DynamicLazySegmentTree")
-            .field("arena", &self.arena)
-            .field("range", &self.range)
-            .field("reusable_buf", &self.reusable_buf)
-            .finish()
-    }
-}
+    /// Combines the elements within `query` in the subtree rooted at `ptr`,
+    /// acting `pending` updates of the ancestors on the fly.
+    ///
+    /// `None` stands for a node which is not allocated yet,
+    /// that is default elements without pending updates.
+    fn query_at(
+        &self,
+        ptr: Option<usize>,
+        range: Range<isize>,
+        query: &Range<isize>,
+        pending: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) -> <<Action as MonoidAction>::Set as Monoid>::Set {
+        let Range { start, end } = range;
 
-impl<Action> Clone for DynamicLazySegmentTree<Action>
-where
-    Action: MonoidAction<Set: Monoid<Set: Clone>, Map: Monoid<Set: Clone>>,
-{
-    fn clone(&self) -> Self {
-        Self {
-            arena: self.arena.clone(),
-            range: self.range.clone(),
-            reusable_buf: self.reusable_buf.clone(),
+        if query.start <= start && end <= query.end {
+            self.counters.combine(1);
+            return if let Some(ptr) = ptr {
+                <Action as MonoidAction>::act(pending, &self.arena.element[ptr], Some(range.len()))
+            } else {
+                <Action as MonoidAction>::act(
+                    pending,
+                    &self.default_n(range.len()),
+                    Some(range.len()),
+                )
+            };
         }
-    }
-}
 
-struct Node<Query, Update>
-where
-    Query: Monoid,
-    Update: Monoid,
-{
-    element: <Query as Monoid>::Set,
-    update: <Update as Monoid>::Set,
+        // updates pending on this node are older than those of the ancestors
+        let composed;
+        let pending = if let Some(ptr) = ptr {
+            composed = <<Action as MonoidAction>::Map as Monoid>::combine(
+                &self.arena.update[ptr],
+                pending,
+            );
+            self.counters.combine(1);
+            &composed
+        } else {
+            pending
+        };
 
-    // index on arena
-    left_ptr: Option<NonZeroUsize>,
-    right_ptr: Option<NonZeroUsize>,
+        let mid = start.midpoint(end);
+        let mut res = <<Action as MonoidAction>::Set as Monoid>::identity();
+        if query.start < mid {
+            let l_ptr = ptr.and_then(|ptr| self.arena.get_left_ptr(ptr));
+            res = self.query_at(l_ptr, start..mid, query, pending);
+        }
+        if query.end > mid {
+            let r_ptr = ptr.and_then(|ptr| self.arena.get_right_ptr(ptr));
+            <<Action as MonoidAction>::Set as Monoid>::combine_assign(
+                &mut res,
+                &self.query_at(r_ptr, mid..end, query, pending),
+            );
+            self.counters.combine(1)
+        }
+
+        res
+    }
+
+    /// Returns all the elements with pending updates resolved.
+    ///
+    /// No node is allocated: pending updates are composed on the fly instead of being propagated,
+    /// and the runs are yielded lazily.
+    /// Every index in the range gets an element, however few of them have been updated,
+    /// so prefer [`runs`](Self::runs) for a wide range.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*), where *N* is the length of the range
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i32>>::new(-2..3).unwrap();
+    /// dlst.range_update(-1..2, &1);
+    /// dlst.range_update(0.., &10);
+    ///
+    /// assert_eq!(dlst.to_vec(), vec![0, 1, 11, 11, 10]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<<<Action as MonoidAction>::Set as Monoid>::Set> {
+        let mut res = Vec::with_capacity(self.range.len());
+        if self.range.is_empty() {
+            return res;
+        }
+        self.to_vec_at(
+            Some(0),
+            self.range.clone(),
+            &<<Action as MonoidAction>::Map as Monoid>::identity(),
+            &mut res,
+        );
+
+        res
+    }
+
+    /// Consumes the tree and returns all the elements with pending updates resolved.
+    ///
+    /// The elements held by the leaf nodes are moved out rather than recalculated.
+    /// Like [`to_vec`](Self::to_vec), this materializes the whole range.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*), where *N* is the length of the range
+    pub fn into_vec(mut self) -> Vec<<<Action as MonoidAction>::Set as Monoid>::Set> {
+        let mut res = Vec::with_capacity(self.range.len());
+        if self.range.is_empty() {
+            return res;
+        }
+        self.move_out_at(
+            0,
+            self.range.clone(),
+            &<<Action as MonoidAction>::Map as Monoid>::identity(),
+            &mut res,
+        );
+
+        res
+    }
+
+    /// Moves the elements in the subtree rooted at `ptr` to `res`,
+    /// acting `pending` updates of the ancestors on them.
+    fn move_out_at(
+        &mut self,
+        ptr: usize,
+        range: Range<isize>,
+        pending: &<<Action as MonoidAction>::Map as Monoid>::Set,
+        res: &mut Vec<<<Action as MonoidAction>::Set as Monoid>::Set>,
+    ) {
+        if range.len() == 1 {
+            let mut element = std::mem::replace(
+                &mut self.arena.element[ptr],
+                <<Action as MonoidAction>::Set as Monoid>::identity(),
+            );
+            <Action as MonoidAction>::act_assign(pending, &mut element, Some(1));
+            res.push(element);
+            return;
+        }
+
+        // updates pending on this node are older than those of the ancestors
+        let pending =
+            <<Action as MonoidAction>::Map as Monoid>::combine(&self.arena.update[ptr], pending);
+
+        let mid = range.start.midpoint(range.end);
+        if let Some(l_ptr) = self.arena.get_left_ptr(ptr) {
+            self.move_out_at(l_ptr, range.start..mid, &pending, res)
+        } else {
+            self.to_vec_at(None, range.start..mid, &pending, res)
+        }
+        if let Some(r_ptr) = self.arena.get_right_ptr(ptr) {
+            self.move_out_at(r_ptr, mid..range.end, &pending, res)
+        } else {
+            self.to_vec_at(None, mid..range.end, &pending, res)
+        }
+    }
+
+    /// Pushes the elements in the subtree rooted at `ptr` to `res`,
+    /// acting `pending` updates of the ancestors on the fly.
+    fn to_vec_at(
+        &self,
+        ptr: Option<usize>,
+        range: Range<isize>,
+        pending: &<<Action as MonoidAction>::Map as Monoid>::Set,
+        res: &mut Vec<<<Action as MonoidAction>::Set as Monoid>::Set>,
+    ) {
+        if range.len() == 1 {
+            res.push(if let Some(ptr) = ptr {
+                <Action as MonoidAction>::act(pending, &self.arena.element[ptr], Some(1))
+            } else {
+                <Action as MonoidAction>::act(pending, &self.default_n(1), Some(1))
+            });
+            return;
+        }
+
+        // updates pending on this node are older than those of the ancestors
+        let composed;
+        let pending = if let Some(ptr) = ptr {
+            composed = <<Action as MonoidAction>::Map as Monoid>::combine(
+                &self.arena.update[ptr],
+                pending,
+            );
+            &composed
+        } else {
+            pending
+        };
+
+        let mid = range.start.midpoint(range.end);
+        let l_ptr = ptr.and_then(|ptr| self.arena.get_left_ptr(ptr));
+        self.to_vec_at(l_ptr, range.start..mid, pending, res);
+        let r_ptr = ptr.and_then(|ptr| self.arena.get_right_ptr(ptr));
+        self.to_vec_at(r_ptr, mid..range.end, pending, res);
+    }
+
+    /// Returns the elements as runs of equal elements, each of which is a pair of a range and the element.
+    ///
+    /// Unlike [`to_vec`](Self::to_vec), the elements under a node which has no children are yielded at once,
+    /// so this takes time proportional to the number of allocated nodes rather than the length of the range.
+    /// The runs cover the range in ascending order, and adjacent runs may hold equal elements.
+    ///
+    /// No node is allocated: pending updates are composed on the fly instead of being propagated,
+    /// and the runs are yielded lazily.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*M*), where *M* is the number of allocated nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst =
+    ///     DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-1 << 40..1 << 40).unwrap();
+    /// dlst.range_update(0.., &1);
+    ///
+    /// let runs = Vec::from_iter(dlst.runs());
+    /// assert!(runs.len() <= 4);
+    /// assert_eq!(runs[0], (-1 << 40..0, 0));
+    /// assert!(runs[1..].iter().all(|(_, element)| *element == 1));
+    /// ```
+    pub fn runs(&self) -> Runs<'_, Action> {
+        let stack = if self.range.is_empty() {
+            Vec::new()
+        } else {
+            vec![(
+                Some(0),
+                self.range.clone(),
+                <<Action as MonoidAction>::Map as Monoid>::identity(),
+            )]
+        };
+
+        Runs { tree: self, stack }
+    }
+
+    /// Passes the elements to `f` in ascending order,
+    /// yielding the elements under a node which has no children at once.
+    #[cfg(feature = "serde")]
+    fn segments(
+        &self,
+        f: &mut impl FnMut(
+            Segment<
+                <<Action as MonoidAction>::Set as Monoid>::Set,
+                <<Action as MonoidAction>::Map as Monoid>::Set,
+            >,
+        ),
+    ) {
+        if !self.range.is_empty() {
+            self.segments_at(
+                0,
+                self.range.clone(),
+                &<<Action as MonoidAction>::Map as Monoid>::identity(),
+                f,
+            )
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn segments_at(
+        &self,
+        ptr: usize,
+        range: Range<isize>,
+        pending: &<<Action as MonoidAction>::Map as Monoid>::Set,
+        f: &mut impl FnMut(
+            Segment<
+                <<Action as MonoidAction>::Set as Monoid>::Set,
+                <<Action as MonoidAction>::Map as Monoid>::Set,
+            >,
+        ),
+    ) {
+        if range.len() == 1 {
+            f(Segment::Element(
+                range.start,
+                <Action as MonoidAction>::act(pending, &self.arena.element[ptr], Some(1)),
+            ));
+            return;
+        }
+
+        // updates pending on this node are older than those of the ancestors
+        let pending =
+            <<Action as MonoidAction>::Map as Monoid>::combine(&self.arena.update[ptr], pending);
+        let children = [self.arena.get_left_ptr(ptr), self.arena.get_right_ptr(ptr)];
+        if children == [None; 2] {
+            f(Segment::Run(range, pending));
+            return;
+        }
+
+        let mid = range.start.midpoint(range.end);
+        for (c_ptr, range) in children.into_iter().zip([range.start..mid, mid..range.end]) {
+            if let Some(c_ptr) = c_ptr {
+                self.segments_at(c_ptr, range, &pending, f)
+            } else {
+                let copied = <<Action as MonoidAction>::Map as Monoid>::combine(
+                    &pending,
+                    &<<Action as MonoidAction>::Map as Monoid>::identity(),
+                );
+                f(Segment::Run(range, copied))
+            }
+        }
+    }
+}
+
+impl<Action> From<DynamicLazySegmentTree<Action>>
+    for Vec<<<Action as MonoidAction>::Set as Monoid>::Set>
+where
+    Action: MonoidAction,
+{
+    fn from(tree: DynamicLazySegmentTree<Action>) -> Self {
+        tree.into_vec()
+    }
+}
+
+/// Materializes the whole range like [`into_vec`](DynamicLazySegmentTree::into_vec).
+impl<Action> IntoIterator for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    type Item = <<Action as MonoidAction>::Set as Monoid>::Set;
+    type IntoIter = std::vec::IntoIter<<<Action as MonoidAction>::Set as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+/// Yields the runs of equal elements like [`runs`](DynamicLazySegmentTree::runs).
+impl<'a, Action> IntoIterator for &'a DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    type Item = (Range<isize>, <<Action as MonoidAction>::Set as Monoid>::Set);
+    type IntoIter = Runs<'a, Action>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.runs()
+    }
+}
+
+/// An iterator over the runs of equal elements of a [`DynamicLazySegmentTree`] in ascending order.
+///
+/// Created by [`DynamicLazySegmentTree::runs`].
+pub struct Runs<'a, Action>
+where
+    Action: MonoidAction,
+{
+    tree: &'a DynamicLazySegmentTree<Action>,
+    /// The subtrees to be visited with the updates pending on their ancestors.
+    stack: Vec<Subtree<<<Action as MonoidAction>::Map as Monoid>::Set>>,
+}
+
+/// The pointer to the root, which is `None` if the subtree has not been allocated,
+/// the range covered by the subtree and the updates pending on its ancestors.
+type Subtree<M> = (Option<usize>, Range<isize>, M);
+
+impl<Action> Iterator for Runs<'_, Action>
+where
+    Action: MonoidAction,
+{
+    type Item = (Range<isize>, <<Action as MonoidAction>::Set as Monoid>::Set);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arena = &self.tree.arena;
+        loop {
+            let (ptr, range, pending) = self.stack.pop()?;
+            let Some(ptr) = ptr else {
+                let element =
+                    <Action as MonoidAction>::act(&pending, &self.tree.default_n(1), Some(1));
+                return Some((range, element));
+            };
+            if range.len() == 1 {
+                let element = <Action as MonoidAction>::act(&pending, &arena.element[ptr], Some(1));
+                return Some((range, element));
+            }
+
+            // updates pending on this node are older than those of the ancestors
+            let pending =
+                <<Action as MonoidAction>::Map as Monoid>::combine(&arena.update[ptr], &pending);
+            let children = [arena.get_left_ptr(ptr), arena.get_right_ptr(ptr)];
+            if children == [None; 2] {
+                let element =
+                    <Action as MonoidAction>::act(&pending, &self.tree.default_n(1), Some(1));
+                return Some((range, element));
+            }
+
+            let mid = range.start.midpoint(range.end);
+            let copied = <<Action as MonoidAction>::Map as Monoid>::combine(
+                &pending,
+                &<<Action as MonoidAction>::Map as Monoid>::identity(),
+            );
+            self.stack.push((children[1], mid..range.end, copied));
+            self.stack.push((children[0], range.start..mid, pending));
+        }
+    }
+}
+
+impl<Action> DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Clone>>,
+{
+    /// Overwrites the elements in `start..start + values.len()` with `values`.
+    ///
+    /// The subtree covering the block is built in bottom-to-top order,
+    /// which is faster than calling [`range_update`](Self::range_update) for each element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K* + log *N*), where *K* is `values.len()`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst =
+    ///     DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(0..1_000_000_000).unwrap();
+    /// dlst.assign_dense(0, &[1, 2, 3, 4, 5]);
+    /// assert_eq!(dlst.range_query(..), 15);
+    ///
+    /// dlst.range_update(2.., &10);
+    /// assert_eq!(dlst.range_query(..4), 1 + 2 + 13 + 14);
+    /// ```
+    pub fn assign_dense(
+        &mut self,
+        start: isize,
+        values: &[<<Action as MonoidAction>::Set as Monoid>::Set],
+    ) {
+        let end = start + values.len() as isize;
+        assert!(
+            self.range.start <= start && end <= self.range.end,
+            "the given block should be within {:?}, but is {:?}",
+            self.range,
+            start..end,
+        );
+        if values.is_empty() {
+            return;
+        }
+
+        self.assign_dense_at(0, self.range.clone(), start, values);
+    }
+
+    /// Same as [`assign_dense`](Self::assign_dense), but returns [`Error::OutOfBounds`]
+    /// with the first index out of the range instead of panicking.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K* + log *N*), where *K* is `values.len()`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, Error, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(0..4).unwrap();
+    /// assert_eq!(
+    ///     dlst.try_assign_dense(2, &[1, 2, 3]),
+    ///     Err(Error::OutOfBounds { index: 4, range: 0..4 })
+    /// );
+    /// assert_eq!(dlst.try_assign_dense(2, &[1, 2]), Ok(()));
+    /// ```
+    pub fn try_assign_dense(
+        &mut self,
+        start: isize,
+        values: &[<<Action as MonoidAction>::Set as Monoid>::Set],
+    ) -> Result<(), Error> {
+        let index = if start < self.range.start {
+            Some(start)
+        } else {
+            isize::try_from(values.len())
+                .ok()
+                .and_then(|n| start.checked_add(n))
+                .filter(|end| *end <= self.range.end)
+                .map_or(Some(self.range.end.max(start)), |_| None)
+        };
+        if let Some(index) = index {
+            return Err(Error::OutOfBounds {
+                index,
+                range: self.range.clone(),
+            });
+        }
+        self.assign_dense(start, values);
+        Ok(())
+    }
+
+    fn assign_dense_at(
+        &mut self,
+        ptr: usize,
+        range: Range<isize>,
+        start: isize,
+        values: &[<<Action as MonoidAction>::Set as Monoid>::Set],
+    ) {
+        let end = start + values.len() as isize;
+        if start <= range.start && range.end <= end {
+            // pending updates are overwritten
+            self.arena.update[ptr] = <<Action as MonoidAction>::Map as Monoid>::identity();
+            if range.len() == 1 {
+                self.arena.element[ptr] = values[(range.start - start) as usize].clone();
+                return;
+            }
+        } else {
+            self.propagate_at(ptr, range.clone());
+        }
+
+        let mid = range.start.midpoint(range.end);
+        if start < mid {
+            let l_ptr = self.get_or_push_left(ptr, range.start..mid);
+            self.assign_dense_at(l_ptr, range.start..mid, start, values);
+        }
+        if end > mid {
+            let r_ptr = self.get_or_push_right(ptr, mid..range.end);
+            self.assign_dense_at(r_ptr, mid..range.end, start, values);
+        }
+
+        // both children are needed to recalculate the node
+        let l_ptr = self.get_or_push_left(ptr, range.start..mid);
+        let r_ptr = self.get_or_push_right(ptr, mid..range.end);
+        self.arena.element[ptr] = <<Action as MonoidAction>::Set as Monoid>::combine(
+            &self.arena.element[l_ptr],
+            &self.arena.element[r_ptr],
+        );
+        self.counters.combine(1)
+    }
+}
+
+impl<Action> DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
+{
+    #[doc = include_str!("../doc/to_dot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K*) where *K* is the number of nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i32>>::new(0..8).unwrap();
+    /// dlst.range_update(4..8, &1);
+    /// assert!(dlst.to_dot().contains("{4..6 | element: 2 | pending: 1}"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = crate::render::Dot::new("DynamicLazySegmentTree");
+        self.render(&mut dot);
+        dot.finish()
+    }
+
+    #[doc = include_str!("../doc/pretty.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K*) where *K* is the number of nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i32>>::new(0..4).unwrap();
+    /// dlst.range_update(2..4, &1);
+    /// assert_eq!(
+    ///     dlst.pretty(),
+    ///     "0: [0..4] element: 2, pending: 0\n\
+    ///      1: [0..2] element: 0, pending: 0  [2..4] element: 2, pending: 0\n\
+    ///      2: [2] element: 1, pending: 1  [3] element: 1, pending: 1\n"
+    /// );
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut levels = crate::render::Levels::default();
+        self.render(&mut levels);
+        levels.finish()
+    }
+
+    #[doc = include_str!("../doc/to_mermaid.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*K*) where *K* is the number of nodes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i32>>::new(0..8).unwrap();
+    /// dlst.range_update(4..8, &1);
+    /// assert!(dlst.to_mermaid().contains(r#"["4..6 | element: 2 | pending: 1"]"#));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = crate::render::Mermaid::default();
+        self.render(&mut mermaid);
+        mermaid.finish()
+    }
+
+    /// Returns an adapter which [displays](std::fmt::Display) the tree like [`pretty`](Self::pretty).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and formatting takes as long as [`pretty`](Self::pretty)
+    pub fn display(&self) -> crate::Pretty<'_, Self> {
+        crate::Pretty(self)
+    }
+
+    /// Passes the nodes to `out` in preorder, visiting the left child first.
+    pub(crate) fn render(&self, out: &mut impl crate::render::Render) {
+        let mut stack =
+            Vec::from_iter((self.arena.len() > 0).then(|| (0, None, 0, self.range.clone())));
+        while let Some((ptr, parent, depth, range)) = stack.pop() {
+            let label = if range.len() == 1 {
+                range.start.to_string()
+            } else {
+                format!("{range:?}")
+            };
+            out.node(
+                ptr,
+                parent,
+                depth,
+                &label,
+                &[
+                    ("element", &self.arena.element[ptr]),
+                    ("pending", &self.arena.update[ptr]),
+                ],
+            );
+
+            let mid = range.start.midpoint(range.end);
+            if let Some(r_ptr) = self.arena.get_right_ptr(ptr) {
+                stack.push((r_ptr, Some(ptr), depth + 1, mid..range.end));
+            }
+            if let Some(l_ptr) = self.arena.get_left_ptr(ptr) {
+                stack.push((l_ptr, Some(ptr), depth + 1, range.start..mid));
+            }
+        }
+    }
+}
+
+/// Renders the tree like [`DynamicLazySegmentTree::pretty`].
+impl<Action> std::fmt::Display for crate::Pretty<'_, DynamicLazySegmentTree<Action>>
+where
+    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.pretty())
+    }
+}
+
+impl<Action> Debug for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicLazySegmentTree")
+            .field("arena", &self.arena)
+            .field("range", &self.range)
+            .field("default", &self.default)
+            .field("reusable_buf", &self.reusable_buf)
+            .field("free_list", &self.free_list)
+            .finish()
+    }
+}
+
+impl<Action> Clone for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Clone>, Map: Monoid<Set: Clone>>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            arena: self.arena.clone(),
+            range: self.range.clone(),
+            default: self.default.clone(),
+            reusable_buf: self.reusable_buf.clone(),
+            free_list: self.free_list.clone(),
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+/// Creates a tree over the empty range `0..0`.
+///
+/// Queries return the identity, and any non-empty update panics.
+/// Unlike [`new`](DynamicLazySegmentTree::new), this never fails.
+impl<Action> Default for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    fn default() -> Self {
+        Self {
+            arena: {
+                let mut arena = Arena::new();
+                arena.push();
+                arena
+            },
+            reusable_buf: Vec::new(),
+            free_list: Vec::new(),
+            range: 0..0,
+            default: None,
+            counters: Counters::default(),
+        }
+    }
+}
+
+impl<Action> DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: PartialEq>>,
+{
+    /// Returns the maximal runs of equal elements in ascending order.
+    ///
+    /// Unlike [`runs`](Self::runs), the result depends only on the elements, not on the shape of the arena.
+    fn uniform_runs(&self) -> Vec<(Range<isize>, <<Action as MonoidAction>::Set as Monoid>::Set)> {
+        let mut res: Vec<(Range<isize>, _)> = Vec::new();
+        for (range, element) in self.runs() {
+            match res.last_mut() {
+                Some((last, prev)) if *prev == element => last.end = range.end,
+                _ => res.push((range, element)),
+            }
+        }
+        res
+    }
+}
+
+/// Compares the elements and the ranges, with pending updates resolved.
+///
+/// Both arenas are walked and compared as runs of equal elements,
+/// so this takes time proportional to the number of allocated nodes rather than the length of the range.
+impl<Action> PartialEq for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: PartialEq>>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.range == other.range && self.uniform_runs() == other.uniform_runs()
+    }
+}
+
+impl<Action> Eq for DynamicLazySegmentTree<Action> where Action: MonoidAction<Set: Monoid<Set: Eq>> {}
+
+/// Hashes the range and the runs of equal elements with pending updates resolved,
+/// consistently with [`PartialEq`].
+impl<Action> Hash for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: PartialEq + Hash>>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.range.hash(state);
+        self.uniform_runs().hash(state)
+    }
 }
 
-impl<Query, Update> Node<Query, Update>
+#[cfg(feature = "serde")]
+impl<Action> serde::Serialize for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: serde::Serialize>, Map: Monoid<Set: serde::Serialize>>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut segments = Vec::new();
+        self.segments(&mut |segment| {
+            // default elements are restored by construction
+            if let Segment::Run(_, update) = &segment
+                && <<Action as MonoidAction>::Map as Monoid>::is_identity(update)
+            {
+                return;
+            }
+            segments.push(segment)
+        });
+        serde::Serialize::serialize(
+            &crate::serialize::RangedSegments {
+                range: self.range.clone(),
+                segments,
+                default: self.default.as_ref(),
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Action> serde::Deserialize<'de> for DynamicLazySegmentTree<Action>
+where
+    Action: MonoidAction<
+            Set: Monoid<Set: Clone + serde::Deserialize<'de>>,
+            Map: Monoid<Set: serde::Deserialize<'de>>,
+        >,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let form = <crate::serialize::RangedSegments<
+            Vec<
+                Segment<
+                    <<Action as MonoidAction>::Set as Monoid>::Set,
+                    <<Action as MonoidAction>::Map as Monoid>::Set,
+                >,
+            >,
+            <<Action as MonoidAction>::Set as Monoid>::Set,
+        > as serde::Deserialize>::deserialize(deserializer)?;
+        let mut tree = Self::try_new(form.range.clone()).map_err(D::Error::custom)?;
+        tree.default = form.default;
+
+        // each segment should lie in the part which has not been written yet
+        let mut start = tree.range.start;
+        for segment in form.segments {
+            let range = match &segment {
+                Segment::Element(i, _) => *i..i.saturating_add(1),
+                Segment::Run(range, _) => range.clone(),
+            };
+            if range.is_empty() || range.start < start || range.end > tree.range.end {
+                return Err(D::Error::custom(format_args!(
+                    "segments should be sorted, disjoint, non-empty and within {:?}, but found {:?}",
+                    tree.range, range,
+                )));
+            }
+            start = range.end;
+
+            match segment {
+                Segment::Element(i, element) => {
+                    tree.assign_dense(i, std::slice::from_ref(&element))
+                }
+                Segment::Run(range, update) => tree.range_update(range, &update),
+            }
+        }
+        Ok(tree)
+    }
+}
+
+/// A piece of the elements, found by walking the arena.
+///
+/// This is the serialized form of the elements, which stays as sparse as the arena.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) enum Segment<T, M> {
+    /// the element of a leaf node
+    Element(isize, T),
+    /// default elements with the update acted on each of them
+    Run(Range<isize>, M),
+}
+
+/// Nodes in structure-of-arrays layout.
+///
+/// Queries mostly touch pointers and elements, so they are stored apart from pending updates.
+struct Arena<Query, Update>
+where
+    Query: Monoid,
+    Update: Monoid,
+{
+    element: Vec<<Query as Monoid>::Set>,
+    update: Vec<<Update as Monoid>::Set>,
+
+    /// `[left, right]`
+    children: Vec<[Option<NodePtr>; 2]>,
+}
+
+impl<Query, Update> Arena<Query, Update>
 where
     Query: Monoid,
     Update: Monoid,
 {
     #[inline]
     fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
         Self {
-            element: <Query as Monoid>::identity(),
-            update: <Update as Monoid>::identity(),
-            left_ptr: None,
-            right_ptr: None,
+            element: Vec::with_capacity(capacity),
+            update: Vec::with_capacity(capacity),
+            children: Vec::with_capacity(capacity),
         }
     }
 
+    fn try_with_capacity(capacity: usize) -> Result<Self, Error> {
+        let mut arena = Self::new();
+        arena
+            .element
+            .try_reserve_exact(capacity)
+            .and_then(|_| arena.update.try_reserve_exact(capacity))
+            .and_then(|_| arena.children.try_reserve_exact(capacity))
+            .map_err(|_| Error::CapacityOverflow)?;
+        Ok(arena)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.element.len()
+    }
+
+    /// Returns the bytes allocated by the arena.
+    fn memory_usage(&self) -> usize {
+        vec_bytes(&self.element) + vec_bytes(&self.update) + vec_bytes(&self.children)
+    }
+
+    fn clear(&mut self) {
+        self.element.clear();
+        self.update.clear();
+        self.children.clear();
+    }
+
+    /// Appends an identity node without children and returns the pointer to it.
+    #[inline]
+    fn push(&mut self) -> usize {
+        self.element.push(<Query as Monoid>::identity());
+        self.update.push(<Update as Monoid>::identity());
+        self.children.push([None; 2]);
+        self.element.len() - 1
+    }
+
+    /// Overwrites the node `ptr` points to with an identity node without children.
     #[inline]
-    fn get_left_ptr(&self) -> Option<usize> {
-        self.left_ptr.map(|i| i.get())
+    fn reset(&mut self, ptr: usize) {
+        self.element[ptr] = <Query as Monoid>::identity();
+        self.update[ptr] = <Update as Monoid>::identity();
+        self.children[ptr] = [None; 2];
     }
 
     #[inline]
-    fn get_right_ptr(&self) -> Option<usize> {
-        self.right_ptr.map(|i| i.get())
+    fn get_left_ptr(&self, ptr: usize) -> Option<usize> {
+        self.children[ptr][0].map(from_node_ptr)
     }
 
-    /// Invalid `ptr` will be ignored.
     #[inline]
-    fn set_left_ptr(&mut self, ptr: usize) {
-        self.left_ptr = NonZeroUsize::new(ptr)
+    fn get_right_ptr(&self, ptr: usize) -> Option<usize> {
+        self.children[ptr][1].map(from_node_ptr)
     }
 
-    /// Invalid `ptr` will be ignored.
+    /// Invalid `child` will be ignored.
     #[inline]
-    fn set_right_ptr(&mut self, ptr: usize) {
-        self.right_ptr = NonZeroUsize::new(ptr)
+    fn set_left_ptr(&mut self, ptr: usize, child: usize) {
+        self.children[ptr][0] = to_node_ptr(child)
+    }
+
+    /// Invalid `child` will be ignored.
+    #[inline]
+    fn set_right_ptr(&mut self, ptr: usize, child: usize) {
+        self.children[ptr][1] = to_node_ptr(child)
     }
 }
 
-impl<Query, Update> Debug for Node<Query, Update>
+impl<Query, Update> Debug for Arena<Query, Update>
 where
     Query: Monoid<Set: Debug>,
     Update: Monoid<Set: Debug>,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Node")
+        f.debug_struct("Arena")
             .field("element", &self.element)
             .field("update", &self.update)
-            .field("left_ptr", &self.left_ptr)
-            .field("right_ptr", &self.right_ptr)
+            .field("children", &self.children)
             .finish()
     }
 }
 
-impl<Query, Update> Clone for Node<Query, Update>
+impl<Query, Update> Clone for Arena<Query, Update>
 where
     Query: Monoid<Set: Clone>,
     Update: Monoid<Set: Clone>,
@@ -382,8 +1688,474 @@ where
         Self {
             element: self.element.clone(),
             update: self.update.clone(),
-            left_ptr: self.left_ptr,
-            right_ptr: self.right_ptr,
+            children: self.children.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod range_query {
+    use rand::Rng;
+
+    use crate::{DynamicLazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        const N: usize = 50;
+        const OFFSET: isize = -25;
+
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut naive = vec![0_i64; N];
+            let mut dlst = DynamicLazySegmentTree::<AddQueryAffineUpdate<i64>>::new(
+                OFFSET..OFFSET + N as isize,
+            )
+            .unwrap();
+
+            for _ in 0..50 {
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                dlst.range_update(OFFSET + l as isize..OFFSET + r as isize, &update);
+                for e in &mut naive[l..r] {
+                    *e = update.0 * *e + update.1
+                }
+
+                let arena_len = dlst.arena.len();
+                for l in 0..N {
+                    for r in l..=N {
+                        assert_eq!(
+                            dlst.range_query(OFFSET + l as isize..OFFSET + r as isize),
+                            naive[l..r].iter().sum::<i64>(),
+                        )
+                    }
+                }
+                assert_eq!(
+                    dlst.arena.len(),
+                    arena_len,
+                    "range query should not allocate"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod propagate {
+    use crate::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+
+    #[test]
+    fn identity_update_allocates_nothing() {
+        let mut dlst =
+            DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-1 << 40..1 << 40).unwrap();
+        dlst.range_update(.., &0);
+        dlst.range_reset(-5..5);
+        dlst.flush();
+        assert_eq!(dlst.arena.len(), 1);
+
+        dlst.range_update(-5..5, &1);
+        let arena_len = dlst.arena.len();
+        dlst.range_reset(100..200);
+        assert_eq!(dlst.range_query(..), 10);
+        assert_eq!(dlst.arena.len(), arena_len);
+    }
+}
+
+#[cfg(test)]
+mod assign_dense {
+    use rand::Rng;
+
+    use crate::{DynamicLazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        const N: usize = 50;
+        const OFFSET: isize = -25;
+
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut naive = vec![0_i64; N];
+            let mut dlst = DynamicLazySegmentTree::<AddQueryAffineUpdate<i64>>::new(
+                OFFSET..OFFSET + N as isize,
+            )
+            .unwrap();
+
+            for _ in 0..50 {
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                if rng.random_bool(0.5) {
+                    let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                    dlst.range_update(OFFSET + l as isize..OFFSET + r as isize, &update);
+                    for e in &mut naive[l..r] {
+                        *e = update.0 * *e + update.1
+                    }
+                } else {
+                    let values = Vec::from_iter((l..r).map(|_| rng.random_range(-10..=10)));
+                    dlst.assign_dense(OFFSET + l as isize, &values);
+                    naive[l..r].copy_from_slice(&values);
+                }
+
+                for l in 0..N {
+                    for r in l..=N {
+                        assert_eq!(
+                            dlst.range_query(OFFSET + l as isize..OFFSET + r as isize),
+                            naive[l..r].iter().sum::<i64>(),
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod range_reset {
+    use rand::Rng;
+
+    use crate::{DynamicLazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        const N: usize = 50;
+        const OFFSET: isize = -25;
+
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut naive = vec![0_i64; N];
+            let mut dlst = DynamicLazySegmentTree::<AddQueryAffineUpdate<i64>>::new(
+                OFFSET..OFFSET + N as isize,
+            )
+            .unwrap();
+
+            for _ in 0..50 {
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                if rng.random_bool(0.5) {
+                    let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                    dlst.range_update(OFFSET + l as isize..OFFSET + r as isize, &update);
+                    for e in &mut naive[l..r] {
+                        *e = update.0 * *e + update.1
+                    }
+                } else {
+                    dlst.range_reset(OFFSET + l as isize..OFFSET + r as isize);
+                    naive[l..r].fill(0);
+                }
+
+                // removed nodes are reused
+                assert!(dlst.arena.len() < 2 * N);
+                for l in 0..N {
+                    for r in l..=N {
+                        assert_eq!(
+                            dlst.range_query(OFFSET + l as isize..OFFSET + r as isize),
+                            naive[l..r].iter().sum::<i64>(),
+                        )
+                    }
+                }
+            }
+
+            dlst.range_reset(..);
+            assert_eq!(dlst.arena.len() - dlst.free_list.len(), 1);
         }
     }
 }
+
+#[cfg(test)]
+mod compact {
+    use rand::Rng;
+
+    use crate::{DynamicLazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        const N: usize = 50;
+
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut dlst =
+                DynamicLazySegmentTree::<AddQueryAffineUpdate<i64>>::new(0..N as isize).unwrap();
+            for _ in 0..50 {
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                if rng.random_bool(0.5) {
+                    let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                    dlst.range_update(l as isize..r as isize, &update);
+                } else {
+                    dlst.range_reset(l as isize..r as isize);
+                }
+            }
+
+            let expected = Vec::from_iter((0..N as isize).map(|i| dlst.range_query(i..=i)));
+            let live = dlst.arena.len() - dlst.free_list.len();
+            dlst.compact();
+            assert_eq!(dlst.arena.len(), live);
+            assert!(dlst.free_list.is_empty());
+            assert_eq!(
+                Vec::from_iter((0..N as isize).map(|i| dlst.range_query(i..=i))),
+                expected
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod flush {
+    use rand::Rng;
+
+    use crate::{DynamicLazySegmentTree, Monoid, acts::AddQueryAffineUpdate, ops::Affine};
+
+    #[test]
+    fn random() {
+        const N: usize = 50;
+
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut dlst =
+                DynamicLazySegmentTree::<AddQueryAffineUpdate<i64>>::new(0..N as isize).unwrap();
+            for _ in 0..20 {
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                dlst.range_update(l as isize..r as isize, &update);
+            }
+
+            let expected = dlst.to_vec();
+            let allocated = dlst.arena.len();
+            dlst.flush();
+            assert_eq!(dlst.arena.len(), allocated);
+            for ptr in 0..allocated {
+                if dlst.arena.get_left_ptr(ptr).is_some() {
+                    assert_eq!(dlst.arena.update[ptr], Affine::identity())
+                }
+            }
+            assert_eq!(dlst.to_vec(), expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod default {
+    use crate::{DynamicLazySegmentTree, acts::AddQueryAddUpdate};
+
+    #[test]
+    fn empty_range() {
+        let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::default();
+        assert_eq!(dlst.len(), 0);
+
+        dlst.range_update(.., &1);
+        dlst.range_reset(..);
+        assert_eq!(dlst.range_query(..), 0);
+        assert!(dlst.to_vec().is_empty());
+
+        dlst.compact();
+        dlst.clear();
+        assert_eq!(dlst, DynamicLazySegmentTree::default());
+    }
+}
+
+#[cfg(test)]
+mod default_element {
+    use rand::Rng;
+
+    use crate::{DynamicLazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        const N: usize = 50;
+        const OFFSET: isize = -25;
+        const DEFAULT: i64 = 3;
+
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut naive = vec![DEFAULT; N];
+            let mut dlst = DynamicLazySegmentTree::<AddQueryAffineUpdate<i64>>::with_default(
+                OFFSET..OFFSET + N as isize,
+                DEFAULT,
+            )
+            .unwrap();
+
+            for _ in 0..50 {
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                match rng.random_range(0..3) {
+                    0 => {
+                        let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                        dlst.range_update(OFFSET + l as isize..OFFSET + r as isize, &update);
+                        for e in &mut naive[l..r] {
+                            *e = update.0 * *e + update.1
+                        }
+                    }
+                    1 => {
+                        dlst.range_reset(OFFSET + l as isize..OFFSET + r as isize);
+                        naive[l..r].fill(DEFAULT);
+                    }
+                    _ => {
+                        let values = Vec::from_iter((l..r).map(|_| rng.random_range(-10..=10)));
+                        dlst.assign_dense(OFFSET + l as isize, &values);
+                        naive[l..r].copy_from_slice(&values);
+                    }
+                }
+
+                for l in 0..N {
+                    for r in l..=N {
+                        assert_eq!(
+                            dlst.range_query(OFFSET + l as isize..OFFSET + r as isize),
+                            naive[l..r].iter().sum::<i64>(),
+                        )
+                    }
+                }
+                assert_eq!(dlst.to_vec(), naive);
+            }
+
+            dlst.clear();
+            assert_eq!(dlst.to_vec(), vec![DEFAULT; N]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod runs {
+    use rand::Rng;
+
+    use crate::{DynamicLazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        const N: usize = 50;
+        const OFFSET: isize = -25;
+
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let mut naive = vec![3_i64; N];
+            let mut dlst = DynamicLazySegmentTree::<AddQueryAffineUpdate<i64>>::with_default(
+                OFFSET..OFFSET + N as isize,
+                3,
+            )
+            .unwrap();
+
+            for _ in 0..50 {
+                let l = rng.random_range(0..N);
+                let r = rng.random_range(l..=N);
+                match rng.random_range(0..3) {
+                    0 => {
+                        let update = (rng.random_range(-1..=1), rng.random_range(-5..=5));
+                        dlst.range_update(OFFSET + l as isize..OFFSET + r as isize, &update);
+                        for e in &mut naive[l..r] {
+                            *e = update.0 * *e + update.1
+                        }
+                    }
+                    1 => {
+                        dlst.range_reset(OFFSET + l as isize..OFFSET + r as isize);
+                        naive[l..r].fill(3);
+                    }
+                    _ => {
+                        let values = Vec::from_iter((l..r).map(|_| rng.random_range(-10..=10)));
+                        dlst.assign_dense(OFFSET + l as isize, &values);
+                        naive[l..r].copy_from_slice(&values);
+                    }
+                }
+
+                let mut expanded = Vec::new();
+                let mut end = OFFSET;
+                for (range, element) in dlst.runs() {
+                    assert_eq!(range.start, end);
+                    end = range.end;
+                    expanded.extend(std::iter::repeat_n(element, range.len()));
+                }
+                assert_eq!(end, OFFSET + N as isize);
+                assert_eq!(expanded, naive);
+            }
+            assert!(Vec::from_iter(&dlst) == Vec::from_iter(dlst.runs()));
+            assert_eq!(dlst.clone().into_vec(), naive);
+            assert_eq!(dlst.to_vec(), naive);
+        }
+    }
+
+    #[test]
+    fn wide() {
+        let mut dlst =
+            DynamicLazySegmentTree::<AddQueryAffineUpdate<i64>>::new(-1 << 60..1 << 60).unwrap();
+        dlst.range_update(-5..5, &(1, 2));
+        dlst.range_update(0.., &(2, 0));
+
+        let runs = Vec::from_iter(dlst.runs());
+        assert!(runs.len() < 4 * 61);
+        assert_eq!(
+            runs.iter().map(|(range, _)| range.len()).sum::<usize>(),
+            1 << 61
+        );
+        assert_eq!(
+            runs.iter()
+                .map(|(range, element)| range.len() as i64 * element)
+                .sum::<i64>(),
+            5 * 2 + 5 * 4
+        );
+    }
+}
+
+#[cfg(test)]
+mod eq {
+    use std::hash::{BuildHasher, RandomState};
+
+    use rand::Rng;
+
+    use crate::{
+        DynamicLazySegmentTree,
+        acts::{AddQueryAddUpdate, AddQueryAssignUpdate},
+    };
+
+    #[test]
+    fn wide_range() {
+        let range = -1 << 40..1 << 40;
+        let mut lhs = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(range.clone()).unwrap();
+        let mut rhs = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(range.clone()).unwrap();
+        assert_eq!(lhs, rhs);
+
+        // the same elements in arenas of different shapes
+        lhs.range_update(-5..5, &2);
+        rhs.range_update(-5..0, &2);
+        rhs.range_update(0..5, &2);
+        rhs.range_update(1 << 30..1 << 31, &0);
+        assert_eq!(lhs, rhs);
+
+        rhs.range_update(4..5, &1);
+        assert_ne!(lhs, rhs);
+        assert_ne!(lhs, DynamicLazySegmentTree::new(range.start..0).unwrap());
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            let n = rng.random_range(1..50);
+            let mut lhs =
+                DynamicLazySegmentTree::<AddQueryAssignUpdate<i32>>::new(0..n as isize).unwrap();
+            let mut rhs =
+                DynamicLazySegmentTree::<AddQueryAssignUpdate<i32>>::new(0..n as isize).unwrap();
+            for _ in 0..rng.random_range(0..10) {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l + 1..=n);
+                let value = Some(rng.random_range(0..2));
+                let range = l as isize..r as isize;
+                if rng.random_bool(0.5) {
+                    lhs.range_update(range, &value)
+                } else {
+                    rhs.range_update(range, &value)
+                }
+            }
+            assert_eq!(lhs == rhs, lhs.to_vec() == rhs.to_vec());
+        }
+    }
+
+    #[test]
+    fn hash() {
+        let hasher = RandomState::new();
+        let mut lhs =
+            DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-1 << 40..1 << 40).unwrap();
+        let mut rhs =
+            DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-1 << 40..1 << 40).unwrap();
+        lhs.range_update(.., &1);
+        rhs.range_update(..0, &1);
+        rhs.range_update(0.., &1);
+        assert_eq!(hasher.hash_one(&lhs), hasher.hash_one(&rhs));
+    }
+}
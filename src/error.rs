@@ -0,0 +1,49 @@
+use std::{fmt::Display, ops::Range};
+
+/// Errors returned by the fallible operations, such as [`DynamicSegmentTree::try_new`](crate::DynamicSegmentTree::try_new).
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{DynamicSegmentTree, Error, ops::Add};
+///
+/// assert_eq!(
+///     DynamicSegmentTree::<Add<i32>>::try_new(5..5).unwrap_err(),
+///     Error::EmptyRange
+/// );
+///
+/// let mut dst = DynamicSegmentTree::<Add<i32>>::try_new(0..5).unwrap();
+/// assert_eq!(
+///     dst.try_point_update(5, 1),
+///     Err(Error::OutOfBounds { index: 5, range: 0..5 })
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The range of a dynamic tree is empty.
+    EmptyRange,
+    /// The `index` is out of the `range` of the tree.
+    OutOfBounds {
+        /// The index accessed.
+        index: isize,
+        /// The range of the tree.
+        range: Range<isize>,
+    },
+    /// The requested capacity exceeds the limit, or the allocator reported a failure.
+    CapacityOverflow,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::EmptyRange => write!(f, "the range should not be empty"),
+            Error::OutOfBounds { index, range } => {
+                write!(f, "the index {index} is out of the range {range:?}")
+            }
+            Error::CapacityOverflow => write!(f, "capacity overflow"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
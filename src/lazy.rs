@@ -1,6 +1,17 @@
-use std::{fmt::Debug, ops::RangeBounds};
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+    ops::{Range, RangeBounds},
+};
 
-use crate::traits::{Monoid, MonoidAction};
+use crate::{
+    memory::MemoryReport,
+    snapshot::{self, Element},
+    stats::Counters,
+    traits::{Monoid, MonoidAction},
+    utility::{convert_range, split_cyclic},
+};
 
 /// A data structure that supports **range query range update** operations.
 ///
@@ -18,7 +29,10 @@ where
     lazy: Box<[<<Action as MonoidAction>::Map as Monoid>::Set]>,
 
     /// calculate if [`MonoidAction::USE_SEGMENT_SIZE`] is `true`.
+    /// The leaves are kept only if they are weighted.
     segment_size: Option<Box<[usize]>>,
+
+    counters: Counters,
 }
 // ANCHOR_END: definition
 
@@ -46,6 +60,74 @@ where
         )
     }
 
+    /// Creates a new instance from `values`, where the `i`-th element weighs `weights[i]`.
+    ///
+    /// [`MonoidAction::act`] receives the sum of the weights in a segment instead of the number of elements,
+    /// so that an update such as "add `x` per unit length" works over cells of different widths,
+    /// such as those of a coordinate-compressed axis.
+    /// The weights are ignored unless [`MonoidAction::USE_SEGMENT_SIZE`] is `true`.
+    ///
+    /// Snapshots do not keep the weights, so [`write_snapshot`](Self::write_snapshot) fails for weighted trees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` and `weights` have different lengths or if the sum of the weights overflows [`usize`].
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// // cells [0, 1), [1, 4), [4, 10)
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::with_weights(vec![0; 3], vec![1, 3, 6]);
+    /// lst.range_update(1.., &2);
+    /// assert_eq!(lst.to_vec(), vec![0, 6, 12]);
+    /// assert_eq!(lst.range_query(..2), 6);
+    /// assert_eq!(lst.weights(), Some(&[1, 3, 6][..]));
+    /// ```
+    pub fn with_weights(
+        values: Vec<<<Action as MonoidAction>::Set as Monoid>::Set>,
+        weights: Vec<usize>,
+    ) -> Self {
+        assert_eq!(
+            values.len(),
+            weights.len(),
+            "the number of weights should be equal to the number of values"
+        );
+        let n = values.len();
+
+        let mut lst = Self::from_leaves(values);
+        if <Action as MonoidAction>::USE_SEGMENT_SIZE {
+            // keep the leaves, which are unit-sized otherwise
+            let mut segment_size = Vec::from_iter(std::iter::repeat_n(0, n).chain(weights));
+            for i in (1..n).rev() {
+                segment_size[i] = segment_size[i << 1]
+                    .checked_add(segment_size[(i << 1) | 1])
+                    .expect("the sum of the weights should not overflow `usize`");
+            }
+            lst.segment_size = Some(segment_size.into_boxed_slice());
+        }
+        lst.recalculate_all();
+        lst
+    }
+
+    /// Returns the weights of the elements if the tree is created by [`with_weights`](Self::with_weights)
+    /// and [`MonoidAction::USE_SEGMENT_SIZE`] is `true`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn weights(&self) -> Option<&[usize]> {
+        self.segment_size
+            .as_deref()
+            .filter(|segment_size| segment_size.len() == self.data.len())
+            .map(|segment_size| &segment_size[segment_size.len() >> 1..])
+    }
+
     /// Returns the number of elements.
     ///
     /// # Time complexity
@@ -60,12 +142,84 @@ where
     /// let lst = LazySegmentTree::<MaxQueryAddUpdate<i32>>::new(100);
     /// assert_eq!(lst.len(), 100);
     /// ```
-    #[allow(clippy::len_without_is_empty)]
     #[inline]
     pub fn len(&self) -> usize {
         self.data.len() >> 1
     }
 
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// Queries over an empty tree return [the identity element](crate::traits::Monoid::identity()),
+    /// and range updates do nothing.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(0);
+    /// assert!(lst.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    #[doc = include_str!("../doc/stats.md")]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAssignUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAssignUpdate<i32>>::new(8);
+    /// lst.range_update(..4, &Some(1));
+    /// lst.reset_stats();
+    ///
+    /// lst.range_update(2..6, &Some(2));
+    /// assert_eq!(lst.stats().propagations, 1);
+    /// ```
+    #[cfg(feature = "instrument")]
+    pub fn stats(&self) -> crate::Stats {
+        self.counters.stats()
+    }
+
+    /// Resets the counts returned by [`stats()`](Self::stats) to zero.
+    #[cfg(feature = "instrument")]
+    pub fn reset_stats(&mut self) {
+        self.counters.reset()
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let lst = LazySegmentTree::<AddQueryAddUpdate<u32>>::new(100);
+    /// let report = lst.memory_usage();
+    /// assert_eq!(report.data, 200 * size_of::<u32>());
+    /// assert_eq!(report.lazy, 100 * size_of::<u32>());
+    /// assert_eq!(report.segment_size, 100 * size_of::<usize>());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            data: size_of_val(&*self.data),
+            lazy: size_of_val(&*self.lazy),
+            segment_size: self.segment_size.as_deref().map_or(0, size_of_val),
+            ..Default::default()
+        }
+    }
+
     /// Returns an iterator over the elements
     ///
     /// # Time complexity
@@ -87,6 +241,134 @@ where
         self.data[self.data.len() >> 1..].iter()
     }
 
+    #[doc = include_str!("../doc/flush.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAffineUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from_iter(0..10);
+    /// lst.range_update(2..8, &(2, 1));
+    ///
+    /// lst.flush();
+    /// assert_eq!(lst.range_query(..), 45 + 27 + 6);
+    /// ```
+    pub fn flush(&mut self) {
+        self.propagate_all();
+        self.recalculate_all();
+    }
+
+    /// Returns the elements with all pending updates resolved.
+    ///
+    /// Unlike [`iter`](Self::iter), the tree is left untouched.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(5);
+    /// lst.range_update(1..4, &1);
+    /// lst.range_update(2.., &10);
+    ///
+    /// assert_eq!(lst.to_vec(), vec![0, 1, 11, 11, 10]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<<<Action as MonoidAction>::Set as Monoid>::Set> {
+        let n = self.data.len() >> 1;
+
+        // composed pending updates of the ancestors, including the node itself
+        let mut composed = Vec::with_capacity(n);
+        composed.push(<<Action as MonoidAction>::Map as Monoid>::identity());
+        for i in 1..n {
+            let mapping = <<Action as MonoidAction>::Map as Monoid>::combine(
+                &self.lazy[i],
+                &composed[i >> 1],
+            );
+            composed.push(mapping);
+        }
+
+        Vec::from_iter((n..n << 1).map(|i| {
+            let size = self
+                .segment_size
+                .as_ref()
+                .map(|segment_size| segment_size.get(i).copied().unwrap_or(1));
+            <Action as MonoidAction>::act(&composed[i >> 1], &self.data[i], size)
+        }))
+    }
+
+    /// Consumes the tree and returns the elements with all pending updates resolved.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::MaxQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<MaxQueryAddUpdate<i32>>::from_iter([Some(0), None, Some(2)]);
+    /// lst.range_update(.., &1);
+    ///
+    /// assert_eq!(lst.into_vec(), vec![Some(1), None, Some(3)]);
+    /// ```
+    pub fn into_vec(mut self) -> Vec<<<Action as MonoidAction>::Set as Monoid>::Set> {
+        self.propagate_all();
+
+        let n = self.data.len() >> 1;
+        let mut data = self.data.into_vec();
+        data.drain(..n);
+        data
+    }
+
+    #[doc = include_str!("../doc/assign_from_iter.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(5);
+    /// lst.range_update(.., &10);
+    ///
+    /// lst.assign_from_iter(0..5);
+    /// assert_eq!(lst.range_query(..), 10);
+    /// ```
+    pub fn assign_from_iter<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = <<Action as MonoidAction>::Set as Monoid>::Set>,
+    {
+        let n = self.data.len() >> 1;
+
+        let mut iter = iter.into_iter();
+        for leaf in &mut self.data[n..] {
+            *leaf = iter
+                .next()
+                .expect("`iter` should yield exactly `len()` items");
+        }
+        assert!(
+            iter.next().is_none(),
+            "`iter` should yield exactly `len()` items"
+        );
+
+        self.lazy
+            .fill_with(<<Action as MonoidAction>::Map as Monoid>::identity);
+        self.recalculate_all();
+    }
+
     #[inline]
     fn inner_index(&self, i: usize) -> usize {
         self.data.len() / 2 + i
@@ -113,14 +395,20 @@ where
     }
 
     fn push_map(&mut self, i: usize, update: &<<Action as MonoidAction>::Map as Monoid>::Set) {
+        if <<Action as MonoidAction>::Map as Monoid>::is_identity(update) {
+            return;
+        }
+
         let size = self
             .segment_size
             .as_ref()
             .map(|segment_size| segment_size.get(i).copied().unwrap_or(1));
-        self.data[i] = <Action as MonoidAction>::act(update, &self.data[i], size);
+        <Action as MonoidAction>::act_assign(update, &mut self.data[i], size);
+        self.counters.combine(1);
 
         if let Some(lazy) = self.lazy.get_mut(i) {
-            *lazy = <<Action as MonoidAction>::Map as Monoid>::combine(lazy, update)
+            <<Action as MonoidAction>::Map as Monoid>::combine_assign(lazy, update);
+            self.counters.combine(1)
         }
     }
 
@@ -130,10 +418,15 @@ where
     ///
     /// Panics if either of children does **not** exist.
     fn propagate_at(&mut self, i: usize) {
+        if <<Action as MonoidAction>::Map as Monoid>::is_identity(&self.lazy[i]) {
+            return;
+        }
+
         let mapping = std::mem::replace(
             &mut self.lazy[i],
             <<Action as MonoidAction>::Map as Monoid>::identity(),
         );
+        self.counters.propagate();
         self.push_map(i << 1, &mapping);
         self.push_map((i << 1) | 1, &mapping);
     }
@@ -144,17 +437,63 @@ where
         }
     }
 
-    /// Recalculates i-th data segments from the children.
+    /// Recalculates i-th data segments from the children and the pending update.
     ///
     /// # Panics
     ///
     /// Panics if either of children does **not** exist.
     #[inline]
     fn recalculate_at(&mut self, i: usize) {
-        self.data[i] = <<Action as MonoidAction>::Set as Monoid>::combine(
+        let mut combined = <<Action as MonoidAction>::Set as Monoid>::combine(
             &self.data[i << 1],
             &self.data[(i << 1) | 1],
+        );
+        self.counters.combine(1);
+        if !<<Action as MonoidAction>::Map as Monoid>::is_identity(&self.lazy[i]) {
+            // pending updates are not propagated if `Map` is commutative
+            let size = self
+                .segment_size
+                .as_ref()
+                .map(|segment_size| segment_size[i]);
+            self.counters.combine(1);
+            <Action as MonoidAction>::act_assign(&self.lazy[i], &mut combined, size)
+        }
+        self.data[i] = combined;
+    }
+
+    /// Creates a new instance whose leaves are `values` and whose other nodes are not calculated yet.
+    fn from_leaves(values: Vec<<<Action as MonoidAction>::Set as Monoid>::Set>) -> Self {
+        let n = values.len();
+
+        let data = Vec::from_iter(
+            std::iter::repeat_with(<<Action as MonoidAction>::Set as Monoid>::identity)
+                .take(n)
+                .chain(values),
+        )
+        .into_boxed_slice();
+
+        let lazy = Vec::from_iter(
+            std::iter::repeat_with(<<Action as MonoidAction>::Map as Monoid>::identity).take(n),
         )
+        .into_boxed_slice();
+
+        let segment_size = <Action as MonoidAction>::USE_SEGMENT_SIZE.then(|| {
+            let mut segment_size =
+                Vec::from_iter(std::iter::repeat_n(0, n).chain(std::iter::repeat_n(1, n)));
+            for i in (1..n).rev() {
+                segment_size[i] = segment_size[i << 1] + segment_size[(i << 1) | 1]
+            }
+            segment_size.truncate(n);
+
+            segment_size.into_boxed_slice()
+        });
+
+        Self {
+            data,
+            lazy,
+            segment_size,
+            counters: Counters::default(),
+        }
     }
 
     /// Recalculates all data segments.
@@ -164,58 +503,25 @@ where
         }
     }
 
-    #[doc = include_str!("../doc/range_update.md")]
-    ///
-    /// # Time complexity
-    ///
-    /// *O*(log *N*)
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use seg_lib::{LazySegmentTree, acts::MaxQueryAddUpdate};
-    ///
-    /// let mut lst = LazySegmentTree::<MaxQueryAddUpdate<i32>>::from_iter(
-    ///     std::iter::repeat_n(Some(0), 100)
-    /// );
-    /// assert_eq!(lst.range_query(..), Some(0));
-    ///
-    /// lst.range_update(..75, &100);
-    /// lst.range_update(25.., &110);
-    /// assert_eq!(lst.range_query(..25), Some(100));
-    /// assert_eq!(lst.range_query(..), Some(210));
-    /// assert_eq!(lst.range_query(75..), Some(110));
-    /// ```
-    pub fn range_update<R>(
+    /// Pushes `update` to the segments which exactly cover `[l, r)` on `self.data`,
+    /// leaving their ancestors stale.
+    fn push_range(
         &mut self,
-        range: R,
+        l: usize,
+        r: usize,
         update: &<<Action as MonoidAction>::Map as Monoid>::Set,
-    ) where
-        R: RangeBounds<usize>,
-    {
-        let [l, r] = {
-            let [l, r] = self.translate_range(range);
-            if l >= r {
-                return;
-            }
-            if l + 1 == r {
-                self.point_update(l, update);
-                return;
-            }
-
-            [self.inner_index(l), self.inner_index(r)]
-        };
-
+    ) {
         // lazy propagation in bottom-to-top order
         if !<<Action as MonoidAction>::Map as Monoid>::IS_COMMUTATIVE {
             let diff = usize::BITS - (l ^ (r - 1)).leading_zeros();
             for d in (diff + 1..usize::BITS - l.leading_zeros()).rev() {
                 self.propagate_at(l >> d);
             }
-            for d in (l.trailing_zeros() + 1..=diff).rev() {
+            let [l_height, r_height] = [l, r - 1].map(|i| usize::BITS - i.leading_zeros());
+            for d in (l.trailing_zeros() + 1..=diff.min(l_height - 1)).rev() {
                 self.propagate_at(l >> d);
             }
-            for d in (r.trailing_zeros() + 1..=diff).rev() {
+            for d in (r.trailing_zeros() + 1..=diff.min(r_height - 1)).rev() {
                 self.propagate_at((r - 1) >> d);
             }
         }
@@ -237,13 +543,18 @@ where
                 l != r
             } {}
         }
+    }
 
+    /// Recalculates the ancestors of the segments which exactly cover `[l, r)` on `self.data`.
+    fn recalculate_range(&mut self, l: usize, r: usize) {
         // recalculate data segments in bottom-to-top order
         let diff = usize::BITS - (l ^ (r - 1)).leading_zeros();
-        for d in l.trailing_zeros() + 1..=diff {
+        // `l` and `r - 1` may differ in bit length, then their ancestors meet at the dummy `data[0]`
+        let [l_height, r_height] = [l, r - 1].map(|i| usize::BITS - i.leading_zeros());
+        for d in l.trailing_zeros() + 1..=diff.min(l_height - 1) {
             self.recalculate_at(l >> d);
         }
-        for d in r.trailing_zeros() + 1..=diff {
+        for d in r.trailing_zeros() + 1..=diff.min(r_height - 1) {
             self.recalculate_at((r - 1) >> d);
         }
         for d in diff + 1..usize::BITS - l.leading_zeros() {
@@ -251,7 +562,8 @@ where
         }
     }
 
-    #[doc = include_str!("../doc/point_update.md")]
+    #[doc = include_str!("../doc/range_update.md")]
+    ///
     /// # Time complexity
     ///
     /// *O*(log *N*)
@@ -266,34 +578,49 @@ where
     /// );
     /// assert_eq!(lst.range_query(..), Some(0));
     ///
-    /// lst.point_update(25, &10);
-    /// lst.point_update(75, &20);
-    /// assert_eq!(lst.range_query(..), Some(20));
-    /// assert_eq!(lst.range_query(..50), Some(10))
+    /// lst.range_update(..75, &100);
+    /// lst.range_update(25.., &110);
+    /// assert_eq!(lst.range_query(..25), Some(100));
+    /// assert_eq!(lst.range_query(..), Some(210));
+    /// assert_eq!(lst.range_query(75..), Some(110));
     /// ```
-    pub fn point_update(
+    pub fn range_update<R>(
         &mut self,
-        i: usize,
+        range: R,
         update: &<<Action as MonoidAction>::Map as Monoid>::Set,
-    ) {
-        let i = self.inner_index(i);
+    ) where
+        R: RangeBounds<usize>,
+    {
+        let [l, r] = self.translate_range(range);
+        #[cfg(feature = "tracing")]
+        let traced = self
+            .counters
+            .enter(crate::stats::trace_span!("range_update", l..r));
+        self.range_update_inner(l, r, update);
+        #[cfg(feature = "tracing")]
+        self.counters.exit(traced);
+    }
 
-        // lazy propagation
-        if !<<Action as MonoidAction>::Map as Monoid>::IS_COMMUTATIVE {
-            for d in (1..usize::BITS - i.leading_zeros()).rev() {
-                self.propagate_at(i >> d);
-            }
+    fn range_update_inner(
+        &mut self,
+        l: usize,
+        r: usize,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) {
+        if l >= r {
+            return;
         }
-
-        self.push_map(i, update);
-
-        // recalculate
-        for d in 1..usize::BITS - i.leading_zeros() {
-            self.recalculate_at(i >> d);
+        if l + 1 == r {
+            self.point_update(l, update);
+            return;
         }
+
+        let [l, r] = [self.inner_index(l), self.inner_index(r)];
+        self.push_range(l, r, update);
+        self.recalculate_range(l, r);
     }
 
-    #[doc = include_str!("../doc/range_query.md")]
+    #[doc = include_str!("../doc/range_update_cyclic.md")]
     /// # Time complexity
     ///
     /// *O*(log *N*)
@@ -301,43 +628,253 @@ where
     /// # Example
     ///
     /// ```
-    /// use seg_lib::{LazySegmentTree, acts::MaxQueryAddUpdate};
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
     ///
-    /// let mut lst = LazySegmentTree::<MaxQueryAddUpdate<i32>>::from_iter(
-    ///     (0..100).map(|v| Some(v))
-    /// );
-    /// assert_eq!(lst.range_query(..), Some(99));
-    /// assert_eq!(lst.range_query(..50), Some(49));
-    /// assert_eq!(lst.range_query(..=50), Some(50));
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::from_iter([0; 5]);
+    /// lst.range_update_cyclic(3..1, &1);
+    /// lst.range_update_cyclic(1..3, &10);
+    /// assert_eq!(lst.to_vec(), vec![1, 10, 10, 1, 1]);
+    /// ```
+    pub fn range_update_cyclic<R>(
+        &mut self,
+        range: R,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) where
+        R: RangeBounds<usize> + Debug,
+    {
+        let n = self.len();
+        for range in split_cyclic(convert_range(range, 0..n), n) {
+            self.range_update(range, update)
+        }
+    }
+
+    /// Starts a burst of range updates which postpones recalculation of the ancestors
+    /// until the returned guard is dropped.
+    ///
+    /// Each ancestor is recalculated at most once,
+    /// which saves time when many updates land in the same region.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and *O*(*K* log *N* log(*K* log *N*)) to drop the guard after *K* updates
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(100);
+    /// {
+    ///     let mut batch = lst.batch_update();
+    ///     for i in 0..10 {
+    ///         batch.range_update(i..i + 10, &1);
+    ///     }
+    /// }
+    /// assert_eq!(lst.range_query(..), 100);
+    /// assert_eq!(lst.range_query(9..10), 10);
+    /// ```
+    pub fn batch_update(&mut self) -> BatchUpdate<'_, Action> {
+        BatchUpdate {
+            tree: self,
+            dirty: Vec::new(),
+        }
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::MaxQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<MaxQueryAddUpdate<i32>>::from_iter(
+    ///     std::iter::repeat_n(Some(0), 100)
+    /// );
+    /// assert_eq!(lst.range_query(..), Some(0));
+    ///
+    /// lst.point_update(25, &10);
+    /// lst.point_update(75, &20);
+    /// assert_eq!(lst.range_query(..), Some(20));
+    /// assert_eq!(lst.range_query(..50), Some(10))
+    /// ```
+    pub fn point_update(
+        &mut self,
+        i: usize,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) {
+        let i = self.inner_index(i);
+
+        // lazy propagation
+        if !<<Action as MonoidAction>::Map as Monoid>::IS_COMMUTATIVE {
+            for d in (1..usize::BITS - i.leading_zeros()).rev() {
+                self.propagate_at(i >> d);
+            }
+        }
+
+        self.push_map(i, update);
+
+        // recalculate
+        for d in 1..usize::BITS - i.leading_zeros() {
+            self.recalculate_at(i >> d);
+        }
+    }
+
+    #[doc = include_str!("../doc/apply_range_with.md")]
+    /// # Time complexity
+    ///
+    /// *O*(*K* + log *N*), where *K* is the length of the range
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::from_iter(0..6);
+    /// lst.range_update(..3, &10);
+    ///
+    /// lst.apply_range_with(1..5, |v| *v = (*v).min(11));
+    /// assert_eq!(lst.to_vec(), vec![10, 11, 11, 3, 4, 5]);
+    /// assert_eq!(lst.range_query(..), 44);
+    /// ```
+    pub fn apply_range_with<R, F>(&mut self, range: R, mut f: F)
+    where
+        R: RangeBounds<usize> + Debug,
+        F: FnMut(&mut <<Action as MonoidAction>::Set as Monoid>::Set),
+    {
+        let Range { start, end } = convert_range(range, 0..self.data.len() / 2);
+        if start == end {
+            return;
+        }
+        let [l, r] = [self.inner_index(start), self.inner_index(end)];
+        let height = usize::BITS - (r - 1).leading_zeros();
+
+        // lazy propagation to every leaf in the range, parents first
+        for d in (1..height).rev() {
+            for i in (l >> d).max(1)..=(r - 1) >> d {
+                self.propagate_at(i);
+            }
+        }
+
+        self.data[l..r].iter_mut().for_each(&mut f);
+
+        // each node is recalculated for the last time after its children
+        for d in 1..height {
+            for i in (l >> d).max(1)..=(r - 1) >> d {
+                self.recalculate_at(i);
+            }
+        }
+    }
+
+    /// Swaps the `i`-th and `j`-th elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::from_iter(0..10);
+    /// lst.range_update(..5, &10);
+    ///
+    /// lst.swap(0, 9);
+    /// assert_eq!(lst.point_query(0), &9);
+    /// assert_eq!(lst.point_query(9), &10);
+    /// assert_eq!(lst.range_query(..5), 9 + 11 + 12 + 13 + 14);
+    /// ```
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let [i, j] = [self.inner_index(i), self.inner_index(j)];
+        assert!(i.max(j) < self.data.len(), "index out of bounds");
+
+        // lazy propagation
+        for d in (1..usize::BITS - i.leading_zeros()).rev() {
+            self.propagate_at(i >> d);
+        }
+        for d in (1..usize::BITS - j.leading_zeros()).rev() {
+            self.propagate_at(j >> d);
+        }
+
+        self.data.swap(i, j);
+
+        // recalculate
+        for d in 1..usize::BITS - i.leading_zeros() {
+            self.recalculate_at(i >> d);
+        }
+        for d in 1..usize::BITS - j.leading_zeros() {
+            self.recalculate_at(j >> d);
+        }
+    }
+
+    #[doc = include_str!("../doc/range_query.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::MaxQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<MaxQueryAddUpdate<i32>>::from_iter(
+    ///     (0..100).map(|v| Some(v))
+    /// );
+    /// assert_eq!(lst.range_query(..), Some(99));
+    /// assert_eq!(lst.range_query(..50), Some(49));
+    /// assert_eq!(lst.range_query(..=50), Some(50));
     /// assert_eq!(lst.range_query(50..), Some(99));
     /// ```
     pub fn range_query<R>(&mut self, range: R) -> <<Action as MonoidAction>::Set as Monoid>::Set
     where
         R: RangeBounds<usize>,
     {
-        let [l, r] = {
-            let [l, r] = self.translate_range(range);
-            if l >= r {
-                return <<Action as MonoidAction>::Set as Monoid>::identity();
-            }
-            if l + 1 == r {
-                return <<Action as MonoidAction>::Set as Monoid>::combine(
-                    self.point_query(l),
-                    &<<Action as MonoidAction>::Set as Monoid>::identity(),
-                );
-            }
-            [self.inner_index(l), self.inner_index(r)]
-        };
+        let [l, r] = self.translate_range(range);
+        #[cfg(feature = "tracing")]
+        let traced = self
+            .counters
+            .enter(crate::stats::trace_span!("range_query", l..r));
+        let res = self.range_query_inner(l, r);
+        #[cfg(feature = "tracing")]
+        self.counters.exit(traced);
+        res
+    }
+
+    fn range_query_inner(
+        &mut self,
+        l: usize,
+        r: usize,
+    ) -> <<Action as MonoidAction>::Set as Monoid>::Set {
+        if l >= r {
+            return <<Action as MonoidAction>::Set as Monoid>::identity();
+        }
+        if l + 1 == r {
+            self.counters.combine(1);
+            return <<Action as MonoidAction>::Set as Monoid>::combine(
+                self.point_query(l),
+                &<<Action as MonoidAction>::Set as Monoid>::identity(),
+            );
+        }
+
+        let [l, r] = [self.inner_index(l), self.inner_index(r)];
 
         // lazy propagation
         let diff = usize::BITS - (l ^ (r - 1)).leading_zeros();
         for d in (diff + 1..usize::BITS - l.leading_zeros()).rev() {
             self.propagate_at(l >> d);
         }
-        for d in (l.trailing_zeros() + 1..=diff).rev() {
+        let [l_height, r_height] = [l, r - 1].map(|i| usize::BITS - i.leading_zeros());
+        for d in (l.trailing_zeros() + 1..=diff.min(l_height - 1)).rev() {
             self.propagate_at(l >> d);
         }
-        for d in (r.trailing_zeros() + 1..=diff).rev() {
+        for d in (r.trailing_zeros() + 1..=diff.min(r_height - 1)).rev() {
             self.propagate_at((r - 1) >> d);
         }
 
@@ -349,7 +886,10 @@ where
         ];
         while {
             if l >= r {
-                acc_l = <<Action as MonoidAction>::Set as Monoid>::combine(&acc_l, &self.data[l]);
+                <<Action as MonoidAction>::Set as Monoid>::combine_assign(
+                    &mut acc_l,
+                    &self.data[l],
+                );
                 l += 1;
                 l >>= l.trailing_zeros();
             } else {
@@ -357,13 +897,46 @@ where
                 acc_r = <<Action as MonoidAction>::Set as Monoid>::combine(&self.data[r], &acc_r);
                 r >>= r.trailing_zeros();
             }
+            self.counters.combine(1);
 
             l != r
         } {}
 
+        self.counters.combine(1);
         <<Action as MonoidAction>::Set as Monoid>::combine(&acc_l, &acc_r)
     }
 
+    #[doc = include_str!("../doc/range_query_cyclic.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAffineUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from_iter([1, 2, 3, 4, 5]);
+    /// lst.range_update_cyclic(4..2, &(2, 0));
+    /// assert_eq!(lst.to_vec(), vec![2, 4, 3, 4, 10]);
+    /// assert_eq!(lst.range_query_cyclic(3..1), 4 + 10 + 2);
+    /// ```
+    pub fn range_query_cyclic<R>(
+        &mut self,
+        range: R,
+    ) -> <<Action as MonoidAction>::Set as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let n = self.len();
+        let [head, tail] = split_cyclic(convert_range(range, 0..n), n);
+        let head = self.range_query(head);
+        if tail.is_empty() {
+            return head;
+        }
+        <<Action as MonoidAction>::Set as Monoid>::combine(&head, &self.range_query(tail))
+    }
+
     #[doc = include_str!("../doc/point_query.md")]
     /// # Time complexity
     ///
@@ -394,101 +967,441 @@ where
     }
 }
 
-impl<Action> From<Vec<<<Action as MonoidAction>::Set as Monoid>::Set>> for LazySegmentTree<Action>
+/// A guard to postpone recalculation of a [`LazySegmentTree`] during a burst of range updates.
+///
+/// Created by [`LazySegmentTree::batch_update`].
+/// The tree is repaired when the guard is dropped, so do not [`forget`](std::mem::forget) it.
+pub struct BatchUpdate<'a, Action>
 where
     Action: MonoidAction,
 {
-    fn from(values: Vec<<<Action as MonoidAction>::Set as Monoid>::Set>) -> Self {
-        let n = values.len();
-
-        let data = Vec::from_iter(
-            std::iter::repeat_with(<<Action as MonoidAction>::Set as Monoid>::identity)
-                .take(n)
-                .chain(values),
-        )
-        .into_boxed_slice();
-
-        let lazy = Vec::from_iter(
-            std::iter::repeat_with(<<Action as MonoidAction>::Map as Monoid>::identity).take(n),
-        )
-        .into_boxed_slice();
-
-        let segment_size = <Action as MonoidAction>::USE_SEGMENT_SIZE.then(|| {
-            let mut segment_size =
-                Vec::from_iter(std::iter::repeat_n(0, n).chain(std::iter::repeat_n(1, n)));
-            for i in (1..n).rev() {
-                segment_size[i] = segment_size[i << 1] + segment_size[(i << 1) | 1]
-            }
-            segment_size.truncate(n);
+    tree: &'a mut LazySegmentTree<Action>,
+    /// The inner indices of the boundary leaves whose ancestors are stale.
+    dirty: Vec<usize>,
+}
 
-            segment_size.into_boxed_slice()
-        });
+impl<Action> BatchUpdate<'_, Action>
+where
+    Action: MonoidAction,
+{
+    #[doc = include_str!("../doc/range_update.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    pub fn range_update<R>(
+        &mut self,
+        range: R,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) where
+        R: RangeBounds<usize>,
+    {
+        let [l, r] = self.tree.translate_range(range);
+        if l >= r {
+            return;
+        }
 
-        let mut lst = Self {
-            data,
-            lazy,
-            segment_size,
-        };
-        lst.recalculate_all();
-        lst
+        let [l, r] = [self.tree.inner_index(l), self.tree.inner_index(r)];
+        self.tree.push_range(l, r, update);
+        self.dirty.extend([l, r - 1]);
     }
 }
 
-impl<Action> FromIterator<<<Action as MonoidAction>::Set as Monoid>::Set>
-    for LazySegmentTree<Action>
+impl<Action> Drop for BatchUpdate<'_, Action>
 where
     Action: MonoidAction,
 {
-    fn from_iter<T: IntoIterator<Item = <<Action as MonoidAction>::Set as Monoid>::Set>>(
-        iter: T,
-    ) -> Self {
-        let iter = iter.into_iter();
-        let (min, max) = iter.size_hint();
-        if Some(min) == max {
-            let data = Vec::from_iter(
-                std::iter::repeat_with(<<Action as MonoidAction>::Set as Monoid>::identity)
-                    .take(min)
-                    .chain(iter),
-            )
-            .into_boxed_slice();
-
-            let lazy = Vec::from_iter(
-                std::iter::repeat_with(<<Action as MonoidAction>::Map as Monoid>::identity)
-                    .take(min),
-            )
-            .into_boxed_slice();
-
-            let segment_size = <Action as MonoidAction>::USE_SEGMENT_SIZE.then(|| {
-                let mut segment_size =
-                    Vec::from_iter(std::iter::repeat_n(0, min).chain(std::iter::repeat_n(1, min)));
-                for i in (1..min).rev() {
-                    segment_size[i] = segment_size[i << 1] + segment_size[(i << 1) | 1]
-                }
-                segment_size.truncate(min);
-
-                segment_size.into_boxed_slice()
-            });
-
-            let mut lst = Self {
-                data,
-                lazy,
-                segment_size,
-            };
-            lst.recalculate_all();
-            lst
-        } else {
-            Vec::from_iter(iter).into()
+    fn drop(&mut self) {
+        let mut nodes = Vec::from_iter(
+            self.dirty
+                .iter()
+                .flat_map(|&i| (1..usize::BITS - i.leading_zeros()).map(move |d| i >> d)),
+        );
+        // children first
+        nodes.sort_unstable_by(|a, b| b.cmp(a));
+        nodes.dedup();
+        for i in nodes {
+            self.tree.recalculate_at(i);
         }
     }
 }
 
-impl<Action> Debug for LazySegmentTree<Action>
+impl<Action> LazySegmentTree<Action>
 where
-    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
+    Action: MonoidAction<Set: Monoid<Set: Clone>>,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("LazySegmentTree")
-            .field("data", &self.data)
+    #[doc = include_str!("../doc/from_elem.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::from_elem(100, 1);
+    /// assert_eq!(lst.range_query(..), 100);
+    ///
+    /// lst.range_update(..50, &1);
+    /// assert_eq!(lst.range_query(..), 150);
+    /// ```
+    #[inline]
+    pub fn from_elem(n: usize, element: <<Action as MonoidAction>::Set as Monoid>::Set) -> Self {
+        Self::from_iter(std::iter::repeat_n(element, n))
+    }
+
+    /// Creates a new instance initialized with clones of `values`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let values = [1, 2, 3, 4, 5];
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::from_slice(&values);
+    /// lst.range_update(1..4, &10);
+    /// assert_eq!(lst.range_query(..), 45);
+    /// ```
+    #[inline]
+    pub fn from_slice(values: &[<<Action as MonoidAction>::Set as Monoid>::Set]) -> Self {
+        Self::from_iter(values.iter().cloned())
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<Action> LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Send + Sync>>,
+{
+    /// Creates a new instance from `values`, calculating the nodes of each level in parallel.
+    ///
+    /// # Time complexity
+    ///
+    /// *Θ*(*N*) work and *O*(log *N*) levels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let n = 1 << 20;
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<u64>>::par_from_vec(Vec::from_iter(0..n));
+    /// lst.range_update(.., &1);
+    /// assert_eq!(lst.range_query(..), n * (n + 1) / 2);
+    /// ```
+    pub fn par_from_vec(values: Vec<<<Action as MonoidAction>::Set as Monoid>::Set>) -> Self {
+        let n = values.len();
+        let mut lst = Self::from_leaves(values);
+        crate::utility::par_build(&mut lst.data, n, |parent, lhs, rhs| {
+            *parent = <<Action as MonoidAction>::Set as Monoid>::combine(lhs, rhs)
+        });
+        lst.counters = Counters::built(n);
+        lst
+    }
+}
+
+impl<Action> LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Element>>,
+{
+    #[doc = include_str!("../doc/write_snapshot.md")]
+    ///
+    /// Also returns an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput)
+    /// if the tree has [weights](Self::weights), which snapshots cannot keep.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(5);
+    /// lst.range_update(1..4, &1);
+    /// let mut bytes = Vec::new();
+    /// lst.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(lst.range_query(..), 3);
+    /// ```
+    pub fn write_snapshot<W>(&self, writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        if self.weights().is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "snapshots do not keep the weights",
+            ));
+        }
+        snapshot::write(writer, self.len(), &self.to_vec())
+    }
+
+    #[doc = include_str!("../doc/read_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, SegmentTree, acts::AddQueryAddUpdate, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_iter(0..5);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::read_snapshot(&bytes[..]).unwrap();
+    /// lst.range_update(.., &1);
+    /// assert_eq!(lst.to_vec(), vec![1, 2, 3, 4, 5]);
+    /// ```
+    pub fn read_snapshot<R>(reader: R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        snapshot::read(reader).map(Self::from)
+    }
+}
+
+impl<Action> From<Vec<<<Action as MonoidAction>::Set as Monoid>::Set>> for LazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    fn from(values: Vec<<<Action as MonoidAction>::Set as Monoid>::Set>) -> Self {
+        let mut lst = Self::from_leaves(values);
+        lst.recalculate_all();
+        lst
+    }
+}
+
+impl<Action> From<&[<<Action as MonoidAction>::Set as Monoid>::Set]> for LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Clone>>,
+{
+    fn from(values: &[<<Action as MonoidAction>::Set as Monoid>::Set]) -> Self {
+        Self::from_slice(values)
+    }
+}
+
+impl<Action> FromIterator<<<Action as MonoidAction>::Set as Monoid>::Set>
+    for LazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    fn from_iter<T: IntoIterator<Item = <<Action as MonoidAction>::Set as Monoid>::Set>>(
+        iter: T,
+    ) -> Self {
+        let iter = iter.into_iter();
+        let (min, max) = iter.size_hint();
+        if Some(min) == max {
+            let data = Vec::from_iter(
+                std::iter::repeat_with(<<Action as MonoidAction>::Set as Monoid>::identity)
+                    .take(min)
+                    .chain(iter),
+            )
+            .into_boxed_slice();
+
+            let lazy = Vec::from_iter(
+                std::iter::repeat_with(<<Action as MonoidAction>::Map as Monoid>::identity)
+                    .take(min),
+            )
+            .into_boxed_slice();
+
+            let segment_size = <Action as MonoidAction>::USE_SEGMENT_SIZE.then(|| {
+                let mut segment_size =
+                    Vec::from_iter(std::iter::repeat_n(0, min).chain(std::iter::repeat_n(1, min)));
+                for i in (1..min).rev() {
+                    segment_size[i] = segment_size[i << 1] + segment_size[(i << 1) | 1]
+                }
+                segment_size.truncate(min);
+
+                segment_size.into_boxed_slice()
+            });
+
+            let mut lst = Self {
+                data,
+                lazy,
+                segment_size,
+                counters: Counters::default(),
+            };
+            lst.recalculate_all();
+            lst
+        } else {
+            Vec::from_iter(iter).into()
+        }
+    }
+}
+
+impl<Action> From<LazySegmentTree<Action>> for Vec<<<Action as MonoidAction>::Set as Monoid>::Set>
+where
+    Action: MonoidAction,
+{
+    fn from(tree: LazySegmentTree<Action>) -> Self {
+        tree.into_vec()
+    }
+}
+
+impl<Action> IntoIterator for LazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    type Item = <<Action as MonoidAction>::Set as Monoid>::Set;
+    type IntoIter = std::vec::IntoIter<<<Action as MonoidAction>::Set as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+/// Resolves the pending updates like [`iter`](LazySegmentTree::iter).
+///
+/// There is no impl for `&LazySegmentTree`: its elements exist only after the pending updates are resolved,
+/// so use this impl or [`to_vec`](LazySegmentTree::to_vec) instead.
+impl<'a, Action> IntoIterator for &'a mut LazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    type Item = &'a <<Action as MonoidAction>::Set as Monoid>::Set;
+    type IntoIter = std::slice::Iter<'a, <<Action as MonoidAction>::Set as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<Action> LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
+{
+    #[doc = include_str!("../doc/to_dot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(4);
+    /// lst.range_update(2..4, &5);
+    /// assert!(lst.to_dot().contains("{2..4 | combined: 10 | pending: 5}"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = crate::render::Dot::new("LazySegmentTree");
+        self.render(&mut dot);
+        dot.finish()
+    }
+
+    #[doc = include_str!("../doc/pretty.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(4);
+    /// lst.range_update(2..4, &5);
+    /// assert_eq!(
+    ///     lst.pretty().lines().nth(1),
+    ///     Some("1: [0..2] combined: 0, pending: 0  [2..4] combined: 10, pending: 5")
+    /// );
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut levels = crate::render::Levels::default();
+        self.render(&mut levels);
+        levels.finish()
+    }
+
+    #[doc = include_str!("../doc/to_mermaid.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{LazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(4);
+    /// lst.range_update(2..4, &5);
+    /// assert!(lst.to_mermaid().contains(r#"["2..4 | combined: 10 | pending: 5"]"#));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = crate::render::Mermaid::default();
+        self.render(&mut mermaid);
+        mermaid.finish()
+    }
+
+    /// Returns an adapter which [displays](std::fmt::Display) the tree like [`pretty`](Self::pretty).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and formatting takes as long as [`pretty`](Self::pretty)
+    pub fn display(&self) -> crate::Pretty<'_, Self> {
+        crate::Pretty(self)
+    }
+
+    /// Passes the nodes to `out` in level order.
+    pub(crate) fn render(&self, out: &mut impl crate::render::Render) {
+        let n = self.lazy.len();
+        for i in 1..n << 1 {
+            let parent = (i > 1).then_some(i >> 1);
+            let depth = i.ilog2() as usize;
+            if i < n {
+                let range = crate::render::bottom_up_range(i, n);
+                out.node(
+                    i,
+                    parent,
+                    depth,
+                    &range,
+                    &[("combined", &self.data[i]), ("pending", &self.lazy[i])],
+                );
+            } else {
+                out.node(
+                    i,
+                    parent,
+                    depth,
+                    &(i - n).to_string(),
+                    &[("element", &self.data[i])],
+                );
+            }
+        }
+    }
+}
+
+/// Renders the tree like [`LazySegmentTree::pretty`].
+impl<Action> std::fmt::Display for crate::Pretty<'_, LazySegmentTree<Action>>
+where
+    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.pretty())
+    }
+}
+
+impl<Action> Debug for LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazySegmentTree")
+            .field("data", &self.data)
             .field("lazy", &self.lazy)
             .field("segment_size", &self.segment_size)
             .finish()
@@ -504,6 +1417,434 @@ where
             data: self.data.clone(),
             lazy: self.lazy.clone(),
             segment_size: self.segment_size.clone(),
+            counters: self.counters.clone(),
         }
     }
 }
+
+/// Creates an empty tree.
+impl<Action> Default for LazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Compares the weights and the elements with pending updates resolved.
+impl<Action> PartialEq for LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: PartialEq>>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self.weights() == other.weights()
+            && self.to_vec() == other.to_vec()
+    }
+}
+
+impl<Action> Eq for LazySegmentTree<Action> where Action: MonoidAction<Set: Monoid<Set: Eq>> {}
+
+/// Hashes the weights and the elements with pending updates resolved, consistently with [`PartialEq`].
+impl<Action> Hash for LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: Hash>>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.weights().hash(state);
+        self.to_vec().hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Action> serde::Serialize for LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: serde::Serialize>>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::WeightedLeaves {
+                values: self.to_vec(),
+                weights: self.weights(),
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Action> serde::Deserialize<'de> for LazySegmentTree<Action>
+where
+    Action: MonoidAction<Set: Monoid<Set: serde::Deserialize<'de>>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let leaves = <crate::serialize::WeightedLeaves<
+            Vec<<<Action as MonoidAction>::Set as Monoid>::Set>,
+            Vec<usize>,
+        > as serde::Deserialize>::deserialize(deserializer)?;
+        match leaves.weights {
+            Some(weights) if weights.len() != leaves.values.len() => Err(
+                serde::de::Error::invalid_length(weights.len(), &"as many weights as values"),
+            ),
+            Some(weights) => Ok(Self::with_weights(leaves.values, weights)),
+            None => Ok(Self::from(leaves.values)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod to_vec {
+    use rand::Rng;
+
+    use crate::{LazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i64; n];
+            let mut lst = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+                lst.range_update(l..r, &update);
+                for e in &mut naive[l..r] {
+                    *e = update.0 * *e + update.1
+                }
+
+                assert_eq!(lst.to_vec(), naive);
+                assert_eq!(lst.clone().into_vec(), naive);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod swap {
+    use rand::Rng;
+
+    use crate::{LazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = Vec::from_iter((0..n as i64).map(|v| v - 20));
+            let mut lst = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(-1..=1), rng.random_range(-5..=5));
+                lst.range_update(l..r, &update);
+                for e in &mut naive[l..r] {
+                    *e = update.0 * *e + update.1
+                }
+
+                let [i, j] = [rng.random_range(0..n), rng.random_range(0..n)];
+                lst.swap(i, j);
+                naive.swap(i, j);
+
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r), naive[l..r].iter().sum::<i64>())
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod apply_range_with {
+    use rand::Rng;
+
+    use crate::{LazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 0..40 {
+            let mut naive = Vec::from_iter((0..n as i64).map(|v| v - 20));
+            let mut lst = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(-1..=1), rng.random_range(-5..=5));
+                lst.range_update(l..r, &update);
+                for e in &mut naive[l..r] {
+                    *e = update.0 * *e + update.1
+                }
+
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let f = |e: &mut i64| *e = if *e % 3 == 0 { -*e / 3 } else { *e + 1 };
+                lst.apply_range_with(l..r, f);
+                naive[l..r].iter_mut().for_each(f);
+
+                assert_eq!(lst.to_vec(), naive);
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r), naive[l..r].iter().sum::<i64>())
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod weights {
+    use std::hash::{BuildHasher, RandomState};
+
+    use rand::Rng;
+
+    use crate::{LazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 0..40 {
+            let weights = Vec::from_iter((0..n).map(|_| rng.random_range(0..5_usize)));
+            let mut naive = Vec::from_iter((0..n).map(|_| rng.random_range(-5..=5_i64)));
+            let mut lst = LazySegmentTree::<AddQueryAffineUpdate<i64>>::with_weights(
+                naive.clone(),
+                weights.clone(),
+            );
+            assert_eq!(lst.weights(), Some(&weights[..]));
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(-1..=1), rng.random_range(-5..=5));
+                lst.range_update(l..r, &update);
+                for (e, &w) in naive[l..r].iter_mut().zip(&weights[l..r]) {
+                    *e = update.0 * *e + update.1 * w as i64
+                }
+
+                assert_eq!(lst.to_vec(), naive);
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r), naive[l..r].iter().sum::<i64>())
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn eq() {
+        let weighted =
+            LazySegmentTree::<AddQueryAffineUpdate<i64>>::with_weights(vec![0; 3], vec![1, 2, 3]);
+        let unweighted = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from(vec![0; 3]);
+        assert_eq!(weighted, weighted.clone());
+        assert_ne!(weighted, unweighted);
+
+        let hasher = RandomState::new();
+        assert_ne!(hasher.hash_one(&weighted), hasher.hash_one(&unweighted));
+    }
+
+    #[test]
+    fn snapshot() {
+        let weighted =
+            LazySegmentTree::<AddQueryAffineUpdate<i64>>::with_weights(vec![0; 3], vec![1, 2, 3]);
+        let err = weighted.write_snapshot(Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[cfg(test)]
+mod flush {
+    use rand::Rng;
+
+    use crate::{LazySegmentTree, Monoid, acts::AddQueryAffineUpdate, ops::Affine};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 0..40 {
+            let mut naive = Vec::from_iter((0..n as i64).map(|v| v - 20));
+            let mut lst = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(-1..=1), rng.random_range(-5..=5));
+                lst.range_update(l..r, &update);
+                for e in &mut naive[l..r] {
+                    *e = update.0 * *e + update.1
+                }
+
+                lst.flush();
+                assert!(lst.lazy.iter().all(|f| *f == Affine::identity()));
+                assert_eq!(lst.data[n..], naive);
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r), naive[l..r].iter().sum::<i64>())
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod recalculate_range {
+    use crate::{LazySegmentTree, acts::AssignAction, ops::Affine};
+
+    #[test]
+    fn bit_lengths_differ() {
+        // `l` and `r - 1` on `data` are 5 and 9, so their paths to the root meet at the dummy node
+        let mut lst = LazySegmentTree::<AssignAction<Affine<i64>>>::from(vec![(1, 0); 5]);
+        for _ in 0..100 {
+            lst.range_update(.., &Some((2, 1)));
+            assert_eq!(lst.range_query(..), (32, 31));
+        }
+    }
+}
+
+#[cfg(test)]
+mod zero_length {
+    use crate::{LazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn identity() {
+        let mut tree = LazySegmentTree::<AddQueryAffineUpdate<i64>>::new(0);
+        assert!(tree.is_empty());
+
+        tree.range_update(.., &(2, 1));
+        assert_eq!(tree.range_query(..), 0);
+        assert_eq!(tree.range_query(0..0), 0);
+        assert_eq!(tree.iter().count(), 0);
+        assert!(tree.to_vec().is_empty());
+
+        tree.assign_from_iter(std::iter::empty());
+        assert!(tree.into_vec().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod batch_update {
+    use rand::Rng;
+
+    use crate::{
+        LazySegmentTree,
+        acts::{AddQueryAddUpdate, AddQueryAffineUpdate},
+    };
+
+    #[test]
+    fn commutative() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i64; n];
+            let mut lst = LazySegmentTree::<AddQueryAddUpdate<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let mut batch = lst.batch_update();
+                for _ in 0..rng.random_range(0..5) {
+                    let l = rng.random_range(0..n);
+                    let r = rng.random_range(l..=n);
+                    let add = rng.random_range(-5..=5);
+                    batch.range_update(l..r, &add);
+                    naive[l..r].iter_mut().for_each(|e| *e += add);
+                }
+                drop(batch);
+
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                assert_eq!(lst.range_query(l..r), naive[l..r].iter().sum::<i64>());
+            }
+        }
+    }
+
+    #[test]
+    fn non_commutative() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = vec![0_i64; n];
+            let mut lst = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let mut batch = lst.batch_update();
+                for _ in 0..rng.random_range(0..5) {
+                    let l = rng.random_range(0..n);
+                    let r = rng.random_range(l..=n);
+                    let update = (rng.random_range(-1..=1), rng.random_range(-5..=5));
+                    batch.range_update(l..r, &update);
+                    naive[l..r]
+                        .iter_mut()
+                        .for_each(|e| *e = update.0 * *e + update.1);
+                }
+                drop(batch);
+
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                assert_eq!(lst.range_query(l..r), naive[l..r].iter().sum::<i64>());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod commutative_update {
+    use crate::{LazySegmentTree, acts::AddQueryAddUpdate};
+
+    #[test]
+    fn pending_update_on_ancestor() {
+        let mut lst = LazySegmentTree::<AddQueryAddUpdate<i64>>::from(vec![0; 8]);
+        lst.range_update(.., &1);
+        lst.point_update(0, &1);
+        assert_eq!(lst.range_query(..), 9);
+
+        lst.range_update(2..6, &1);
+        assert_eq!(lst.range_query(..), 13);
+    }
+}
+
+#[cfg(test)]
+mod eq {
+    use std::hash::{BuildHasher, RandomState};
+
+    use crate::{LazySegmentTree, acts::AddQueryAffineUpdate};
+
+    #[test]
+    fn pending_updates() {
+        let mut lhs = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from_iter(0..10);
+        let mut rhs = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from_iter(0..10);
+        assert_eq!(lhs, rhs);
+
+        // pending at different nodes
+        lhs.range_update(.., &(2, 1));
+        for i in 0..10 {
+            rhs.range_update(i..i + 1, &(2, 1));
+        }
+        assert_eq!(lhs, rhs);
+
+        rhs.point_update(9, &(1, 1));
+        assert_ne!(lhs, rhs);
+        assert_ne!(lhs, LazySegmentTree::from_iter(0..9));
+    }
+
+    #[test]
+    fn hash() {
+        let mut lhs = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from_iter(0..10);
+        let mut rhs = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from_iter(0..10);
+        lhs.range_update(..5, &(3, 0));
+        for i in 0..5 {
+            rhs.point_update(i, &(3, 0));
+        }
+
+        let hasher = RandomState::new();
+        assert_eq!(hasher.hash_one(&lhs), hasher.hash_one(&rhs));
+    }
+}
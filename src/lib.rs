@@ -21,30 +21,76 @@ See more [examples](https://github.com/qdot3/seg_lib/tree/master/examples).
 | [`LazySegmentTree`]        | ✅           | ✅            |                                     |
 | [`DynamicLazySegmentTree`] | ✅           | ✅            | large array                         |
 | [`AssignSegmentTree`]      | ✅           | ✅            | specialized for range assign update |
+| [`VebSegmentTree`]         | ✅           | ❌            | cache-friendly layout for deep tree |
+| [`BufferedSegmentTree`]    | ✅           | ❌            | buffers point updates until a query |
+| [`SegmentTreeNd`]          | ✅           | ❌            | `D`-dimensional box query           |
+| [`DequeSegmentTree`]       | ✅           | ❌            | push and pop at both ends           |
+| [`WideSegmentTree`]        | ✅           | ❌            | `B`-ary nodes for cheap combines    |
+| [`WideLazySegmentTree`]    | ✅           | ✅            | `B`-ary nodes for cheap combines    |
 
 Dynamic dual segment tree will no be implemented because it is useless.
+
+For offline range queries that fit none of the trees, see [Mo's algorithm](crate::mo).
+
+# Features
+
+| feature      | description                                                                                         |
+| ------------ | --------------------------------------------------------------------------------------------------- |
+| `num`        | use `num-traits` for [numeric traits](crate::num) (default)                                         |
+| `u32_ptr`    | use `u32` for node pointers of the dynamic trees, limiting the nodes to 2³²                         |
+| `derive`     | provide [`#[derive(Monoid)]`](derive@Monoid) for structs                                            |
+| `modint`     | provide [`ModInt`](crate::modint::ModInt) for modular arithmetic                                    |
+| `rayon`      | provide parallel construction and batch queries, such as `par_from_vec`                             |
+| `simd`       | use [SIMD kernels](crate::simd) for primitive types (nightly only)                                  |
+| `instrument` | count operations of each tree, such as combines and allocations, via `stats()`                      |
+| `tracing`    | emit [`tracing`](https://docs.rs/tracing) spans for range queries and range updates                 |
+| `serde`      | implement `Serialize` and `Deserialize` for the trees, keeping only the elements and the parameters |
+| `acl`        | provide [adapters](crate::acl) between the algebra traits of `ac-library-rs` and this crate         |
+| `proptest`   | implement `proptest` `Arbitrary` for the trees and the [naive models](crate::naive)                 |
 */
 
 #![warn(missing_docs)]
+#![cfg_attr(feature = "simd", feature(portable_simd, min_specialization))]
 #![allow(clippy::needless_doctest_main)]
 
 mod normal;
 pub use normal::SegmentTree;
 
 mod dynamic;
-pub use dynamic::DynamicSegmentTree;
+pub use dynamic::{DynamicIter, DynamicSegmentTree};
 
 mod dual;
 pub use dual::DualSegmentTree;
 
 mod lazy;
-pub use lazy::LazySegmentTree;
+pub use lazy::{BatchUpdate, LazySegmentTree};
 
 mod dynamic_lazy;
-pub use dynamic_lazy::DynamicLazySegmentTree;
+pub use dynamic_lazy::{DynamicLazySegmentTree, Runs};
 
 mod assign;
-pub use assign::AssignSegmentTree;
+pub use assign::{AssignSegmentTree, GcPolicy};
+
+mod buffered;
+pub use buffered::BufferedSegmentTree;
+
+mod veb;
+pub use veb::VebSegmentTree;
+
+mod nd;
+pub use nd::SegmentTreeNd;
+
+mod deque;
+pub use deque::DequeSegmentTree;
+
+mod wide;
+pub use wide::WideSegmentTree;
+
+mod wide_lazy;
+pub use wide_lazy::WideLazySegmentTree;
+
+mod builder;
+pub use builder::{FromBuilder, SegmentTreeBuilder};
 
 mod beats;
 // pub use beats::SegmentTreeBeats;
@@ -53,7 +99,78 @@ pub mod acts;
 
 pub mod ops;
 
+pub mod prelude;
+
+pub mod num;
+
+pub mod snapshot;
+
+pub mod naive;
+
+pub mod mo;
+
+#[cfg(feature = "proptest")]
+mod arbitrary;
+
+#[cfg(feature = "acl")]
+pub mod acl;
+
+#[cfg(feature = "modint")]
+pub mod modint;
+
 mod traits;
-pub use traits::{Monoid, MonoidAction, QuasiMonoidAction};
+pub use traits::{CommutativeMonoid, Group, Monoid, MonoidAction, QuasiMonoidAction, Semigroup};
+
+/// Derives [`Monoid`](trait@Monoid) for a struct by combining the fields independently.
+///
+/// Every field should be annotated with `#[monoid(M)]`, where `M` is a [`Monoid`](trait@Monoid)
+/// whose set is the type of the field.
+/// This is a named alternative to the [`Monoid`](trait@Monoid) implementations for tuples.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{
+///     Monoid, SegmentTree,
+///     ops::{Add, Max},
+/// };
+///
+/// #[derive(Debug, Clone, PartialEq, Monoid)]
+/// struct SumMax {
+///     #[monoid(Add<i64>)]
+///     sum: i64,
+///     #[monoid(Max<i64>)]
+///     max: Option<i64>,
+/// }
+///
+/// let seg = SegmentTree::<SumMax>::from_iter([3, 1, 4, 1, 5].map(|v| SumMax {
+///     sum: v,
+///     max: Some(v),
+/// }));
+///
+/// assert_eq!(seg.range_query(1..4), SumMax { sum: 6, max: Some(4) });
+/// assert_eq!(seg.range_query(2..2), SumMax::identity());
+/// ```
+#[cfg(feature = "derive")]
+pub use seg_lib_derive::Monoid;
+
+#[cfg(feature = "simd")]
+pub mod simd;
+
+mod render;
+pub use render::Pretty;
+
+mod memory;
+pub use memory::MemoryReport;
+
+mod error;
+pub use error::Error;
+
+#[cfg(feature = "serde")]
+mod serialize;
+
+mod stats;
+#[cfg(feature = "instrument")]
+pub use stats::Stats;
 
 pub(crate) mod utility;
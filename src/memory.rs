@@ -0,0 +1,88 @@
+/// Heap memory used by a tree in bytes, returned by `memory_usage()`.
+///
+/// Only the buffers owned by the tree itself are measured, including their spare capacity.
+/// Memory owned by the elements, such as the contents of [`String`]s, is not included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MemoryReport {
+    /// The nodes of the array-based trees.
+    pub data: usize,
+    /// The pending updates of [`LazySegmentTree`](crate::LazySegmentTree)
+    /// and the pointers to them of [`AssignSegmentTree`](crate::AssignSegmentTree).
+    pub lazy: usize,
+    /// The sizes of the segments, which are calculated only if
+    /// [`MonoidAction::USE_SEGMENT_SIZE`](crate::MonoidAction::USE_SEGMENT_SIZE) is `true`.
+    pub segment_size: usize,
+    /// The nodes of the dynamic trees, including removed ones to be reused.
+    pub arena: usize,
+    /// The table of assigned values of [`AssignSegmentTree`](crate::AssignSegmentTree).
+    pub map: usize,
+    /// Working buffers, such as reusable stacks, free lists and pending indices.
+    pub buffers: usize,
+}
+
+impl MemoryReport {
+    /// Returns the sum of all components.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<u64>>::new(100);
+    /// let report = st.memory_usage();
+    /// assert_eq!(report.total(), report.data);
+    /// assert_eq!(report.data, 200 * size_of::<u64>());
+    /// ```
+    pub const fn total(&self) -> usize {
+        self.data + self.lazy + self.segment_size + self.arena + self.map + self.buffers
+    }
+}
+
+/// Returns the bytes allocated by `vec`, including its spare capacity.
+#[inline]
+pub(crate) const fn vec_bytes<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * size_of::<T>()
+}
+
+#[cfg(test)]
+mod memory_report {
+    use crate::{
+        AssignSegmentTree, DynamicSegmentTree, LazySegmentTree, SegmentTree,
+        acts::{AddQueryAddUpdate, MaxQueryAddUpdate},
+        ops::Add,
+    };
+
+    #[test]
+    fn array_trees() {
+        for n in [0, 1, 100, 1 << 10] {
+            let st = SegmentTree::<Add<u64>>::new(n);
+            let report = st.memory_usage();
+            assert_eq!(report.data, 2 * n * size_of::<u64>());
+            assert_eq!(report.total(), report.data);
+
+            let lst = LazySegmentTree::<AddQueryAddUpdate<u64>>::new(n);
+            let report = lst.memory_usage();
+            assert_eq!(report.lazy, n * size_of::<u64>());
+            assert_eq!(report.segment_size, n * size_of::<usize>());
+
+            // segment sizes are not needed
+            let lst = LazySegmentTree::<MaxQueryAddUpdate<u64>>::new(n);
+            assert_eq!(lst.memory_usage().segment_size, 0);
+        }
+    }
+
+    #[test]
+    fn growing_trees() {
+        let mut ast = AssignSegmentTree::<Add<u64>>::new(1 << 10);
+        let mut dst = DynamicSegmentTree::<Add<u64>>::new(0..1 << 20).unwrap();
+        let mut prev = [ast.memory_usage().map, dst.memory_usage().arena];
+        for i in 0..100 {
+            ast.range_assign(i..i + 10, i as u64);
+            dst.point_update(i as isize, 1);
+            let next = [ast.memory_usage().map, dst.memory_usage().arena];
+            assert!(prev[0] <= next[0] && prev[1] <= next[1]);
+            prev = next
+        }
+        assert!(prev[0] > 0 && prev[1] > 0);
+    }
+}
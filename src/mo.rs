@@ -0,0 +1,429 @@
+//! Offline range queries by Mo's algorithm.
+//!
+//! [`Mo`] sorts *Q* ranges over an array of *N* elements so that moving the two ends of a window
+//! from one range to the next takes *O*(*N* √*Q*) steps in total.
+//! At each step it calls back a user-defined [`MoState`], which adds or removes one element,
+//! and records [the answer](MoState::answer) whenever the window matches a range.
+//!
+//! This fits problems where no segment tree works, such as counting distinct values in a range,
+//! as long as all the queries are known in advance.
+//! For [groups](Group), [`Mo::fold`] combines the ranges without a hand-written state.
+//!
+//! # Example
+//!
+//! ```
+//! use seg_lib::mo::{Mo, MoState};
+//!
+//! /// Counts distinct values in a window.
+//! struct Distinct<'a> {
+//!     values: &'a [usize],
+//!     count: Vec<usize>,
+//!     distinct: usize,
+//! }
+//!
+//! impl MoState for Distinct<'_> {
+//!     type Answer = usize;
+//!
+//!     fn add(&mut self, index: usize) {
+//!         let c = &mut self.count[self.values[index]];
+//!         *c += 1;
+//!         if *c == 1 {
+//!             self.distinct += 1
+//!         }
+//!     }
+//!
+//!     fn remove(&mut self, index: usize) {
+//!         let c = &mut self.count[self.values[index]];
+//!         *c -= 1;
+//!         if *c == 0 {
+//!             self.distinct -= 1
+//!         }
+//!     }
+//!
+//!     fn answer(&self) -> usize {
+//!         self.distinct
+//!     }
+//! }
+//!
+//! let values = [1, 2, 1, 3, 2, 2, 4];
+//! let mo = Mo::new(values.len(), [0..3, 2..7, 1..1, 0..7]);
+//! let mut state = Distinct { values: &values, count: vec![0; 5], distinct: 0 };
+//! assert_eq!(mo.solve(&mut state), vec![2, 4, 0, 4]);
+//! ```
+
+use std::{fmt::Debug, ops::Range, ops::RangeBounds};
+
+use crate::{
+    traits::{Group, Monoid},
+    utility::convert_range,
+};
+
+/// A state over a window `l..r`, which [`Mo`] moves one element at a time.
+///
+/// Only [`add`](MoState::add) and [`remove`](MoState::remove) are required.
+/// Override the sided methods if the state depends on the order of the elements.
+pub trait MoState {
+    /// The answer for a window.
+    type Answer;
+
+    /// Adds the element at `index` to the window.
+    fn add(&mut self, index: usize);
+
+    /// Removes the element at `index` from the window.
+    fn remove(&mut self, index: usize);
+
+    /// Returns the answer for the current window.
+    fn answer(&self) -> Self::Answer;
+
+    /// Extends the window `index + 1..r` to `index..r`.
+    fn add_left(&mut self, index: usize) {
+        self.add(index)
+    }
+
+    /// Extends the window `l..index` to `l..index + 1`.
+    fn add_right(&mut self, index: usize) {
+        self.add(index)
+    }
+
+    /// Shrinks the window `index..r` to `index + 1..r`.
+    fn remove_left(&mut self, index: usize) {
+        self.remove(index)
+    }
+
+    /// Shrinks the window `l..index + 1` to `l..index`.
+    fn remove_right(&mut self, index: usize) {
+        self.remove(index)
+    }
+}
+
+/// An offline processor that answers range queries in Mo's order.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{mo::Mo, ops::Add};
+///
+/// let values: Vec<i64> = (1..=10).collect();
+/// let mo = Mo::hilbert(values.len(), [2..5, 0..10, 7..8]);
+/// assert_eq!(mo.fold::<Add<i64>>(&values), vec![3 + 4 + 5, 55, 8]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mo {
+    len: usize,
+    ranges: Vec<Range<usize>>,
+    /// indices of `ranges` in the processing order
+    order: Vec<usize>,
+}
+
+impl Mo {
+    /// Creates a new instance for the `ranges` over an array of `len` elements,
+    /// which are sorted by blocks of the left ends, alternating the direction of the right ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range is explicitly out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q* log *Q*)
+    pub fn new<I, R>(len: usize, ranges: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: RangeBounds<usize> + Debug,
+    {
+        let ranges = Self::convert_ranges(len, ranges);
+        let block = (len / (ranges.len() as f64).sqrt().ceil().max(1.0) as usize).max(1);
+
+        let mut order = Vec::from_iter(0..ranges.len());
+        order.sort_unstable_by_key(|&i| {
+            let Range { start, end } = ranges[i];
+            let b = start / block;
+            (b, if b & 1 == 0 { end } else { !end })
+        });
+
+        Self { len, ranges, order }
+    }
+
+    /// Creates a new instance for the `ranges` over an array of `len` elements,
+    /// which are sorted along the Hilbert curve through the points (*l*, *r*).
+    ///
+    /// This order often moves the window less than [`Mo::new`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range is explicitly out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q* log *N* + *Q* log *Q*)
+    pub fn hilbert<I, R>(len: usize, ranges: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: RangeBounds<usize> + Debug,
+    {
+        let ranges = Self::convert_ranges(len, ranges);
+        let log = (len + 1).next_power_of_two().trailing_zeros();
+
+        let mut order = Vec::from_iter(0..ranges.len());
+        order.sort_by_cached_key(|&i| hilbert_index(ranges[i].start, ranges[i].end, log));
+
+        Self { len, ranges, order }
+    }
+
+    fn convert_ranges<I, R>(len: usize, ranges: I) -> Vec<Range<usize>>
+    where
+        I: IntoIterator<Item = R>,
+        R: RangeBounds<usize> + Debug,
+    {
+        ranges
+            .into_iter()
+            .map(|range| convert_range(range, 0..len))
+            .collect()
+    }
+
+    /// Returns the number of elements of the underlying array.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the underlying array is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the ranges in the given order.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    /// Returns the indices of the ranges in the processing order.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Moves the window of `state` through the ranges, starting from the empty window `0..0`,
+    /// and returns the answers in the given order of the ranges.
+    ///
+    /// The window never becomes *inverted*: it is extended before it is shrunk.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N* √*Q* + *Q*) calls of the methods of [`MoState`]
+    pub fn solve<S>(&self, state: &mut S) -> Vec<S::Answer>
+    where
+        S: MoState,
+    {
+        let mut answers = Vec::from_iter(std::iter::repeat_with(|| None).take(self.ranges.len()));
+        let [mut l, mut r] = [0, 0];
+        for &i in &self.order {
+            let Range { start, end } = self.ranges[i];
+            while start < l {
+                l -= 1;
+                state.add_left(l)
+            }
+            while r < end {
+                state.add_right(r);
+                r += 1
+            }
+            while l < start {
+                state.remove_left(l);
+                l += 1
+            }
+            while end < r {
+                r -= 1;
+                state.remove_right(r)
+            }
+            answers[i] = Some(state.answer())
+        }
+
+        answers.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Combines the `values` in each range and returns the results in the given order of the ranges.
+    ///
+    /// The group need not be commutative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `values` differs from [`Mo::len`].
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N* √*Q* + *Q*) combines
+    pub fn fold<G>(&self, values: &[<G as Monoid>::Set]) -> Vec<<G as Monoid>::Set>
+    where
+        G: Group<Set: Clone>,
+    {
+        assert_eq!(
+            values.len(),
+            self.len,
+            "the number of values should be equal to the length"
+        );
+
+        self.solve(&mut GroupFold::<G> {
+            values,
+            acc: <G as Monoid>::identity(),
+        })
+    }
+}
+
+/// Returns the position of (`x`, `y`) along the Hilbert curve filling the 2<sup>`log`</sup> square.
+fn hilbert_index(x: usize, y: usize, log: u32) -> u128 {
+    let [mut x, mut y] = [x as u128, y as u128];
+    let n = 1_u128 << log;
+    let mut d = 0;
+    let mut s = n >> 1;
+    while s > 0 {
+        let rx = (x & s != 0) as u128;
+        let ry = (y & s != 0) as u128;
+        d += s * s * ((3 * rx) ^ ry);
+        // rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    d
+}
+
+/// Maintains the product of the window.
+struct GroupFold<'a, G>
+where
+    G: Group,
+{
+    values: &'a [<G as Monoid>::Set],
+    acc: <G as Monoid>::Set,
+}
+
+impl<G> MoState for GroupFold<'_, G>
+where
+    G: Group<Set: Clone>,
+{
+    type Answer = <G as Monoid>::Set;
+
+    fn add(&mut self, index: usize) {
+        self.add_right(index)
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.remove_right(index)
+    }
+
+    fn answer(&self) -> Self::Answer {
+        self.acc.clone()
+    }
+
+    fn add_left(&mut self, index: usize) {
+        self.acc = <G as Monoid>::combine(&self.values[index], &self.acc)
+    }
+
+    fn add_right(&mut self, index: usize) {
+        <G as Monoid>::combine_assign(&mut self.acc, &self.values[index])
+    }
+
+    fn remove_left(&mut self, index: usize) {
+        self.acc = <G as Monoid>::combine(&<G as Group>::inverse(&self.values[index]), &self.acc)
+    }
+
+    fn remove_right(&mut self, index: usize) {
+        <G as Monoid>::combine_assign(&mut self.acc, &<G as Group>::inverse(&self.values[index]))
+    }
+}
+
+#[cfg(test)]
+mod random {
+    use std::collections::VecDeque;
+
+    use rand::Rng;
+
+    use super::{Mo, MoState};
+    use crate::ops::Add;
+
+    /// Keeps the window itself to check the sides.
+    struct Window(VecDeque<usize>);
+
+    impl MoState for Window {
+        type Answer = Vec<usize>;
+
+        fn add(&mut self, _: usize) {
+            unreachable!()
+        }
+
+        fn remove(&mut self, _: usize) {
+            unreachable!()
+        }
+
+        fn answer(&self) -> Self::Answer {
+            Vec::from_iter(self.0.iter().copied())
+        }
+
+        fn add_left(&mut self, index: usize) {
+            self.0.push_front(index)
+        }
+
+        fn add_right(&mut self, index: usize) {
+            self.0.push_back(index)
+        }
+
+        fn remove_left(&mut self, index: usize) {
+            assert_eq!(self.0.pop_front(), Some(index))
+        }
+
+        fn remove_right(&mut self, index: usize) {
+            assert_eq!(self.0.pop_back(), Some(index))
+        }
+    }
+
+    fn random_ranges(n: usize, q: usize) -> Vec<std::ops::Range<usize>> {
+        let mut rng = rand::rng();
+        Vec::from_iter((0..q).map(|_| {
+            let [l, r] = [rng.random_range(0..=n), rng.random_range(0..=n)];
+            l.min(r)..l.max(r)
+        }))
+    }
+
+    #[test]
+    fn window() {
+        for (n, q) in [(0, 3), (1, 5), (10, 30), (100, 50), (37, 200)] {
+            let ranges = random_ranges(n, q);
+            let expected = Vec::from_iter(ranges.iter().map(|r| Vec::from_iter(r.clone())));
+            for mo in [Mo::new(n, ranges.clone()), Mo::hilbert(n, ranges.clone())] {
+                assert_eq!(mo.solve(&mut Window(VecDeque::new())), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn fold() {
+        let mut rng = rand::rng();
+        for (n, q) in [(0, 3), (1, 5), (10, 30), (100, 50), (37, 200)] {
+            let values = Vec::from_iter((0..n).map(|_| rng.random_range(-100..100)));
+            let ranges = random_ranges(n, q);
+            let expected =
+                Vec::from_iter(ranges.iter().map(|r| values[r.clone()].iter().sum::<i64>()));
+            for mo in [Mo::new(n, ranges.clone()), Mo::hilbert(n, ranges.clone())] {
+                assert_eq!(mo.fold::<Add<i64>>(&values), expected);
+            }
+        }
+    }
+}
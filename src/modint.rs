@@ -0,0 +1,217 @@
+/*!
+Modular integers, available with the `modint` feature.
+*/
+
+use std::{
+    fmt::{Debug, Display},
+    iter::{Product, Sum},
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use crate::num::{FromPrimitive, One, Zero};
+
+use crate::utility::{mod_add, mod_mul};
+
+/// An integer modulo `M`.
+///
+/// It implements the arithmetic traits required by the predefined [`ops`](crate::ops) and [`acts`](crate::acts),
+/// so that the identity elements and the segment size are reduced modulo `M` automatically.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{LazySegmentTree, acts::AddQueryAffineUpdate, modint::ModInt};
+///
+/// type Mint = ModInt<998_244_353>;
+///
+/// let mut lst = LazySegmentTree::<AddQueryAffineUpdate<Mint>>::from_iter((1..=3).map(Mint::new));
+/// lst.range_update(1.., &(Mint::new(2), -Mint::new(1)));
+///
+/// assert_eq!(lst.range_query(..), Mint::new(1 + 3 + 5));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ModInt<const M: u64>(u64);
+
+impl<const M: u64> ModInt<M> {
+    /// The modulus.
+    pub const MODULUS: u64 = M;
+
+    /// Creates a new instance from `value mod M`.
+    pub const fn new(value: u64) -> Self {
+        Self(value % M)
+    }
+
+    /// Returns the representative in `0..M`.
+    pub const fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Returns `self` to the power of `exp`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log `exp`)
+    pub const fn pow(self, mut exp: u64) -> Self {
+        let mut res = 1 % M;
+        let mut base = self.0;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = mod_mul::<M>(res, base)
+            }
+            base = mod_mul::<M>(base, base);
+            exp >>= 1
+        }
+        Self(res)
+    }
+}
+
+impl<const M: u64> Debug for ModInt<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl<const M: u64> Display for ModInt<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<const M: u64> From<ModInt<M>> for u64 {
+    fn from(value: ModInt<M>) -> Self {
+        value.0
+    }
+}
+
+macro_rules! impl_binary_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $f:expr) => {
+        impl<const M: u64> $trait for ModInt<M> {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                Self($f(self.0, rhs.0))
+            }
+        }
+
+        impl<const M: u64> $trait for &ModInt<M> {
+            type Output = ModInt<M>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                ModInt($f(self.0, rhs.0))
+            }
+        }
+
+        impl<const M: u64> $assign_trait for ModInt<M> {
+            fn $assign_method(&mut self, rhs: Self) {
+                self.0 = $f(self.0, rhs.0)
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, AddAssign, add_assign, mod_add::<M>);
+impl_binary_op!(Sub, sub, SubAssign, sub_assign, |lhs, rhs| mod_add::<M>(
+    lhs,
+    M - rhs
+));
+impl_binary_op!(Mul, mul, MulAssign, mul_assign, mod_mul::<M>);
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self((M - self.0) % M)
+    }
+}
+
+impl<const M: u64> Neg for &ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn neg(self) -> Self::Output {
+        -*self
+    }
+}
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::one(), Mul::mul)
+    }
+}
+
+impl<const M: u64> Zero for ModInt<M> {
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl<const M: u64> One for ModInt<M> {
+    fn one() -> Self {
+        Self(1 % M)
+    }
+}
+
+impl<const M: u64> FromPrimitive for ModInt<M> {
+    fn from_i64(n: i64) -> Option<Self> {
+        let value = Self::new(n.unsigned_abs());
+        Some(if n < 0 { -value } else { value })
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(Self::new(n))
+    }
+}
+
+#[cfg(test)]
+mod mod_int {
+    use rand::Rng;
+
+    use crate::{
+        LazySegmentTree,
+        acts::{AddQueryAffineUpdate, ModAffineSum},
+        modint::ModInt,
+    };
+
+    const MOD: u64 = 998_244_353;
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let values = Vec::from_iter((0..n).map(|_| rng.random_range(0..MOD)));
+            let mut expected = LazySegmentTree::<ModAffineSum<MOD>>::from(values.clone());
+            let mut lst = LazySegmentTree::<AddQueryAffineUpdate<ModInt<MOD>>>::from_iter(
+                values.into_iter().map(ModInt::new),
+            );
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(0..MOD), rng.random_range(0..MOD));
+                expected.range_update(l..r, &update);
+                lst.range_update(l..r, &(ModInt::new(update.0), ModInt::new(update.1)));
+
+                for l in 0..n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r).value(), expected.range_query(l..r))
+                    }
+                }
+            }
+        }
+    }
+}
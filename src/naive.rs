@@ -0,0 +1,809 @@
+//! Straightforward reference implementations of the trees for differential testing.
+//!
+//! Each model stores the elements in a plain [`Vec`] and performs every operation element by element,
+//! so a query or an update over *K* elements takes *O*(*K*) time.
+//! They are easy to trust, which makes them suitable as an oracle for custom [monoids](Monoid)
+//! and [actions](MonoidAction):
+//! apply the same random operations to a tree and to its model, and compare the results.
+//!
+//! [`check_lazy`], [`check_dual`] and [`check_assign`] do this for a sequence of [`Op`]s,
+//! and report the first [`Divergence`] with a minimized reproduction.
+//!
+//! # Example
+//!
+//! ```
+//! use seg_lib::{LazySegmentTree, acts::AddQueryAffineUpdate, naive::NaiveLazySegmentTree};
+//!
+//! let mut tree = LazySegmentTree::<AddQueryAffineUpdate<i64>>::from_iter(0..10);
+//! let mut model = NaiveLazySegmentTree::<AddQueryAffineUpdate<i64>>::from_iter(0..10);
+//! for (l, r, mapping) in [(2, 8, (2, 1)), (0, 5, (1, -3)), (4, 10, (-1, 0))] {
+//!     tree.range_update(l..r, &mapping);
+//!     model.range_update(l..r, &mapping);
+//!
+//!     for l in 0..=10 {
+//!         for r in l..=10 {
+//!             assert_eq!(tree.range_query(l..r), model.range_query(l..r));
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::{
+    fmt::{Debug, Display},
+    ops::{Range, RangeBounds},
+};
+
+use crate::{
+    AssignSegmentTree, DualSegmentTree, LazySegmentTree,
+    traits::{Monoid, MonoidAction},
+    utility::convert_range,
+};
+
+/// The reference model of [`SegmentTree`](crate::SegmentTree)
+/// and [`AssignSegmentTree`](crate::AssignSegmentTree).
+pub struct NaiveSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    values: Vec<<Query as Monoid>::Set>,
+}
+
+impl<Query> NaiveSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    #[doc = include_str!("../doc/new.md")]
+    pub fn new(n: usize) -> Self {
+        Self::from_iter(std::iter::repeat_with(<Query as Monoid>::identity).take(n))
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the model has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the elements as a slice.
+    pub fn as_slice(&self) -> &[<Query as Monoid>::Set] {
+        &self.values
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    pub fn point_update(&mut self, i: usize, element: <Query as Monoid>::Set) {
+        self.values[i] = element
+    }
+
+    #[doc = include_str!("../doc/point_query.md")]
+    pub fn point_query(&self, i: usize) -> &<Query as Monoid>::Set {
+        &self.values[i]
+    }
+
+    #[doc = include_str!("../doc/range_query.md")]
+    pub fn range_query<R>(&self, range: R) -> <Query as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len());
+        self.values[range]
+            .iter()
+            .fold(<Query as Monoid>::identity(), |acc, element| {
+                <Query as Monoid>::combine(&acc, element)
+            })
+    }
+
+    /// The reference of [`SegmentTree::partition_end`](crate::SegmentTree::partition_end).
+    pub fn partition_end<P>(&self, start: usize, pred: P) -> usize
+    where
+        P: Fn(&<Query as Monoid>::Set) -> bool,
+    {
+        assert!(start <= self.len());
+
+        let mut combined = <Query as Monoid>::identity();
+        for (end, element) in self.values.iter().enumerate().skip(start) {
+            combined = <Query as Monoid>::combine(&combined, element);
+            if !pred(&combined) {
+                return end;
+            }
+        }
+        self.len()
+    }
+
+    /// The reference of [`SegmentTree::partition_start`](crate::SegmentTree::partition_start).
+    pub fn partition_start<P>(&self, end: usize, pred: P) -> usize
+    where
+        P: Fn(&<Query as Monoid>::Set) -> bool,
+    {
+        assert!(end <= self.len());
+
+        let mut combined = <Query as Monoid>::identity();
+        for (start, element) in self.values[..end].iter().enumerate().rev() {
+            combined = <Query as Monoid>::combine(element, &combined);
+            if !pred(&combined) {
+                return start + 1;
+            }
+        }
+        0
+    }
+}
+
+impl<Query> NaiveSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    /// The reference of [`AssignSegmentTree::range_assign`](crate::AssignSegmentTree::range_assign).
+    pub fn range_assign<R>(&mut self, range: R, element: <Query as Monoid>::Set)
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len());
+        self.values[range].fill(element)
+    }
+}
+
+/// The reference model of [`LazySegmentTree`](crate::LazySegmentTree).
+///
+/// Every update acts on each element with the segment size `1`.
+pub struct NaiveLazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    values: Vec<<<Action as MonoidAction>::Set as Monoid>::Set>,
+}
+
+impl<Action> NaiveLazySegmentTree<Action>
+where
+    Action: MonoidAction,
+{
+    #[doc = include_str!("../doc/new.md")]
+    pub fn new(n: usize) -> Self {
+        Self::from_iter(
+            std::iter::repeat_with(<<Action as MonoidAction>::Set as Monoid>::identity).take(n),
+        )
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the model has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the elements as a slice.
+    pub fn as_slice(&self) -> &[<<Action as MonoidAction>::Set as Monoid>::Set] {
+        &self.values
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    pub fn point_update(
+        &mut self,
+        i: usize,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) {
+        <Action as MonoidAction>::act_assign(update, &mut self.values[i], Some(1))
+    }
+
+    #[doc = include_str!("../doc/point_query.md")]
+    pub fn point_query(&self, i: usize) -> &<<Action as MonoidAction>::Set as Monoid>::Set {
+        &self.values[i]
+    }
+
+    #[doc = include_str!("../doc/range_query.md")]
+    pub fn range_query<R>(&self, range: R) -> <<Action as MonoidAction>::Set as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len());
+        self.values[range].iter().fold(
+            <<Action as MonoidAction>::Set as Monoid>::identity(),
+            |acc, element| <<Action as MonoidAction>::Set as Monoid>::combine(&acc, element),
+        )
+    }
+
+    #[doc = include_str!("../doc/range_update.md")]
+    pub fn range_update<R>(
+        &mut self,
+        range: R,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len());
+        for element in &mut self.values[range] {
+            <Action as MonoidAction>::act_assign(update, element, Some(1))
+        }
+    }
+}
+
+/// The reference model of [`DualSegmentTree`](crate::DualSegmentTree).
+pub struct NaiveDualSegmentTree<Update>
+where
+    Update: Monoid,
+{
+    values: Vec<<Update as Monoid>::Set>,
+}
+
+impl<Update> NaiveDualSegmentTree<Update>
+where
+    Update: Monoid,
+{
+    #[doc = include_str!("../doc/new.md")]
+    pub fn new(n: usize) -> Self {
+        Self::from_iter(std::iter::repeat_with(<Update as Monoid>::identity).take(n))
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the model has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the elements as a slice.
+    pub fn as_slice(&self) -> &[<Update as Monoid>::Set] {
+        &self.values
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    pub fn point_update(&mut self, i: usize, update: &<Update as Monoid>::Set) {
+        <Update as Monoid>::combine_assign(&mut self.values[i], update)
+    }
+
+    #[doc = include_str!("../doc/point_query.md")]
+    pub fn point_query(&self, i: usize) -> &<Update as Monoid>::Set {
+        &self.values[i]
+    }
+
+    #[doc = include_str!("../doc/range_update.md")]
+    pub fn range_update<R>(&mut self, range: R, update: &<Update as Monoid>::Set)
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len());
+        for element in &mut self.values[range] {
+            <Update as Monoid>::combine_assign(element, update)
+        }
+    }
+}
+
+macro_rules! naive_impl {
+    ($model:ident<$p:ident: $bound:path>, $set:ty) => {
+        impl<$p> From<Vec<$set>> for $model<$p>
+        where
+            $p: $bound,
+        {
+            fn from(values: Vec<$set>) -> Self {
+                Self { values }
+            }
+        }
+
+        impl<$p> Default for $model<$p>
+        where
+            $p: $bound,
+        {
+            fn default() -> Self {
+                Self::from(Vec::new())
+            }
+        }
+
+        impl<$p> FromIterator<$set> for $model<$p>
+        where
+            $p: $bound,
+        {
+            fn from_iter<I: IntoIterator<Item = $set>>(iter: I) -> Self {
+                Self::from(Vec::from_iter(iter))
+            }
+        }
+
+        impl<$p> From<$model<$p>> for Vec<$set>
+        where
+            $p: $bound,
+        {
+            fn from(model: $model<$p>) -> Self {
+                model.values
+            }
+        }
+
+        impl<$p> Debug for $model<$p>
+        where
+            $p: $bound,
+            $set: Debug,
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($model))
+                    .field("values", &self.values)
+                    .finish()
+            }
+        }
+
+        impl<$p> PartialEq for $model<$p>
+        where
+            $p: $bound,
+            $set: PartialEq,
+        {
+            fn eq(&self, other: &Self) -> bool {
+                self.values == other.values
+            }
+        }
+
+        impl<$p> Eq for $model<$p>
+        where
+            $p: $bound,
+            $set: Eq,
+        {
+        }
+
+        impl<$p> std::hash::Hash for $model<$p>
+        where
+            $p: $bound,
+            $set: std::hash::Hash,
+        {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.values.hash(state)
+            }
+        }
+
+        impl<$p> Clone for $model<$p>
+        where
+            $p: $bound,
+            $set: Clone,
+        {
+            fn clone(&self) -> Self {
+                Self {
+                    values: self.values.clone(),
+                }
+            }
+        }
+    };
+}
+
+naive_impl!(NaiveSegmentTree<Query: Monoid>, <Query as Monoid>::Set);
+naive_impl!(
+    NaiveLazySegmentTree<Action: MonoidAction>,
+    <<Action as MonoidAction>::Set as Monoid>::Set
+);
+naive_impl!(NaiveDualSegmentTree<Update: Monoid>, <Update as Monoid>::Set);
+
+/// An operation applied to both a tree and its model by [`check_lazy`], [`check_dual`] and [`check_assign`].
+///
+/// `U` is the type of the updates: maps for [`LazySegmentTree`] and [`DualSegmentTree`],
+/// and elements for [`AssignSegmentTree`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Op<U> {
+    /// Updates the `i`-th element.
+    PointUpdate(usize, U),
+    /// Updates the elements in the range.
+    RangeUpdate(Range<usize>, U),
+    /// Compares the `i`-th element.
+    PointQuery(usize),
+    /// Compares the result of the range query.
+    /// For [`DualSegmentTree`], compares each element in the range instead.
+    RangeQuery(Range<usize>),
+}
+
+/// The first divergence between a tree and its model, found by [`check_lazy`], [`check_dual`] or [`check_assign`].
+///
+/// The reproduction is minimized:
+/// no single operation can be removed from [`ops`](Self::ops) without hiding the divergence.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Divergence<T, U> {
+    /// The initial elements.
+    pub values: Vec<T>,
+    /// The operations to reproduce the divergence. The last one is the diverging query.
+    pub ops: Vec<Op<U>>,
+    /// The result of the model.
+    pub expected: T,
+    /// The result of the tree.
+    pub actual: T,
+}
+
+impl<T, U> Display for Divergence<T, U>
+where
+    T: Debug,
+    U: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "the tree diverged from the model: expected {:?}, found {:?}",
+            self.expected, self.actual
+        )?;
+        writeln!(f, "values: {:?}", self.values)?;
+        write!(f, "ops: {:?}", self.ops)
+    }
+}
+
+impl<T, U> std::error::Error for Divergence<T, U>
+where
+    T: Debug,
+    U: Debug,
+{
+}
+
+/// Runs `ops` and returns the index of the first diverging operation with the expected and actual results.
+type Run<'a, T, U> = &'a dyn Fn(&[T], &[Op<U>]) -> Option<(usize, T, T)>;
+
+/// The [`Divergence`] found by [`check_lazy`].
+type LazyDivergence<Action> = Divergence<
+    <<Action as MonoidAction>::Set as Monoid>::Set,
+    <<Action as MonoidAction>::Map as Monoid>::Set,
+>;
+
+/// Finds the first divergence and removes operations greedily while the divergence remains.
+fn check<T, U>(values: Vec<T>, ops: &[Op<U>], run: Run<'_, T, U>) -> Result<(), Divergence<T, U>>
+where
+    U: Clone,
+{
+    let Some((k, mut expected, mut actual)) = run(&values, ops) else {
+        return Ok(());
+    };
+    let mut ops = ops[..=k].to_vec();
+
+    let mut removed = true;
+    while removed {
+        removed = false;
+        let mut i = 0;
+        while i < ops.len() {
+            let mut candidate = ops.clone();
+            candidate.remove(i);
+            if let Some((k, e, a)) = run(&values, &candidate) {
+                candidate.truncate(k + 1);
+                (ops, expected, actual) = (candidate, e, a);
+                removed = true
+            } else {
+                i += 1
+            }
+        }
+    }
+
+    Err(Divergence {
+        values,
+        ops,
+        expected,
+        actual,
+    })
+}
+
+/// Applies `ops` to a [`LazySegmentTree`] and a [`NaiveLazySegmentTree`] built from `values`,
+/// and compares the results of the queries.
+///
+/// Useful for catching propagation-order bugs in user-defined non-commutative actions.
+///
+/// # Panics
+///
+/// Panics if an operation is out of bounds.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{
+///     acts::AddQueryAffineUpdate,
+///     naive::{Op, check_lazy},
+/// };
+///
+/// let ops = [
+///     Op::RangeUpdate(1..4, (2, 1)),
+///     Op::PointUpdate(2, (-1, 3)),
+///     Op::RangeQuery(0..3),
+///     Op::PointQuery(3),
+/// ];
+/// assert!(check_lazy::<AddQueryAffineUpdate<i64>>(vec![1, 2, 3, 4, 5], &ops).is_ok());
+/// ```
+pub fn check_lazy<Action>(
+    values: Vec<<<Action as MonoidAction>::Set as Monoid>::Set>,
+    ops: &[Op<<<Action as MonoidAction>::Map as Monoid>::Set>],
+) -> Result<(), LazyDivergence<Action>>
+where
+    Action: MonoidAction<Set: Monoid<Set: Clone + PartialEq>, Map: Monoid<Set: Clone>>,
+{
+    check(values, ops, &|values, ops| {
+        let mut tree = LazySegmentTree::<Action>::from(values.to_vec());
+        let mut model = NaiveLazySegmentTree::<Action>::from(values.to_vec());
+        for (k, op) in ops.iter().enumerate() {
+            let (expected, actual) = match op {
+                Op::PointUpdate(i, update) => {
+                    tree.point_update(*i, update);
+                    model.point_update(*i, update);
+                    continue;
+                }
+                Op::RangeUpdate(range, update) => {
+                    tree.range_update(range.clone(), update);
+                    model.range_update(range.clone(), update);
+                    continue;
+                }
+                Op::PointQuery(i) => (model.point_query(*i).clone(), tree.point_query(*i).clone()),
+                Op::RangeQuery(range) => (
+                    model.range_query(range.clone()),
+                    tree.range_query(range.clone()),
+                ),
+            };
+            if expected != actual {
+                return Some((k, expected, actual));
+            }
+        }
+        None
+    })
+}
+
+/// Applies `ops` to a [`DualSegmentTree`] and a [`NaiveDualSegmentTree`] built from `values`,
+/// and compares the elements.
+///
+/// # Panics
+///
+/// Panics if an operation is out of bounds.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{
+///     naive::{Op, check_dual},
+///     ops::Affine,
+/// };
+///
+/// let ops = [Op::RangeUpdate(0..2, (2, 1)), Op::PointUpdate(1, (3, 0)), Op::RangeQuery(0..3)];
+/// assert!(check_dual::<Affine<i64>>(vec![(1, 0); 3], &ops).is_ok());
+/// ```
+pub fn check_dual<Update>(
+    values: Vec<<Update as Monoid>::Set>,
+    ops: &[Op<<Update as Monoid>::Set>],
+) -> Result<(), Divergence<<Update as Monoid>::Set, <Update as Monoid>::Set>>
+where
+    Update: Monoid<Set: Clone + PartialEq>,
+{
+    check(values, ops, &|values, ops| {
+        let mut tree = DualSegmentTree::<Update>::from(values.to_vec());
+        let mut model = NaiveDualSegmentTree::<Update>::from(values.to_vec());
+        for (k, op) in ops.iter().enumerate() {
+            let range = match op {
+                Op::PointUpdate(i, update) => {
+                    tree.point_update(*i, update);
+                    model.point_update(*i, update);
+                    continue;
+                }
+                Op::RangeUpdate(range, update) => {
+                    tree.range_update(range.clone(), update);
+                    model.range_update(range.clone(), update);
+                    continue;
+                }
+                Op::PointQuery(i) => *i..*i + 1,
+                Op::RangeQuery(range) => range.clone(),
+            };
+            for i in range {
+                let (expected, actual) = (model.point_query(i).clone(), tree.point_query(i));
+                if expected != actual {
+                    return Some((k, expected, actual));
+                }
+            }
+        }
+        None
+    })
+}
+
+/// Applies `ops` to an [`AssignSegmentTree`] and a [`NaiveSegmentTree`] built from `values`,
+/// and compares the results of the queries.
+///
+/// The updates assign the element, as [`AssignSegmentTree::point_assign`] and [`AssignSegmentTree::range_assign`].
+///
+/// # Panics
+///
+/// Panics if an operation is out of bounds.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{
+///     naive::{Op, check_assign},
+///     ops::Add,
+/// };
+///
+/// let ops = [Op::RangeUpdate(1..4, 7), Op::PointUpdate(2, 0), Op::RangeQuery(0..5)];
+/// assert!(check_assign::<Add<i32>>(vec![1, 2, 3, 4, 5], &ops).is_ok());
+/// ```
+pub fn check_assign<Query>(
+    values: Vec<<Query as Monoid>::Set>,
+    ops: &[Op<<Query as Monoid>::Set>],
+) -> Result<(), Divergence<<Query as Monoid>::Set, <Query as Monoid>::Set>>
+where
+    Query: Monoid<Set: Clone + PartialEq>,
+{
+    check(values, ops, &|values, ops| {
+        let mut tree = AssignSegmentTree::<Query>::from(values.to_vec());
+        let mut model = NaiveSegmentTree::<Query>::from(values.to_vec());
+        for (k, op) in ops.iter().enumerate() {
+            let (expected, actual) = match op {
+                Op::PointUpdate(i, element) => {
+                    tree.point_assign(*i, element.clone());
+                    model.point_update(*i, element.clone());
+                    continue;
+                }
+                Op::RangeUpdate(range, element) => {
+                    tree.range_assign(range.clone(), element.clone());
+                    model.range_assign(range.clone(), element.clone());
+                    continue;
+                }
+                Op::PointQuery(i) => (model.point_query(*i).clone(), tree.point_query(*i).clone()),
+                Op::RangeQuery(range) => (
+                    model.range_query(range.clone()),
+                    tree.range_query(range.clone()),
+                ),
+            };
+            if expected != actual {
+                return Some((k, expected, actual));
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod models {
+    use rand::Rng;
+
+    use super::{NaiveDualSegmentTree, NaiveSegmentTree, Op, check_assign, check_dual, check_lazy};
+    use crate::{
+        AssignSegmentTree, DualSegmentTree, SegmentTree,
+        acts::AddQueryAffineUpdate,
+        ops::{Add, Affine},
+        traits::{Monoid, MonoidAction},
+    };
+
+    /// [`Affine`] which is wrongly marked as commutative.
+    struct MislabeledAffine;
+
+    impl Monoid for MislabeledAffine {
+        type Set = (i64, i64);
+
+        const IS_COMMUTATIVE: bool = true;
+
+        fn identity() -> Self::Set {
+            <Affine<i64> as Monoid>::identity()
+        }
+
+        fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+            <Affine<i64> as Monoid>::combine(lhs_or_prev, rhs_or_new)
+        }
+    }
+
+    struct MislabeledAction;
+
+    impl MonoidAction for MislabeledAction {
+        type Map = MislabeledAffine;
+        type Set = Add<i64>;
+
+        const USE_SEGMENT_SIZE: bool = true;
+
+        fn act(
+            mapping: &<Self::Map as Monoid>::Set,
+            element: &<Self::Set as Monoid>::Set,
+            size: Option<usize>,
+        ) -> <Self::Set as Monoid>::Set {
+            <AddQueryAffineUpdate<i64> as MonoidAction>::act(mapping, element, size)
+        }
+    }
+
+    fn random_ops(rng: &mut impl Rng, n: usize, q: usize) -> Vec<Op<(i64, i64)>> {
+        Vec::from_iter((0..q).map(|_| {
+            let l = rng.random_range(0..n);
+            let r = rng.random_range(l + 1..=n);
+            let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+            match rng.random_range(0..4) {
+                0 => Op::PointUpdate(l, update),
+                1 => Op::RangeUpdate(l..r, update),
+                2 => Op::PointQuery(l),
+                _ => Op::RangeQuery(l..r),
+            }
+        }))
+    }
+
+    #[test]
+    fn segment_tree() {
+        let mut rng = rand::rng();
+        for n in 0..30 {
+            let values = Vec::from_iter((0..n).map(|_| rng.random_range(0..10_u32)));
+            let mut tree = AssignSegmentTree::<Add<u32>>::from(values.clone());
+            let mut model = NaiveSegmentTree::<Add<u32>>::from(values.clone());
+            let st = SegmentTree::<Add<u32>>::from(values);
+
+            for start in 0..=n {
+                for sum in 0..20 {
+                    let pred = |v: &u32| *v <= sum;
+                    assert_eq!(
+                        st.partition_end(start, pred),
+                        model.partition_end(start, pred)
+                    );
+                    assert_eq!(
+                        st.partition_start(start, pred),
+                        model.partition_start(start, pred)
+                    );
+                }
+            }
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let v = rng.random_range(0..10);
+                tree.range_assign(l..r, v);
+                model.range_assign(l..r, v);
+                assert_eq!(tree.to_vec(), model.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn dual_segment_tree() {
+        let mut rng = rand::rng();
+        let mut tree = DualSegmentTree::<Affine<i64>>::new(20);
+        let mut model = NaiveDualSegmentTree::<Affine<i64>>::new(20);
+        for _ in 0..100 {
+            let l = rng.random_range(0..=20);
+            let r = rng.random_range(l..=20);
+            let update = (rng.random_range(-2..=2), rng.random_range(-5..=5));
+            tree.range_update(l..r, &update);
+            model.range_update(l..r, &update);
+            assert_eq!(tree.to_vec(), model.as_slice());
+
+            let i = rng.random_range(0..20);
+            tree.point_update(i, &update);
+            model.point_update(i, &update);
+            assert_eq!(tree.to_vec(), model.as_slice());
+        }
+    }
+
+    #[test]
+    fn check() {
+        let mut rng = rand::rng();
+        for n in 1..20 {
+            let values = Vec::from_iter((0..n).map(|_| rng.random_range(-10..10)));
+            let ops = random_ops(&mut rng, n, 100);
+            assert_eq!(
+                check_lazy::<AddQueryAffineUpdate<i64>>(values.clone(), &ops),
+                Ok(())
+            );
+            assert_eq!(check_dual::<Affine<i64>>(vec![(1, 0); n], &ops), Ok(()));
+            let ops = Vec::from_iter(ops.into_iter().map(|op| match op {
+                Op::PointUpdate(i, (a, _)) => Op::PointUpdate(i, a),
+                Op::RangeUpdate(range, (a, _)) => Op::RangeUpdate(range, a),
+                Op::PointQuery(i) => Op::PointQuery(i),
+                Op::RangeQuery(range) => Op::RangeQuery(range),
+            }));
+            assert_eq!(check_assign::<Add<i64>>(values, &ops), Ok(()));
+        }
+    }
+
+    #[test]
+    fn minimize() {
+        let mut rng = rand::rng();
+        let values = Vec::from_iter((0..32).map(|_| rng.random_range(-10..10)));
+        let mut ops = random_ops(&mut rng, 32, 200);
+        // make sure that the propagation order matters
+        ops.extend([
+            Op::RangeUpdate(0..32, (2, 0)),
+            Op::RangeUpdate(0..32, (1, 1)),
+            Op::PointUpdate(0, (2, 0)),
+            Op::PointQuery(0),
+        ]);
+
+        let divergence = check_lazy::<MislabeledAction>(values.clone(), &ops).unwrap_err();
+        assert_ne!(divergence.expected, divergence.actual);
+        assert!(matches!(
+            divergence.ops.last(),
+            Some(Op::PointQuery(_) | Op::RangeQuery(_))
+        ));
+        assert!(check_lazy::<MislabeledAction>(values.clone(), &divergence.ops).is_err());
+        // the reproduction is minimal
+        for i in 0..divergence.ops.len() {
+            let mut ops = divergence.ops.clone();
+            ops.remove(i);
+            assert!(check_lazy::<MislabeledAction>(values.clone(), &ops).is_ok());
+        }
+    }
+}
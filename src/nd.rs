@@ -0,0 +1,371 @@
+use std::{fmt::Debug, ops::RangeBounds};
+
+use crate::{
+    traits::{CommutativeMonoid, Monoid},
+    utility::convert_range,
+};
+
+/// A data structure that supports **box query point update** operations on a `D`-dimensional array.
+///
+/// This is equivalent to nesting `D` segment trees, each of whose nodes is the tree of the next dimension,
+/// but all nodes are stored in one flat buffer of ∏ 2*N<sub>k</sub> elements.
+/// Operations descend the dimensions recursively.
+///
+/// The monoid should be commutative because the nodes of a box are combined in no particular order.
+/// Both the time and the space grow exponentially in `D`, so this is meant for small `D` such as 2 or 3.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTreeNd, ops::Add};
+///
+/// // 3 x 4 grid
+/// let mut st = SegmentTreeNd::<Add<i32>, 2>::from_fn([3, 4], |[i, j]| (4 * i + j) as i32);
+/// assert_eq!(st.range_query([1..3, 1..3]), 5 + 6 + 9 + 10);
+///
+/// st.point_update([2, 2], 100);
+/// assert_eq!(st.range_query([1..3, 1..3]), 5 + 6 + 9 + 100);
+/// assert_eq!(st.range_query([.., ..]), 66 - 10 + 100);
+/// ```
+pub struct SegmentTreeNd<Query, const D: usize>
+where
+    Query: CommutativeMonoid,
+{
+    /// `2 * shape[k]` nodes along each dimension `k`, stored in row-major order.
+    data: Box<[<Query as Monoid>::Set]>,
+    shape: [usize; D],
+    /// the distance between adjacent nodes along each dimension
+    strides: [usize; D],
+}
+
+impl<Query, const D: usize> SegmentTreeNd<Query, D>
+where
+    Query: CommutativeMonoid,
+{
+    /// Creates a new instance of the given `shape`,
+    /// initialized with [identity elements](crate::traits::Monoid::identity()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of nodes overflows [`usize`].
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(∏ *N*<sub>k</sub>)
+    pub fn new(shape: [usize; D]) -> Self {
+        // `range_query` combines the nodes out of order, so the marker alone is not trusted
+        const {
+            assert!(
+                <Query as Monoid>::IS_COMMUTATIVE,
+                "CommutativeMonoid should set IS_COMMUTATIVE to true"
+            )
+        };
+
+        let (strides, len) = Self::layout(shape);
+        Self {
+            data: Box::from_iter(std::iter::repeat_with(<Query as Monoid>::identity).take(len)),
+            shape,
+            strides,
+        }
+    }
+
+    /// Creates a new instance of the given `shape`, whose element at `index` is `f(index)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of nodes overflows [`usize`].
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*D* ∏ *N*<sub>k</sub>)
+    pub fn from_fn<F>(shape: [usize; D], mut f: F) -> Self
+    where
+        F: FnMut([usize; D]) -> <Query as Monoid>::Set,
+    {
+        let mut tree = Self::new(shape);
+        if shape.contains(&0) {
+            return tree;
+        }
+
+        let mut index = [0; D];
+        loop {
+            let i = tree.leaf_index(index);
+            tree.data[i] = f(index);
+
+            // advance in row-major order
+            let Some(k) = (0..D).rev().find(|&k| index[k] + 1 < shape[k]) else {
+                break;
+            };
+            index[k] += 1;
+            index[k + 1..].fill(0);
+        }
+
+        // build the dimensions one by one
+        let len = tree.data.len();
+        for (n, stride) in shape.into_iter().zip(tree.strides) {
+            for block in (0..len).step_by(2 * n * stride) {
+                for i in (1..n).rev() {
+                    for j in block + i * stride..block + (i + 1) * stride {
+                        tree.data[j] = <Query as Monoid>::combine(
+                            &tree.data[j + i * stride],
+                            &tree.data[j + (i + 1) * stride],
+                        )
+                    }
+                }
+            }
+        }
+
+        tree
+    }
+
+    /// Returns the strides and the number of nodes.
+    fn layout(shape: [usize; D]) -> ([usize; D], usize) {
+        let mut strides = [0; D];
+        let mut len = 1_usize;
+        for k in (0..D).rev() {
+            strides[k] = len;
+            len = shape[k]
+                .checked_mul(2)
+                .and_then(|n| n.checked_mul(len))
+                .expect("the number of nodes should not overflow `usize`");
+        }
+        (strides, len)
+    }
+
+    fn leaf_index(&self, index: [usize; D]) -> usize {
+        (0..D)
+            .map(|k| {
+                assert!(
+                    index[k] < self.shape[k],
+                    "the index {index:?} should be within the shape {:?}",
+                    self.shape
+                );
+                (self.shape[k] + index[k]) * self.strides[k]
+            })
+            .sum()
+    }
+
+    /// Returns the number of elements along each dimension.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn shape(&self) -> [usize; D] {
+        self.shape
+    }
+
+    /// Replaces the element at `index` with `element`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(∏ log *N*<sub>k</sub>)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTreeNd, ops::Max};
+    ///
+    /// let mut st = SegmentTreeNd::<Max<i32>, 3>::new([4, 4, 4]);
+    /// st.point_update([1, 2, 3], Some(5));
+    /// assert_eq!(st.point_query([1, 2, 3]), &Some(5));
+    /// assert_eq!(st.range_query([0..2, 2..4, 0..4]), Some(5));
+    /// assert_eq!(st.range_query([0..2, 2..4, 0..3]), None);
+    /// ```
+    pub fn point_update(&mut self, index: [usize; D], element: <Query as Monoid>::Set) {
+        let leaf = self.leaf_index(index);
+        self.data[leaf] = element;
+
+        // the nodes on the paths to the root, lower levels first along every dimension
+        let leaves: [usize; D] = std::array::from_fn(|k| self.shape[k] + index[k]);
+        let mut levels = [0_u32; D];
+        while let Some(k) = (0..D).rev().find(|&k| leaves[k] >> (levels[k] + 1) != 0) {
+            levels[k] += 1;
+            levels[k + 1..].fill(0);
+
+            let mut i = 0;
+            for k in 0..D {
+                i += (leaves[k] >> levels[k]) * self.strides[k]
+            }
+            // combine the children along the first internal dimension,
+            // which are already up to date
+            let k = (0..D).find(|&k| levels[k] != 0).unwrap();
+            let (node, stride) = (leaves[k] >> levels[k], self.strides[k]);
+            let child = i + node * stride;
+            self.data[i] =
+                <Query as Monoid>::combine(&self.data[child], &self.data[child + stride]);
+        }
+    }
+
+    /// Returns the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*D*)
+    pub fn point_query(&self, index: [usize; D]) -> &<Query as Monoid>::Set {
+        &self.data[self.leaf_index(index)]
+    }
+
+    /// Combines the elements in the box, which is the product of the `ranges`.
+    ///
+    /// Returns [the identity element](crate::traits::Monoid::identity()) if the box is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range is explicitly out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(∏ log *N*<sub>k</sub>)
+    pub fn range_query<R>(&self, ranges: [R; D]) -> <Query as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let mut k = 0;
+        let ranges = ranges.map(|range| {
+            let range = convert_range(range, 0..self.shape[k]);
+            k += 1;
+            range
+        });
+
+        let mut res = <Query as Monoid>::identity();
+        if ranges.iter().all(|range| !range.is_empty()) {
+            self.fold_at(0, 0, &ranges, &mut res);
+        }
+        res
+    }
+
+    /// Combines the nodes covering `ranges[k..]` in the subarray starting at `offset` into `acc`.
+    fn fold_at(
+        &self,
+        k: usize,
+        offset: usize,
+        ranges: &[std::ops::Range<usize>; D],
+        acc: &mut <Query as Monoid>::Set,
+    ) {
+        if k == D {
+            <Query as Monoid>::combine_assign(acc, &self.data[offset]);
+            return;
+        }
+
+        let (n, stride) = (self.shape[k], self.strides[k]);
+        let [mut l, mut r] = [ranges[k].start + n, ranges[k].end + n];
+        while l < r {
+            if l & 1 == 1 {
+                self.fold_at(k + 1, offset + l * stride, ranges, acc);
+                l += 1
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                self.fold_at(k + 1, offset + r * stride, ranges, acc)
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+    }
+}
+
+impl<Query, const D: usize> Debug for SegmentTreeNd<Query, D>
+where
+    Query: CommutativeMonoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SegmentTreeNd")
+            .field("data", &self.data)
+            .field("shape", &self.shape)
+            .field("strides", &self.strides)
+            .finish()
+    }
+}
+
+impl<Query, const D: usize> Clone for SegmentTreeNd<Query, D>
+where
+    Query: CommutativeMonoid<Set: Clone>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            shape: self.shape,
+            strides: self.strides,
+        }
+    }
+}
+
+#[cfg(test)]
+mod random {
+    use rand::Rng;
+
+    use crate::{SegmentTreeNd, ops::Add};
+
+    #[test]
+    fn two_dims() {
+        let mut rng = rand::rng();
+        for (h, w) in [(1, 1), (1, 7), (5, 1), (6, 9), (8, 8)] {
+            let mut naive = vec![vec![0_i64; w]; h];
+            for row in &mut naive {
+                row.fill_with(|| rng.random_range(-10..10));
+            }
+            let mut st = SegmentTreeNd::<Add<i64>, 2>::from_fn([h, w], |[i, j]| naive[i][j]);
+
+            for _ in 0..100 {
+                let [i, j] = [rng.random_range(0..h), rng.random_range(0..w)];
+                let v = rng.random_range(-10..10);
+                st.point_update([i, j], v);
+                naive[i][j] = v;
+
+                let [t, b] = [rng.random_range(0..=h), rng.random_range(0..=h)];
+                let [l, r] = [rng.random_range(0..=w), rng.random_range(0..=w)];
+                let (t, b, l, r) = (t.min(b), t.max(b), l.min(r), l.max(r));
+                let expected: i64 = naive[t..b]
+                    .iter()
+                    .map(|row| row[l..r].iter().sum::<i64>())
+                    .sum();
+                assert_eq!(st.range_query([t..b, l..r]), expected);
+                assert_eq!(st.point_query([i, j]), &v);
+            }
+        }
+    }
+
+    #[test]
+    fn three_dims() {
+        let mut rng = rand::rng();
+        let shape = [3, 5, 4];
+        let mut naive = vec![0_i64; 3 * 5 * 4];
+        let mut st = SegmentTreeNd::<Add<i64>, 3>::new(shape);
+        for _ in 0..200 {
+            let index = shape.map(|n| rng.random_range(0..n));
+            let v = rng.random_range(-10..10);
+            st.point_update(index, v);
+            naive[(index[0] * 5 + index[1]) * 4 + index[2]] = v;
+
+            let ranges = shape.map(|n| {
+                let [l, r] = [rng.random_range(0..=n), rng.random_range(0..=n)];
+                l.min(r)..l.max(r)
+            });
+            let mut expected = 0;
+            for i in ranges[0].clone() {
+                for j in ranges[1].clone() {
+                    for k in ranges[2].clone() {
+                        expected += naive[(i * 5 + j) * 4 + k]
+                    }
+                }
+            }
+            assert_eq!(st.range_query(ranges), expected);
+        }
+        assert_eq!(st.range_query([.., .., ..]), naive.iter().sum::<i64>());
+    }
+
+    #[test]
+    fn empty() {
+        let st = SegmentTreeNd::<Add<i32>, 2>::from_fn([0, 5], |_| 1);
+        assert_eq!(st.range_query([.., ..]), 0);
+        assert_eq!(st.shape(), [0, 5]);
+    }
+}
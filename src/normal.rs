@@ -1,11 +1,19 @@
 use std::{
+    collections::BinaryHeap,
     fmt::Debug,
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+    mem::MaybeUninit,
     ops::{Range, RangeBounds},
 };
 
-use num_traits::WrappingShl;
-
-use crate::{traits::Monoid, utility::convert_range};
+use crate::{
+    memory::MemoryReport,
+    snapshot::{self, Element},
+    stats::Counters,
+    traits::{Group, Monoid},
+    utility::{combine_pairs, convert_range, split_cyclic},
+};
 
 /// A data structure that supports **range query point update** operations.
 ///
@@ -28,6 +36,8 @@ where
 
     /// `len` (number of elements) and offset (dummy + cache)
     len_or_offset: usize,
+
+    counters: Counters,
 }
 // ANCHOR_END: definition
 
@@ -35,6 +45,10 @@ impl<Query> SegmentTree<Query>
 where
     Query: Monoid,
 {
+    /// Ranges up to this length are combined directly by [SIMD kernels](crate::simd) if available.
+    #[cfg(feature = "simd")]
+    const SHORT_RANGE: usize = 64;
+
     #[doc = include_str!("../doc/new.md")]
     /// # Example
     ///
@@ -59,12 +73,116 @@ where
     /// *Θ*(*N*)
     #[inline]
     fn build(&mut self) -> &mut Self {
+        self.counters.combine(self.len_or_offset.saturating_sub(1));
+        if !std::mem::needs_drop::<<Query as Monoid>::Set>() {
+            let n = self.len_or_offset;
+            let nodes = &mut self.data[..];
+            // SAFETY: the leaves are initialized and overwriting nodes without drop is harmless.
+            unsafe {
+                Self::build_nodes(
+                    &mut *(nodes as *mut [<Query as Monoid>::Set]
+                        as *mut [MaybeUninit<<Query as Monoid>::Set>]),
+                    n,
+                )
+            };
+            return self;
+        }
+
         for i in (1..self.len_or_offset).rev() {
             self.data[i] = <Query as Monoid>::combine(&self.data[i * 2], &self.data[i * 2 + 1])
         }
         self
     }
 
+    /// Calculates `nodes[..n]` in bottom-to-top order, where `nodes[n..2n]` are leaves.
+    ///
+    /// The nodes are processed in blocks `lo..hi` with `hi <= 2lo`,
+    /// so that the children of a block are contiguous and lie in `hi..`.
+    ///
+    /// # Safety
+    ///
+    /// `nodes` should have `2n` nodes and `nodes[n..]` should be initialized.
+    /// Every node is initialized when this function returns.
+    ///
+    /// # Time complexity
+    ///
+    /// *Θ*(*N*)
+    unsafe fn build_nodes(nodes: &mut [MaybeUninit<<Query as Monoid>::Set>], n: usize) {
+        if n == 0 {
+            return;
+        }
+
+        // dummy node
+        nodes[0].write(<Query as Monoid>::identity());
+        let mut hi = n;
+        while hi > 1 {
+            let lo = hi.div_ceil(2);
+            let (parents, children) = nodes.split_at_mut(hi);
+            // SAFETY: `nodes[hi..]` is initialized in the previous iterations.
+            let children = unsafe {
+                &*(&children[(lo << 1) - hi..hi] as *const [MaybeUninit<<Query as Monoid>::Set>]
+                    as *const [<Query as Monoid>::Set])
+            };
+            combine_pairs::<Query>(children, &mut parents[lo..hi]);
+            hi = lo
+        }
+    }
+
+    /// Builds the tree from `n` elements without filling the buffer with identity elements.
+    ///
+    /// Falls back to [`From<Vec>`] if `values` does not yield exactly `n` elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *Θ*(*N*)
+    fn from_exact_size_iter<I>(n: usize, mut values: I) -> Self
+    where
+        I: Iterator<Item = <Query as Monoid>::Set>,
+    {
+        let mut data = Box::<[<Query as Monoid>::Set]>::new_uninit_slice(n << 1);
+
+        let mut len = 0;
+        for (slot, value) in data[n..].iter_mut().zip(values.by_ref()) {
+            slot.write(value);
+            len += 1;
+        }
+        if len < n || n == 0 {
+            // SAFETY: `data[n..n + len]` is initialized and will not be read again.
+            let init = data[n..n + len]
+                .iter()
+                .map(|value| unsafe { value.assume_init_read() });
+            let rest = Vec::from_iter(init.chain(values));
+            return if rest.is_empty() {
+                Self {
+                    data: Box::new([]),
+                    len_or_offset: 0,
+                    counters: Counters::default(),
+                }
+            } else {
+                Self::from(rest)
+            };
+        }
+        if let Some(value) = values.next() {
+            // SAFETY: `data[n..]` is initialized and will not be read again.
+            let init = data[n..]
+                .iter()
+                .map(|value| unsafe { value.assume_init_read() });
+            return Self::from(Vec::from_iter(
+                init.chain(std::iter::once(value)).chain(values),
+            ));
+        }
+
+        // SAFETY: the leaves are initialized above.
+        unsafe { Self::build_nodes(&mut data, n) };
+
+        Self {
+            // SAFETY: every node is initialized by `build_nodes()`.
+            data: unsafe { data.assume_init() },
+            len_or_offset: n,
+            counters: Counters::built(n),
+        }
+    }
+
     /// Returns the number of elements.
     ///
     /// # Time complexity
@@ -81,12 +199,79 @@ where
     ///
     /// assert_eq!(st.len(), n as usize)
     /// ```
-    #[allow(clippy::len_without_is_empty)]
     #[inline]
     pub const fn len(&self) -> usize {
         self.len_or_offset
     }
 
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// Range queries over an empty tree return [the identity element](crate::traits::Monoid::identity()),
+    /// and there is no index to update.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::new(0);
+    /// assert!(st.is_empty());
+    /// ```
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len_or_offset == 0
+    }
+
+    #[doc = include_str!("../doc/stats.md")]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let mut st = SegmentTree::<Add<i32>>::new(8);
+    /// assert_eq!(st.stats().combines, 7);
+    ///
+    /// st.reset_stats();
+    /// st.point_update(0, 1);
+    /// assert_eq!(st.stats().combines, 3);
+    /// ```
+    #[cfg(feature = "instrument")]
+    pub fn stats(&self) -> crate::Stats {
+        self.counters.stats()
+    }
+
+    /// Resets the counts returned by [`stats()`](Self::stats) to zero.
+    #[cfg(feature = "instrument")]
+    pub fn reset_stats(&mut self) {
+        self.counters.reset()
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<u32>>::new(100);
+    /// assert_eq!(st.memory_usage().data, 200 * size_of::<u32>());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            data: size_of_val(&*self.data),
+            ..Default::default()
+        }
+    }
+
     /// Returns an iterator over the elements
     ///
     /// # Time complexity
@@ -109,7 +294,85 @@ where
     #[inline]
     #[must_use = "iterators are lazy and do nothing unless consumed"]
     pub fn iter(&self) -> std::slice::Iter<'_, <Query as Monoid>::Set> {
-        self.data[self.len_or_offset..].iter()
+        self.as_leaf_slice().iter()
+    }
+
+    /// Returns the elements as a contiguous slice.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let mut st = SegmentTree::<Add<i32>>::from_iter(0..5);
+    /// st.point_update(2, 20);
+    ///
+    /// assert_eq!(st.as_leaf_slice(), &[0, 1, 20, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn as_leaf_slice(&self) -> &[<Query as Monoid>::Set] {
+        &self.data[self.len_or_offset..]
+    }
+
+    /// Consumes the tree and returns the elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let mut st = SegmentTree::<Add<i32>>::from_iter(0..10);
+    /// st.point_update(5, 50);
+    ///
+    /// assert_eq!(st.into_vec(), vec![0, 1, 2, 3, 4, 50, 6, 7, 8, 9]);
+    /// ```
+    pub fn into_vec(self) -> Vec<<Query as Monoid>::Set> {
+        let mut data = self.data.into_vec();
+        data.drain(..self.len_or_offset);
+        data
+    }
+
+    #[doc = include_str!("../doc/assign_from_iter.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let mut st = SegmentTree::<Add<i32>>::new(5);
+    /// for t in 0..3 {
+    ///     st.assign_from_iter((0..5).map(|v| v * t));
+    ///     assert_eq!(st.range_query(..), 10 * t);
+    /// }
+    /// ```
+    pub fn assign_from_iter<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = <Query as Monoid>::Set>,
+    {
+        let mut iter = iter.into_iter();
+        for leaf in &mut self.data[self.len_or_offset..] {
+            *leaf = iter
+                .next()
+                .expect("`iter` should yield exactly `len()` items");
+        }
+        assert!(
+            iter.next().is_none(),
+            "`iter` should yield exactly `len()` items"
+        );
+
+        self.build();
     }
 
     #[inline]
@@ -140,7 +403,99 @@ where
         self.data[i] = element;
         while i > 1 {
             i >>= 1;
-            self.data[i] = <Query as Monoid>::combine(&self.data[i << 1], &self.data[(i << 1) + 1])
+            self.data[i] = <Query as Monoid>::combine(&self.data[i << 1], &self.data[(i << 1) + 1]);
+            self.counters.combine(1)
+        }
+    }
+
+    /// Swaps the `i`-th and `j`-th elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Affine};
+    ///
+    /// let mut st = SegmentTree::<Affine<i32>>::from_iter([(2, 0), (1, 1), (1, 2)]);
+    /// assert_eq!(st.range_query(..), (2, 3));
+    ///
+    /// st.swap(0, 2);
+    /// assert_eq!(st.range_query(..), (2, 6));
+    /// ```
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let [mut i, mut j] = [self.inner_index(i), self.inner_index(j)];
+        self.data.swap(i, j);
+
+        // recalculate the both paths in bottom-to-top order
+        while i != j {
+            let k = i.max(j) >> 1;
+            self.data[k] = <Query as Monoid>::combine(&self.data[k << 1], &self.data[(k << 1) + 1]);
+            self.counters.combine(1);
+            if i > j { i = k } else { j = k }
+        }
+        while i > 1 {
+            i >>= 1;
+            self.data[i] = <Query as Monoid>::combine(&self.data[i << 1], &self.data[(i << 1) + 1]);
+            self.counters.combine(1)
+        }
+    }
+
+    /// Returns a mutable reference to the `i`-th leaf, leaving its ancestors stale.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    #[inline]
+    pub(crate) fn leaf_mut(&mut self, i: usize) -> &mut <Query as Monoid>::Set {
+        assert!(i < self.len_or_offset, "index out of bounds");
+        let i = self.inner_index(i);
+        &mut self.data[i]
+    }
+
+    /// Recalculates every ancestor of the given leaves exactly once in bottom-to-top order.
+    ///
+    /// Rebuilds the whole tree instead if there are too many leaves.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(min(*K* log *N* log *K*, *N*))
+    pub(crate) fn recalculate_ancestors<I>(&mut self, leaves: I)
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut heap = BinaryHeap::from_iter(leaves.into_iter().map(|i| self.inner_index(i)));
+        if heap.is_empty() {
+            return;
+        }
+        if heap
+            .len()
+            .saturating_mul(self.len_or_offset.ilog2() as usize + 1)
+            >= self.len_or_offset
+        {
+            self.build();
+            return;
+        }
+
+        // children are popped before their parent
+        while let Some(i) = heap.pop() {
+            while heap.peek() == Some(&i) {
+                heap.pop();
+            }
+            if i < self.len_or_offset {
+                self.data[i] =
+                    <Query as Monoid>::combine(&self.data[i << 1], &self.data[(i << 1) + 1]);
+                self.counters.combine(1)
+            }
+            if i > 1 {
+                heap.push(i >> 1)
+            }
         }
     }
 
@@ -179,7 +534,46 @@ where
         self.data[i] = f(&self.data[i]);
         while i > 1 {
             i >>= 1;
-            self.data[i] = <Query as Monoid>::combine(&self.data[i << 1], &self.data[(i << 1) + 1])
+            self.data[i] = <Query as Monoid>::combine(&self.data[i << 1], &self.data[(i << 1) + 1]);
+            self.counters.combine(1)
+        }
+    }
+
+    #[doc = include_str!("../doc/apply_range_with.md")]
+    /// # Time complexity
+    ///
+    /// *O*(*K* + log *N*), where *K* is the length of the range
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let mut st = SegmentTree::<Add<u32>>::from_iter([3, 8, 5, 6, 7]);
+    /// // apply the Collatz map to 1..4
+    /// st.apply_range_with(1..4, |v| *v = if *v % 2 == 0 { *v / 2 } else { 3 * *v + 1 });
+    /// assert_eq!(st.as_leaf_slice(), [3, 4, 16, 3, 7]);
+    /// assert_eq!(st.range_query(..), 33);
+    /// ```
+    pub fn apply_range_with<R, F>(&mut self, range: R, mut f: F)
+    where
+        R: RangeBounds<usize> + Debug,
+        F: FnMut(&mut <Query as Monoid>::Set),
+    {
+        let Range { start, end } = convert_range(range, 0..self.len_or_offset);
+        if start == end {
+            return;
+        }
+        let [l, r] = [self.inner_index(start), self.inner_index(end)];
+        self.data[l..r].iter_mut().for_each(&mut f);
+
+        // each node is recalculated for the last time after its children
+        for d in 1..usize::BITS - (r - 1).leading_zeros() {
+            for i in (l >> d).max(1)..=(r - 1) >> d {
+                self.data[i] =
+                    <Query as Monoid>::combine(&self.data[i << 1], &self.data[(i << 1) + 1]);
+                self.counters.combine(1)
+            }
         }
     }
 
@@ -207,9 +601,29 @@ where
         R: RangeBounds<usize> + Debug,
     {
         let range = convert_range(range, 0..self.len_or_offset);
+        #[cfg(feature = "tracing")]
+        let traced = self
+            .counters
+            .enter(crate::stats::trace_span!("range_query", range));
+        let res = self.range_query_inner(range);
+        #[cfg(feature = "tracing")]
+        self.counters.exit(traced);
+        res
+    }
+
+    fn range_query_inner(&self, range: Range<usize>) -> <Query as Monoid>::Set {
         if range.is_empty() {
             return <Query as Monoid>::identity();
         }
+        #[cfg(feature = "simd")]
+        if range.len() <= Self::SHORT_RANGE
+            && let Some(res) = crate::utility::fold_leaves::<Query>(
+                &self.data[self.inner_index(range.start)..self.inner_index(range.end)],
+            )
+        {
+            self.counters.combine(range.len() - 1);
+            return res;
+        }
 
         let [mut l, mut r] = {
             // Consumes range and avoids copy
@@ -221,7 +635,7 @@ where
         while {
             // This is branchy but necessary for avoiding invalid buffers. ...really?
             if l >= r {
-                acc_l = <Query as Monoid>::combine(&acc_l, &self.data[l]);
+                <Query as Monoid>::combine_assign(&mut acc_l, &self.data[l]);
                 l += 1;
                 l >>= l.trailing_zeros()
             } else {
@@ -229,13 +643,44 @@ where
                 acc_r = <Query as Monoid>::combine(&self.data[r], &acc_r);
                 r >>= r.trailing_zeros();
             }
+            self.counters.combine(1);
 
             l != r
         } {}
 
+        self.counters.combine(1);
         <Query as Monoid>::combine(&acc_l, &acc_r)
     }
 
+    #[doc = include_str!("../doc/range_query_cyclic.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Concat};
+    ///
+    /// let st = SegmentTree::<Concat<char>>::from_iter("abcde".chars().map(|c| vec![c]));
+    /// assert_eq!(st.range_query_cyclic(3..1), vec!['d', 'e', 'a']);
+    /// assert_eq!(st.range_query_cyclic(1..3), vec!['b', 'c']);
+    /// assert_eq!(st.range_query_cyclic(2..2), vec![]);
+    /// ```
+    pub fn range_query_cyclic<R>(&self, range: R) -> <Query as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let [head, tail] = split_cyclic(
+            convert_range(range, 0..self.len_or_offset),
+            self.len_or_offset,
+        );
+        if tail.is_empty() {
+            return self.range_query_inner(head);
+        }
+        <Query as Monoid>::combine(&self.range_query_inner(head), &self.range_query_inner(tail))
+    }
+
     #[doc = include_str!("../doc/point_query.md")]
     /// # Time complexity
     ///
@@ -299,7 +744,7 @@ where
         assert!(start <= self.len_or_offset);
 
         let mut i = self.inner_index(start);
-        let mut segment_size = 1.wrapping_shl(i.trailing_zeros());
+        let mut segment_size = 1_usize.wrapping_shl(i.trailing_zeros());
         i = i.wrapping_shr(i.trailing_zeros());
         let mut combined = <Query as Monoid>::identity();
 
@@ -307,6 +752,7 @@ where
         // The first condition ensures next segment is valid.
         while start + segment_size <= self.len_or_offset && {
             tmp = <Query as Monoid>::combine(&combined, &self.data[i]);
+            self.counters.combine(1);
             pred(&tmp)
         } {
             combined = tmp;
@@ -329,6 +775,7 @@ where
         };
         while {
             tmp = <Query as Monoid>::combine(&combined, &self.data[i]);
+            self.counters.combine(1);
 
             // branchless if block
             {
@@ -391,7 +838,7 @@ where
         assert!(end <= self.len_or_offset);
 
         let mut i = self.inner_index(end);
-        let mut segment_size = 1.wrapping_shl(i.trailing_zeros());
+        let mut segment_size = 1_usize.wrapping_shl(i.trailing_zeros());
         i = i.wrapping_shr(i.trailing_zeros());
         let mut combined = <Query as Monoid>::identity();
 
@@ -399,6 +846,7 @@ where
         while end >= segment_size && {
             // i > 0 && i % 2 == 1
             tmp = <Query as Monoid>::combine(&combined, &self.data[i - 1]);
+            self.counters.combine(1);
             pred(&tmp)
         } {
             combined = tmp;
@@ -420,6 +868,7 @@ where
         };
         while {
             tmp = <Query as Monoid>::combine(&combined, &self.data[i - 1]);
+            self.counters.combine(1);
 
             // branchless if block
             {
@@ -432,37 +881,388 @@ where
             i <<= 1;
             segment_size >>= 1;
 
-            i <= self.len_or_offset * 2
+            segment_size > 0 && i <= self.len_or_offset * 2
         } {}
 
         end
     }
 }
 
-impl<Query> From<Vec<<Query as Monoid>::Set>> for SegmentTree<Query>
+impl<Query> SegmentTree<Query>
 where
-    Query: Monoid,
+    Query: Monoid<Set: Clone>,
 {
-    fn from(values: Vec<<Query as Monoid>::Set>) -> Self {
-        let n = values.len();
-        let data = Vec::from_iter(
-            std::iter::repeat_with(<Query as Monoid>::identity)
-                .take(n)
-                .chain(values),
-        )
-        .into_boxed_slice();
+    #[doc = include_str!("../doc/from_elem.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_elem(100, 3);
+    /// assert_eq!(st.range_query(10..20), 30);
+    /// ```
+    #[inline]
+    pub fn from_elem(n: usize, element: <Query as Monoid>::Set) -> Self {
+        Self::from_iter(std::iter::repeat_n(element, n))
+    }
 
-        let mut tree = Self {
-            data,
-            len_or_offset: n,
-        };
-        tree.build();
+    /// Creates a new instance initialized with clones of `values`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let values = [1, 2, 3, 4, 5];
+    /// let st = SegmentTree::<Add<i32>>::from_slice(&values);
+    /// assert_eq!(st.range_query(1..4), 9);
+    /// ```
+    #[inline]
+    pub fn from_slice(values: &[<Query as Monoid>::Set]) -> Self {
+        Self::from_iter(values.iter().cloned())
+    }
 
-        tree
+    /// Returns a copy of the elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Max};
+    ///
+    /// let st = SegmentTree::<Max<i32>>::from_iter([Some(1), None, Some(3)]);
+    /// assert_eq!(st.to_vec(), vec![Some(1), None, Some(3)]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<<Query as Monoid>::Set> {
+        self.as_leaf_slice().to_vec()
     }
 }
 
-impl<Query> FromIterator<<Query as Monoid>::Set> for SegmentTree<Query>
+#[cfg(feature = "rayon")]
+impl<Query> SegmentTree<Query>
+where
+    Query: Monoid<Set: Send + Sync>,
+{
+    /// Creates a new instance from `values`, calculating the nodes of each level in parallel.
+    ///
+    /// # Time complexity
+    ///
+    /// *Θ*(*N*) work and *O*(log *N*) levels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let n = 1 << 20;
+    /// let st = SegmentTree::<Add<u64>>::par_from_vec(Vec::from_iter(0..n));
+    /// assert_eq!(st.range_query(..), n * (n - 1) / 2);
+    /// ```
+    pub fn par_from_vec(values: Vec<<Query as Monoid>::Set>) -> Self {
+        let n = values.len();
+        if n == 0 {
+            return Self::from(values);
+        }
+
+        let mut data = Box::<[<Query as Monoid>::Set]>::new_uninit_slice(n << 1);
+        for (slot, value) in data[n..].iter_mut().zip(values) {
+            slot.write(value);
+        }
+        // data[0] is a dummy node
+        data[0].write(<Query as Monoid>::identity());
+        crate::utility::par_build(&mut data, n, |parent, lhs, rhs| {
+            // SAFETY: the children are calculated before the parent.
+            parent.write(unsafe {
+                <Query as Monoid>::combine(lhs.assume_init_ref(), rhs.assume_init_ref())
+            });
+        });
+
+        Self {
+            // SAFETY: every node is initialized above.
+            data: unsafe { data.assume_init() },
+            len_or_offset: n,
+            counters: Counters::built(n),
+        }
+    }
+
+    /// Answers independent range queries in parallel.
+    ///
+    /// The `k`-th result is equal to `self.range_query(ranges[k].clone())`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*Q* log *N*) work
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_iter(1..=5);
+    /// assert_eq!(st.par_range_query_batch(&[0..2, 1..4, 3..3]), vec![3, 9, 0]);
+    /// ```
+    pub fn par_range_query_batch<R>(&self, ranges: &[R]) -> Vec<<Query as Monoid>::Set>
+    where
+        R: RangeBounds<usize> + Debug + Clone + Sync,
+    {
+        use rayon::prelude::*;
+
+        ranges
+            .par_iter()
+            .map(|range| self.range_query(range.clone()))
+            .collect()
+    }
+}
+
+impl<Query> SegmentTree<Query>
+where
+    Query: Group,
+{
+    /// Returns an iterator over the combined values of all the windows of length `window`,
+    /// in the order of their starting index.
+    ///
+    /// Each window is derived from the previous one with the [inverse](Group::inverse) element,
+    /// so that the iteration takes *O*(1) combinations per window.
+    /// The iterator is empty if `window` is larger than the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_iter([1, 2, 3, 4, 5]);
+    /// assert!(st.sliding_window_query(3).eq([6, 9, 12]));
+    /// ```
+    pub fn sliding_window_query(
+        &self,
+        window: usize,
+    ) -> impl Iterator<Item = <Query as Monoid>::Set> + '_ {
+        let leaves = self.as_leaf_slice();
+        let num_windows = (leaves.len() + 1).saturating_sub(window);
+        let mut acc = if num_windows > 0 {
+            self.range_query(..window)
+        } else {
+            <Query as Monoid>::identity()
+        };
+
+        (0..num_windows).map(move |i| {
+            let next = if i + 1 < num_windows {
+                self.counters.combine(2);
+                <Query as Monoid>::combine(
+                    &<Query as Monoid>::combine(&<Query as Group>::inverse(&leaves[i]), &acc),
+                    &leaves[i + window],
+                )
+            } else {
+                <Query as Monoid>::identity()
+            };
+
+            std::mem::replace(&mut acc, next)
+        })
+    }
+
+    /// Returns the combined value of the elements in `..end`.
+    ///
+    /// Prefixes cached by the caller are turned into the combined value of any range
+    /// by [`prefix_difference`](Self::prefix_difference) in *O*(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `end` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_iter([1, 2, 3, 4, 5]);
+    /// let prefixes = Vec::from_iter((0..=st.len()).map(|end| st.prefix_query(end)));
+    /// assert_eq!(prefixes, [0, 1, 3, 6, 10, 15]);
+    /// assert_eq!(SegmentTree::<Add<i32>>::prefix_difference(&prefixes[1], &prefixes[4]), 9);
+    /// ```
+    #[inline]
+    pub fn prefix_query(&self, end: usize) -> <Query as Monoid>::Set {
+        self.range_query(..end)
+    }
+
+    /// Returns the combined value of the elements in `start..end`
+    /// from the results of [`prefix_query(start)`](Self::prefix_query) and [`prefix_query(end)`](Self::prefix_query).
+    ///
+    /// The results should come from the same state of a tree and satisfy `start <= end`.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn prefix_difference(
+        prefix_start: &<Query as Monoid>::Set,
+        prefix_end: &<Query as Monoid>::Set,
+    ) -> <Query as Monoid>::Set {
+        // prefix_end = prefix_start · range
+        <Query as Monoid>::combine(&<Query as Group>::inverse(prefix_start), prefix_end)
+    }
+
+    /// Combines `element` to the right of the `i`-th element.
+    ///
+    /// The update is undone by [`point_undo`](Self::point_undo) with the same `element`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let mut st = SegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+    /// st.point_combine(1, &10);
+    /// assert_eq!(st.range_query(..), 16);
+    ///
+    /// st.point_undo(1, &10);
+    /// assert_eq!(st.as_leaf_slice(), [1, 2, 3]);
+    /// ```
+    pub fn point_combine(&mut self, i: usize, element: &<Query as Monoid>::Set) {
+        self.counters.combine(1);
+        self.point_update_with(i, |prev| <Query as Monoid>::combine(prev, element))
+    }
+
+    /// Undoes [`point_combine(i, element)`](Self::point_combine) by combining the [inverse](Group::inverse) of `element`.
+    ///
+    /// If the operation is not commutative, updates at the same index should be undone in the reverse order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N*)
+    pub fn point_undo(&mut self, i: usize, element: &<Query as Monoid>::Set) {
+        self.counters.combine(1);
+        let inverse = <Query as Group>::inverse(element);
+        self.point_update_with(i, |prev| <Query as Monoid>::combine(prev, &inverse))
+    }
+}
+
+impl<Query> SegmentTree<Query>
+where
+    Query: Monoid<Set: Element>,
+{
+    #[doc = include_str!("../doc/write_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<u32>>::from_iter(0..10);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let st = SegmentTree::<Add<u32>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(st.range_query(..), 45);
+    /// ```
+    pub fn write_snapshot<W>(&self, writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        snapshot::write(writer, self.len(), self.as_leaf_slice())
+    }
+
+    #[doc = include_str!("../doc/read_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Max};
+    ///
+    /// let st = SegmentTree::<Max<i64>>::from_iter([Some(3), None, Some(5)]);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let st = SegmentTree::<Max<i64>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(st.to_vec(), [Some(3), None, Some(5)]);
+    ///
+    /// // the element type is checked
+    /// assert!(SegmentTree::<Max<i32>>::read_snapshot(&bytes[..]).is_err());
+    /// ```
+    pub fn read_snapshot<R>(reader: R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        snapshot::read(reader).map(Self::from)
+    }
+}
+
+impl<Query> From<Vec<<Query as Monoid>::Set>> for SegmentTree<Query>
+where
+    Query: Monoid,
+{
+    /// Reuses the allocation of `values` for the nodes.
+    fn from(mut values: Vec<<Query as Monoid>::Set>) -> Self {
+        let n = values.len();
+        values.reserve_exact(n);
+
+        // SAFETY: the capacity is at least 2n and every node is initialized before `set_len(2n)`.
+        // The elements are leaked rather than dropped twice if `identity()` or `combine()` panics.
+        unsafe {
+            values.set_len(0);
+            let nodes = &mut values.spare_capacity_mut()[..n << 1];
+            let ptr = nodes.as_mut_ptr();
+            std::ptr::copy(ptr, ptr.add(n), n);
+            Self::build_nodes(nodes, n);
+            values.set_len(n << 1);
+        }
+
+        Self {
+            data: values.into_boxed_slice(),
+            len_or_offset: n,
+            counters: Counters::built(n),
+        }
+    }
+}
+
+impl<Query> From<&[<Query as Monoid>::Set]> for SegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn from(values: &[<Query as Monoid>::Set]) -> Self {
+        Self::from_slice(values)
+    }
+}
+
+impl<Query> FromIterator<<Query as Monoid>::Set> for SegmentTree<Query>
 where
     Query: Monoid,
 {
@@ -470,26 +1270,160 @@ where
         let iter = iter.into_iter();
         let (min, max) = iter.size_hint();
         if Some(min) == max {
-            let data = Vec::from_iter(
-                std::iter::repeat_with(<Query as Monoid>::identity)
-                    .take(min)
-                    .chain(iter),
-            )
-            .into_boxed_slice();
-
-            let mut tree = Self {
-                data,
-                len_or_offset: min,
-            };
-            tree.build();
-
-            tree
+            Self::from_exact_size_iter(min, iter)
         } else {
             Self::from(Vec::from_iter(iter))
         }
     }
 }
 
+impl<Query> From<SegmentTree<Query>> for Vec<<Query as Monoid>::Set>
+where
+    Query: Monoid,
+{
+    fn from(tree: SegmentTree<Query>) -> Self {
+        tree.into_vec()
+    }
+}
+
+impl<Query> IntoIterator for SegmentTree<Query>
+where
+    Query: Monoid,
+{
+    type Item = <Query as Monoid>::Set;
+    type IntoIter = std::vec::IntoIter<<Query as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, Query> IntoIterator for &'a SegmentTree<Query>
+where
+    Query: Monoid,
+{
+    type Item = &'a <Query as Monoid>::Set;
+    type IntoIter = std::slice::Iter<'a, <Query as Monoid>::Set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<Query> SegmentTree<Query>
+where
+    Query: Monoid<Set: Debug>,
+{
+    #[doc = include_str!("../doc/to_dot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+    /// let dot = st.to_dot();
+    /// assert!(dot.contains("{0..3 | combined: 6}"));
+    /// assert!(dot.contains("{2 | element: 3}"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = crate::render::Dot::new("SegmentTree");
+        self.render(&mut dot);
+        dot.finish()
+    }
+
+    #[doc = include_str!("../doc/pretty.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_iter([1, 2, 3, 4]);
+    /// assert_eq!(
+    ///     st.pretty(),
+    ///     "0: [0..4] combined: 10\n\
+    ///      1: [0..2] combined: 3  [2..4] combined: 7\n\
+    ///      2: [0] element: 1  [1] element: 2  [2] element: 3  [3] element: 4\n"
+    /// );
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut levels = crate::render::Levels::default();
+        self.render(&mut levels);
+        levels.finish()
+    }
+
+    #[doc = include_str!("../doc/to_mermaid.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+    /// let mermaid = st.to_mermaid();
+    /// assert!(mermaid.contains(r#"["0..3 | combined: 6"]"#));
+    /// assert!(mermaid.contains(r#"["2 | element: 3"]"#));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = crate::render::Mermaid::default();
+        self.render(&mut mermaid);
+        mermaid.finish()
+    }
+
+    /// Returns an adapter which [displays](std::fmt::Display) the tree like [`pretty`](Self::pretty).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and formatting takes as long as [`pretty`](Self::pretty)
+    pub fn display(&self) -> crate::Pretty<'_, Self> {
+        crate::Pretty(self)
+    }
+
+    /// Passes the nodes to `out` in level order.
+    pub(crate) fn render(&self, out: &mut impl crate::render::Render) {
+        let n = self.len_or_offset;
+        for i in 1..n << 1 {
+            let parent = (i > 1).then_some(i >> 1);
+            let depth = i.ilog2() as usize;
+            if i < n {
+                let range = crate::render::bottom_up_range(i, n);
+                out.node(i, parent, depth, &range, &[("combined", &self.data[i])]);
+            } else {
+                out.node(
+                    i,
+                    parent,
+                    depth,
+                    &(i - n).to_string(),
+                    &[("element", &self.data[i])],
+                );
+            }
+        }
+    }
+}
+
+/// Renders the tree like [`SegmentTree::pretty`].
+impl<Query> std::fmt::Display for crate::Pretty<'_, SegmentTree<Query>>
+where
+    Query: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.pretty())
+    }
+}
+
 impl<Query> Debug for SegmentTree<Query>
 where
     Query: Monoid<Set: Debug>,
@@ -510,15 +1444,85 @@ where
         Self {
             data: self.data.clone(),
             len_or_offset: self.len_or_offset,
+            counters: self.counters.clone(),
         }
     }
 }
 
+/// Creates an empty tree.
+impl<Query> Default for SegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Compares the elements.
+impl<Query> PartialEq for SegmentTree<Query>
+where
+    Query: Monoid<Set: PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.as_leaf_slice() == other.as_leaf_slice()
+    }
+}
+
+impl<Query> Eq for SegmentTree<Query> where Query: Monoid<Set: Eq> {}
+
+/// Hashes the elements, consistently with [`PartialEq`].
+impl<Query> Hash for SegmentTree<Query>
+where
+    Query: Monoid<Set: Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_leaf_slice().hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Query> serde::Serialize for SegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Serialize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::Leaves {
+                values: self.as_leaf_slice(),
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Query> serde::Deserialize<'de> for SegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Deserialize<'de>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <crate::serialize::Leaves<Vec<<Query as Monoid>::Set>> as serde::Deserialize>::deserialize(
+            deserializer,
+        )
+        .map(|leaves| Self::from(leaves.values))
+    }
+}
+
 #[cfg(test)]
 mod range_query {
     use rand::Rng;
 
-    use crate::{SegmentTree, ops::Add};
+    use crate::{
+        SegmentTree,
+        ops::{Add, ConcatString},
+    };
 
     fn template(n: usize) {
         let range_sum = SegmentTree::<Add<usize>>::from_iter(0..n);
@@ -536,6 +1540,21 @@ mod range_query {
         }
     }
 
+    #[test]
+    fn small_non_commutative() {
+        for n in 0..=40 {
+            let st = SegmentTree::<ConcatString>::from_iter((0..n).map(|i| i.to_string()));
+            for i in 0..=n {
+                for j in i..=n {
+                    assert_eq!(
+                        st.range_query(i..j),
+                        String::from_iter((i..j).map(|i| i.to_string()))
+                    )
+                }
+            }
+        }
+    }
+
     #[test]
     fn random() {
         let mut rng = rand::rng();
@@ -545,6 +1564,278 @@ mod range_query {
     }
 }
 
+#[cfg(test)]
+mod construction {
+    use crate::{
+        SegmentTree,
+        ops::{Add, ConcatString},
+    };
+
+    /// An iterator whose size hint is exact but wrong.
+    struct Liar<I> {
+        iter: I,
+        hint: usize,
+    }
+
+    impl<I: Iterator> Iterator for Liar<I> {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.iter.next()
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.hint, Some(self.hint))
+        }
+    }
+
+    #[test]
+    fn wrong_size_hint() {
+        for n in 0..20 {
+            for hint in 0..20 {
+                let st = SegmentTree::<Add<usize>>::from_iter(Liar { iter: 0..n, hint });
+                assert_eq!(st.len(), n);
+                assert_eq!(st.range_query(..), n * n.saturating_sub(1) / 2);
+                assert_eq!(st.to_vec(), Vec::from_iter(0..n));
+            }
+        }
+    }
+
+    #[test]
+    fn from_vec() {
+        for n in 0..20 {
+            let st = SegmentTree::<Add<usize>>::from(Vec::from_iter(0..n));
+            for i in 0..=n {
+                for j in i..=n {
+                    assert_eq!(st.range_query(i..j), (i..j).sum::<usize>())
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_from_vec() {
+        for n in (0..100).chain([4095, 4096, 4097, 10_000, 100_000]) {
+            let values = Vec::from_iter(0..n);
+            let st = SegmentTree::<Add<usize>>::par_from_vec(values.clone());
+            assert_eq!(st.data, SegmentTree::<Add<usize>>::from(values).data);
+        }
+    }
+
+    #[test]
+    fn from_vec_with_heap_elements() {
+        for n in 0..20 {
+            let values = Vec::from_iter((0..n).map(|i| i.to_string()));
+            let st = SegmentTree::<ConcatString>::from(values.clone());
+            assert_eq!(st.range_query(..), values.concat());
+            assert_eq!(st.to_vec(), values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod swap {
+    use rand::Rng;
+
+    use crate::{Monoid, SegmentTree, ops::Affine};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = Vec::from_iter((0..n).map(|_| {
+                (
+                    rng.random_range(0..3_usize) as i64 - 1,
+                    rng.random_range(0..11_usize) as i64 - 5,
+                )
+            }));
+            let mut st = SegmentTree::<Affine<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let [i, j] = [rng.random_range(0..n), rng.random_range(0..n)];
+                st.swap(i, j);
+                naive.swap(i, j);
+
+                for l in 0..n {
+                    for r in l..=n {
+                        let expected = naive[l..r]
+                            .iter()
+                            .fold(Affine::identity(), |acc, e| Affine::combine(&acc, e));
+                        assert_eq!(st.range_query(l..r), expected)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod zero_length {
+    use crate::{SegmentTree, ops::Add};
+
+    #[test]
+    fn identity() {
+        let mut tree = SegmentTree::<Add<i64>>::new(0);
+        assert!(tree.is_empty());
+        assert_eq!(tree.len(), 0);
+
+        assert_eq!(tree.range_query(..), 0);
+        assert_eq!(tree.range_query(0..0), 0);
+        assert_eq!(tree.partition_end(0, |v| *v < 10), 0);
+        assert_eq!(tree.partition_start(0, |v| *v < 10), 0);
+        assert_eq!(tree.iter().count(), 0);
+        assert!(tree.to_vec().is_empty());
+
+        tree.assign_from_iter(std::iter::empty());
+        assert!(tree.into_vec().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod apply_range_with {
+    use rand::Rng;
+
+    use crate::{Monoid, SegmentTree, ops::Affine};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 0..40 {
+            let mut naive = Vec::from_iter((0..n).map(|_| (1, rng.random_range(-5..=5_i64))));
+            let mut st = SegmentTree::<Affine<i64>>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let a = rng.random_range(-1..=1);
+                // depends on the element, so not a monoid action
+                let f = |e: &mut (i64, i64)| {
+                    *e = if e.1 % 2 == 0 {
+                        (a, e.1 / 2)
+                    } else {
+                        (e.0, e.1 + a)
+                    }
+                };
+                st.apply_range_with(l..r, f);
+                naive[l..r].iter_mut().for_each(f);
+
+                assert_eq!(st.as_leaf_slice(), naive);
+                for l in 0..n {
+                    for r in l..=n {
+                        let expected = naive[l..r]
+                            .iter()
+                            .fold(Affine::identity(), |acc, e| Affine::combine(&acc, e));
+                        assert_eq!(st.range_query(l..r), expected)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod sliding_window_query {
+    use rand::Rng;
+
+    use crate::{SegmentTree, ops::Add};
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 0..30 {
+            let naive = Vec::from_iter((0..n).map(|_| rng.random_range(0..21_usize) as i64 - 10));
+            let st = SegmentTree::<Add<i64>>::from_slice(&naive);
+
+            for window in 0..=n + 1 {
+                let expected = if window <= n {
+                    Vec::from_iter(
+                        (0..=n - window).map(|i| naive[i..i + window].iter().sum::<i64>()),
+                    )
+                } else {
+                    Vec::new()
+                };
+                assert_eq!(Vec::from_iter(st.sliding_window_query(window)), expected);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod group {
+    use rand::Rng;
+
+    use crate::{
+        SegmentTree,
+        ops::{Add, BitXor},
+    };
+
+    #[test]
+    fn prefix_difference() {
+        type Query = (Add<i64>, BitXor<u32>);
+
+        let mut rng = rand::rng();
+        for n in 0..30 {
+            let naive = Vec::from_iter(
+                (0..n).map(|_| (rng.random_range(-10..=10), rng.random_range(0..16))),
+            );
+            let st = SegmentTree::<Query>::from_slice(&naive);
+            let fold = |values: &[(i64, u32)]| {
+                values
+                    .iter()
+                    .fold((0, 0), |acc, v| (acc.0 + v.0, acc.1 ^ v.1))
+            };
+
+            let prefixes = Vec::from_iter((0..=n).map(|end| st.prefix_query(end)));
+            for end in 0..=n {
+                assert_eq!(prefixes[end], fold(&naive[..end]));
+                for start in 0..=end {
+                    assert_eq!(
+                        SegmentTree::<Query>::prefix_difference(&prefixes[start], &prefixes[end]),
+                        fold(&naive[start..end]),
+                    )
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn point_undo() {
+        const N: usize = 20;
+
+        let mut rng = rand::rng();
+        let initial = Vec::from_iter((0..N).map(|_| rng.random_range(-10..=10)));
+        let mut naive = initial.clone();
+        let mut st = SegmentTree::<Add<i64>>::from_slice(&initial);
+
+        let mut history = Vec::new();
+        for _ in 0..200 {
+            if history.is_empty() || rng.random_bool(0.6) {
+                let (i, element) = (rng.random_range(0..N), rng.random_range(-10..=10));
+                st.point_combine(i, &element);
+                naive[i] += element;
+                history.push((i, element));
+            } else {
+                let (i, element) = history.pop().unwrap();
+                st.point_undo(i, &element);
+                naive[i] -= element;
+            }
+
+            assert_eq!(st.as_leaf_slice(), naive);
+            for l in 0..=N {
+                for r in l..=N {
+                    assert_eq!(st.range_query(l..r), naive[l..r].iter().sum::<i64>())
+                }
+            }
+        }
+
+        while let Some((i, element)) = history.pop() {
+            st.point_undo(i, &element)
+        }
+        assert_eq!(st.as_leaf_slice(), initial);
+    }
+}
+
 #[cfg(test)]
 mod partition_end {
     use rand::Rng;
@@ -561,7 +1852,7 @@ mod partition_end {
             let range_sum_query =
                 SegmentTree::<Add<isize>>::from_iter(std::iter::repeat_n(1, size as usize));
             for start in 0..=size {
-                for sum in -OFFSET..=size as isize + OFFSET {
+                for sum in -OFFSET..=size + OFFSET {
                     assert_eq!(
                         range_sum_query.partition_end(start as usize, |&v| v <= sum),
                         (start + sum).clamp(start, size) as usize,
@@ -578,7 +1869,7 @@ mod partition_end {
         const SIZE: u32 = 100;
 
         // *O*(*N*)
-        fn naive(values: &Vec<u32>, start: usize, pred: impl Fn(&u32) -> bool) -> usize {
+        fn naive(values: &[u32], start: usize, pred: impl Fn(&u32) -> bool) -> usize {
             let additional = values[start..]
                 .iter()
                 .scan(0, |acc, v| {
@@ -624,7 +1915,7 @@ mod partition_start {
             let range_sum_query =
                 SegmentTree::<Add<isize>>::from_iter(std::iter::repeat_n(1, size as usize));
             for end in 0..=size {
-                for sum in -OFFSET..=size as isize + OFFSET {
+                for sum in -OFFSET..=size + OFFSET {
                     assert_eq!(
                         range_sum_query.partition_start(end as usize, |&v| v <= sum),
                         (end - sum).clamp(0, end) as usize,
@@ -640,7 +1931,7 @@ mod partition_start {
         const SIZE: u32 = 100;
 
         // *O*(*N*)
-        fn naive(values: &Vec<u32>, end: usize, pred: impl Fn(&u32) -> bool) -> usize {
+        fn naive(values: &[u32], end: usize, pred: impl Fn(&u32) -> bool) -> usize {
             let additional = values[..end]
                 .iter()
                 .rev()
@@ -671,6 +1962,31 @@ mod partition_start {
             }
         }
     }
+
+    #[test]
+    fn exhaustive_zero_one() {
+        const MAX_SIZE: usize = 10;
+
+        for size in 0..=MAX_SIZE {
+            for bits in 0..1_u32 << size {
+                let values = Vec::from_iter((0..size).map(|i| bits >> i & 1));
+                let range_sum_query = SegmentTree::<Add<u32>>::from(values.clone());
+
+                for end in 0..=size {
+                    for sum in 0..=size as u32 {
+                        let expected = (0..=end)
+                            .find(|&start| values[start..end].iter().sum::<u32>() <= sum)
+                            .unwrap();
+                        assert_eq!(
+                            range_sum_query.partition_start(end, |v| *v <= sum),
+                            expected,
+                            "end: {end}, sum: {sum}, values: {values:?}"
+                        )
+                    }
+                }
+            }
+        }
+    }
 }
 
 // pub struct IterMut<'a, Query>
@@ -0,0 +1,16 @@
+//! Numeric traits required by the [operations](crate::ops) and [actions](crate::acts).
+//!
+//! With the `num` feature (enabled by default), these are re-exported from `num-traits` and `num-integer`,
+//! so any type implementing them, e.g. big integers, can be used.
+//! Otherwise, minimal equivalents are defined in this crate and implemented for primitive integers and floats,
+//! which makes the crate free of dependencies.
+
+#[cfg(feature = "num")]
+pub use num_integer::Integer;
+#[cfg(feature = "num")]
+pub use num_traits::{CheckedAdd, FromPrimitive, One, SaturatingAdd, Zero, float::TotalOrder};
+
+#[cfg(not(feature = "num"))]
+mod primitive;
+#[cfg(not(feature = "num"))]
+pub use primitive::{CheckedAdd, FromPrimitive, Integer, One, SaturatingAdd, TotalOrder, Zero};
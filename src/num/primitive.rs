@@ -0,0 +1,209 @@
+use std::{
+    cmp::Ordering,
+    ops::{Add, Mul},
+};
+
+/// Defines an additive identity element for `Self`.
+pub trait Zero: Sized + Add<Self, Output = Self> {
+    /// Returns the additive identity element, `0`.
+    fn zero() -> Self;
+
+    /// Returns `true` if `self` is equal to the additive identity.
+    fn is_zero(&self) -> bool;
+}
+
+/// Defines a multiplicative identity element for `Self`.
+pub trait One: Sized + Mul<Self, Output = Self> {
+    /// Returns the multiplicative identity element, `1`.
+    fn one() -> Self;
+
+    /// Returns `true` if `self` is equal to the multiplicative identity.
+    fn is_one(&self) -> bool
+    where
+        Self: PartialEq,
+    {
+        *self == Self::one()
+    }
+}
+
+/// Converts primitive integers into `Self` if representable.
+pub trait FromPrimitive: Sized {
+    /// Converts an `i64` to `Self`.
+    fn from_i64(n: i64) -> Option<Self>;
+
+    /// Converts an `u64` to `Self`.
+    fn from_u64(n: u64) -> Option<Self>;
+
+    /// Converts an `usize` to `Self`.
+    fn from_usize(n: usize) -> Option<Self> {
+        Self::from_u64(n as u64)
+    }
+}
+
+/// Performs addition that returns [`None`] instead of overflowing.
+pub trait CheckedAdd: Sized + Add<Self, Output = Self> {
+    /// Adds two numbers, returning [`None`] if overflow occurred.
+    fn checked_add(&self, v: &Self) -> Option<Self>;
+}
+
+/// Performs addition that saturates at the numeric bounds instead of overflowing.
+pub trait SaturatingAdd: Sized + Add<Self, Output = Self> {
+    /// Adds two numbers, saturating at the numeric bounds.
+    fn saturating_add(&self, v: &Self) -> Self;
+}
+
+/// Provides the total ordering of IEEE 754 floating-point numbers.
+pub trait TotalOrder {
+    /// Returns the ordering between `self` and `other` in the manner of [`f64::total_cmp`].
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+/// Provides integer operations.
+pub trait Integer: Sized + Ord + Zero + One {
+    /// Returns the greatest common divisor, which is non-negative.
+    ///
+    /// `gcd(0, 0)` is `0`.
+    fn gcd(&self, other: &Self) -> Self;
+
+    /// Returns the least common multiple, which is non-negative.
+    ///
+    /// `lcm(0, n)` is `0`.
+    fn lcm(&self, other: &Self) -> Self;
+}
+
+macro_rules! number_impl {
+    ( $zero:literal, $one:literal; $( $t:ty ),* ) => {$(
+        impl Zero for $t {
+            #[inline]
+            fn zero() -> Self {
+                $zero
+            }
+
+            #[inline]
+            fn is_zero(&self) -> bool {
+                *self == $zero
+            }
+        }
+
+        impl One for $t {
+            #[inline]
+            fn one() -> Self {
+                $one
+            }
+        }
+    )*};
+}
+
+number_impl!(0, 1; i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+number_impl!(0.0, 1.0; f32, f64);
+
+macro_rules! integer_impl {
+    ( $( $t:ty => $u:ty ),* ) => {$(
+        impl FromPrimitive for $t {
+            #[inline]
+            fn from_i64(n: i64) -> Option<Self> {
+                Self::try_from(n).ok()
+            }
+
+            #[inline]
+            fn from_u64(n: u64) -> Option<Self> {
+                Self::try_from(n).ok()
+            }
+
+            #[inline]
+            fn from_usize(n: usize) -> Option<Self> {
+                Self::try_from(n).ok()
+            }
+        }
+
+        impl CheckedAdd for $t {
+            #[inline]
+            fn checked_add(&self, v: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *v)
+            }
+        }
+
+        impl SaturatingAdd for $t {
+            #[inline]
+            fn saturating_add(&self, v: &Self) -> Self {
+                <$t>::saturating_add(*self, *v)
+            }
+        }
+
+        impl Integer for $t {
+            #[inline]
+            fn gcd(&self, other: &Self) -> Self {
+                // Euclidean algorithm on the absolute values
+                let [mut a, mut b]: [$u; 2] = [self.abs_diff(0), other.abs_diff(0)];
+                while b != 0 {
+                    [a, b] = [b, a % b]
+                }
+                a as $t
+            }
+
+            #[inline]
+            fn lcm(&self, other: &Self) -> Self {
+                if self.is_zero() && other.is_zero() {
+                    return 0;
+                }
+                let gcd = self.gcd(other);
+                (*self / gcd * *other).abs_diff(0) as $t
+            }
+        }
+    )*};
+}
+
+integer_impl!(
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize,
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize
+);
+
+macro_rules! float_impl {
+    ( $( $t:ty ),* ) => {$(
+        impl FromPrimitive for $t {
+            #[inline]
+            fn from_i64(n: i64) -> Option<Self> {
+                Some(n as $t)
+            }
+
+            #[inline]
+            fn from_u64(n: u64) -> Option<Self> {
+                Some(n as $t)
+            }
+        }
+
+        impl TotalOrder for $t {
+            #[inline]
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                <$t>::total_cmp(self, other)
+            }
+        }
+    )*};
+}
+
+float_impl!(f32, f64);
+
+#[cfg(test)]
+mod integer {
+    use super::Integer;
+
+    #[test]
+    fn gcd_lcm() {
+        for a in -30_i64..=30 {
+            for b in -30_i64..=30 {
+                let gcd = (1..=30)
+                    .rev()
+                    .find(|d| a % d == 0 && b % d == 0)
+                    .filter(|_| a != 0 || b != 0)
+                    .unwrap_or(0);
+                assert_eq!(a.gcd(&b), gcd, "gcd({a}, {b})");
+                let lcm = if a == 0 || b == 0 {
+                    0
+                } else {
+                    (a * b).abs() / gcd
+                };
+                assert_eq!(a.lcm(&b), lcm, "lcm({a}, {b})");
+            }
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 
-use num_traits::Zero;
+use crate::num::Zero;
 
-use crate::traits::Monoid;
+use crate::traits::{CommutativeMonoid, Group, Monoid};
 
 /// Performs `+` operation.
 // ANCHOR: def_and_impl_monoid
@@ -25,5 +25,26 @@ where
     fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
         lhs_or_prev + rhs_or_new
     }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_zero()
+    }
 }
 // ANCHOR_END: def_and_impl_monoid
+
+impl<T> CommutativeMonoid for Add<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::Add<Output = T>,
+{
+}
+
+impl<T> Group for Add<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::Add<Output = T> + std::ops::Neg<Output = T>,
+{
+    fn inverse(element: &Self::Set) -> Self::Set {
+        -element
+    }
+}
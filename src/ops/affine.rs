@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 
-use num_traits::{One, Zero};
+use crate::num::{One, Zero};
 
 use crate::traits::Monoid;
 
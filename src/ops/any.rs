@@ -0,0 +1,42 @@
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs `||` operation on [`bool`]s.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::Any};
+///
+/// let seg = SegmentTree::<Any>::from(vec![false, true, false, false]);
+///
+/// assert!(seg.range_query(..));
+/// assert!(!seg.range_query(2..));
+/// assert_eq!(seg.range_query(2..2), false);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Any;
+
+impl Monoid for Any {
+    type Set = bool;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        false
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        *lhs_or_prev || *rhs_or_new
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 { Self::identity() } else { *element }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        !*element
+    }
+}
+
+impl CommutativeMonoid for Any {}
@@ -0,0 +1,81 @@
+use std::{cmp::Ordering, marker::PhantomData};
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Finds the maximum together with its index, preferring the leftmost one on ties.
+///
+/// Each element is `Some((value, index))`.
+/// Use [`ArgMax::enumerate()`] to attach the indices.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::ArgMax};
+///
+/// let seg = SegmentTree::<ArgMax<i32>>::from_iter(ArgMax::enumerate([3, 1, 3, 1, 2]));
+///
+/// assert_eq!(seg.range_query(..), Some((3, 0)));
+/// assert_eq!(seg.range_query(2..), Some((3, 2)));
+/// assert_eq!(seg.range_query(2..2), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArgMax<T>(PhantomData<T>);
+
+impl<T> ArgMax<T> {
+    /// Attaches the indices to the values.
+    pub fn enumerate<I>(iter: I) -> impl Iterator<Item = Option<(T, usize)>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        iter.into_iter()
+            .enumerate()
+            .map(|(index, value)| Some((value, index)))
+    }
+}
+
+impl<T> Monoid for ArgMax<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+    type Set = Option<(T, usize)>;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        None
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        match (lhs_or_prev, rhs_or_new) {
+            (None, None) => None,
+            (None, Some(_)) => rhs_or_new.clone(),
+            (Some(_), None) => lhs_or_prev.clone(),
+            (Some(lhs), Some(rhs)) => match Ord::cmp(&&lhs.0, &&rhs.0) {
+                Ordering::Greater => lhs_or_prev.clone(),
+                Ordering::Equal if lhs.1 <= rhs.1 => lhs_or_prev.clone(),
+                _ => rhs_or_new.clone(),
+            },
+        }
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            element.clone()
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
+
+impl<T> CommutativeMonoid for ArgMax<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+}
@@ -0,0 +1,81 @@
+use std::{cmp::Ordering, marker::PhantomData};
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Finds the minimum together with its index, preferring the leftmost one on ties.
+///
+/// Each element is `Some((value, index))`.
+/// Use [`ArgMin::enumerate()`] to attach the indices.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::ArgMin};
+///
+/// let seg = SegmentTree::<ArgMin<i32>>::from_iter(ArgMin::enumerate([3, 1, 3, 1, 2]));
+///
+/// assert_eq!(seg.range_query(..), Some((1, 1)));
+/// assert_eq!(seg.range_query(2..), Some((1, 3)));
+/// assert_eq!(seg.range_query(2..2), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArgMin<T>(PhantomData<T>);
+
+impl<T> ArgMin<T> {
+    /// Attaches the indices to the values.
+    pub fn enumerate<I>(iter: I) -> impl Iterator<Item = Option<(T, usize)>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        iter.into_iter()
+            .enumerate()
+            .map(|(index, value)| Some((value, index)))
+    }
+}
+
+impl<T> Monoid for ArgMin<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+    type Set = Option<(T, usize)>;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        None
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        match (lhs_or_prev, rhs_or_new) {
+            (None, None) => None,
+            (None, Some(_)) => rhs_or_new.clone(),
+            (Some(_), None) => lhs_or_prev.clone(),
+            (Some(lhs), Some(rhs)) => match Ord::cmp(&&lhs.0, &&rhs.0) {
+                Ordering::Less => lhs_or_prev.clone(),
+                Ordering::Equal if lhs.1 <= rhs.1 => lhs_or_prev.clone(),
+                _ => rhs_or_new.clone(),
+            },
+        }
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            element.clone()
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
+
+impl<T> CommutativeMonoid for ArgMin<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+}
@@ -21,4 +21,23 @@ where
     fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
         rhs_or_new.as_ref().or(lhs_or_prev.as_ref()).cloned()
     }
+
+    fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+        if rhs_or_new.is_some() {
+            lhs_or_prev.clone_from(rhs_or_new)
+        }
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            Self::combine(element, element)
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
 }
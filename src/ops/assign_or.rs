@@ -47,6 +47,13 @@ where
             }
         }
     }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        match element {
+            AssignOr::Assign(element) => element.is_none(),
+            AssignOr::Other(element) => <M as Monoid>::is_identity(element),
+        }
+    }
 }
 
 impl<M> Debug for AssignOr<M>
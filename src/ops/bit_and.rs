@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 
-use num_traits::Zero;
+use crate::num::Zero;
 
-use crate::traits::Monoid;
+use crate::traits::{CommutativeMonoid, Monoid};
 
 /// Performs `&` operation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -24,4 +24,20 @@ where
     fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
         lhs_or_prev & rhs_or_new
     }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            Self::combine(element, element)
+        }
+    }
+}
+
+impl<T> CommutativeMonoid for BitAnd<T>
+where
+    T: Zero + std::ops::Not<Output = T>,
+    for<'a> &'a T: std::ops::BitAnd<Output = T>,
+{
 }
@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 
-use num_traits::Zero;
+use crate::num::Zero;
 
-use crate::traits::Monoid;
+use crate::traits::{CommutativeMonoid, Monoid};
 
 /// Performs `|` operation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -24,4 +24,24 @@ where
     fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
         lhs_or_prev | rhs_or_new
     }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            Self::combine(element, element)
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_zero()
+    }
+}
+
+impl<T> CommutativeMonoid for BitOr<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::BitOr<Output = T>,
+{
 }
@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 
-use num_traits::Zero;
+use crate::num::Zero;
 
-use crate::traits::Monoid;
+use crate::traits::{CommutativeMonoid, Group, Monoid};
 
 /// Performs `^` operation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -24,4 +24,33 @@ where
     fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
         lhs_or_prev ^ rhs_or_new
     }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        if n & 1 == 0 {
+            Self::identity()
+        } else {
+            Self::combine(&Self::identity(), element)
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_zero()
+    }
+}
+
+impl<T> CommutativeMonoid for BitXor<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::BitXor<Output = T>,
+{
+}
+
+impl<T> Group for BitXor<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::BitXor<Output = T>,
+{
+    fn inverse(element: &Self::Set) -> Self::Set {
+        Self::combine(&Self::identity(), element)
+    }
 }
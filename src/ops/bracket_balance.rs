@@ -0,0 +1,63 @@
+use crate::traits::Monoid;
+
+/// Matches brackets greedily.
+///
+/// Each element is `(matched pairs, unmatched closing brackets, unmatched opening brackets)`.
+/// Use [`BracketBalance::OPEN`] and [`BracketBalance::CLOSE`] to create leaves.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::BracketBalance};
+///
+/// let mut seg = SegmentTree::<BracketBalance>::from_iter("(()))(".chars().map(|c| match c {
+///     '(' => BracketBalance::OPEN,
+///     _ => BracketBalance::CLOSE,
+/// }));
+///
+/// assert!(BracketBalance::is_valid(&seg.range_query(..4)));
+/// assert!(!BracketBalance::is_valid(&seg.range_query(..)));
+/// assert_eq!(seg.range_query(..).0, 2);
+///
+/// seg.point_update(4, BracketBalance::OPEN);
+/// assert_eq!(seg.range_query(3..), (0, 1, 2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BracketBalance;
+
+impl BracketBalance {
+    /// An opening bracket.
+    pub const OPEN: (usize, usize, usize) = (0, 0, 1);
+    /// A closing bracket.
+    pub const CLOSE: (usize, usize, usize) = (0, 1, 0);
+
+    /// Returns `true` if the brackets form a valid (balanced) sequence.
+    pub const fn is_valid(element: &(usize, usize, usize)) -> bool {
+        element.1 == 0 && element.2 == 0
+    }
+}
+
+impl Monoid for BracketBalance {
+    type Set = (usize, usize, usize);
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        (0, 0, 0)
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        let &(l_matched, l_close, l_open) = lhs_or_prev;
+        let &(r_matched, r_close, r_open) = rhs_or_new;
+        let matched = l_open.min(r_close);
+        (
+            l_matched + r_matched + matched,
+            l_close + r_close - matched,
+            l_open + r_open - matched,
+        )
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        *element == (0, 0, 0)
+    }
+}
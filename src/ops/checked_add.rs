@@ -0,0 +1,49 @@
+use std::marker::PhantomData;
+
+use crate::num::Zero;
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs `+` operation, which results in [`None`] on overflow.
+///
+/// # Warning
+///
+/// Checked addition is associative only if all the elements have the same sign,
+/// e.g. for unsigned integers.
+/// Otherwise, whether an intermediate sum overflows depends on the shape of the tree.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::CheckedAdd};
+///
+/// let seg = SegmentTree::<CheckedAdd<u8>>::from(vec![Some(100), Some(100), Some(100)]);
+///
+/// assert_eq!(seg.range_query(..2), Some(200));
+/// assert_eq!(seg.range_query(..), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CheckedAdd<T>(PhantomData<T>);
+
+impl<T> Monoid for CheckedAdd<T>
+where
+    T: Zero + crate::num::CheckedAdd,
+{
+    type Set = Option<T>;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        Some(T::zero())
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        lhs_or_prev.as_ref()?.checked_add(rhs_or_new.as_ref()?)
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.as_ref().is_some_and(Zero::is_zero)
+    }
+}
+
+impl<T> CommutativeMonoid for CheckedAdd<T> where T: Zero + crate::num::CheckedAdd {}
@@ -0,0 +1,94 @@
+use std::marker::PhantomData;
+
+use crate::traits::Monoid;
+
+/// Performs concatenation of [`Vec`]s.
+///
+/// # Complexity
+///
+/// Unlike most operations, [`combine`](Monoid::combine) takes time proportional to the lengths of the operands.
+/// Every node stores its whole segment, so the tree takes *O*(*N* log *N*) memory
+/// and a range query takes *O*(*K* log *N*) time, where *K* is the length of the result.
+/// Prefer this only for short sequences or for debugging.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::Concat};
+///
+/// let mut seg = SegmentTree::<Concat<i32>>::from_iter((0..5).map(|i| vec![i]));
+/// seg.point_update(2, vec![]);
+///
+/// assert_eq!(seg.range_query(1..4), vec![1, 3]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Concat<T>(PhantomData<T>);
+
+impl<T> Monoid for Concat<T>
+where
+    T: Clone,
+{
+    type Set = Vec<T>;
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        Vec::new()
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        let mut res = Vec::with_capacity(lhs_or_prev.len() + rhs_or_new.len());
+        res.extend_from_slice(lhs_or_prev);
+        res.extend_from_slice(rhs_or_new);
+        res
+    }
+
+    fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+        lhs_or_prev.extend_from_slice(rhs_or_new)
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_empty()
+    }
+}
+
+/// Performs concatenation of [`String`]s.
+///
+/// See [`Concat`] for the complexity caveats.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::ConcatString};
+///
+/// let seg = SegmentTree::<ConcatString>::from_iter(["seg", "ment", " ", "tree"].map(String::from));
+///
+/// assert_eq!(seg.range_query(..2), "segment");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConcatString;
+
+impl Monoid for ConcatString {
+    type Set = String;
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        String::new()
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        let mut res = String::with_capacity(lhs_or_prev.len() + rhs_or_new.len());
+        res.push_str(lhs_or_prev);
+        res.push_str(rhs_or_new);
+        res
+    }
+
+    fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+        lhs_or_prev.push_str(rhs_or_new)
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_empty()
+    }
+}
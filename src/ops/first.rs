@@ -0,0 +1,60 @@
+use std::marker::PhantomData;
+
+use crate::traits::Monoid;
+
+/// Keeps the leftmost present element.
+///
+/// [`None`] represents an absent element.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::First};
+///
+/// // find the leftmost set cell in a range
+/// let cells = [false, true, false, true, false, false, true, false];
+/// let seg = SegmentTree::<First<usize>>::from_iter(
+///     cells.iter().enumerate().map(|(i, &is_set)| is_set.then_some(i)),
+/// );
+///
+/// assert_eq!(seg.range_query(2..7), Some(3));
+/// assert_eq!(seg.range_query(4..6), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct First<T>(PhantomData<T>);
+
+impl<T> Monoid for First<T>
+where
+    T: Clone,
+{
+    type Set = Option<T>;
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        None
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        lhs_or_prev.as_ref().or(rhs_or_new.as_ref()).cloned()
+    }
+
+    fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+        if lhs_or_prev.is_none() {
+            lhs_or_prev.clone_from(rhs_or_new)
+        }
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            element.clone()
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
@@ -0,0 +1,61 @@
+use std::marker::PhantomData;
+
+use crate::num::TotalOrder;
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs `chmax` operation on floating-point numbers.
+///
+/// Unlike [`Max`](crate::ops::Max), this accepts [`f32`] and [`f64`] by comparing them with `total_cmp`.
+/// In the total order, `-0.0 < +0.0` and a positive NaN is greater than any other value.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::FloatMax};
+///
+/// let seg = SegmentTree::<FloatMax<f64>>::from_iter([1.0, -0.0, 3.5, 0.0].map(Some));
+///
+/// assert_eq!(seg.range_query(..), Some(3.5));
+/// assert_eq!(seg.range_query(2..2), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FloatMax<T>(PhantomData<T>);
+
+impl<T> Monoid for FloatMax<T>
+where
+    T: Copy + TotalOrder,
+{
+    type Set = Option<T>;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        None
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        match (lhs_or_prev, rhs_or_new) {
+            (None, _) => *rhs_or_new,
+            (_, None) => *lhs_or_prev,
+            (Some(lhs), Some(rhs)) => {
+                if lhs.total_cmp(rhs).is_gt() {
+                    *lhs_or_prev
+                } else {
+                    *rhs_or_new
+                }
+            }
+        }
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 { Self::identity() } else { *element }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
+
+impl<T> CommutativeMonoid for FloatMax<T> where T: Copy + TotalOrder {}
@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 
-use num_integer::Integer;
+use crate::num::Integer;
 
-use crate::traits::Monoid;
+use crate::traits::{CommutativeMonoid, Monoid};
 
 /// Performs `gcd` operation.
 ///
@@ -28,4 +28,19 @@ where
     fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
         lhs_or_prev.gcd(rhs_or_new)
     }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            Self::combine(element, element)
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_zero()
+    }
 }
+
+impl<T> CommutativeMonoid for GCD<T> where T: Integer {}
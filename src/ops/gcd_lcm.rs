@@ -0,0 +1,69 @@
+use std::marker::PhantomData;
+
+use crate::num::Integer;
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs `gcd` and `lcm` operations at once.
+///
+/// Each element is `(gcd, lcm)`.
+/// Use [`GcdLcm::singleton()`] to create a leaf.
+/// See [`GCD`](crate::ops::GCD) and [`LCM`](crate::ops::LCM) for the identity elements.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::GcdLcm};
+///
+/// let seg = SegmentTree::<GcdLcm<u64>>::from_iter([4, 6, 10].map(GcdLcm::singleton));
+///
+/// assert_eq!(seg.range_query(..), (2, 60));
+/// assert_eq!(seg.range_query(1..1), (0, 1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GcdLcm<T>(PhantomData<T>);
+
+impl<T> GcdLcm<T>
+where
+    T: Clone,
+{
+    /// Returns the element which consists of a single value.
+    pub fn singleton(value: T) -> (T, T) {
+        (value.clone(), value)
+    }
+}
+
+impl<T> Monoid for GcdLcm<T>
+where
+    T: Integer,
+{
+    type Set = (T, T);
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        (T::zero(), T::one())
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        (
+            lhs_or_prev.0.gcd(&rhs_or_new.0),
+            lhs_or_prev.1.lcm(&rhs_or_new.1),
+        )
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            Self::combine(element, element)
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.0.is_zero() && element.1.is_one()
+    }
+}
+
+impl<T> CommutativeMonoid for GcdLcm<T> where T: Integer {}
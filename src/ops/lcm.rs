@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 
-use num_integer::Integer;
+use crate::num::Integer;
 
-use crate::traits::Monoid;
+use crate::traits::{CommutativeMonoid, Monoid};
 
 /// Performs `lcm` operation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -24,4 +24,19 @@ where
     fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
         lhs_or_prev.lcm(rhs_or_new)
     }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            Self::combine(element, element)
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_one()
+    }
 }
+
+impl<T> CommutativeMonoid for LCM<T> where T: Integer {}
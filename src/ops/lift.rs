@@ -0,0 +1,68 @@
+use std::marker::PhantomData;
+
+use crate::traits::{Monoid, Semigroup};
+
+/// Lifts a [`Semigroup`] to a [`Monoid`] by adjoining [`None`] as the identity element.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, Semigroup, ops::Lift};
+///
+/// /// Keeps the leftmost element.
+/// struct First;
+///
+/// impl Semigroup for First {
+///     type Set = char;
+///
+///     const IS_COMMUTATIVE: bool = false;
+///
+///     fn combine(lhs_or_prev: &Self::Set, _rhs_or_new: &Self::Set) -> Self::Set {
+///         *lhs_or_prev
+///     }
+/// }
+///
+/// let seg = SegmentTree::<Lift<First>>::from_iter("abc".chars().map(Some));
+///
+/// assert_eq!(seg.range_query(1..), Some('b'));
+/// assert_eq!(seg.range_query(3..), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lift<S>(PhantomData<S>);
+
+impl<S> Monoid for Lift<S>
+where
+    S: Semigroup<Set: Clone>,
+{
+    type Set = Option<<S as Semigroup>::Set>;
+
+    const IS_COMMUTATIVE: bool = <S as Semigroup>::IS_COMMUTATIVE;
+
+    fn identity() -> Self::Set {
+        None
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        match (lhs_or_prev, rhs_or_new) {
+            (None, None) => None,
+            (None, Some(rhs_or_new)) => Some(rhs_or_new.clone()),
+            (Some(lhs_or_prev), None) => Some(lhs_or_prev.clone()),
+            (Some(lhs_or_prev), Some(rhs_or_new)) => {
+                Some(<S as Semigroup>::combine(lhs_or_prev, rhs_or_new))
+            }
+        }
+    }
+
+    fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+        if let Some(rhs_or_new) = rhs_or_new {
+            *lhs_or_prev = Some(match lhs_or_prev {
+                Some(lhs_or_prev) => <S as Semigroup>::combine(lhs_or_prev, rhs_or_new),
+                None => rhs_or_new.clone(),
+            })
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
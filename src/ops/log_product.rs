@@ -0,0 +1,69 @@
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs `*` operation of [`f64`]s in log domain.
+///
+/// Each element is `(is negative, ln |value|)`.
+/// Long products neither overflow nor underflow, since the logarithms are summed instead.
+/// Use [`LogProduct::singleton()`] to create a leaf and [`LogProduct::value()`] to restore the product.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::LogProduct};
+///
+/// let seg = SegmentTree::<LogProduct>::from_elem(1_000, LogProduct::singleton(-0.5));
+///
+/// // 0.5¹⁰⁰⁰ underflows, but its logarithm does not
+/// let (is_negative, ln_abs) = seg.range_query(..);
+/// assert!(!is_negative);
+/// assert!((ln_abs - 1_000.0 * 0.5_f64.ln()).abs() < 1e-9);
+///
+/// let product = LogProduct::value(&seg.range_query(..3));
+/// assert!((product + 0.125).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LogProduct;
+
+impl LogProduct {
+    /// Returns the element which consists of a single value.
+    ///
+    /// `0.0` is represented by `ln |value| = -∞`.
+    pub fn singleton(value: f64) -> (bool, f64) {
+        (value.is_sign_negative(), value.abs().ln())
+    }
+
+    /// Returns the product, which may overflow or underflow.
+    pub fn value(element: &(bool, f64)) -> f64 {
+        let abs = element.1.exp();
+        if element.0 { -abs } else { abs }
+    }
+}
+
+impl Monoid for LogProduct {
+    type Set = (bool, f64);
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        (false, 0.0)
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        (lhs_or_prev.0 ^ rhs_or_new.0, lhs_or_prev.1 + rhs_or_new.1)
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // avoid `-∞ * 0.0`, which is NaN
+        if n == 0 {
+            Self::identity()
+        } else {
+            (element.0 && n % 2 == 1, element.1 * n as f64)
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        !element.0 && element.1 == 0.0
+    }
+}
+
+impl CommutativeMonoid for LogProduct {}
@@ -0,0 +1,66 @@
+use crate::traits::Monoid;
+
+/// Finds the longest run of consecutive elements that satisfy a predicate.
+///
+/// Each element is `(length, prefix run, suffix run, longest run)`.
+/// Use [`LongestRun::singleton()`] to create a leaf from the result of the predicate.
+///
+/// To find the longest run of equal elements in `a[l..r]`,
+/// mark `a[i] == a[i - 1]` at `i` and add `1` to the result of the query over `l + 1..r`.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::LongestRun};
+///
+/// let mut seg =
+///     SegmentTree::<LongestRun>::from_iter([1, 1, 0, 1, 1, 1, 0].map(|v| LongestRun::singleton(v == 1)));
+/// assert_eq!(seg.range_query(..).3, 3);
+/// assert_eq!(seg.range_query(..5).3, 2);
+///
+/// seg.point_update(2, LongestRun::singleton(true));
+/// assert_eq!(seg.range_query(..).3, 6);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LongestRun;
+
+impl LongestRun {
+    /// Returns the element which consists of a single value.
+    pub const fn singleton(satisfied: bool) -> (usize, usize, usize, usize) {
+        let run = satisfied as usize;
+        (1, run, run, run)
+    }
+}
+
+impl Monoid for LongestRun {
+    type Set = (usize, usize, usize, usize);
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        (0, 0, 0, 0)
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        let &(l_len, l_prefix, l_suffix, l_best) = lhs_or_prev;
+        let &(r_len, r_prefix, r_suffix, r_best) = rhs_or_new;
+        (
+            l_len + r_len,
+            if l_prefix == l_len {
+                l_len + r_prefix
+            } else {
+                l_prefix
+            },
+            if r_suffix == r_len {
+                r_len + l_suffix
+            } else {
+                r_suffix
+            },
+            l_best.max(r_best).max(l_suffix + r_prefix),
+        )
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.0 == 0
+    }
+}
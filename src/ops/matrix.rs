@@ -0,0 +1,48 @@
+use std::marker::PhantomData;
+
+use crate::num::{One, Zero};
+
+use crate::traits::Monoid;
+
+/// Performs multiplication of `N × N` matrices.
+///
+/// `combine(a, b)` returns the product `a × b`,
+/// so that a range query returns the product of the matrices from left to right.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::Matrix};
+///
+/// // Fibonacci numbers
+/// let seg = SegmentTree::<Matrix<u64, 2>>::from_elem(10, [[1, 1], [1, 0]]);
+///
+/// assert_eq!(seg.range_query(..), [[89, 55], [55, 34]]);
+/// assert_eq!(seg.range_query(..0), [[1, 0], [0, 1]]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Matrix<T, const N: usize>(PhantomData<T>);
+
+impl<T, const N: usize> Monoid for Matrix<T, N>
+where
+    T: Zero + One,
+    for<'a> &'a T: std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+{
+    type Set = [[T; N]; N];
+
+    const IS_COMMUTATIVE: bool = N <= 1;
+
+    fn identity() -> Self::Set {
+        std::array::from_fn(|i| std::array::from_fn(|j| if i == j { T::one() } else { T::zero() }))
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                (0..N).fold(T::zero(), |acc, k| {
+                    &acc + &(&lhs_or_prev[i][k] * &rhs_or_new[k][j])
+                })
+            })
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use std::marker::PhantomData;
+
+use crate::traits::Monoid;
+
+/// Finds the maximum sum of non-empty contiguous subarrays.
+///
+/// Each element is `Some((total, best prefix, best suffix, best subarray))`.
+/// Use [`MaxSubarraySum::singleton()`] to create a leaf.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::MaxSubarraySum};
+///
+/// let mut seg = SegmentTree::<MaxSubarraySum<i64>>::from_iter(
+///     [2, -3, 4, -1, 2, -5].map(MaxSubarraySum::singleton),
+/// );
+/// assert_eq!(seg.range_query(..).map(|(_, _, _, best)| best), Some(5));
+///
+/// seg.point_update(1, MaxSubarraySum::singleton(3));
+/// assert_eq!(seg.range_query(..).map(|(_, _, _, best)| best), Some(10));
+/// assert_eq!(seg.range_query(5..).map(|(_, _, _, best)| best), Some(-5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaxSubarraySum<T>(PhantomData<T>);
+
+impl<T> MaxSubarraySum<T>
+where
+    T: Clone,
+{
+    /// Returns the element which consists of a single value.
+    pub fn singleton(value: T) -> Option<(T, T, T, T)> {
+        Some((value.clone(), value.clone(), value.clone(), value))
+    }
+}
+
+impl<T> Monoid for MaxSubarraySum<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord + std::ops::Add<Output = T>,
+{
+    type Set = Option<(T, T, T, T)>;
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        None
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        match (lhs_or_prev, rhs_or_new) {
+            (None, _) => rhs_or_new.clone(),
+            (_, None) => lhs_or_prev.clone(),
+            (
+                Some((l_total, l_prefix, l_suffix, l_best)),
+                Some((r_total, r_prefix, r_suffix, r_best)),
+            ) => {
+                let prefix = l_total + r_prefix;
+                let suffix = r_total + l_suffix;
+                let best = l_suffix + r_prefix;
+                Some((
+                    l_total + r_total,
+                    l_prefix.max(&prefix).clone(),
+                    r_suffix.max(&suffix).clone(),
+                    l_best.max(r_best).max(&best).clone(),
+                ))
+            }
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
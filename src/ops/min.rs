@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 
-use crate::traits::Monoid;
+use crate::traits::{CommutativeMonoid, Monoid};
 
 /// Performs `chmin` operation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -28,4 +28,34 @@ where
         }
         .cloned()
     }
+
+    fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+        if let Some(rhs_or_new) = rhs_or_new
+            && lhs_or_prev
+                .as_ref()
+                .is_none_or(|lhs_or_prev| rhs_or_new < lhs_or_prev)
+        {
+            *lhs_or_prev = Some(rhs_or_new.clone())
+        }
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 {
+            Self::identity()
+        } else {
+            Self::combine(element, element)
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
+
+impl<T> CommutativeMonoid for Min<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
 }
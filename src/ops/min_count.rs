@@ -0,0 +1,71 @@
+use std::{cmp::Ordering, marker::PhantomData};
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs `chmin` operation, counting the elements that achieve the minimum.
+///
+/// Each element is `Some((value, count))`.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::MinCount};
+///
+/// let seg = SegmentTree::<MinCount<i32>>::from_iter([3, 1, 3, 1, 2].map(|v| Some((v, 1))));
+///
+/// assert_eq!(seg.range_query(..), Some((1, 2)));
+/// assert_eq!(seg.range_query(2..), Some((1, 1)));
+/// assert_eq!(seg.range_query(2..2), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MinCount<T>(PhantomData<T>);
+
+impl<T> Monoid for MinCount<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+    type Set = Option<(T, usize)>;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        None
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        match (lhs_or_prev, rhs_or_new) {
+            (None, None) => None,
+            (None, Some(rhs_or_new)) => Some(rhs_or_new.clone()),
+            (Some(lhs_or_prev), None) => Some(lhs_or_prev.clone()),
+            (Some(lhs_or_prev), Some(rhs_or_new)) => {
+                Some(match Ord::cmp(&&lhs_or_prev.0, &&rhs_or_new.0) {
+                    Ordering::Equal => (lhs_or_prev.0.clone(), lhs_or_prev.1 + rhs_or_new.1),
+                    Ordering::Less => lhs_or_prev.clone(),
+                    _ => rhs_or_new.clone(),
+                })
+            }
+        }
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        if n == 0 {
+            Self::identity()
+        } else {
+            element
+                .as_ref()
+                .map(|(value, count)| (value.clone(), count * n))
+        }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_none()
+    }
+}
+
+impl<T> CommutativeMonoid for MinCount<T>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+}
@@ -30,9 +30,18 @@ where
 mod add;
 pub use add::Add;
 
+mod saturating_add;
+pub use saturating_add::SaturatingAdd;
+
+mod checked_add;
+pub use checked_add::CheckedAdd;
+
 mod mul;
 pub use mul::Mul;
 
+mod log_product;
+pub use log_product::LogProduct;
+
 mod bit_and;
 pub use bit_and::BitAnd;
 
@@ -42,23 +51,110 @@ pub use bit_or::BitOr;
 mod bit_xor;
 pub use bit_xor::BitXor;
 
+mod xor_basis;
+pub use xor_basis::XorBasis;
+
+mod any;
+pub use any::Any;
+
+mod all;
+pub use all::All;
+
+mod parity;
+pub use parity::Parity;
+
 mod max;
 pub use max::Max;
 
 mod min;
 pub use min::Min;
 
+mod float_max;
+pub use float_max::FloatMax;
+
+mod float_min;
+pub use float_min::FloatMin;
+
+mod max_count;
+pub use max_count::MaxCount;
+
+mod min_count;
+pub use min_count::MinCount;
+
+mod arg_max;
+pub use arg_max::ArgMax;
+
+mod arg_min;
+pub use arg_min::ArgMin;
+
+mod top_k;
+pub use top_k::{Top2, TopK};
+
+mod sum_sq;
+pub use sum_sq::SumSq;
+
+mod weighted_sum;
+pub use weighted_sum::WeightedSum;
+
+mod max_subarray_sum;
+pub use max_subarray_sum::MaxSubarraySum;
+
+mod longest_run;
+pub use longest_run::LongestRun;
+
+mod bracket_balance;
+pub use bracket_balance::BracketBalance;
+
+mod concat;
+pub use concat::{Concat, ConcatString};
+
+mod rolling_hash;
+pub use rolling_hash::RollingHash;
+
 mod assign;
 pub use assign::Assign;
 
 mod assign_or;
 pub use assign_or::AssignOr;
 
+mod first;
+pub use first::First;
+
+mod last;
+pub use last::Last;
+
 mod affine;
 pub use affine::Affine;
 
+mod matrix;
+pub use matrix::Matrix;
+
+mod vector_sum;
+pub use vector_sum::VectorSum;
+
+mod mod_add;
+pub use mod_add::ModAdd;
+
+mod mod_mul;
+pub use mod_mul::ModMul;
+
+mod mod_affine;
+pub use mod_affine::ModAffine;
+
 mod gcd;
 pub use gcd::GCD;
 
 mod lcm;
 pub use lcm::LCM;
+
+mod gcd_lcm;
+pub use gcd_lcm::GcdLcm;
+
+mod shared;
+pub use shared::Shared;
+
+mod reverse;
+pub use reverse::Reverse;
+
+mod lift;
+pub use lift::Lift;
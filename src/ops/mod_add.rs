@@ -0,0 +1,51 @@
+use crate::{
+    traits::{CommutativeMonoid, Group, Monoid},
+    utility::{mod_add, mod_mul},
+};
+
+/// Performs `+` operation modulo `M`.
+///
+/// The result is always less than `M`, even if the operands are not.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::ModAdd};
+///
+/// const MOD: u64 = 998_244_353;
+///
+/// let seg = SegmentTree::<ModAdd<MOD>>::from_elem(3, MOD - 1);
+/// assert_eq!(seg.range_query(..), MOD - 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModAdd<const M: u64>;
+
+impl<const M: u64> Monoid for ModAdd<M> {
+    type Set = u64;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        0
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        mod_add::<M>(*lhs_or_prev, *rhs_or_new)
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        mod_mul::<M>(*element, (n as u64) % M)
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element % M == 0
+    }
+}
+
+impl<const M: u64> CommutativeMonoid for ModAdd<M> {}
+
+impl<const M: u64> Group for ModAdd<M> {
+    fn inverse(element: &Self::Set) -> Self::Set {
+        (M - element % M) % M
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{
+    traits::Monoid,
+    utility::{mod_add, mod_mul},
+};
+
+/// Performs affine transformation modulo `M`.
+///
+/// `(a, b)` represents `x ↦ a * x + b`.
+/// The result is always less than `M`, even if the operands are not.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::ModAffine};
+///
+/// const MOD: u64 = 998_244_353;
+///
+/// let seg = SegmentTree::<ModAffine<MOD>>::from_iter([(2, 1), (3, MOD - 1)]);
+/// // 3 * (2 * x + 1) - 1 = 6 * x + 2
+/// assert_eq!(seg.range_query(..), (6, 2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModAffine<const M: u64>;
+
+impl<const M: u64> Monoid for ModAffine<M> {
+    type Set = (u64, u64);
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        (1 % M, 0)
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        (
+            mod_mul::<M>(rhs_or_new.0, lhs_or_prev.0),
+            mod_add::<M>(mod_mul::<M>(rhs_or_new.0, lhs_or_prev.1), rhs_or_new.1),
+        )
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.0 % M == 1 % M && element.1 % M == 0
+    }
+}
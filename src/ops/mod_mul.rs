@@ -0,0 +1,30 @@
+use crate::{
+    traits::{CommutativeMonoid, Monoid},
+    utility::mod_mul,
+};
+
+/// Performs `*` operation modulo `M`.
+///
+/// The result is always less than `M`, even if the operands are not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModMul<const M: u64>;
+
+impl<const M: u64> Monoid for ModMul<M> {
+    type Set = u64;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        1 % M
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        mod_mul::<M>(*lhs_or_prev, *rhs_or_new)
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element % M == 1 % M
+    }
+}
+
+impl<const M: u64> CommutativeMonoid for ModMul<M> {}
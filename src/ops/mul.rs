@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 
-use num_traits::One;
+use crate::num::One;
 
-use crate::traits::Monoid;
+use crate::traits::{CommutativeMonoid, Monoid};
 
 /// Performs `*` operation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -25,3 +25,10 @@ where
         lhs_or_prev * rhs_or_new
     }
 }
+
+impl<T> CommutativeMonoid for Mul<T>
+where
+    T: One,
+    for<'a> &'a T: std::ops::Mul<Output = T>,
+{
+}
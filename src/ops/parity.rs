@@ -0,0 +1,46 @@
+use crate::traits::{CommutativeMonoid, Group, Monoid};
+
+/// Performs `^` operation on [`bool`]s.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::Parity};
+///
+/// let seg = SegmentTree::<Parity>::from(vec![true, true, false, true]);
+///
+/// assert!(!seg.range_query(..2));
+/// assert!(seg.range_query(..));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Parity;
+
+impl Monoid for Parity {
+    type Set = bool;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        false
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        lhs_or_prev ^ rhs_or_new
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        *element && n % 2 == 1
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        !element
+    }
+}
+
+impl CommutativeMonoid for Parity {}
+
+impl Group for Parity {
+    fn inverse(element: &Self::Set) -> Self::Set {
+        *element
+    }
+}
@@ -0,0 +1,63 @@
+use std::marker::PhantomData;
+
+use crate::traits::{CommutativeMonoid, Group, Monoid};
+
+/// Performs the operation of `M` with the arguments flipped.
+///
+/// Folding with `Reverse<M>` is equivalent to folding the reversed sequence with `M`,
+/// which is useful for non-commutative operations.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{
+///     SegmentTree,
+///     ops::{ConcatString, Reverse},
+/// };
+///
+/// let seg = SegmentTree::<(ConcatString, Reverse<ConcatString>)>::from_iter(
+///     "abcba".chars().map(|c| (c.to_string(), c.to_string())),
+/// );
+///
+/// assert_eq!(seg.range_query(1..4), ("bcb".to_string(), "bcb".to_string()));
+/// assert_eq!(seg.range_query(2..5), ("cba".to_string(), "abc".to_string()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Reverse<M>(PhantomData<M>);
+
+impl<M> Monoid for Reverse<M>
+where
+    M: Monoid,
+{
+    type Set = <M as Monoid>::Set;
+
+    const IS_COMMUTATIVE: bool = <M as Monoid>::IS_COMMUTATIVE;
+
+    fn identity() -> Self::Set {
+        <M as Monoid>::identity()
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        <M as Monoid>::combine(rhs_or_new, lhs_or_prev)
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // every operand is the same
+        <M as Monoid>::combine_n(element, n)
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        <M as Monoid>::is_identity(element)
+    }
+}
+
+impl<M> CommutativeMonoid for Reverse<M> where M: CommutativeMonoid {}
+
+impl<M> Group for Reverse<M>
+where
+    M: Group,
+{
+    fn inverse(element: &Self::Set) -> Self::Set {
+        <M as Group>::inverse(element)
+    }
+}
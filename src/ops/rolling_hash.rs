@@ -0,0 +1,68 @@
+use crate::{
+    traits::Monoid,
+    utility::{mod_add, mod_mul},
+};
+
+/// Computes the polynomial rolling hash `Σ aᵢ Bⁿ⁻¹⁻ⁱ mod M` of a sequence.
+///
+/// Each element is `(hash, Bⁿ mod M)`, where `n` is the length of the segment.
+/// Use [`RollingHash::singleton()`] to create a leaf,
+/// and [`Reverse`](crate::ops::Reverse) to hash the reversed sequence at the same time.
+///
+/// Choose a large prime `M` such as `2⁶¹ - 1` and a random base `B` to avoid collisions.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{
+///     SegmentTree,
+///     ops::{Reverse, RollingHash},
+/// };
+///
+/// type Hash = RollingHash<{ (1 << 61) - 1 }, 1_000_003>;
+///
+/// let mut seg = SegmentTree::<(Hash, Reverse<Hash>)>::from_iter("abcab".bytes().map(|b| {
+///     let leaf = Hash::singleton(b as u64);
+///     (leaf, leaf)
+/// }));
+///
+/// // substring equality
+/// assert_eq!(seg.range_query(0..2).0, seg.range_query(3..5).0);
+/// assert_ne!(seg.range_query(0..2).0, seg.range_query(1..3).0);
+///
+/// // palindrome check
+/// let is_palindrome = |(hash, rev)| hash == rev;
+/// assert!(!is_palindrome(seg.range_query(1..4)));
+/// seg.point_update(3, (Hash::singleton(b'b' as u64), Hash::singleton(b'b' as u64)));
+/// assert!(is_palindrome(seg.range_query(1..4)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RollingHash<const M: u64, const B: u64>;
+
+impl<const M: u64, const B: u64> RollingHash<M, B> {
+    /// Returns the element which consists of a single value.
+    pub const fn singleton(value: u64) -> (u64, u64) {
+        (value % M, B % M)
+    }
+}
+
+impl<const M: u64, const B: u64> Monoid for RollingHash<M, B> {
+    type Set = (u64, u64);
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        (0, 1 % M)
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        (
+            mod_add::<M>(mod_mul::<M>(lhs_or_prev.0, rhs_or_new.1), rhs_or_new.0),
+            mod_mul::<M>(lhs_or_prev.1, rhs_or_new.1),
+        )
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.1 == 1 % M && element.0 == 0
+    }
+}
@@ -0,0 +1,48 @@
+use std::marker::PhantomData;
+
+use crate::num::Zero;
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs `+` operation, saturating at the numeric bounds instead of overflowing.
+///
+/// # Warning
+///
+/// Saturating addition is associative only if all the elements have the same sign,
+/// e.g. for unsigned integers.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::SaturatingAdd};
+///
+/// let seg = SegmentTree::<SaturatingAdd<u8>>::from(vec![100, 100, 100]);
+///
+/// assert_eq!(seg.range_query(..2), 200);
+/// assert_eq!(seg.range_query(..), u8::MAX);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SaturatingAdd<T>(PhantomData<T>);
+
+impl<T> Monoid for SaturatingAdd<T>
+where
+    T: Zero + crate::num::SaturatingAdd,
+{
+    type Set = T;
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        T::zero()
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        lhs_or_prev.saturating_add(rhs_or_new)
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.is_zero()
+    }
+}
+
+impl<T> CommutativeMonoid for SaturatingAdd<T> where T: Zero + crate::num::SaturatingAdd {}
@@ -0,0 +1,56 @@
+use std::{marker::PhantomData, rc::Rc};
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs the operation of `M` on values shared through [`Rc`].
+///
+/// Cloning an element only increments the reference count,
+/// so [`AssignSegmentTree`](crate::AssignSegmentTree) can range-assign
+/// expensive-to-clone values without deep copies.
+///
+/// # Example
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use seg_lib::{
+///     AssignSegmentTree,
+///     ops::{ConcatString, Shared},
+/// };
+///
+/// let mut ast = AssignSegmentTree::<Shared<ConcatString>>::new(4);
+/// ast.range_assign(.., Rc::new("a".repeat(1_000)));
+/// ast.range_assign(1..3, Rc::new("b".to_string()));
+///
+/// assert_eq!(ast.range_query(1..3).as_str(), "bb");
+/// assert_eq!(ast.range_query(..).len(), 2_002);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Shared<M>(PhantomData<M>);
+
+impl<M> Monoid for Shared<M>
+where
+    M: Monoid,
+{
+    type Set = Rc<<M as Monoid>::Set>;
+
+    const IS_COMMUTATIVE: bool = <M as Monoid>::IS_COMMUTATIVE;
+
+    fn identity() -> Self::Set {
+        Rc::new(<M as Monoid>::identity())
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        Rc::new(<M as Monoid>::combine_n(element, n))
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        <M as Monoid>::is_identity(element)
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        Rc::new(<M as Monoid>::combine(lhs_or_prev, rhs_or_new))
+    }
+}
+
+impl<M> CommutativeMonoid for Shared<M> where M: CommutativeMonoid {}
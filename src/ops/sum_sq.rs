@@ -0,0 +1,72 @@
+use std::marker::PhantomData;
+
+use crate::num::{One, Zero};
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs `+` operation on `(count, sum, sum of squares)`.
+///
+/// This enables range variance queries: `sum_sq / count - (sum / count)²`.
+/// Use [`SumSq::singleton()`] to create a leaf,
+/// and [`SumSqQueryAddUpdate`](crate::acts::SumSqQueryAddUpdate) for range add update.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::SumSq};
+///
+/// let seg = SegmentTree::<SumSq<f64>>::from_iter([1.0, 2.0, 3.0, 4.0].map(SumSq::singleton));
+///
+/// let (count, sum, sum_sq) = seg.range_query(1..4);
+/// assert_eq!((count, sum, sum_sq), (3.0, 9.0, 29.0));
+///
+/// let variance = sum_sq / count - (sum / count).powi(2);
+/// assert!((variance - 2.0 / 3.0).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SumSq<T>(PhantomData<T>);
+
+impl<T> SumSq<T>
+where
+    T: One,
+    for<'a> &'a T: std::ops::Mul<Output = T>,
+{
+    /// Returns the element which consists of a single value.
+    pub fn singleton(value: T) -> (T, T, T) {
+        let sq = &value * &value;
+        (T::one(), value, sq)
+    }
+}
+
+impl<T> Monoid for SumSq<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::Add<Output = T>,
+{
+    type Set = (T, T, T);
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        (T::zero(), T::zero(), T::zero())
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        (
+            &lhs_or_prev.0 + &rhs_or_new.0,
+            &lhs_or_prev.1 + &rhs_or_new.1,
+            &lhs_or_prev.2 + &rhs_or_new.2,
+        )
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.0.is_zero() && element.1.is_zero() && element.2.is_zero()
+    }
+}
+
+impl<T> CommutativeMonoid for SumSq<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::Add<Output = T>,
+{
+}
@@ -0,0 +1,73 @@
+use std::marker::PhantomData;
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Keeps the `K` largest values in descending order, counting duplicates.
+///
+/// Each element is an array whose missing values are [`None`].
+/// Use [`TopK::singleton()`] to create a leaf.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::Top2};
+///
+/// let seg = SegmentTree::<Top2<i32>>::from_iter([3, 1, 4, 1, 5].map(Top2::singleton));
+///
+/// assert_eq!(seg.range_query(..), [Some(5), Some(4)]);
+/// assert_eq!(seg.range_query(1..4), [Some(4), Some(1)]);
+/// assert_eq!(seg.range_query(1..2), [Some(1), None]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TopK<T, const K: usize>(PhantomData<T>);
+
+/// Keeps the two largest values. See [`TopK`] for details.
+pub type Top2<T> = TopK<T, 2>;
+
+impl<T, const K: usize> TopK<T, K> {
+    /// Returns the element which consists of a single value.
+    pub fn singleton(value: T) -> [Option<T>; K] {
+        let mut value = Some(value);
+        std::array::from_fn(|_| value.take())
+    }
+}
+
+impl<T, const K: usize> Monoid for TopK<T, K>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+    type Set = [Option<T>; K];
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        std::array::from_fn(|_| None)
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        // merge two sorted arrays, where `None` is the smallest
+        let (mut lhs, mut rhs) = (lhs_or_prev.iter().peekable(), rhs_or_new.iter().peekable());
+        std::array::from_fn(|_| {
+            let next = match (lhs.peek(), rhs.peek()) {
+                (Some(Some(l)), Some(Some(r))) if l >= r => lhs.next(),
+                (Some(Some(_)), Some(Some(_))) => rhs.next(),
+                (Some(Some(_)), _) => lhs.next(),
+                (_, Some(Some(_))) => rhs.next(),
+                _ => None,
+            };
+            next.cloned().flatten()
+        })
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.iter().all(Option::is_none)
+    }
+}
+
+impl<T, const K: usize> CommutativeMonoid for TopK<T, K>
+where
+    T: Clone,
+    for<'a> &'a T: Ord,
+{
+}
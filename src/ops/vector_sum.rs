@@ -0,0 +1,48 @@
+use std::marker::PhantomData;
+
+use crate::num::Zero;
+
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Performs componentwise `+` operation on arrays.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::VectorSum};
+///
+/// let seg = SegmentTree::<VectorSum<i32, 2>>::from(vec![[1, 10], [2, 20], [3, 30]]);
+///
+/// assert_eq!(seg.range_query(1..), [5, 50]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VectorSum<T, const N: usize>(PhantomData<T>);
+
+impl<T, const N: usize> Monoid for VectorSum<T, N>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::Add<Output = T>,
+{
+    type Set = [T; N];
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        std::array::from_fn(|_| T::zero())
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        std::array::from_fn(|i| &lhs_or_prev[i] + &rhs_or_new[i])
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.iter().all(Zero::is_zero)
+    }
+}
+
+impl<T, const N: usize> CommutativeMonoid for VectorSum<T, N>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::Add<Output = T>,
+{
+}
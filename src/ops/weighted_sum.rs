@@ -0,0 +1,62 @@
+use std::marker::PhantomData;
+
+use crate::num::{One, Zero};
+
+use crate::traits::Monoid;
+
+/// Performs `+` operation on `(length, Σ aᵢ, Σ i aᵢ)`, where `i` is the offset within the segment.
+///
+/// Use [`WeightedSum::singleton()`] to create a leaf,
+/// and [`WeightedSumQueryAddUpdate`](crate::acts::WeightedSumQueryAddUpdate) for range add update.
+/// Add `l Σ aᵢ` to the weighted sum over `l..r` to obtain `Σ i aᵢ` with absolute indices.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::WeightedSum};
+///
+/// let seg = SegmentTree::<WeightedSum<i64>>::from_iter([5, 1, 2, 3].map(WeightedSum::singleton));
+///
+/// // 0 * 1 + 1 * 2 + 2 * 3
+/// assert_eq!(seg.range_query(1..), (3, 6, 8));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WeightedSum<T>(PhantomData<T>);
+
+impl<T> WeightedSum<T>
+where
+    T: Zero + One,
+{
+    /// Returns the element which consists of a single value.
+    pub fn singleton(value: T) -> (T, T, T) {
+        (T::one(), value, T::zero())
+    }
+}
+
+impl<T> Monoid for WeightedSum<T>
+where
+    T: Zero,
+    for<'a> &'a T: std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+{
+    type Set = (T, T, T);
+
+    const IS_COMMUTATIVE: bool = false;
+
+    fn identity() -> Self::Set {
+        (T::zero(), T::zero(), T::zero())
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        // the offsets in `rhs_or_new` are shifted by the length of `lhs_or_prev`
+        let shift = &lhs_or_prev.0 * &rhs_or_new.1;
+        (
+            &lhs_or_prev.0 + &rhs_or_new.0,
+            &lhs_or_prev.1 + &rhs_or_new.1,
+            &(&lhs_or_prev.2 + &rhs_or_new.2) + &shift,
+        )
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.0.is_zero() && element.1.is_zero() && element.2.is_zero()
+    }
+}
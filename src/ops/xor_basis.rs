@@ -0,0 +1,103 @@
+use crate::traits::{CommutativeMonoid, Monoid};
+
+/// Merges linear bases over GF(2) of 64-bit vectors.
+///
+/// Each element is a basis in reduced row echelon form,
+/// where `basis[b]` is the vector whose highest set bit is `b`, or `0`.
+/// The form is unique for each span, so the operation is commutative.
+/// Use [`XorBasis::singleton()`] to create a leaf.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::XorBasis};
+///
+/// let seg = SegmentTree::<XorBasis>::from_iter([0b1100, 0b0101, 0b1010, 0b0001].map(XorBasis::singleton));
+///
+/// assert_eq!(XorBasis::max_xor(&seg.range_query(..2)), 0b1100);
+/// assert_eq!(XorBasis::max_xor(&seg.range_query(..3)), 0b1111);
+/// assert_eq!(XorBasis::rank(&seg.range_query(..)), 4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XorBasis;
+
+impl XorBasis {
+    /// Returns the basis spanned by a single vector.
+    pub fn singleton(vector: u64) -> [u64; 64] {
+        let mut basis = [0; 64];
+        Self::insert(&mut basis, vector);
+        basis
+    }
+
+    /// Adds the vector to the basis if it is linearly independent.
+    ///
+    /// Returns `true` if the basis is extended.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(64)
+    pub fn insert(basis: &mut [u64; 64], mut vector: u64) -> bool {
+        for b in (0..64).rev() {
+            if vector >> b & 1 == 1 {
+                vector ^= basis[b]
+            }
+        }
+        if vector == 0 {
+            return false;
+        }
+
+        // keep the basis reduced
+        let top = vector.ilog2() as usize;
+        for v in &mut basis[top + 1..] {
+            if *v >> top & 1 == 1 {
+                *v ^= vector
+            }
+        }
+        basis[top] = vector;
+
+        true
+    }
+
+    /// Returns the maximum xor of a subset of the vectors.
+    pub fn max_xor(basis: &[u64; 64]) -> u64 {
+        basis.iter().fold(0, |acc, v| acc ^ v)
+    }
+
+    /// Returns the dimension of the span.
+    pub fn rank(basis: &[u64; 64]) -> usize {
+        basis.iter().filter(|&&v| v != 0).count()
+    }
+}
+
+impl Monoid for XorBasis {
+    type Set = [u64; 64];
+
+    const IS_COMMUTATIVE: bool = true;
+
+    fn identity() -> Self::Set {
+        [0; 64]
+    }
+
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
+        let mut res = *lhs_or_prev;
+        Self::combine_assign(&mut res, rhs_or_new);
+        res
+    }
+
+    fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+        for &v in rhs_or_new.iter().filter(|&&v| v != 0) {
+            Self::insert(lhs_or_prev, v);
+        }
+    }
+
+    fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+        // idempotent
+        if n == 0 { Self::identity() } else { *element }
+    }
+
+    fn is_identity(element: &Self::Set) -> bool {
+        element.iter().all(|&v| v == 0)
+    }
+}
+
+impl CommutativeMonoid for XorBasis {}
@@ -0,0 +1,66 @@
+//! The trees, the algebra traits and type aliases for common cases.
+//!
+//! The aliases name a tree together with its operation,
+//! so that the common cases need neither [`ops`](crate::ops) nor [`acts`](crate::acts).
+//!
+//! # Example
+//!
+//! ```
+//! use seg_lib::prelude::*;
+//!
+//! let st = RangeSumTree::<i64>::from_iter(0..10);
+//! assert_eq!(st.range_query(2..5), 2 + 3 + 4);
+//!
+//! let rmq = RmqTree::<i64>::from_iter([3, 1, 4, 1, 5].map(Some));
+//! assert_eq!(rmq.range_query(2..), Some(1));
+//!
+//! let mut lst = RangeAddSumTree::<i64>::new(10);
+//! lst.range_update(3..7, &2);
+//! assert_eq!(lst.range_query(..5), 2 + 2);
+//! ```
+
+pub use crate::{
+    AssignSegmentTree, BufferedSegmentTree, CommutativeMonoid, DequeSegmentTree, DualSegmentTree,
+    DynamicLazySegmentTree, DynamicSegmentTree, Group, LazySegmentTree, Monoid, MonoidAction,
+    SegmentTree, VebSegmentTree, WideLazySegmentTree, WideSegmentTree,
+};
+use crate::{
+    acts::{
+        AddQueryAddUpdate, AddQueryAffineUpdate, AddQueryAssignUpdate, MaxQueryAddUpdate,
+        MaxQueryAssignUpdate, MinQueryAddUpdate, MinQueryAssignUpdate,
+    },
+    ops::{Add, Max, Min},
+};
+
+/// Performs **range add query point update**.
+pub type RangeSumTree<T> = SegmentTree<Add<T>>;
+
+/// Performs **range min query point update**.
+pub type RmqTree<T> = SegmentTree<Min<T>>;
+
+/// Performs **range max query point update**.
+pub type RangeMaxTree<T> = SegmentTree<Max<T>>;
+
+/// Performs **point query range add update**.
+pub type RangeAddTree<T> = DualSegmentTree<Add<T>>;
+
+/// Performs **range add query range add update**.
+pub type RangeAddSumTree<T> = LazySegmentTree<AddQueryAddUpdate<T>>;
+
+/// Performs **range min query range add update**.
+pub type RangeAddMinTree<T> = LazySegmentTree<MinQueryAddUpdate<T>>;
+
+/// Performs **range max query range add update**.
+pub type RangeAddMaxTree<T> = LazySegmentTree<MaxQueryAddUpdate<T>>;
+
+/// Performs **range add query range assign update**.
+pub type RangeAssignSumTree<T> = LazySegmentTree<AddQueryAssignUpdate<T>>;
+
+/// Performs **range min query range assign update**.
+pub type RangeAssignMinTree<T> = LazySegmentTree<MinQueryAssignUpdate<T>>;
+
+/// Performs **range max query range assign update**.
+pub type RangeAssignMaxTree<T> = LazySegmentTree<MaxQueryAssignUpdate<T>>;
+
+/// Performs **range add query range affine update**.
+pub type RangeAffineSumTree<T> = LazySegmentTree<AddQueryAffineUpdate<T>>;
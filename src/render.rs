@@ -0,0 +1,358 @@
+//! Helpers to render the structure of the trees for debugging.
+
+use std::fmt::{Debug, Write};
+
+/// Receives the nodes of a tree, each after its parent.
+pub(crate) trait Render {
+    /// Receives a node labeled with the covered `range` and the named `fields`.
+    ///
+    /// The root is at `depth` 0 and has no `parent`.
+    fn node(
+        &mut self,
+        id: usize,
+        parent: Option<usize>,
+        depth: usize,
+        range: &str,
+        fields: &[(&str, &dyn Debug)],
+    );
+}
+
+/// A DOT digraph under construction, whose nodes are records.
+pub(crate) struct Dot {
+    out: String,
+}
+
+impl Dot {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            out: format!("digraph {name} {{\n    node [shape=record];\n"),
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+}
+
+impl Render for Dot {
+    fn node(
+        &mut self,
+        id: usize,
+        parent: Option<usize>,
+        _: usize,
+        range: &str,
+        fields: &[(&str, &dyn Debug)],
+    ) {
+        let mut label = escape(range);
+        for (name, value) in fields {
+            label.push_str(" | ");
+            label.push_str(&escape(&format!("{name}: {value:?}")));
+        }
+        writeln!(self.out, "    n{id} [label=\"{{{label}}}\"];").unwrap();
+        if let Some(parent) = parent {
+            writeln!(self.out, "    n{parent} -> n{id};").unwrap()
+        }
+    }
+}
+
+/// A Mermaid flowchart under construction, whose nodes are labeled like DOT records.
+#[derive(Default)]
+pub(crate) struct Mermaid {
+    out: String,
+}
+
+impl Mermaid {
+    pub(crate) fn finish(self) -> String {
+        format!("graph TD\n{}", self.out)
+    }
+}
+
+impl Render for Mermaid {
+    fn node(
+        &mut self,
+        id: usize,
+        parent: Option<usize>,
+        _: usize,
+        range: &str,
+        fields: &[(&str, &dyn Debug)],
+    ) {
+        let mut label = escape_mermaid(range);
+        for (name, value) in fields {
+            label.push_str(" | ");
+            label.push_str(&escape_mermaid(&format!("{name}: {value:?}")));
+        }
+        writeln!(self.out, "    n{id}[\"{label}\"]").unwrap();
+        if let Some(parent) = parent {
+            writeln!(self.out, "    n{parent} --> n{id}").unwrap()
+        }
+    }
+}
+
+/// Replaces the characters which break quoted Mermaid labels with entity codes.
+fn escape_mermaid(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("#quot;"),
+            '<' => escaped.push_str("#lt;"),
+            '>' => escaped.push_str("#gt;"),
+            '#' => escaped.push_str("#35;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the characters which have special meanings in record labels.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            escaped.push('\\')
+        }
+        escaped.push(c)
+    }
+    escaped
+}
+
+/// Displays a tree level by level, like the `pretty` method of the tree.
+///
+/// This is returned by the `display` method of the trees,
+/// so that the tree can be written by [`format!`] or [`println!`] without building a [`String`] first.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{SegmentTree, ops::Add};
+///
+/// let st = SegmentTree::<Add<i32>>::from_iter([1, 2]);
+/// assert_eq!(
+///     format!("{}", st.display()),
+///     "0: [0..2] combined: 3\n\
+///      1: [0] element: 1  [1] element: 2\n"
+/// );
+/// ```
+pub struct Pretty<'a, T>(pub(crate) &'a T);
+
+/// Plain text with a line per level.
+///
+/// The nodes of each level should be received from left to right.
+#[derive(Default)]
+pub(crate) struct Levels {
+    levels: Vec<Vec<String>>,
+}
+
+impl Levels {
+    pub(crate) fn finish(self) -> String {
+        let mut out = String::new();
+        for (depth, level) in self.levels.into_iter().enumerate() {
+            writeln!(out, "{depth}: {}", level.join("  ")).unwrap()
+        }
+        out
+    }
+}
+
+impl Render for Levels {
+    fn node(
+        &mut self,
+        _: usize,
+        _: Option<usize>,
+        depth: usize,
+        range: &str,
+        fields: &[(&str, &dyn Debug)],
+    ) {
+        let mut node = format!("[{range}]");
+        for (i, (name, value)) in fields.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(node, "{sep}{name}: {value:?}").unwrap()
+        }
+        if self.levels.len() <= depth {
+            self.levels.resize_with(depth + 1, Vec::new)
+        }
+        self.levels[depth].push(node)
+    }
+}
+
+/// Formats the ranges of the elements under the `i`-th node of the tree
+/// whose `n` leaves are stored at `n..2n` and the children of `i` are `2i` and `2i + 1`.
+///
+/// The elements may not be contiguous unless `n` is a power of two.
+pub(crate) fn bottom_up_range(i: usize, n: usize) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let (mut l, mut r) = (i, i + 1);
+    while l < n << 1 {
+        let (start, end) = (l.max(n), r.min(n << 1));
+        if start < end {
+            match ranges.last_mut() {
+                Some(last) if last.1 == start - n => last.1 = end - n,
+                _ => ranges.push((start - n, end - n)),
+            }
+        }
+        (l, r) = (l << 1, r << 1);
+    }
+
+    let ranges = Vec::from_iter(ranges.into_iter().map(|(l, r)| format!("{l}..{r}")));
+    ranges.join(", ")
+}
+
+/// Returns the range of the elements under the `i`-th node of the perfect binary tree with `leaves` leaves,
+/// clamped to `0..len`.
+pub(crate) fn perfect_range(i: usize, leaves: usize, len: usize) -> std::ops::Range<usize> {
+    let k = leaves.trailing_zeros() - i.ilog2();
+    let start = (i << k) - leaves;
+    start.min(len)..(start + (1 << k)).min(len)
+}
+
+#[cfg(test)]
+mod renderers {
+    use crate::{
+        AssignSegmentTree, BufferedSegmentTree, DualSegmentTree, DynamicLazySegmentTree,
+        DynamicSegmentTree, LazySegmentTree, SegmentTree, VebSegmentTree,
+        acts::AddQueryAddUpdate,
+        ops::{Add, Max},
+    };
+
+    /// Returns the number of nodes and edges.
+    fn count(dot: &str) -> (usize, usize) {
+        assert!(dot.starts_with("digraph ") && dot.ends_with("}\n"), "{dot}");
+        let edges = dot.matches(" -> ").count();
+        (dot.matches("[label=").count(), edges)
+    }
+
+    #[test]
+    fn bottom_up_range() {
+        assert_eq!(super::bottom_up_range(1, 4), "0..4");
+        assert_eq!(super::bottom_up_range(3, 4), "2..4");
+        assert_eq!(super::bottom_up_range(1, 5), "0..5");
+        assert_eq!(super::bottom_up_range(2, 5), "0..1, 3..5");
+        assert_eq!(super::bottom_up_range(6, 5), "1..2");
+    }
+
+    #[test]
+    fn flat_trees() {
+        for n in 1..20 {
+            let st = SegmentTree::<Add<i32>>::from_iter(0..n);
+            let n = n as usize;
+            assert_eq!(count(&st.to_dot()), (2 * n - 1, 2 * n - 2));
+            assert_eq!(
+                count(&BufferedSegmentTree::from(st).to_dot()),
+                (2 * n - 1, 2 * n - 2)
+            );
+            assert_eq!(
+                count(&LazySegmentTree::<AddQueryAddUpdate<i32>>::new(n).to_dot()),
+                (2 * n - 1, 2 * n - 2)
+            );
+            assert_eq!(
+                count(&DualSegmentTree::<Add<i32>>::new(n).to_dot()),
+                (2 * n - 1, 2 * n - 2)
+            );
+
+            // padding nodes are omitted
+            let (nodes, edges) = count(&VebSegmentTree::<Add<i32>>::new(n).to_dot());
+            assert_eq!(nodes, edges + 1);
+            let (nodes, edges) = count(&AssignSegmentTree::<Add<i32>>::new(n).to_dot());
+            assert_eq!(nodes, edges + 1);
+        }
+    }
+
+    #[test]
+    fn labels() {
+        let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(4);
+        lst.range_update(2..4, &5);
+        let dot = lst.to_dot();
+        assert!(
+            dot.contains("n3 [label=\"{2..4 | combined: 10 | pending: 5}\"];"),
+            "{dot}"
+        );
+
+        let st = SegmentTree::<Max<i32>>::from_iter([Some(1), None]);
+        let dot = st.to_dot();
+        assert!(
+            dot.contains("n1 [label=\"{0..2 | combined: Some(1)}\"];"),
+            "{dot}"
+        );
+        assert!(dot.contains("n3 [label=\"{1 | element: None}\"];"), "{dot}");
+    }
+
+    #[test]
+    fn dynamic_trees() {
+        let mut dst = DynamicSegmentTree::<Add<i32>>::new(0..8).unwrap();
+        assert_eq!(count(&dst.to_dot()), (0, 0));
+        for i in [4, 1, 6] {
+            dst.point_update(i, 1);
+        }
+        let dot = dst.to_dot();
+        assert_eq!(count(&dot), (3, 2));
+        assert!(
+            dot.contains("{0..8 | index: 4 | element: 1 | combined: 3}"),
+            "{dot}"
+        );
+
+        let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i32>>::new(0..8).unwrap();
+        dlst.range_update(1..2, &1);
+        let dot = dlst.to_dot();
+        assert_eq!(count(&dot), (7, 6));
+        assert!(dot.contains("{1 | element: 1 | pending: 1}"), "{dot}");
+    }
+
+    #[test]
+    fn levels() {
+        // the leaves are at two levels unless the length is a power of two
+        let st = SegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+        assert_eq!(
+            st.pretty(),
+            "0: [0..3] combined: 6\n\
+             1: [1..3] combined: 5  [0] element: 1\n\
+             2: [1] element: 2  [2] element: 3\n"
+        );
+
+        // the nodes of each level are listed from left to right regardless of allocation order
+        let mut dst = DynamicSegmentTree::<Add<i32>>::new(0..8).unwrap();
+        for i in [3, 6, 1, 5, 7, 0] {
+            dst.point_update(i, i as i32);
+        }
+        let pretty = dst.pretty();
+        let indices = Vec::from_iter(pretty.lines().map(|line| {
+            Vec::from_iter(
+                line.split("index: ")
+                    .skip(1)
+                    .map(|s| s[..s.find(',').unwrap()].parse::<isize>().unwrap()),
+            )
+        }));
+        for level in &indices {
+            assert!(level.is_sorted(), "{pretty}");
+        }
+        assert_eq!(indices.concat().len(), 6);
+    }
+
+    #[test]
+    fn mermaid() {
+        for n in 1..20 {
+            let st = SegmentTree::<Add<i32>>::from_iter(0..n);
+            let mermaid = st.to_mermaid();
+            assert!(mermaid.starts_with("graph TD\n"), "{mermaid}");
+            let n = n as usize;
+            assert_eq!(mermaid.matches("[\"").count(), 2 * n - 1);
+            assert_eq!(mermaid.matches(" --> ").count(), 2 * n - 2);
+        }
+
+        // quotes in the values do not close the labels
+        let st = SegmentTree::<crate::ops::ConcatString>::from_iter(["\"<a>#".to_string()]);
+        let mermaid = st.to_mermaid();
+        assert!(
+            mermaid.contains(r#"n1["0 | element: #quot;\#quot;#lt;a#gt;#35;#quot;"]"#),
+            "{mermaid}"
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(4);
+        lst.range_update(2..4, &5);
+        assert_eq!(lst.display().to_string(), lst.pretty());
+
+        let dst = DynamicSegmentTree::<Add<i32>>::new(0..8).unwrap();
+        assert_eq!(format!("{}", dst.display()), "");
+    }
+}
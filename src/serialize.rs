@@ -0,0 +1,180 @@
+//! The serialized forms of the trees.
+//!
+//! Trees are serialized as their resolved elements along with the parameters,
+//! and rebuilt from them on deserialization.
+//! Internal nodes, pending updates and working buffers are never serialized.
+//!
+//! [`DynamicLazySegmentTree`](crate::DynamicLazySegmentTree) is the exception:
+//! a range of default elements under an update is stored as the range and the update,
+//! so that the form stays as small as the arena rather than the range.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::GcPolicy;
+
+/// The form of the trees over `0..values.len()`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Leaves<V> {
+    pub(crate) values: V,
+}
+
+/// The form of [`LazySegmentTree`](crate::LazySegmentTree), which may have weighted elements.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct WeightedLeaves<V, W> {
+    pub(crate) values: V,
+    /// omitted if the elements are unweighted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) weights: Option<W>,
+}
+
+/// The form of the dynamic trees over `range`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RangedLeaves<V, T> {
+    pub(crate) range: Range<isize>,
+    pub(crate) values: V,
+    /// the element at the indexes which have never been updated, omitted if it is the identity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) default: Option<T>,
+}
+
+/// The form of [`DynamicLazySegmentTree`](crate::DynamicLazySegmentTree) over `range`,
+/// which lists elements and updated runs of default elements in ascending order.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RangedSegments<S, T> {
+    pub(crate) range: Range<isize>,
+    pub(crate) segments: S,
+    /// the element at the indexes which have never been updated, omitted if it is the identity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) default: Option<T>,
+}
+
+/// The form of [`AssignSegmentTree`](crate::AssignSegmentTree).
+#[derive(Serialize, Deserialize)]
+pub(crate) struct AssignLeaves<V> {
+    pub(crate) values: V,
+    pub(crate) gc_policy: GcPolicy,
+}
+
+#[cfg(test)]
+mod roundtrip {
+    use serde::{Serialize, de::DeserializeOwned};
+
+    use crate::{
+        AssignSegmentTree, BufferedSegmentTree, DualSegmentTree, DynamicLazySegmentTree,
+        DynamicSegmentTree, GcPolicy, LazySegmentTree, SegmentTree, VebSegmentTree,
+        acts::{AddQueryAddUpdate, AddQueryAssignUpdate},
+        ops::{Add, Max},
+    };
+
+    fn roundtrip<T: Serialize + DeserializeOwned>(tree: &T) -> T {
+        serde_json::from_str(&serde_json::to_string(tree).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn static_trees() {
+        let st = SegmentTree::<Add<i32>>::from_iter(0..10);
+        assert_eq!(roundtrip(&st).range_query(2..7), 20);
+
+        let st = VebSegmentTree::<Add<i32>>::from_iter(0..10);
+        assert_eq!(roundtrip(&st).range_query(2..7), 20);
+
+        let mut st = BufferedSegmentTree::<Add<i32>>::new(10);
+        st.point_update(3, 5);
+        assert_eq!(roundtrip(&st).range_query(..), 5);
+
+        let mut dst = DualSegmentTree::<Add<i32>>::new(5);
+        dst.range_update(1..4, &1);
+        assert_eq!(roundtrip(&dst).to_vec(), [0, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn lazy_trees() {
+        let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(10);
+        lst.range_update(2..8, &3);
+        assert_eq!(roundtrip(&lst).to_vec(), lst.to_vec());
+
+        let mut lst =
+            LazySegmentTree::<AddQueryAddUpdate<i32>>::with_weights(vec![0; 3], vec![1, 2, 3]);
+        lst.range_update(.., &1);
+        let mut lst = roundtrip(&lst);
+        assert_eq!(lst.weights(), Some(&[1, 2, 3][..]));
+        lst.range_update(1.., &1);
+        assert_eq!(lst.to_vec(), [1, 4, 6]);
+
+        let mut ast = AssignSegmentTree::<Max<i32>>::new(10);
+        ast.set_gc_policy(GcPolicy::Manual);
+        ast.range_assign(3..6, Some(7));
+        let mut ast = roundtrip(&ast);
+        assert_eq!(ast.range_query(..), Some(7));
+        assert_eq!(ast.gc_policy(), GcPolicy::Manual);
+    }
+
+    #[test]
+    fn dynamic_trees() {
+        let mut dst = DynamicSegmentTree::<Add<i64>>::new(-100..100).unwrap();
+        dst.point_update(-50, 3);
+        dst.point_update(70, 4);
+        let json = serde_json::to_string(&dst).unwrap();
+        assert_eq!(
+            json,
+            r#"{"range":{"start":-100,"end":100},"values":[[-50,3],[70,4]]}"#
+        );
+        assert_eq!(roundtrip(&dst).to_vec(), dst.to_vec());
+
+        let mut dst = DynamicSegmentTree::<Add<i64>>::with_default(0..10, 1).unwrap();
+        dst.point_update(3, 5);
+        assert_eq!(roundtrip(&dst).range_query(..), 9 + 5);
+
+        let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-5..5).unwrap();
+        dlst.range_update(-2..3, &2);
+        assert_eq!(roundtrip(&dlst).to_vec(), dlst.to_vec());
+
+        // the form is as sparse as the arena
+        let mut dlst =
+            DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(-1 << 40..1 << 40).unwrap();
+        dlst.range_update(0.., &2);
+        dlst.range_update(-7..=-7, &3);
+        let json = serde_json::to_string(&dlst).unwrap();
+        assert!(json.len() < 2_000, "{json}");
+        let copied = roundtrip(&dlst);
+        assert_eq!(copied.range_query(..), dlst.range_query(..));
+        assert_eq!(copied.range_query(-8..1), 3 + 2);
+        for r in [-7, -6, 0, 1 << 39] {
+            assert_eq!(copied.range_query(..r), dlst.range_query(..r));
+        }
+
+        let mut dlst =
+            DynamicLazySegmentTree::<AddQueryAssignUpdate<i64>>::with_default(0..1 << 30, 1)
+                .unwrap();
+        dlst.range_update(10..1 << 20, &Some(5));
+        dlst.assign_dense(3, &[7, 8]);
+        let copied = roundtrip(&dlst);
+        assert_eq!(copied.range_query(..), dlst.range_query(..));
+        assert_eq!(copied.range_query(..20), 3 + 7 + 8 + 5 + 5 * 10);
+    }
+
+    #[test]
+    fn invalid() {
+        let json = r#"{"range":{"start":0,"end":10},"values":[[10,1]]}"#;
+        assert!(serde_json::from_str::<DynamicSegmentTree<Add<i64>>>(json).is_err());
+
+        let json = r#"{"range":{"start":0,"end":0},"values":[]}"#;
+        assert!(serde_json::from_str::<DynamicSegmentTree<Add<i64>>>(json).is_err());
+
+        for segments in [
+            r#"[{"Element":[3,1]}]"#,
+            r#"[{"Run":[{"start":1,"end":1},1]}]"#,
+            r#"[{"Run":[{"start":0,"end":2},1]},{"Element":[1,1]}]"#,
+            r#"[{"Element":[2,1]},{"Run":[{"start":0,"end":2},1]}]"#,
+        ] {
+            let json = format!(r#"{{"range":{{"start":0,"end":3}},"segments":{segments}}}"#);
+            assert!(
+                serde_json::from_str::<DynamicLazySegmentTree<AddQueryAddUpdate<i64>>>(&json)
+                    .is_err(),
+                "{json}"
+            );
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! SIMD kernels for the built-in operations on primitive types.
+//!
+//! Requires a nightly compiler because this module uses [`std::simd`] and specialization.
+//! The kernels are used when building [`SegmentTree`](crate::SegmentTree)
+//! and answering short range queries of it.
+//!
+//! | operation                | set                                    |
+//! | ------------------------ | -------------------------------------- |
+//! | [`Add`](crate::ops::Add) | `u32`, `u64`, `i32`, `i64`, `f32`, `f64` |
+//! | [`Min`](crate::ops::Min) | `Option<T>` for `T` = `u32`, `u64`, `i32`, `i64` |
+//! | [`Max`](crate::ops::Max) | `Option<T>` for `T` = `u32`, `u64`, `i32`, `i64` |
+//!
+//! [`Min`](crate::ops::Min) and [`Max`](crate::ops::Max) only fold leaves with SIMD.
+//! Their sets are [`Option`]s, which do not fit in SIMD lanes,
+//! so `None` is loaded as `T::MAX` (resp. `T::MIN`) and the tree is built one by one.
+//!
+//! Note that the integer kernels wrap around on overflow even in debug builds,
+//! and that the floating-point kernels may sum in a different order.
+
+use std::{mem::MaybeUninit, simd::Simd};
+
+use crate::{
+    Monoid,
+    ops::{Add, Max, Min},
+    utility::combine_pairs_scalar,
+};
+
+/// Combines many elements at once.
+pub(crate) trait Kernel: Monoid {
+    /// Writes `combine(children[2k], children[2k + 1])` into `parents[k]`.
+    fn combine_pairs(children: &[Self::Set], parents: &mut [MaybeUninit<Self::Set>]);
+
+    /// Combines `leaves` if specialized.
+    fn fold_leaves(leaves: &[Self::Set]) -> Option<Self::Set>;
+}
+
+impl<M> Kernel for M
+where
+    M: Monoid,
+{
+    #[inline]
+    default fn combine_pairs(children: &[Self::Set], parents: &mut [MaybeUninit<Self::Set>]) {
+        combine_pairs_scalar::<M>(children, parents)
+    }
+
+    #[inline]
+    default fn fold_leaves(_leaves: &[Self::Set]) -> Option<Self::Set> {
+        None
+    }
+}
+
+macro_rules! add_kernel_impl {
+    ( $( $t:ty => $lanes:literal ),* $(,)? ) => {$(
+        impl Kernel for Add<$t> {
+            #[inline]
+            fn combine_pairs(children: &[$t], parents: &mut [MaybeUninit<$t>]) {
+                const LANES: usize = $lanes;
+
+                let mut k = 0;
+                while k + LANES <= parents.len() {
+                    let lhs = Simd::<$t, LANES>::from_slice(&children[k << 1..]);
+                    let rhs = Simd::<$t, LANES>::from_slice(&children[(k << 1) + LANES..]);
+                    let (even, odd) = lhs.deinterleave(rhs);
+                    // SAFETY: `parents[k..k + LANES]` is in bounds
+                    // and `MaybeUninit<T>` has the same layout as `T`.
+                    unsafe {
+                        parents
+                            .as_mut_ptr()
+                            .add(k)
+                            .cast::<Simd<$t, LANES>>()
+                            .write_unaligned(even + odd)
+                    }
+                    k += LANES
+                }
+                combine_pairs_scalar::<Self>(&children[k << 1..], &mut parents[k..])
+            }
+
+            #[inline]
+            fn fold_leaves(leaves: &[$t]) -> Option<$t> {
+                use std::simd::num::*;
+
+                const LANES: usize = $lanes;
+
+                let (prefix, middle, suffix) = leaves.as_simd::<LANES>();
+                let middle = middle.iter().fold(Simd::splat(0 as $t), |acc, v| acc + v);
+                Some(
+                    prefix.iter().chain(suffix).fold(0 as $t, |acc, v| acc + v)
+                        + middle.reduce_sum(),
+                )
+            }
+        }
+    )*};
+}
+
+add_kernel_impl! {
+    u32 => 8,
+    u64 => 4,
+    i32 => 8,
+    i64 => 4,
+    f32 => 8,
+    f64 => 4,
+}
+
+macro_rules! extremum_kernel_impl {
+    ( $op:ident, $ord:ident, $simd_op:ident, $reduce:ident, $none:ident; $( $t:ty => $lanes:literal ),* $(,)? ) => {$(
+        impl Kernel for $op<$t> {
+            #[inline]
+            fn combine_pairs(children: &[Option<$t>], parents: &mut [MaybeUninit<Option<$t>>]) {
+                combine_pairs_scalar::<Self>(children, parents)
+            }
+
+            #[inline]
+            fn fold_leaves(leaves: &[Option<$t>]) -> Option<Option<$t>> {
+                use std::simd::{cmp::SimdOrd, num::*};
+
+                const LANES: usize = $lanes;
+
+                let mut acc = Simd::<$t, LANES>::splat(<$t>::$none);
+                let mut found = false;
+                let chunks = leaves.chunks_exact(LANES);
+                let rest = chunks.remainder();
+                for chunk in chunks {
+                    let lanes = std::array::from_fn(|i| {
+                        found |= chunk[i].is_some();
+                        chunk[i].unwrap_or(<$t>::$none)
+                    });
+                    acc = acc.$simd_op(Simd::from_array(lanes))
+                }
+                let res = rest
+                    .iter()
+                    .flatten()
+                    .fold(acc.$reduce(), |acc, &v| Ord::$ord(acc, v));
+                Some((found || rest.iter().any(Option::is_some)).then_some(res))
+            }
+        }
+    )*};
+}
+
+extremum_kernel_impl! {
+    Min, min, simd_min, reduce_min, MAX;
+    u32 => 8,
+    u64 => 4,
+    i32 => 8,
+    i64 => 4,
+}
+
+extremum_kernel_impl! {
+    Max, max, simd_max, reduce_max, MIN;
+    u32 => 8,
+    u64 => 4,
+    i32 => 8,
+    i64 => 4,
+}
+
+#[cfg(test)]
+mod kernel {
+    use rand::Rng;
+
+    use crate::{
+        SegmentTree,
+        ops::{Add, Max, Min},
+    };
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 0..200 {
+            let values = Vec::from_iter((0..n).map(|_| rng.random_range(0..1_000_u64)));
+            let st = SegmentTree::<Add<u64>>::from(values.clone());
+            let float = SegmentTree::<Add<f64>>::from_iter(values.iter().map(|&v| v as f64));
+            for i in 0..=n {
+                for j in i..=n {
+                    let sum = values[i..j].iter().sum::<u64>();
+                    assert_eq!(st.range_query(i..j), sum);
+                    assert_eq!(float.range_query(i..j), sum as f64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extremum() {
+        let mut rng = rand::rng();
+        for n in 0..200 {
+            let values = Vec::from_iter((0..n).map(|_| {
+                rng.random_bool(0.8)
+                    .then(|| rng.random_range(-1_000..1_000_i64))
+            }));
+            let min = SegmentTree::<Min<i64>>::from(values.clone());
+            let max = SegmentTree::<Max<i64>>::from(values.clone());
+            for i in 0..=n {
+                for j in i..=n {
+                    let leaves = values[i..j].iter().flatten();
+                    assert_eq!(min.range_query(i..j), leaves.clone().min().copied());
+                    assert_eq!(max.range_query(i..j), leaves.max().copied());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Compact binary snapshots of the trees.
+//!
+//! A snapshot stores the elements of a tree in raw little-endian encoding after a small header,
+//! so that a large tree can be saved and restored much faster than through a general serialization format.
+//! Internal nodes are rebuilt on load in *O*(*N*) time.
+//!
+//! # Format
+//!
+//! | offset | size                    | content                                           |
+//! | ------ | ----------------------- | ------------------------------------------------- |
+//! | 0      | 6                       | magic bytes `b"SEGLIB"`                           |
+//! | 6      | 1                       | format version, currently [`VERSION`]             |
+//! | 7      | 1                       | [`Element::KIND`]                                 |
+//! | 8      | 1                       | [`Element::SIZE`]                                 |
+//! | 9      | 8                       | the number of elements *N* as little-endian `u64` |
+//! | 17     | *N* × [`Element::SIZE`] | the elements in little-endian encoding            |
+//!
+//! Only the elements are stored, so a snapshot written by one tree can be read by another tree
+//! over the same element type.
+//! For the same reason, a [`LazySegmentTree`](crate::LazySegmentTree) with weights refuses to write a snapshot.
+//! The dynamic trees, which are sparse, do not support snapshots.
+//!
+//! # Example
+//!
+//! ```
+//! use seg_lib::{LazySegmentTree, SegmentTree, acts::AddQueryAddUpdate, ops::Add};
+//!
+//! let st = SegmentTree::<Add<u64>>::from_iter(0..100);
+//! let mut bytes = Vec::new();
+//! st.write_snapshot(&mut bytes).unwrap();
+//! assert_eq!(bytes.len(), 17 + 100 * 8);
+//!
+//! let lst = LazySegmentTree::<AddQueryAddUpdate<u64>>::read_snapshot(&bytes[..]).unwrap();
+//! assert_eq!(lst.to_vec(), st.to_vec());
+//! ```
+
+use std::io::{self, Read, Write};
+
+/// The magic bytes at the beginning of a snapshot.
+const MAGIC: [u8; 6] = *b"SEGLIB";
+
+/// The current version of the snapshot format.
+pub const VERSION: u8 = 1;
+
+/// The number of bytes of the header.
+const HEADER: usize = MAGIC.len() + 3 + size_of::<u64>();
+
+/// The number of elements encoded at once.
+const CHUNK: usize = 1 << 12;
+
+/// Elements which have a fixed-size little-endian encoding.
+///
+/// Implemented for primitive integers, floats, [`bool`] and [`Option`] of them.
+pub trait Element: Copy {
+    /// Identifies the type in the header together with [`SIZE`](Self::SIZE).
+    const KIND: u8;
+    /// The number of bytes of the encoding.
+    const SIZE: usize;
+
+    /// Writes the encoding into `bytes` of length [`SIZE`](Self::SIZE).
+    fn write_le(&self, bytes: &mut [u8]);
+
+    /// Reads the encoding from `bytes` of length [`SIZE`](Self::SIZE).
+    ///
+    /// Returns `None` if `bytes` is not a valid encoding.
+    fn read_le(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! element_impl {
+    ($kind:literal, $($t:ty)*) => {$(
+        impl Element for $t {
+            const KIND: u8 = $kind;
+            const SIZE: usize = size_of::<$t>();
+
+            #[inline]
+            fn write_le(&self, bytes: &mut [u8]) {
+                bytes.copy_from_slice(&self.to_le_bytes())
+            }
+
+            #[inline]
+            fn read_le(bytes: &[u8]) -> Option<Self> {
+                bytes.try_into().ok().map(<$t>::from_le_bytes)
+            }
+        }
+    )*};
+}
+
+element_impl!(b'u', u8 u16 u32 u64 u128 usize);
+element_impl!(b'i', i8 i16 i32 i64 i128 isize);
+element_impl!(b'f', f32 f64);
+
+impl Element for bool {
+    const KIND: u8 = b'b';
+    const SIZE: usize = 1;
+
+    #[inline]
+    fn write_le(&self, bytes: &mut [u8]) {
+        bytes[0] = *self as u8
+    }
+
+    #[inline]
+    fn read_le(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// Encoded as a flag byte followed by the value, which is zero-filled for `None`.
+impl<T> Element for Option<T>
+where
+    T: Element,
+{
+    const KIND: u8 = T::KIND.to_ascii_uppercase();
+    const SIZE: usize = 1 + T::SIZE;
+
+    #[inline]
+    fn write_le(&self, bytes: &mut [u8]) {
+        let (flag, value) = bytes.split_at_mut(1);
+        match self {
+            Some(v) => {
+                flag[0] = 1;
+                v.write_le(value)
+            }
+            None => {
+                flag[0] = 0;
+                value.fill(0)
+            }
+        }
+    }
+
+    #[inline]
+    fn read_le(bytes: &[u8]) -> Option<Self> {
+        match bytes.split_first()? {
+            (0, _) => Some(None),
+            (1, value) => T::read_le(value).map(Some),
+            _ => None,
+        }
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Writes the header and `len` elements yielded by `elements`.
+pub(crate) fn write<'a, T, W>(
+    mut writer: W,
+    len: usize,
+    elements: impl IntoIterator<Item = &'a T>,
+) -> io::Result<()>
+where
+    T: Element + 'a,
+    W: Write,
+{
+    let mut header = [0; HEADER];
+    header[..MAGIC.len()].copy_from_slice(&MAGIC);
+    header[MAGIC.len()..MAGIC.len() + 3].copy_from_slice(&[VERSION, T::KIND, T::SIZE as u8]);
+    header[MAGIC.len() + 3..].copy_from_slice(&(len as u64).to_le_bytes());
+    writer.write_all(&header)?;
+
+    let mut buf = vec![0; CHUNK * T::SIZE];
+    let mut elements = elements.into_iter();
+    let mut rest = len;
+    while rest > 0 {
+        let k = rest.min(CHUNK);
+        for (bytes, element) in buf.chunks_exact_mut(T::SIZE).zip(elements.by_ref().take(k)) {
+            element.write_le(bytes)
+        }
+        writer.write_all(&buf[..k * T::SIZE])?;
+        rest -= k
+    }
+
+    Ok(())
+}
+
+/// Reads the header and the elements, checking that they are written for `T`.
+pub(crate) fn read<T, R>(mut reader: R) -> io::Result<Vec<T>>
+where
+    T: Element,
+    R: Read,
+{
+    let mut header = [0; HEADER];
+    reader.read_exact(&mut header)?;
+    if header[..MAGIC.len()] != MAGIC {
+        return Err(invalid_data("not a snapshot"));
+    }
+    let [version, kind, size] = [6, 7, 8].map(|i| header[i]);
+    if version != VERSION {
+        return Err(invalid_data("unsupported snapshot version"));
+    }
+    if kind != T::KIND || size as usize != T::SIZE {
+        return Err(invalid_data("snapshot element type mismatch"));
+    }
+    let len = u64::from_le_bytes(header[MAGIC.len() + 3..].try_into().unwrap());
+    let len = usize::try_from(len).map_err(|_| invalid_data("snapshot too large"))?;
+
+    // the length is not trusted until the elements are actually read
+    let mut values = Vec::with_capacity(len.min(CHUNK));
+    let mut buf = vec![0; CHUNK * T::SIZE];
+    let mut rest = len;
+    while rest > 0 {
+        let k = rest.min(CHUNK);
+        reader.read_exact(&mut buf[..k * T::SIZE])?;
+        for bytes in buf[..k * T::SIZE].chunks_exact(T::SIZE) {
+            values.push(T::read_le(bytes).ok_or_else(|| invalid_data("invalid element"))?)
+        }
+        rest -= k
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod read_write {
+    use rand::Rng;
+
+    use super::Element;
+    use crate::{
+        AssignSegmentTree, DualSegmentTree, SegmentTree, VebSegmentTree,
+        ops::{Add, Max},
+    };
+
+    fn roundtrip<T: Element + PartialEq + std::fmt::Debug>(values: Vec<T>) {
+        let mut bytes = Vec::new();
+        super::write(&mut bytes, values.len(), &values).unwrap();
+        assert_eq!(bytes.len(), super::HEADER + values.len() * T::SIZE);
+        assert_eq!(super::read::<T, _>(&bytes[..]).unwrap(), values);
+    }
+
+    #[test]
+    fn elements() {
+        let mut rng = rand::rng();
+        for n in [0, 1, 100, super::CHUNK, 3 * super::CHUNK + 1] {
+            roundtrip(Vec::from_iter((0..n).map(|_| rng.random::<u8>())));
+            roundtrip(Vec::from_iter((0..n).map(|_| rng.random::<i64>())));
+            roundtrip(Vec::from_iter((0..n).map(|_| rng.random::<f64>())));
+            roundtrip(Vec::from_iter((0..n).map(|_| rng.random::<bool>())));
+            roundtrip(Vec::from_iter(
+                (0..n).map(|_| rng.random_bool(0.5).then(|| rng.random::<i32>())),
+            ));
+        }
+    }
+
+    #[test]
+    fn trees() {
+        let st = SegmentTree::<Max<i32>>::from_iter((0..1000).map(|i| Some(i % 37)));
+        let mut bytes = Vec::new();
+        st.write_snapshot(&mut bytes).unwrap();
+
+        let veb = VebSegmentTree::<Max<i32>>::read_snapshot(&bytes[..]).unwrap();
+        assert_eq!(veb.range_query(..), Some(36));
+        let mut ast = AssignSegmentTree::<Max<i32>>::read_snapshot(&bytes[..]).unwrap();
+        assert_eq!(ast.to_vec(), st.to_vec());
+        ast.range_assign(.., Some(1));
+
+        bytes.clear();
+        ast.write_snapshot(&mut bytes).unwrap();
+        let st = SegmentTree::<Max<i32>>::read_snapshot(&bytes[..]).unwrap();
+        assert_eq!(st.to_vec(), vec![Some(1); 1000]);
+    }
+
+    #[test]
+    fn invalid() {
+        let dst = DualSegmentTree::<Add<u32>>::new(10);
+        let mut bytes = Vec::new();
+        dst.write_snapshot(&mut bytes).unwrap();
+        assert!(SegmentTree::<Add<u32>>::read_snapshot(&bytes[..]).is_ok());
+
+        // element type mismatch
+        assert!(SegmentTree::<Add<i32>>::read_snapshot(&bytes[..]).is_err());
+        assert!(SegmentTree::<Add<u64>>::read_snapshot(&bytes[..]).is_err());
+        // truncated
+        assert!(SegmentTree::<Add<u32>>::read_snapshot(&bytes[..bytes.len() - 1]).is_err());
+        // corrupted header
+        bytes[0] = 0;
+        assert!(SegmentTree::<Add<u32>>::read_snapshot(&bytes[..]).is_err());
+
+        // invalid flag
+        let mut bytes = Vec::new();
+        super::write(&mut bytes, 1, &[Some(1_u8)]).unwrap();
+        bytes[super::HEADER] = 2;
+        assert!(super::read::<Option<u8>, _>(&bytes[..]).is_err());
+    }
+}
@@ -0,0 +1,309 @@
+#[cfg(any(feature = "instrument", feature = "tracing"))]
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed};
+
+/// Counts of the operations performed by a tree, returned by `stats()`.
+///
+/// Available with the `instrument` feature.
+#[cfg(any(feature = "instrument", feature = "tracing"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// The number of [`combine()`](crate::Monoid::combine) and [`act()`](crate::MonoidAction::act) calls on nodes.
+    pub combines: u64,
+    /// The number of pending updates pushed down to the children.
+    pub propagations: u64,
+    /// The number of nodes allocated by updates of the dynamic trees, including recycled ones.
+    pub allocations: u64,
+    /// The maximum length of the internal stack used to recalculate the dynamic trees.
+    pub max_depth: usize,
+}
+
+/// Operation counters embedded in each tree.
+///
+/// This is a zero-sized type and every method is a no-op without the `instrument` or `tracing` feature.
+/// The counters are atomic so that the trees remain [`Sync`] and queries through `&self` can be counted.
+#[derive(Default)]
+pub(crate) struct Counters {
+    #[cfg(any(feature = "instrument", feature = "tracing"))]
+    combines: AtomicU64,
+    #[cfg(any(feature = "instrument", feature = "tracing"))]
+    propagations: AtomicU64,
+    #[cfg(any(feature = "instrument", feature = "tracing"))]
+    allocations: AtomicU64,
+    #[cfg(any(feature = "instrument", feature = "tracing"))]
+    max_depth: AtomicUsize,
+}
+
+#[cfg_attr(
+    not(any(feature = "instrument", feature = "tracing")),
+    allow(unused_variables)
+)]
+impl Counters {
+    /// Creates counters which record the `n - 1` combine operations to build a tree with `n` leaves.
+    #[inline(always)]
+    pub(crate) fn built(n: usize) -> Self {
+        let counters = Self::default();
+        counters.combine(n.saturating_sub(1));
+        counters
+    }
+
+    /// Records `n` combine operations.
+    #[inline(always)]
+    pub(crate) fn combine(&self, n: usize) {
+        #[cfg(any(feature = "instrument", feature = "tracing"))]
+        self.combines.fetch_add(n as u64, Relaxed);
+    }
+
+    /// Records a propagation.
+    #[inline(always)]
+    pub(crate) fn propagate(&self) {
+        #[cfg(any(feature = "instrument", feature = "tracing"))]
+        self.propagations.fetch_add(1, Relaxed);
+    }
+
+    /// Records a node allocation.
+    #[inline(always)]
+    pub(crate) fn allocate(&self) {
+        #[cfg(any(feature = "instrument", feature = "tracing"))]
+        self.allocations.fetch_add(1, Relaxed);
+    }
+
+    /// Records the current stack depth.
+    #[inline(always)]
+    pub(crate) fn depth(&self, depth: usize) {
+        #[cfg(any(feature = "instrument", feature = "tracing"))]
+        self.max_depth.fetch_max(depth, Relaxed);
+    }
+
+    #[cfg(any(feature = "instrument", feature = "tracing"))]
+    pub(crate) fn stats(&self) -> Stats {
+        Stats {
+            combines: self.combines.load(Relaxed),
+            propagations: self.propagations.load(Relaxed),
+            allocations: self.allocations.load(Relaxed),
+            max_depth: self.max_depth.load(Relaxed),
+        }
+    }
+
+    #[cfg(feature = "instrument")]
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default()
+    }
+}
+
+/// Creates a span for an operation over `range` whose counts are recorded by [`Counters::exit`].
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+    ($name:literal, $range:expr) => {
+        tracing::trace_span!(
+            $name,
+            range = ?$range,
+            combines = tracing::field::Empty,
+            propagations = tracing::field::Empty,
+            allocations = tracing::field::Empty,
+        )
+    };
+}
+#[cfg(feature = "tracing")]
+pub(crate) use trace_span;
+
+/// An entered span with the counts at the time of entry.
+#[cfg(feature = "tracing")]
+pub(crate) struct Traced {
+    span: tracing::span::EnteredSpan,
+    start: Stats,
+}
+
+#[cfg(feature = "tracing")]
+impl Counters {
+    /// Enters `span` created by [`trace_span!`].
+    pub(crate) fn enter(&self, span: tracing::Span) -> Traced {
+        Traced {
+            start: self.stats(),
+            span: span.entered(),
+        }
+    }
+
+    /// Records the counts since [`enter()`](Self::enter) and exits the span.
+    pub(crate) fn exit(&self, traced: Traced) {
+        let Traced { span, start } = traced;
+        let end = self.stats();
+        span.record("combines", end.combines - start.combines);
+        span.record("propagations", end.propagations - start.propagations);
+        span.record("allocations", end.allocations - start.allocations);
+    }
+}
+
+impl Clone for Counters {
+    #[cfg(any(feature = "instrument", feature = "tracing"))]
+    fn clone(&self) -> Self {
+        let Stats {
+            combines,
+            propagations,
+            allocations,
+            max_depth,
+        } = self.stats();
+        Self {
+            combines: AtomicU64::new(combines),
+            propagations: AtomicU64::new(propagations),
+            allocations: AtomicU64::new(allocations),
+            max_depth: AtomicUsize::new(max_depth),
+        }
+    }
+
+    #[cfg(not(any(feature = "instrument", feature = "tracing")))]
+    fn clone(&self) -> Self {
+        Self {}
+    }
+}
+
+#[cfg(all(test, feature = "instrument"))]
+mod counters {
+    use rand::Rng;
+
+    use crate::{
+        DynamicLazySegmentTree, DynamicSegmentTree, LazySegmentTree, SegmentTree,
+        acts::{AddQueryAddUpdate, AddQueryAssignUpdate},
+        ops::Add,
+    };
+
+    #[test]
+    fn segment_tree() {
+        let mut st = SegmentTree::<Add<i32>>::new(1 << 10);
+        assert_eq!(st.stats().combines, (1 << 10) - 1);
+
+        st.reset_stats();
+        st.point_update(0, 1);
+        assert_eq!(st.stats().combines, 10);
+
+        let clone = st.clone();
+        assert_eq!(clone.stats(), st.stats());
+        assert_eq!(st.stats().propagations + st.stats().allocations, 0);
+    }
+
+    #[test]
+    fn lazy_segment_tree() {
+        // commutative updates are not propagated by updates
+        let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(100);
+        lst.range_update(10..90, &1);
+        lst.range_update(20..30, &1);
+        assert_eq!(lst.stats().propagations, 0);
+
+        let mut lst = LazySegmentTree::<AddQueryAssignUpdate<i32>>::new(100);
+        lst.range_update(10..90, &Some(1));
+        lst.range_query(20..30);
+        assert!(lst.stats().propagations > 0);
+    }
+
+    #[test]
+    fn dynamic_segment_tree() {
+        let mut rng = rand::rng();
+        let mut dst = DynamicSegmentTree::<Add<i64>>::new(0..1 << 20).unwrap();
+        let mut indices = Vec::new();
+        for _ in 0..1000 {
+            let i = rng.random_range(0..1_i64 << 20) as isize;
+            dst.point_update(i, 1);
+            dst.range_query(i / 2..i);
+            indices.push(i);
+        }
+        indices.sort_unstable();
+        indices.dedup();
+
+        let stats = dst.stats();
+        assert_eq!(stats.allocations, indices.len() as u64);
+        assert!(stats.max_depth <= 21, "{stats:?}");
+    }
+
+    #[test]
+    fn dynamic_lazy_segment_tree() {
+        let mut dlst = DynamicLazySegmentTree::<AddQueryAddUpdate<i64>>::new(0..1 << 20).unwrap();
+        dlst.range_update(.., &1);
+        assert_eq!(dlst.stats().allocations, 2);
+
+        // the children of every internal node on the path are allocated
+        dlst.reset_stats();
+        dlst.range_update(1..2, &1);
+        let stats = dlst.stats();
+        assert_eq!(stats.allocations, 2 * 19);
+        assert_eq!(stats.propagations, 19);
+
+        dlst.reset_stats();
+        dlst.range_query(..);
+        assert_eq!(dlst.stats().combines, 1);
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod spans {
+    use std::sync::Mutex;
+
+    use tracing::{
+        Event, Metadata, Subscriber,
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+    };
+
+    use crate::{LazySegmentTree, SegmentTree, acts::AddQueryAddUpdate, ops::Add};
+
+    /// Collects the names and the recorded `combines` of the spans.
+    #[derive(Default)]
+    struct Collector {
+        spans: Mutex<Vec<(&'static str, u64)>>,
+    }
+
+    struct Combines<'a>(&'a mut u64);
+
+    impl Visit for Combines<'_> {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "combines" {
+                *self.0 = value
+            }
+        }
+
+        fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+    }
+
+    impl Subscriber for &'static Collector {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata().name(), 0));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            values.record(&mut Combines(&mut spans[span.into_u64() as usize - 1].1));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn spans() {
+        let collector = Box::leak(Box::new(Collector::default()));
+        tracing::subscriber::with_default(&*collector, || {
+            let st = SegmentTree::<Add<i32>>::from_iter(0..100);
+            assert_eq!(st.range_query(10..90), (10..90).sum::<i32>());
+
+            let mut lst = LazySegmentTree::<AddQueryAddUpdate<i32>>::new(100);
+            lst.range_update(10..90, &1);
+            assert_eq!(lst.range_query(..), 80);
+        });
+
+        let spans = collector.spans.lock().unwrap();
+        assert_eq!(
+            Vec::from_iter(spans.iter().map(|&(name, _)| name)),
+            ["range_query", "range_update", "range_query"]
+        );
+        assert!(spans.iter().all(|&(_, combines)| combines > 0), "{spans:?}");
+    }
+}
@@ -18,6 +18,7 @@ pub trait Monoid {
     /// If [`Self::combine`] is commutative, some operations can be optimized.
     ///
     /// If unsure about the commutativity, use [`false`] for safety.
+    /// If [`true`], consider implementing [`CommutativeMonoid`] as well.
     ///
     /// # Commutative low
     ///
@@ -35,6 +36,57 @@ pub trait Monoid {
     ///
     /// If the operation is **not** commutative, the position of the arguments matters.
     fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set;
+
+    /// Combines `rhs_or_new` into `lhs_or_prev` in place.
+    ///
+    /// Override this to reuse the memory of `lhs_or_prev` if the elements own heap memory.
+    /// The default implementation calls [`Self::combine`].
+    #[inline]
+    fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+        *lhs_or_prev = Self::combine(lhs_or_prev, rhs_or_new)
+    }
+
+    /// Combines `n` copies of the element and returns the result.
+    ///
+    /// Override this if a closed form is available.
+    /// The default implementation uses repeated doubling in *O*(log *n*) combinations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{Monoid, ops::Mul};
+    ///
+    /// assert_eq!(Mul::<u64>::combine_n(&3, 5), 243);
+    /// assert_eq!(Mul::<u64>::combine_n(&3, 0), 1);
+    /// ```
+    fn combine_n(element: &Self::Set, mut n: usize) -> Self::Set {
+        let mut res = Self::identity();
+        if n == 0 {
+            return res;
+        }
+
+        let mut pow = Self::combine(&res, element);
+        loop {
+            if n & 1 == 1 {
+                Self::combine_assign(&mut res, &pow);
+            }
+            n >>= 1;
+            if n == 0 {
+                return res;
+            }
+            pow = Self::combine(&pow, &pow);
+        }
+    }
+
+    /// Returns [`true`] if the element is known to be the identity element.
+    ///
+    /// Segment trees skip pointless propagation of pending updates for which this returns [`true`].
+    /// The default implementation always returns [`false`], which is always safe.
+    #[inline]
+    fn is_identity(element: &Self::Set) -> bool {
+        let _ = element;
+        false
+    }
 }
 // ANCHOR_END: monoid_trait
 
@@ -55,6 +107,18 @@ macro_rules! monoid_tuple_impl {
             fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set {
                 ($( <$ty_names as Monoid>::combine(&lhs_or_prev.$indexes, &rhs_or_new.$indexes), )*)
             }
+
+            fn combine_assign(lhs_or_prev: &mut Self::Set, rhs_or_new: &Self::Set) {
+                $( <$ty_names as Monoid>::combine_assign(&mut lhs_or_prev.$indexes, &rhs_or_new.$indexes); )*
+            }
+
+            fn combine_n(element: &Self::Set, n: usize) -> Self::Set {
+                ($( <$ty_names as Monoid>::combine_n(&element.$indexes, n), )*)
+            }
+
+            fn is_identity(element: &Self::Set) -> bool {
+                true $( && <$ty_names as Monoid>::is_identity(&element.$indexes) )*
+            }
         }
     };
 }
@@ -62,6 +126,242 @@ macro_rules! monoid_tuple_impl {
 monoid_tuple_impl!((M0, 0), (M1, 1),);
 monoid_tuple_impl!((M0, 0), (M1, 1), (M2, 2),);
 monoid_tuple_impl!((M0, 0), (M1, 1), (M2, 2), (M3, 3),);
+monoid_tuple_impl!((M0, 0), (M1, 1), (M2, 2), (M3, 3), (M4, 4),);
+monoid_tuple_impl!((M0, 0), (M1, 1), (M2, 2), (M3, 3), (M4, 4), (M5, 5),);
+monoid_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+);
+monoid_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+);
+monoid_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+    (M8, 8),
+);
+monoid_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+    (M8, 8),
+    (M9, 9),
+);
+monoid_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+    (M8, 8),
+    (M9, 9),
+    (M10, 10),
+);
+monoid_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+    (M8, 8),
+    (M9, 9),
+    (M10, 10),
+    (M11, 11),
+);
+
+/// A marker trait for [`Monoid`]s whose [`combine`](Monoid::combine) is commutative.
+///
+/// Unlike [`Monoid::IS_COMMUTATIVE`], this allows functions to accept commutative monoids only.
+/// Implementors should also set [`Monoid::IS_COMMUTATIVE`] to [`true`],
+/// which segment trees use to select faster code paths at compile time,
+/// and which trees requiring this trait check at compile time as well.
+/// The fast paths are not specialized on this trait, since stable Rust has no specialization.
+///
+/// # Commutative low
+///
+/// ```text
+/// a · b = b · a    ∀ a, b ∈ Set
+/// ```
+pub trait CommutativeMonoid: Monoid {}
+
+macro_rules! commutative_monoid_tuple_impl {
+    ( $( $ty_names:ident, )* ) => {
+        impl<$( $ty_names, )*> CommutativeMonoid for ($( $ty_names, )*)
+        where
+            $( $ty_names: CommutativeMonoid, )*
+        {
+        }
+    };
+}
+
+commutative_monoid_tuple_impl!(M0, M1,);
+commutative_monoid_tuple_impl!(M0, M1, M2,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3, M4,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3, M4, M5,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3, M4, M5, M6,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3, M4, M5, M6, M7,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3, M4, M5, M6, M7, M8,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3, M4, M5, M6, M7, M8, M9,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3, M4, M5, M6, M7, M8, M9, M10,);
+commutative_monoid_tuple_impl!(M0, M1, M2, M3, M4, M5, M6, M7, M8, M9, M10, M11,);
+
+/// A **group** is a [`Monoid`] in which every element has an inverse element.
+///
+/// # Low
+///
+/// ```text
+/// a · a⁻¹ = a⁻¹ · a = e    ∀ a ∈ Set
+/// ```
+///
+/// See [Monoid] for reference.
+pub trait Group: Monoid {
+    /// Returns the inverse element.
+    fn inverse(element: &Self::Set) -> Self::Set;
+}
+
+macro_rules! group_tuple_impl {
+    ( $( ($ty_names:ident, $indexes:tt), )* ) => {
+        impl<$( $ty_names, )*> Group for ($( $ty_names, )*)
+        where
+            $( $ty_names: Group, )*
+        {
+            fn inverse(element: &Self::Set) -> Self::Set {
+                ($( <$ty_names as Group>::inverse(&element.$indexes), )*)
+            }
+        }
+    };
+}
+
+group_tuple_impl!((M0, 0), (M1, 1),);
+group_tuple_impl!((M0, 0), (M1, 1), (M2, 2),);
+group_tuple_impl!((M0, 0), (M1, 1), (M2, 2), (M3, 3),);
+group_tuple_impl!((M0, 0), (M1, 1), (M2, 2), (M3, 3), (M4, 4),);
+group_tuple_impl!((M0, 0), (M1, 1), (M2, 2), (M3, 3), (M4, 4), (M5, 5),);
+group_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+);
+group_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+);
+group_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+    (M8, 8),
+);
+group_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+    (M8, 8),
+    (M9, 9),
+);
+group_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+    (M8, 8),
+    (M9, 9),
+    (M10, 10),
+);
+group_tuple_impl!(
+    (M0, 0),
+    (M1, 1),
+    (M2, 2),
+    (M3, 3),
+    (M4, 4),
+    (M5, 5),
+    (M6, 6),
+    (M7, 7),
+    (M8, 8),
+    (M9, 9),
+    (M10, 10),
+    (M11, 11),
+);
+
+/// A **semigroup** is a set equipped with an associative binary operation, which may lack an identity element.
+///
+/// # Low
+///
+/// ```text
+/// a · (b · c) = (a · b) · c    ∀ a, b, c ∈ Set
+/// ```
+///
+/// Use [`Lift`](crate::ops::Lift) to turn it into a [`Monoid`] over [`Option`].
+pub trait Semigroup {
+    /// The set of the semigroup.
+    type Set;
+
+    /// If [`true`], [`Lift`](crate::ops::Lift) is commutative as well.
+    ///
+    /// See [`Monoid::IS_COMMUTATIVE`] for reference.
+    const IS_COMMUTATIVE: bool;
+
+    /// Combines the two elements and returns the result.
+    ///
+    /// # Warning
+    ///
+    /// If the operation is **not** commutative, the position of the arguments matters.
+    fn combine(lhs_or_prev: &Self::Set, rhs_or_new: &Self::Set) -> Self::Set;
+}
 
 /// A **monoid action** is a function `*: M x S -> S` of a monoid `M` on a monoid `S`.
 ///
@@ -94,9 +394,127 @@ pub trait MonoidAction {
         element: &<Self::Set as Monoid>::Set,
         size: Option<usize>,
     ) -> <Self::Set as Monoid>::Set;
+
+    /// Acts the mapping on the element in place.
+    ///
+    /// Override this to reuse the memory of `element` if the elements own heap memory.
+    /// The default implementation calls [`Self::act`].
+    #[inline]
+    fn act_assign(
+        mapping: &<Self::Map as Monoid>::Set,
+        element: &mut <Self::Set as Monoid>::Set,
+        size: Option<usize>,
+    ) {
+        *element = Self::act(mapping, element, size)
+    }
 }
 // ANCHOR_END: monoid_action_trait
 
+macro_rules! monoid_action_tuple_impl {
+    ( $( ($ty_names:ident, $indexes:tt), )* ) => {
+        impl<$( $ty_names, )*> MonoidAction for ($( $ty_names, )*)
+        where
+            $( $ty_names: MonoidAction, )*
+        {
+            type Map = ($( <$ty_names as MonoidAction>::Map, )*);
+            type Set = ($( <$ty_names as MonoidAction>::Set, )*);
+
+            const USE_SEGMENT_SIZE: bool = false $( | <$ty_names as MonoidAction>::USE_SEGMENT_SIZE )*;
+
+            fn act(
+                mapping: &<Self::Map as Monoid>::Set,
+                element: &<Self::Set as Monoid>::Set,
+                size: Option<usize>,
+            ) -> <Self::Set as Monoid>::Set {
+                ($( <$ty_names as MonoidAction>::act(&mapping.$indexes, &element.$indexes, size), )*)
+            }
+
+            fn act_assign(
+                mapping: &<Self::Map as Monoid>::Set,
+                element: &mut <Self::Set as Monoid>::Set,
+                size: Option<usize>,
+            ) {
+                $( <$ty_names as MonoidAction>::act_assign(&mapping.$indexes, &mut element.$indexes, size); )*
+            }
+        }
+    };
+}
+
+monoid_action_tuple_impl!((A0, 0), (A1, 1),);
+monoid_action_tuple_impl!((A0, 0), (A1, 1), (A2, 2),);
+monoid_action_tuple_impl!((A0, 0), (A1, 1), (A2, 2), (A3, 3),);
+monoid_action_tuple_impl!((A0, 0), (A1, 1), (A2, 2), (A3, 3), (A4, 4),);
+monoid_action_tuple_impl!((A0, 0), (A1, 1), (A2, 2), (A3, 3), (A4, 4), (A5, 5),);
+monoid_action_tuple_impl!(
+    (A0, 0),
+    (A1, 1),
+    (A2, 2),
+    (A3, 3),
+    (A4, 4),
+    (A5, 5),
+    (A6, 6),
+);
+monoid_action_tuple_impl!(
+    (A0, 0),
+    (A1, 1),
+    (A2, 2),
+    (A3, 3),
+    (A4, 4),
+    (A5, 5),
+    (A6, 6),
+    (A7, 7),
+);
+monoid_action_tuple_impl!(
+    (A0, 0),
+    (A1, 1),
+    (A2, 2),
+    (A3, 3),
+    (A4, 4),
+    (A5, 5),
+    (A6, 6),
+    (A7, 7),
+    (A8, 8),
+);
+monoid_action_tuple_impl!(
+    (A0, 0),
+    (A1, 1),
+    (A2, 2),
+    (A3, 3),
+    (A4, 4),
+    (A5, 5),
+    (A6, 6),
+    (A7, 7),
+    (A8, 8),
+    (A9, 9),
+);
+monoid_action_tuple_impl!(
+    (A0, 0),
+    (A1, 1),
+    (A2, 2),
+    (A3, 3),
+    (A4, 4),
+    (A5, 5),
+    (A6, 6),
+    (A7, 7),
+    (A8, 8),
+    (A9, 9),
+    (A10, 10),
+);
+monoid_action_tuple_impl!(
+    (A0, 0),
+    (A1, 1),
+    (A2, 2),
+    (A3, 3),
+    (A4, 4),
+    (A5, 5),
+    (A6, 6),
+    (A7, 7),
+    (A8, 8),
+    (A9, 9),
+    (A10, 10),
+    (A11, 11),
+);
+
 /// A function that behaves like a monoid action under well-defined conditions,
 /// which frequently hold in practice.
 ///
@@ -123,3 +541,135 @@ pub trait QuasiMonoidAction {
         size: Option<usize>,
     ) -> Result<<Self::Set as Monoid>::Set, ()>;
 }
+
+#[cfg(test)]
+mod combine_n {
+    use crate::{
+        Monoid,
+        ops::{Add, Affine, BitAnd, BitXor, GCD, Max, Min},
+    };
+
+    fn template<M>(elements: &[<M as Monoid>::Set])
+    where
+        M: Monoid<Set: PartialEq + std::fmt::Debug>,
+    {
+        for element in elements {
+            let mut naive = M::identity();
+            for n in 0..40 {
+                assert_eq!(
+                    M::combine_n(element, n),
+                    naive,
+                    "n = {n}, element = {element:?}"
+                );
+                naive = M::combine(&naive, element);
+            }
+        }
+    }
+
+    #[test]
+    fn default() {
+        template::<Add<i64>>(&[-3, 0, 7]);
+        template::<Affine<i64>>(&[(1, 2), (-1, 3), (0, 5)]);
+        template::<(Add<i64>, Max<i64>)>(&[(1, Some(2)), (0, None)]);
+        template::<(
+            Add<i64>,
+            Max<i64>,
+            Min<i64>,
+            BitAnd<u32>,
+            BitXor<u32>,
+            GCD<i64>,
+            Add<i64>,
+            Max<i64>,
+            Min<i64>,
+            BitXor<u32>,
+            Add<i64>,
+            GCD<i64>,
+        )>(&[(1, Some(2), Some(3), 5, 6, 4, -1, None, None, 1, 0, 0)]);
+    }
+
+    #[test]
+    fn overridden() {
+        template::<Max<i64>>(&[None, Some(-1), Some(5)]);
+        template::<Min<i64>>(&[None, Some(-1), Some(5)]);
+        template::<BitAnd<u32>>(&[0, 5, !0]);
+        template::<BitXor<u32>>(&[0, 5, !0]);
+        template::<GCD<i64>>(&[0, -6, 9]);
+    }
+}
+
+#[cfg(test)]
+mod commutative_monoid {
+    use crate::{
+        CommutativeMonoid,
+        ops::{Add, BitAnd, BitOr, BitXor, GCD, LCM, Max, Min, Mul, Shared},
+    };
+
+    fn template<M>()
+    where
+        M: CommutativeMonoid,
+    {
+        assert!(M::IS_COMMUTATIVE)
+    }
+
+    #[test]
+    fn consistent_with_const() {
+        template::<Add<i32>>();
+        template::<Mul<i32>>();
+        template::<BitAnd<u32>>();
+        template::<BitOr<u32>>();
+        template::<BitXor<u32>>();
+        template::<Max<i32>>();
+        template::<Min<i32>>();
+        template::<GCD<i32>>();
+        template::<LCM<i32>>();
+        template::<Shared<Add<i32>>>();
+        template::<(Add<i32>, Max<i32>, Min<i32>, GCD<i32>)>();
+    }
+}
+
+#[cfg(test)]
+mod monoid_action_tuple {
+    use rand::Rng;
+
+    use crate::{
+        LazySegmentTree,
+        acts::{AddQueryAddUpdate, MaxQueryAddUpdate, MinQueryAddUpdate},
+    };
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 1..40 {
+            let mut naive = Vec::from_iter((0..n as i64).map(|v| v * 7 % 11 - 5));
+            let mut lst = LazySegmentTree::<(
+                AddQueryAddUpdate<i64>,
+                MaxQueryAddUpdate<i64>,
+                MinQueryAddUpdate<i64>,
+            )>::from_iter(naive.iter().map(|&v| (v, Some(v), Some(v))));
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..n);
+                let r = rng.random_range(l..=n);
+                let add = rng.random_range(0..11_usize) as i64 - 5;
+                lst.range_update(l..r, &(add, add, add));
+                for e in &mut naive[l..r] {
+                    *e += add
+                }
+
+                for l in 0..n {
+                    for r in l..=n {
+                        let naive = &naive[l..r];
+                        assert_eq!(
+                            lst.range_query(l..r),
+                            (
+                                naive.iter().sum::<i64>(),
+                                naive.iter().max().copied(),
+                                naive.iter().min().copied()
+                            )
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use std::{
     fmt::Debug,
+    mem::MaybeUninit,
     ops::{Range, RangeBounds},
 };
 
+use crate::Monoid;
+
 /// Convert [`RangeBounds`] trait objects into [`Range`] which can be empty.
 ///
 /// # Panics
@@ -38,6 +41,97 @@ where
     start..end
 }
 
+/// Splits a range of the circular array of length `n` into two ordinary ranges in order.
+///
+/// A range `l..r` with `l > r` wraps around the end and is split into `l..n` and `0..r`.
+/// Otherwise, the second range is empty.
+#[inline(always)]
+pub(crate) fn split_cyclic(range: Range<usize>, n: usize) -> [Range<usize>; 2] {
+    if range.start <= range.end {
+        [range, n..n]
+    } else {
+        [range.start..n, 0..range.end]
+    }
+}
+
+/// Returns `(lhs + rhs) mod M` without overflow.
+#[inline(always)]
+pub(crate) const fn mod_add<const M: u64>(lhs: u64, rhs: u64) -> u64 {
+    ((lhs as u128 + rhs as u128) % M as u128) as u64
+}
+
+/// Returns `lhs * rhs mod M` without overflow.
+#[inline(always)]
+pub(crate) const fn mod_mul<const M: u64>(lhs: u64, rhs: u64) -> u64 {
+    (lhs as u128 * rhs as u128 % M as u128) as u64
+}
+
+/// Writes `combine(children[2k], children[2k + 1])` into `parents[k]`.
+///
+/// Uses [SIMD kernels](crate::simd) if available.
+#[inline]
+pub(crate) fn combine_pairs<M>(children: &[M::Set], parents: &mut [MaybeUninit<M::Set>])
+where
+    M: Monoid,
+{
+    #[cfg(feature = "simd")]
+    <M as crate::simd::Kernel>::combine_pairs(children, parents);
+    #[cfg(not(feature = "simd"))]
+    combine_pairs_scalar::<M>(children, parents);
+}
+
+/// Writes `combine(children[2k], children[2k + 1])` into `parents[k]` one by one.
+#[inline]
+pub(crate) fn combine_pairs_scalar<M>(children: &[M::Set], parents: &mut [MaybeUninit<M::Set>])
+where
+    M: Monoid,
+{
+    for (k, parent) in parents.iter_mut().enumerate() {
+        parent.write(M::combine(&children[k << 1], &children[(k << 1) | 1]));
+    }
+}
+
+/// Combines `leaves` at once if a [SIMD kernel](crate::simd) is available.
+#[cfg(feature = "simd")]
+#[inline]
+pub(crate) fn fold_leaves<M>(leaves: &[M::Set]) -> Option<M::Set>
+where
+    M: Monoid,
+{
+    <M as crate::simd::Kernel>::fold_leaves(leaves)
+}
+
+/// Calculates `nodes[1..n]` in bottom-to-top order, where `nodes[n..2n]` are leaves.
+///
+/// The nodes are processed in blocks `lo..hi` with `hi <= 2lo`,
+/// so that the children of a block lie in `hi..` and the block can be processed in parallel.
+#[cfg(feature = "rayon")]
+pub(crate) fn par_build<T, F>(nodes: &mut [T], n: usize, f: F)
+where
+    T: Send + Sync,
+    F: Fn(&mut T, &T, &T) + Sync,
+{
+    use rayon::prelude::*;
+
+    /// Avoids spawning tasks for tiny blocks.
+    const MIN_LEN: usize = 1 << 12;
+
+    let mut hi = n;
+    while hi > 1 {
+        let lo = hi.div_ceil(2);
+        let (parents, children) = nodes.split_at_mut(hi);
+        parents[lo..hi]
+            .par_iter_mut()
+            .with_min_len(MIN_LEN)
+            .enumerate()
+            .for_each(|(k, parent)| {
+                let i = (lo + k) << 1;
+                f(parent, &children[i - hi], &children[i + 1 - hi])
+            });
+        hi = lo
+    }
+}
+
 #[cfg(test)]
 mod test_convert_range {
     use std::panic::catch_unwind;
@@ -105,3 +199,44 @@ fn test_min_invalid_index() {
         assert_eq!(min_invalid_index(n * 2), naive(&size))
     }
 }
+
+/// Pointer to a node on the arena of the dynamic segment tree variants.
+///
+/// Enabling the `u32_ptr` feature halves the size of pointers.
+#[cfg(not(feature = "u32_ptr"))]
+pub(crate) type NodePtr = std::num::NonZeroUsize;
+/// Pointer to a node on the arena of the dynamic segment tree variants.
+///
+/// Enabling the `u32_ptr` feature halves the size of pointers.
+#[cfg(feature = "u32_ptr")]
+pub(crate) type NodePtr = std::num::NonZeroU32;
+
+/// Converts the index on the arena into [`NodePtr`], where `0` is converted into [`None`].
+///
+/// # Panics
+///
+/// Panics if `ptr` cannot be represented by [`NodePtr`].
+#[inline(always)]
+pub(crate) fn to_node_ptr(ptr: usize) -> Option<NodePtr> {
+    #[cfg(not(feature = "u32_ptr"))]
+    {
+        NodePtr::new(ptr)
+    }
+    #[cfg(feature = "u32_ptr")]
+    {
+        NodePtr::new(u32::try_from(ptr).expect("the number of nodes should be less than 2^32"))
+    }
+}
+
+/// Converts [`NodePtr`] into the index on the arena.
+#[inline(always)]
+pub(crate) const fn from_node_ptr(ptr: NodePtr) -> usize {
+    #[cfg(not(feature = "u32_ptr"))]
+    {
+        ptr.get()
+    }
+    #[cfg(feature = "u32_ptr")]
+    {
+        ptr.get() as usize
+    }
+}
@@ -0,0 +1,604 @@
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+    ops::RangeBounds,
+};
+
+use crate::{
+    memory::MemoryReport,
+    snapshot::{self, Element},
+    traits::Monoid,
+    utility::convert_range,
+};
+
+/// A data structure that supports **range query point update** operations
+/// with nodes stored in the van Emde Boas layout.
+///
+/// The tree is a perfect binary tree split into the top half and the bottom halves recursively,
+/// each of which is stored contiguously.
+/// As a result, a root-to-leaf path touches *O*(log<sub>*B*</sub> *N*) cache lines for any cache line size *B*,
+/// while [`SegmentTree`](crate::SegmentTree) touches *O*(log *N*) cache lines for deep trees.
+/// The trade-off is *O*(log log *N*) index calculation per node and up to twice as many nodes.
+///
+/// Prefer [`SegmentTree`](crate::SegmentTree) unless *N* is large, say *N* ≥ 10⁷.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{VebSegmentTree, ops::Add};
+///
+/// let mut st = VebSegmentTree::<Add<i64>>::from_iter(0..10);
+/// assert_eq!(st.range_query(..), 45);
+///
+/// st.point_update(3, 100);
+/// assert_eq!(st.range_query(2..5), 2 + 100 + 4);
+/// assert_eq!(st.point_query(3), &100);
+/// ```
+pub struct VebSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    /// `2 * leaves - 1` nodes in the van Emde Boas layout.
+    data: Box<[<Query as Monoid>::Set]>,
+    /// The number of elements.
+    len: usize,
+    /// The number of levels of the perfect binary tree.
+    height: u32,
+}
+
+impl<Query> VebSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    #[doc = include_str!("../doc/new.md")]
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{VebSegmentTree, ops::Add};
+    ///
+    /// let st = VebSegmentTree::<Add<i32>>::new(10_000);
+    /// assert_eq!(st.range_query(..), 0);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn new(n: usize) -> Self {
+        Self::from_iter(std::iter::repeat_with(<Query as Monoid>::identity).take(n))
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{VebSegmentTree, ops::Add};
+    ///
+    /// let st = VebSegmentTree::<Add<u32>>::new(100);
+    /// assert_eq!(st.memory_usage().data, 255 * size_of::<u32>());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            data: size_of_val(&*self.data),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the number of leaves including padding.
+    #[inline]
+    const fn leaves(&self) -> usize {
+        1 << (self.height - 1)
+    }
+
+    /// Converts the heap index (the root is `1` and the children of `i` are `2i` and `2i + 1`)
+    /// into the position in [`data`](Self::data).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log log *N*)
+    #[inline]
+    const fn position(&self, i: usize) -> usize {
+        let mut depth = i.ilog2();
+        let mut k = i ^ (1 << depth);
+        let mut height = self.height;
+
+        let mut pos = 0;
+        while height > 1 {
+            let top = height / 2;
+            if depth < top {
+                height = top
+            } else {
+                let bottom = height - top;
+                depth -= top;
+                pos += (1 << top) - 1 + (k >> depth) * ((1 << bottom) - 1);
+                k &= (1 << depth) - 1;
+                height = bottom
+            }
+        }
+
+        pos
+    }
+
+    #[inline]
+    fn recalculate_at(&mut self, i: usize) {
+        self.data[self.position(i)] = <Query as Monoid>::combine(
+            &self.data[self.position(i << 1)],
+            &self.data[self.position((i << 1) | 1)],
+        )
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N* log log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{VebSegmentTree, ops::Mul};
+    ///
+    /// let mut st = VebSegmentTree::<Mul<i32>>::new(100);
+    /// st.point_update(50, 2);
+    /// assert_eq!(st.range_query(..), 2);
+    /// assert_eq!(st.range_query(..50), 1);
+    /// ```
+    pub fn point_update(&mut self, i: usize, element: <Query as Monoid>::Set) {
+        assert!(
+            i < self.len,
+            "index out of bounds: the len is {} but the index is {i}",
+            self.len
+        );
+
+        let mut i = self.leaves() + i;
+        let pos = self.position(i);
+        self.data[pos] = element;
+        while i > 1 {
+            i >>= 1;
+            self.recalculate_at(i)
+        }
+    }
+
+    #[doc = include_str!("../doc/point_query.md")]
+    /// # Time complexity
+    ///
+    /// *O*(log log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{VebSegmentTree, ops::BitXor};
+    ///
+    /// let mut st = VebSegmentTree::<BitXor<u32>>::new(100);
+    /// st.point_update(10, 6);
+    /// assert_eq!(st.point_query(10), &6);
+    /// ```
+    pub fn point_query(&self, i: usize) -> &<Query as Monoid>::Set {
+        assert!(
+            i < self.len,
+            "index out of bounds: the len is {} but the index is {i}",
+            self.len
+        );
+
+        &self.data[self.position(self.leaves() + i)]
+    }
+
+    #[doc = include_str!("../doc/range_query.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log *N* log log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{VebSegmentTree, ops::Max};
+    ///
+    /// let st = VebSegmentTree::<Max<i32>>::from_iter([3, 1, 4, 1, 5].map(Some));
+    /// assert_eq!(st.range_query(1..4), Some(4));
+    /// assert_eq!(st.range_query(2..2), None);
+    /// ```
+    pub fn range_query<R>(&self, range: R) -> <Query as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len);
+        let [mut l, mut r] = [range.start, range.end].map(|i| self.leaves() + i);
+
+        let (mut acc_l, mut acc_r) = (<Query as Monoid>::identity(), <Query as Monoid>::identity());
+        while l < r {
+            if l & 1 == 1 {
+                <Query as Monoid>::combine_assign(&mut acc_l, &self.data[self.position(l)]);
+                l += 1
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                acc_r = <Query as Monoid>::combine(&self.data[self.position(r)], &acc_r)
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+
+        <Query as Monoid>::combine(&acc_l, &acc_r)
+    }
+}
+
+impl<Query> VebSegmentTree<Query>
+where
+    Query: Monoid<Set: Element>,
+{
+    #[doc = include_str!("../doc/write_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, VebSegmentTree, ops::Add};
+    ///
+    /// let st = VebSegmentTree::<Add<u32>>::from_iter(0..10);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let st = SegmentTree::<Add<u32>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(st.range_query(..), 45);
+    /// ```
+    pub fn write_snapshot<W>(&self, writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        snapshot::write(writer, self.len, (0..self.len).map(|i| self.point_query(i)))
+    }
+
+    #[doc = include_str!("../doc/read_snapshot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{SegmentTree, VebSegmentTree, ops::Add};
+    ///
+    /// let st = SegmentTree::<Add<u32>>::from_iter(0..10);
+    /// let mut bytes = Vec::new();
+    /// st.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let st = VebSegmentTree::<Add<u32>>::read_snapshot(&bytes[..]).unwrap();
+    /// assert_eq!(st.range_query(..), 45);
+    /// ```
+    pub fn read_snapshot<R>(reader: R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        snapshot::read(reader).map(Self::from)
+    }
+}
+
+impl<Query> From<Vec<<Query as Monoid>::Set>> for VebSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn from(values: Vec<<Query as Monoid>::Set>) -> Self {
+        let len = values.len();
+        let leaves = len.next_power_of_two();
+        let height = leaves.trailing_zeros() + 1;
+
+        let data = Vec::from_iter(
+            std::iter::repeat_with(<Query as Monoid>::identity).take((leaves << 1) - 1),
+        )
+        .into_boxed_slice();
+        let mut tree = Self { data, len, height };
+
+        for (i, value) in values.into_iter().enumerate() {
+            let pos = tree.position(leaves + i);
+            tree.data[pos] = value
+        }
+        for i in (1..leaves).rev() {
+            tree.recalculate_at(i)
+        }
+
+        tree
+    }
+}
+
+impl<Query> FromIterator<<Query as Monoid>::Set> for VebSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn from_iter<I: IntoIterator<Item = <Query as Monoid>::Set>>(iter: I) -> Self {
+        Self::from(Vec::from_iter(iter))
+    }
+}
+
+impl<Query> VebSegmentTree<Query>
+where
+    Query: Monoid<Set: Debug>,
+{
+    #[doc = include_str!("../doc/to_dot.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{VebSegmentTree, ops::Add};
+    ///
+    /// let st = VebSegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+    /// let dot = st.to_dot();
+    /// assert!(dot.contains("{0..3 | combined: 6}"));
+    /// assert!(dot.contains("{2 | element: 3}"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = crate::render::Dot::new("VebSegmentTree");
+        self.render(&mut dot);
+        dot.finish()
+    }
+
+    #[doc = include_str!("../doc/pretty.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N* log log *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{VebSegmentTree, ops::Add};
+    ///
+    /// let st = VebSegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+    /// assert_eq!(
+    ///     st.pretty(),
+    ///     "0: [0..3] combined: 6\n\
+    ///      1: [0..2] combined: 3  [2..3] combined: 3\n\
+    ///      2: [0] element: 1  [1] element: 2  [2] element: 3\n"
+    /// );
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut levels = crate::render::Levels::default();
+        self.render(&mut levels);
+        levels.finish()
+    }
+
+    #[doc = include_str!("../doc/to_mermaid.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{VebSegmentTree, ops::Add};
+    ///
+    /// let st = VebSegmentTree::<Add<i32>>::from_iter([1, 2, 3]);
+    /// let mermaid = st.to_mermaid();
+    /// assert!(mermaid.contains(r#"["0..3 | combined: 6"]"#));
+    /// assert!(mermaid.contains(r#"["2 | element: 3"]"#));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = crate::render::Mermaid::default();
+        self.render(&mut mermaid);
+        mermaid.finish()
+    }
+
+    /// Returns an adapter which [displays](std::fmt::Display) the tree like [`pretty`](Self::pretty).
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1), and formatting takes as long as [`pretty`](Self::pretty)
+    pub fn display(&self) -> crate::Pretty<'_, Self> {
+        crate::Pretty(self)
+    }
+
+    /// Passes the nodes to `out` in level order.
+    pub(crate) fn render(&self, out: &mut impl crate::render::Render) {
+        let leaves = self.leaves();
+        // nodes covering only padding are omitted
+        for i in 1..leaves << 1 {
+            let range = crate::render::perfect_range(i, leaves, self.len);
+            if range.is_empty() {
+                continue;
+            }
+
+            let parent = (i > 1).then_some(i >> 1);
+            let depth = i.ilog2() as usize;
+            let element = &self.data[self.position(i)];
+            if i < leaves {
+                out.node(
+                    i,
+                    parent,
+                    depth,
+                    &format!("{range:?}"),
+                    &[("combined", element)],
+                );
+            } else {
+                out.node(
+                    i,
+                    parent,
+                    depth,
+                    &range.start.to_string(),
+                    &[("element", element)],
+                );
+            }
+        }
+    }
+}
+
+/// Renders the tree like [`VebSegmentTree::pretty`].
+impl<Query> std::fmt::Display for crate::Pretty<'_, VebSegmentTree<Query>>
+where
+    Query: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.pretty())
+    }
+}
+
+impl<Query> Debug for VebSegmentTree<Query>
+where
+    Query: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VebSegmentTree")
+            .field("data", &self.data)
+            .field("len", &self.len)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+impl<Query> Clone for VebSegmentTree<Query>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            len: self.len,
+            height: self.height,
+        }
+    }
+}
+
+/// Creates an empty tree.
+impl<Query> Default for VebSegmentTree<Query>
+where
+    Query: Monoid,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Compares the elements.
+impl<Query> PartialEq for VebSegmentTree<Query>
+where
+    Query: Monoid<Set: PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && (0..self.len).all(|i| self.point_query(i) == other.point_query(i))
+    }
+}
+
+impl<Query> Eq for VebSegmentTree<Query> where Query: Monoid<Set: Eq> {}
+
+/// Hashes the elements, consistently with [`PartialEq`].
+impl<Query> Hash for VebSegmentTree<Query>
+where
+    Query: Monoid<Set: Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for i in 0..self.len {
+            self.point_query(i).hash(state)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Query> serde::Serialize for VebSegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Serialize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::Leaves {
+                values: Vec::from_iter((0..self.len).map(|i| self.point_query(i))),
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Query> serde::Deserialize<'de> for VebSegmentTree<Query>
+where
+    Query: Monoid<Set: serde::Deserialize<'de>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <crate::serialize::Leaves<Vec<<Query as Monoid>::Set>> as serde::Deserialize>::deserialize(
+            deserializer,
+        )
+        .map(|leaves| Self::from(leaves.values))
+    }
+}
+
+#[cfg(test)]
+mod veb_segment_tree {
+    use rand::Rng;
+
+    use crate::{VebSegmentTree, ops::Add};
+
+    #[test]
+    fn layout_is_permutation() {
+        for n in 0..100 {
+            let tree = VebSegmentTree::<Add<u32>>::new(n);
+            let mut seen = vec![false; tree.data.len()];
+            for i in 1..tree.leaves() << 1 {
+                let pos = tree.position(i);
+                assert!(!seen[pos], "{n} {i}");
+                seen[pos] = true
+            }
+            assert!(seen.into_iter().all(|seen| seen))
+        }
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = rand::rng();
+        for n in 0..70 {
+            let mut naive = Vec::from_iter((0..n).map(|_| rng.random_range(0..100_u64)));
+            let mut tree = VebSegmentTree::<Add<u64>>::from(naive.clone());
+            for _ in 0..100 {
+                if n > 0 {
+                    let i = rng.random_range(0..n);
+                    let v = rng.random_range(0..100);
+                    naive[i] = v;
+                    tree.point_update(i, v);
+                    assert_eq!(tree.point_query(i), &v);
+                }
+
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                assert_eq!(tree.range_query(l..r), naive[l..r].iter().sum::<u64>())
+            }
+        }
+    }
+}
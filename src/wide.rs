@@ -0,0 +1,421 @@
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::{Range, RangeBounds},
+};
+
+use crate::{memory::MemoryReport, traits::Monoid, utility::convert_range};
+
+/// A data structure that supports **range query point update** operations
+/// on a tree whose nodes have `B` children.
+///
+/// The height is log<sub>`B`</sub> *N* instead of log<sub>2</sub> *N*,
+/// and each level is stored contiguously, so the children of a node share a few cache lines.
+/// The trade-off is up to `B` combines per node.
+/// This pays off for cheap combines such as integer sum or min with `B` of 4 to 16.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{WideSegmentTree, ops::Add};
+///
+/// let mut st = WideSegmentTree::<Add<i64>, 4>::from_iter(0..10);
+/// assert_eq!(st.range_query(..), 45);
+///
+/// st.point_update(3, 100);
+/// assert_eq!(st.range_query(2..5), 2 + 100 + 4);
+/// assert_eq!(st.point_query(3), &100);
+/// ```
+pub struct WideSegmentTree<Query, const B: usize>
+where
+    Query: Monoid,
+{
+    /// `levels[0]` holds the elements and `levels[k + 1][j]` combines `levels[k][B * j..B * (j + 1)]`.
+    /// The last level has at most one node.
+    levels: Vec<Box<[<Query as Monoid>::Set]>>,
+}
+
+impl<Query, const B: usize> WideSegmentTree<Query, B>
+where
+    Query: Monoid,
+{
+    #[doc = include_str!("../doc/new.md")]
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{WideSegmentTree, ops::Add};
+    ///
+    /// let st = WideSegmentTree::<Add<i32>, 8>::new(10_000);
+    /// assert_eq!(st.range_query(..), 0);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn new(n: usize) -> Self {
+        Self::from_iter(std::iter::repeat_with(<Query as Monoid>::identity).take(n))
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of levels, including the leaves.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{WideSegmentTree, ops::Add};
+    ///
+    /// assert_eq!(WideSegmentTree::<Add<i32>, 2>::new(1000).height(), 11);
+    /// assert_eq!(WideSegmentTree::<Add<i32>, 16>::new(1000).height(), 4);
+    /// ```
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.levels.len()
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log<sub>`B`</sub> *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{WideSegmentTree, ops::Add};
+    ///
+    /// let st = WideSegmentTree::<Add<u32>, 4>::new(100);
+    /// // 100 + 25 + 7 + 2 + 1 nodes
+    /// assert_eq!(st.memory_usage().data, 135 * size_of::<u32>());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            data: self.levels.iter().map(|level| size_of_val(&**level)).sum(),
+            buffers: self.levels.capacity() * size_of::<Box<[<Query as Monoid>::Set]>>(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the elements as a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn as_slice(&self) -> &[<Query as Monoid>::Set] {
+        &self.levels[0]
+    }
+
+    /// Recalculates the `j`-th node of the `k`-th level from its children.
+    fn recalculate_at(&mut self, k: usize, j: usize) {
+        let (lower, upper) = self.levels.split_at_mut(k);
+        let children = &lower[k - 1];
+        upper[0][j] = children[B * j..(B * (j + 1)).min(children.len())]
+            .iter()
+            .fold(<Query as Monoid>::identity(), |acc, child| {
+                <Query as Monoid>::combine(&acc, child)
+            });
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(`B` log<sub>`B`</sub> *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{WideSegmentTree, ops::Mul};
+    ///
+    /// let mut st = WideSegmentTree::<Mul<i32>, 4>::new(100);
+    /// st.point_update(50, 2);
+    /// assert_eq!(st.range_query(..), 2);
+    /// assert_eq!(st.range_query(..50), 1);
+    /// ```
+    pub fn point_update(&mut self, i: usize, element: <Query as Monoid>::Set) {
+        assert!(
+            i < self.len(),
+            "index out of bounds: the len is {} but the index is {i}",
+            self.len()
+        );
+
+        self.levels[0][i] = element;
+        let mut j = i;
+        for k in 1..self.levels.len() {
+            j /= B;
+            self.recalculate_at(k, j)
+        }
+    }
+
+    #[doc = include_str!("../doc/point_query.md")]
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    pub fn point_query(&self, i: usize) -> &<Query as Monoid>::Set {
+        &self.levels[0][i]
+    }
+
+    #[doc = include_str!("../doc/range_query.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(`B` log<sub>`B`</sub> *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{WideSegmentTree, ops::Max};
+    ///
+    /// let st = WideSegmentTree::<Max<i32>, 3>::from_iter([3, 1, 4, 1, 5].map(Some));
+    /// assert_eq!(st.range_query(1..4), Some(4));
+    /// assert_eq!(st.range_query(2..2), None);
+    /// ```
+    pub fn range_query<R>(&self, range: R) -> <Query as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let Range {
+            start: mut l,
+            end: mut r,
+        } = convert_range(range, 0..self.len());
+
+        let (mut acc_l, mut acc_r) = (<Query as Monoid>::identity(), <Query as Monoid>::identity());
+        for level in &self.levels {
+            if l >= r {
+                break;
+            }
+            // combine the partial blocks at both ends, leaving whole blocks to the next level
+            while l % B != 0 && l < r {
+                <Query as Monoid>::combine_assign(&mut acc_l, &level[l]);
+                l += 1
+            }
+            while r % B != 0 && l < r {
+                r -= 1;
+                acc_r = <Query as Monoid>::combine(&level[r], &acc_r)
+            }
+            l /= B;
+            r /= B;
+        }
+
+        <Query as Monoid>::combine(&acc_l, &acc_r)
+    }
+}
+
+impl<Query, const B: usize> WideSegmentTree<Query, B>
+where
+    Query: Monoid<Set: Clone>,
+{
+    /// Returns the elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn to_vec(&self) -> Vec<<Query as Monoid>::Set> {
+        self.levels[0].to_vec()
+    }
+}
+
+impl<Query, const B: usize> From<Vec<<Query as Monoid>::Set>> for WideSegmentTree<Query, B>
+where
+    Query: Monoid,
+{
+    fn from(values: Vec<<Query as Monoid>::Set>) -> Self {
+        const { assert!(B >= 2, "the branching factor should be at least 2") };
+
+        let mut levels = vec![values.into_boxed_slice()];
+        while let Some(lower) = levels.last()
+            && lower.len() > 1
+        {
+            let upper = Box::from_iter(lower.chunks(B).map(|children| {
+                children
+                    .iter()
+                    .fold(<Query as Monoid>::identity(), |acc, child| {
+                        <Query as Monoid>::combine(&acc, child)
+                    })
+            }));
+            levels.push(upper);
+        }
+
+        Self { levels }
+    }
+}
+
+impl<Query, const B: usize> FromIterator<<Query as Monoid>::Set> for WideSegmentTree<Query, B>
+where
+    Query: Monoid,
+{
+    fn from_iter<I: IntoIterator<Item = <Query as Monoid>::Set>>(iter: I) -> Self {
+        Self::from(Vec::from_iter(iter))
+    }
+}
+
+impl<Query, const B: usize> From<WideSegmentTree<Query, B>> for Vec<<Query as Monoid>::Set>
+where
+    Query: Monoid,
+{
+    fn from(mut tree: WideSegmentTree<Query, B>) -> Self {
+        tree.levels.swap_remove(0).into_vec()
+    }
+}
+
+impl<Query, const B: usize> Debug for WideSegmentTree<Query, B>
+where
+    Query: Monoid<Set: Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WideSegmentTree")
+            .field("levels", &self.levels)
+            .finish()
+    }
+}
+
+impl<Query, const B: usize> Clone for WideSegmentTree<Query, B>
+where
+    Query: Monoid<Set: Clone>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            levels: self.levels.clone(),
+        }
+    }
+}
+
+/// Creates an empty tree.
+impl<Query, const B: usize> Default for WideSegmentTree<Query, B>
+where
+    Query: Monoid,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Compares the elements.
+impl<Query, const B: usize> PartialEq for WideSegmentTree<Query, B>
+where
+    Query: Monoid<Set: PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.levels[0] == other.levels[0]
+    }
+}
+
+impl<Query, const B: usize> Eq for WideSegmentTree<Query, B> where Query: Monoid<Set: Eq> {}
+
+/// Hashes the elements, consistently with [`PartialEq`].
+impl<Query, const B: usize> Hash for WideSegmentTree<Query, B>
+where
+    Query: Monoid<Set: Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.levels[0].hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Query, const B: usize> serde::Serialize for WideSegmentTree<Query, B>
+where
+    Query: Monoid<Set: serde::Serialize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::Leaves {
+                values: &self.levels[0],
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Query, const B: usize> serde::Deserialize<'de> for WideSegmentTree<Query, B>
+where
+    Query: Monoid<Set: serde::Deserialize<'de>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <crate::serialize::Leaves<Vec<<Query as Monoid>::Set>> as serde::Deserialize>::deserialize(
+            deserializer,
+        )
+        .map(|leaves| Self::from(leaves.values))
+    }
+}
+
+#[cfg(test)]
+mod random {
+    use rand::Rng;
+
+    use crate::{WideSegmentTree, ops::Concat};
+
+    fn template<const B: usize>() {
+        let mut rng = rand::rng();
+        for n in [0, 1, 2, B - 1, B, B + 1, B * B, B * B + 3, 50] {
+            let mut naive = Vec::from_iter((0..n).map(|i| vec![i]));
+            let mut st = WideSegmentTree::<Concat<usize>, B>::from(naive.clone());
+
+            for _ in 0..20 {
+                if n > 0 {
+                    let i = rng.random_range(0..n);
+                    let element = vec![rng.random_range(0..100)];
+                    st.point_update(i, element.clone());
+                    naive[i] = element;
+                }
+
+                for l in 0..=n {
+                    for r in l..=n {
+                        assert_eq!(st.range_query(l..r), naive[l..r].concat())
+                    }
+                }
+            }
+            assert_eq!(st.as_slice(), naive);
+        }
+    }
+
+    #[test]
+    fn binary() {
+        template::<2>()
+    }
+
+    #[test]
+    fn ternary() {
+        template::<3>()
+    }
+
+    #[test]
+    fn quaternary() {
+        template::<4>()
+    }
+
+    #[test]
+    fn hexadecimal() {
+        template::<16>()
+    }
+}
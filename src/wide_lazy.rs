@@ -0,0 +1,569 @@
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::{Range, RangeBounds},
+};
+
+use crate::{
+    memory::MemoryReport,
+    traits::{Monoid, MonoidAction},
+    utility::convert_range,
+};
+
+/// A data structure that supports **range query range update** operations
+/// on a tree whose nodes have `B` children.
+///
+/// See [`WideSegmentTree`](crate::WideSegmentTree) for the trade-off.
+/// A pending update is pushed to `B` children at once, so a range update or a range query
+/// takes *O*(`B` log<sub>`B`</sub> *N*) combines and acts.
+///
+/// # Example
+///
+/// ```
+/// use seg_lib::{WideLazySegmentTree, acts::AddQueryAffineUpdate};
+///
+/// let mut lst = WideLazySegmentTree::<AddQueryAffineUpdate<i64>, 4>::from_iter(0..10);
+/// lst.range_update(2..8, &(2, 1));
+/// assert_eq!(lst.range_query(..), 45 + 27 + 6);
+/// assert_eq!(lst.point_query(3), &7);
+/// ```
+pub struct WideLazySegmentTree<Action, const B: usize>
+where
+    Action: MonoidAction,
+{
+    /// `data[0]` holds the elements and `data[k + 1][j]` combines `data[k][B * j..B * (j + 1)]`
+    /// with `lazy[k + 1][j]` applied.
+    /// The last level has at most one node.
+    data: Vec<Box<[<<Action as MonoidAction>::Set as Monoid>::Set]>>,
+    /// the pending updates for the children, where `lazy[0]` is empty
+    lazy: Vec<Box<[<<Action as MonoidAction>::Map as Monoid>::Set]>>,
+}
+
+impl<Action, const B: usize> WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction,
+{
+    #[doc = include_str!("../doc/new.md")]
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{WideLazySegmentTree, acts::MaxQueryAddUpdate};
+    ///
+    /// let mut lst = WideLazySegmentTree::<MaxQueryAddUpdate<i32>, 8>::new(100);
+    /// assert_eq!(lst.range_query(..), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn new(n: usize) -> Self {
+        Self::from_iter(
+            std::iter::repeat_with(<<Action as MonoidAction>::Set as Monoid>::identity).take(n),
+        )
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data[0].len()
+    }
+
+    /// Returns `true` if the tree has no elements.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of levels, including the leaves.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(1)
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.data.len()
+    }
+
+    #[doc = include_str!("../doc/memory_usage.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(log<sub>`B`</sub> *N*)
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            data: self.data.iter().map(|level| size_of_val(&**level)).sum(),
+            lazy: self.lazy.iter().map(|level| size_of_val(&**level)).sum(),
+            buffers: self.data.capacity()
+                * size_of::<Box<[<<Action as MonoidAction>::Set as Monoid>::Set]>>()
+                + self.lazy.capacity()
+                    * size_of::<Box<[<<Action as MonoidAction>::Map as Monoid>::Set]>>(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the range of the leaves under the `j`-th node of the `k`-th level.
+    #[inline]
+    fn leaves(&self, k: usize, j: usize) -> Range<usize> {
+        let width = B.pow(k as u32);
+        j * width..((j + 1) * width).min(self.len())
+    }
+
+    /// Returns the range of the children of the `j`-th node of the `k`-th level.
+    #[inline]
+    fn children(&self, k: usize, j: usize) -> Range<usize> {
+        B * j..(B * (j + 1)).min(self.data[k - 1].len())
+    }
+
+    fn push_map(
+        &mut self,
+        k: usize,
+        j: usize,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) {
+        let size = <Action as MonoidAction>::USE_SEGMENT_SIZE.then(|| self.leaves(k, j).len());
+        <Action as MonoidAction>::act_assign(update, &mut self.data[k][j], size);
+        if k > 0 {
+            <<Action as MonoidAction>::Map as Monoid>::combine_assign(&mut self.lazy[k][j], update);
+        }
+    }
+
+    /// Propagates the pending update of the `j`-th node of the `k`-th level to its children.
+    fn propagate_at(&mut self, k: usize, j: usize) {
+        if k == 0 || <<Action as MonoidAction>::Map as Monoid>::is_identity(&self.lazy[k][j]) {
+            return;
+        }
+
+        let update = std::mem::replace(
+            &mut self.lazy[k][j],
+            <<Action as MonoidAction>::Map as Monoid>::identity(),
+        );
+        for c in self.children(k, j) {
+            self.push_map(k - 1, c, &update);
+        }
+    }
+
+    /// Recalculates the `j`-th node of the `k`-th level from its children,
+    /// which should have no pending update from the node.
+    fn recalculate_at(&mut self, k: usize, j: usize) {
+        let children = self.children(k, j);
+        let (lower, upper) = self.data.split_at_mut(k);
+        upper[0][j] = lower[k - 1][children].iter().fold(
+            <<Action as MonoidAction>::Set as Monoid>::identity(),
+            |acc, child| <<Action as MonoidAction>::Set as Monoid>::combine(&acc, child),
+        );
+    }
+
+    #[doc = include_str!("../doc/flush.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn flush(&mut self) {
+        for k in (1..self.data.len()).rev() {
+            for j in 0..self.data[k].len() {
+                self.propagate_at(k, j);
+            }
+        }
+    }
+
+    /// Returns an iterator over the elements, resolving all pending updates.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn iter(&mut self) -> std::slice::Iter<'_, <<Action as MonoidAction>::Set as Monoid>::Set> {
+        self.flush();
+        self.data[0].iter()
+    }
+
+    /// Consumes the tree and returns the elements with all pending updates resolved.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn into_vec(mut self) -> Vec<<<Action as MonoidAction>::Set as Monoid>::Set> {
+        self.flush();
+        self.data.swap_remove(0).into_vec()
+    }
+
+    #[doc = include_str!("../doc/range_update.md")]
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(`B` log<sub>`B`</sub> *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{WideLazySegmentTree, acts::MaxQueryAddUpdate};
+    ///
+    /// let mut lst = WideLazySegmentTree::<MaxQueryAddUpdate<i32>, 4>::from_iter(
+    ///     std::iter::repeat_n(Some(0), 100)
+    /// );
+    /// lst.range_update(..75, &100);
+    /// lst.range_update(25.., &110);
+    /// assert_eq!(lst.range_query(..25), Some(100));
+    /// assert_eq!(lst.range_query(..), Some(210));
+    /// ```
+    pub fn range_update<R>(
+        &mut self,
+        range: R,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len());
+        if !range.is_empty() {
+            self.update_at(self.data.len() - 1, 0, &range, update)
+        }
+    }
+
+    fn update_at(
+        &mut self,
+        k: usize,
+        j: usize,
+        range: &Range<usize>,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) {
+        let leaves = self.leaves(k, j);
+        if range.end <= leaves.start || leaves.end <= range.start {
+            return;
+        }
+        if range.start <= leaves.start && leaves.end <= range.end {
+            self.push_map(k, j, update);
+            return;
+        }
+
+        // lazy propagation in top-to-bottom order
+        self.propagate_at(k, j);
+        for c in self.children(k, j) {
+            self.update_at(k - 1, c, range, update);
+        }
+        self.recalculate_at(k, j)
+    }
+
+    #[doc = include_str!("../doc/point_update.md")]
+    /// # Time complexity
+    ///
+    /// *O*(`B` log<sub>`B`</sub> *N*)
+    pub fn point_update(
+        &mut self,
+        i: usize,
+        update: &<<Action as MonoidAction>::Map as Monoid>::Set,
+    ) {
+        self.range_update(i..=i, update)
+    }
+
+    #[doc = include_str!("../doc/range_query.md")]
+    /// # Time complexity
+    ///
+    /// *O*(`B` log<sub>`B`</sub> *N*)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use seg_lib::{WideLazySegmentTree, acts::AddQueryAddUpdate};
+    ///
+    /// let mut lst = WideLazySegmentTree::<AddQueryAddUpdate<i32>, 3>::from_iter(0..10);
+    /// lst.range_update(5.., &1);
+    /// assert_eq!(lst.range_query(3..7), 3 + 4 + 6 + 7);
+    /// assert_eq!(lst.range_query(2..2), 0);
+    /// ```
+    pub fn range_query<R>(&mut self, range: R) -> <<Action as MonoidAction>::Set as Monoid>::Set
+    where
+        R: RangeBounds<usize> + Debug,
+    {
+        let range = convert_range(range, 0..self.len());
+        let mut acc = <<Action as MonoidAction>::Set as Monoid>::identity();
+        if !range.is_empty() {
+            self.query_at(self.data.len() - 1, 0, &range, &mut acc)
+        }
+        acc
+    }
+
+    /// Combines the elements of `range` under the `j`-th node of the `k`-th level into `acc`.
+    fn query_at(
+        &mut self,
+        k: usize,
+        j: usize,
+        range: &Range<usize>,
+        acc: &mut <<Action as MonoidAction>::Set as Monoid>::Set,
+    ) {
+        let leaves = self.leaves(k, j);
+        if range.end <= leaves.start || leaves.end <= range.start {
+            return;
+        }
+        if range.start <= leaves.start && leaves.end <= range.end {
+            <<Action as MonoidAction>::Set as Monoid>::combine_assign(acc, &self.data[k][j]);
+            return;
+        }
+
+        self.propagate_at(k, j);
+        for c in self.children(k, j) {
+            self.query_at(k - 1, c, range, acc);
+        }
+    }
+
+    #[doc = include_str!("../doc/point_query.md")]
+    /// # Time complexity
+    ///
+    /// *O*(`B` log<sub>`B`</sub> *N*)
+    pub fn point_query(&mut self, i: usize) -> &<<Action as MonoidAction>::Set as Monoid>::Set {
+        assert!(
+            i < self.len(),
+            "index out of bounds: the len is {} but the index is {i}",
+            self.len()
+        );
+
+        // lazy propagation in top-to-bottom order
+        for k in (1..self.data.len()).rev() {
+            self.propagate_at(k, i / B.pow(k as u32));
+        }
+        &self.data[0][i]
+    }
+
+    /// Returns the elements with all pending updates resolved.
+    ///
+    /// Unlike [`iter`](Self::iter), the tree is left untouched.
+    ///
+    /// # Time complexity
+    ///
+    /// *O*(*N*)
+    pub fn to_vec(&self) -> Vec<<<Action as MonoidAction>::Set as Monoid>::Set> {
+        // composed pending updates of the ancestors, including the node itself
+        let mut composed = Vec::new();
+        for k in (1..self.data.len()).rev() {
+            composed = Vec::from_iter(self.lazy[k].iter().enumerate().map(|(j, update)| {
+                match composed.get(j / B) {
+                    Some(parent) => {
+                        <<Action as MonoidAction>::Map as Monoid>::combine(update, parent)
+                    }
+                    None => <<Action as MonoidAction>::Map as Monoid>::combine(
+                        update,
+                        &<<Action as MonoidAction>::Map as Monoid>::identity(),
+                    ),
+                }
+            }));
+        }
+
+        let size = <Action as MonoidAction>::USE_SEGMENT_SIZE.then_some(1);
+        Vec::from_iter(self.data[0].iter().enumerate().map(
+            |(i, element)| match composed.get(i / B) {
+                Some(update) => <Action as MonoidAction>::act(update, element, size),
+                None => <Action as MonoidAction>::act(
+                    &<<Action as MonoidAction>::Map as Monoid>::identity(),
+                    element,
+                    size,
+                ),
+            },
+        ))
+    }
+}
+
+impl<Action, const B: usize> From<Vec<<<Action as MonoidAction>::Set as Monoid>::Set>>
+    for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction,
+{
+    fn from(values: Vec<<<Action as MonoidAction>::Set as Monoid>::Set>) -> Self {
+        const { assert!(B >= 2, "the branching factor should be at least 2") };
+
+        let mut data = vec![values.into_boxed_slice()];
+        let mut lazy = vec![Box::default()];
+        while let Some(lower) = data.last()
+            && lower.len() > 1
+        {
+            let upper = Box::from_iter(lower.chunks(B).map(|children| {
+                children.iter().fold(
+                    <<Action as MonoidAction>::Set as Monoid>::identity(),
+                    |acc, child| <<Action as MonoidAction>::Set as Monoid>::combine(&acc, child),
+                )
+            }));
+            lazy.push(Box::from_iter(
+                std::iter::repeat_with(<<Action as MonoidAction>::Map as Monoid>::identity)
+                    .take(upper.len()),
+            ));
+            data.push(upper);
+        }
+
+        Self { data, lazy }
+    }
+}
+
+impl<Action, const B: usize> FromIterator<<<Action as MonoidAction>::Set as Monoid>::Set>
+    for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction,
+{
+    fn from_iter<I: IntoIterator<Item = <<Action as MonoidAction>::Set as Monoid>::Set>>(
+        iter: I,
+    ) -> Self {
+        Self::from(Vec::from_iter(iter))
+    }
+}
+
+impl<Action, const B: usize> From<WideLazySegmentTree<Action, B>>
+    for Vec<<<Action as MonoidAction>::Set as Monoid>::Set>
+where
+    Action: MonoidAction,
+{
+    fn from(tree: WideLazySegmentTree<Action, B>) -> Self {
+        tree.into_vec()
+    }
+}
+
+impl<Action, const B: usize> Debug for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction<Set: Monoid<Set: Debug>, Map: Monoid<Set: Debug>>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WideLazySegmentTree")
+            .field("data", &self.data)
+            .field("lazy", &self.lazy)
+            .finish()
+    }
+}
+
+impl<Action, const B: usize> Clone for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction<Set: Monoid<Set: Clone>, Map: Monoid<Set: Clone>>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            lazy: self.lazy.clone(),
+        }
+    }
+}
+
+/// Creates an empty tree.
+impl<Action, const B: usize> Default for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Compares the elements with pending updates resolved.
+impl<Action, const B: usize> PartialEq for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction<Set: Monoid<Set: PartialEq>>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.to_vec() == other.to_vec()
+    }
+}
+
+impl<Action, const B: usize> Eq for WideLazySegmentTree<Action, B> where
+    Action: MonoidAction<Set: Monoid<Set: Eq>>
+{
+}
+
+/// Hashes the elements with pending updates resolved, consistently with [`PartialEq`].
+impl<Action, const B: usize> Hash for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction<Set: Monoid<Set: Hash>>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_vec().hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Action, const B: usize> serde::Serialize for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction<Set: Monoid<Set: serde::Serialize>>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(
+            &crate::serialize::Leaves {
+                values: self.to_vec(),
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Action, const B: usize> serde::Deserialize<'de> for WideLazySegmentTree<Action, B>
+where
+    Action: MonoidAction<Set: Monoid<Set: serde::Deserialize<'de>>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <crate::serialize::Leaves<Vec<<<Action as MonoidAction>::Set as Monoid>::Set>> as serde::Deserialize>::deserialize(deserializer)
+            .map(|leaves| Self::from(leaves.values))
+    }
+}
+
+#[cfg(test)]
+mod random {
+    use rand::Rng;
+
+    use crate::{WideLazySegmentTree, acts::AddQueryAffineUpdate};
+
+    fn template<const B: usize>() {
+        let mut rng = rand::rng();
+        for n in [0, 1, 2, B - 1, B, B + 1, B * B, B * B + 3, 50] {
+            let mut naive = Vec::from_iter((0..n as i64).map(|v| v - 20));
+            let mut lst = WideLazySegmentTree::<AddQueryAffineUpdate<i64>, B>::from(naive.clone());
+
+            for _ in 0..20 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let update = (rng.random_range(-1..=1), rng.random_range(-5..=5));
+                lst.range_update(l..r, &update);
+                for e in &mut naive[l..r] {
+                    *e = update.0 * *e + update.1
+                }
+
+                assert_eq!(lst.to_vec(), naive);
+                for l in 0..=n {
+                    for r in l..=n {
+                        assert_eq!(lst.range_query(l..r), naive[l..r].iter().sum::<i64>())
+                    }
+                }
+                if n > 0 {
+                    let i = rng.random_range(0..n);
+                    assert_eq!(lst.point_query(i), &naive[i]);
+                }
+            }
+            assert_eq!(lst.into_vec(), naive);
+        }
+    }
+
+    #[test]
+    fn binary() {
+        template::<2>()
+    }
+
+    #[test]
+    fn ternary() {
+        template::<3>()
+    }
+
+    #[test]
+    fn quaternary() {
+        template::<4>()
+    }
+
+    #[test]
+    fn hexadecimal() {
+        template::<16>()
+    }
+}